@@ -0,0 +1,125 @@
+//! Blocking wrapper around [`BandcampClient`] and [`download_item`] for programs that don't
+//! run an async runtime of their own, like GUI toolkits or scripting bindings.
+//!
+//! Every call is driven to completion on a runtime owned by the [`Client`], so none of its
+//! methods may be called from inside an async context.
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+
+use crate::core::auth::Credentials;
+use crate::core::client::{BandcampClient, CollectionPage};
+use crate::core::download::{DownloadOptions, DownloadProgressReporter, download_item};
+use crate::core::library::{AudioFormat, ItemDetails, LibraryItem, WishlistItem};
+use crate::error::Result;
+
+/// Polls for a pending encoding as often as the CLI does
+const ENCODING_ATTEMPTS: u32 = 30;
+
+pub struct Client {
+    runtime: tokio::runtime::Runtime,
+    inner: BandcampClient,
+}
+
+impl Client {
+    pub fn new() -> Result<Self> {
+        Self::with_client(BandcampClient::new())
+    }
+
+    /// Wraps a client set up beforehand, e.g. with [`BandcampClient::with_base_url`]
+    pub fn with_client(inner: BandcampClient) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(Self { runtime, inner })
+    }
+
+    /// The async client underneath, e.g. for the parts of the API not mirrored here
+    pub fn inner(&self) -> &BandcampClient {
+        &self.inner
+    }
+
+    pub fn validate_cookie(&mut self, identity_cookie: &str) -> Result<Credentials> {
+        self.runtime
+            .block_on(self.inner.validate_cookie(identity_cookie))
+    }
+
+    pub fn get_collection_page(&self, token: &str) -> Result<CollectionPage> {
+        self.runtime.block_on(self.inner.get_collection_page(token))
+    }
+
+    pub fn get_collection(&self) -> Result<Vec<LibraryItem>> {
+        self.runtime.block_on(self.inner.get_collection())
+    }
+
+    pub fn get_wishlist(&self) -> Result<Vec<WishlistItem>> {
+        self.runtime.block_on(self.inner.get_wishlist())
+    }
+
+    pub fn get_item_details(&self, item: &LibraryItem) -> Result<ItemDetails> {
+        self.runtime.block_on(self.inner.get_item_details(item))
+    }
+
+    pub fn set_item_hidden(&self, item: &LibraryItem, hidden: bool) -> Result<()> {
+        self.runtime
+            .block_on(self.inner.set_item_hidden(item, hidden))
+    }
+
+    /// Signed URL of `item` in `format`, waiting for Bandcamp to encode it if needed
+    pub fn get_download_url(&self, item: &LibraryItem, format: AudioFormat) -> Result<String> {
+        self.runtime
+            .block_on(
+                self.inner
+                    .get_download_url_with_retry(item, format, ENCODING_ATTEMPTS),
+            )
+    }
+
+    /// Downloads `item` into `output_dir` like the CLI does, returning the file or album folder
+    pub fn download_item(
+        &self,
+        item: &LibraryItem,
+        output_dir: &Path,
+        format: AudioFormat,
+        name_format: Option<&str>,
+        options: &DownloadOptions,
+    ) -> Result<PathBuf> {
+        self.download_item_with_progress(item, output_dir, format, name_format, options, |_, _| {})
+    }
+
+    /// Same as [`Client::download_item`], calling `on_progress` with the bytes received so far
+    /// and the total size when Bandcamp sent one
+    pub fn download_item_with_progress(
+        &self,
+        item: &LibraryItem,
+        output_dir: &Path,
+        format: AudioFormat,
+        name_format: Option<&str>,
+        options: &DownloadOptions,
+        on_progress: impl Fn(u64, Option<u64>) + Send + Sync,
+    ) -> Result<PathBuf> {
+        self.runtime.block_on(download_item(
+            &self.inner,
+            item,
+            output_dir,
+            format,
+            name_format,
+            options,
+            ProgressCallback(on_progress),
+        ))
+    }
+}
+
+/// Forwards byte progress to a closure and ignores every other event
+struct ProgressCallback<F>(F);
+
+impl<F: Fn(u64, Option<u64>) + Send + Sync> DownloadProgressReporter for ProgressCallback<F> {
+    fn on_progress(
+        &self,
+        downloaded: u64,
+        total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (self.0)(downloaded, total);
+        Box::pin(async {})
+    }
+}
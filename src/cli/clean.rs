@@ -0,0 +1,62 @@
+use std::path::Path;
+
+use tracing::{error, info};
+
+use crate::core::clean::{Disposal, find_clean_targets, remove_target};
+use crate::core::state::Scratch;
+use crate::error::Result;
+use crate::i18n::{Msg, tr_fmt};
+
+pub fn run_clean(output: &Path, dry_run: bool, disposal: &Disposal) -> Result<()> {
+    if !dry_run {
+        let purged = disposal.purge_expired()?;
+        if purged > 0 {
+            info!("Emptied {purged} expired quarantine folders");
+        }
+    }
+
+    let mut targets = find_clean_targets(output)?;
+    // A quarantine kept in the output directory isn't cleaned into itself
+    if let Disposal::Quarantine { dir, .. } = disposal {
+        let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.clone());
+        targets.retain(|target| {
+            !std::fs::canonicalize(target.path()).is_ok_and(|path| path.starts_with(&dir))
+        });
+    }
+
+    if targets.is_empty() {
+        println!("{}", tr_fmt(Msg::CleanNothing, &[&output.display()]));
+        return Ok(());
+    }
+
+    if dry_run {
+        println!("{}", tr_fmt(Msg::CleanWouldRemove, &[&targets.len()]));
+        for target in &targets {
+            println!("{} ({})", target.path().display(), target.describe());
+        }
+        return Ok(());
+    }
+
+    let mut removed = 0;
+    for target in &targets {
+        match remove_target(target, output, disposal) {
+            Ok(()) => {
+                removed += 1;
+                println!("{}", target.path().display());
+            }
+            Err(e) => error!("Failed to remove {}: {e}", target.path().display()),
+        }
+    }
+    Scratch::forget_missing(output)?;
+
+    let summary = match disposal {
+        Disposal::Delete => tr_fmt(Msg::CleanRemoved, &[&removed]),
+        Disposal::Trash => tr_fmt(Msg::CleanTrashed, &[&removed]),
+        Disposal::Quarantine { dir, .. } => {
+            tr_fmt(Msg::CleanQuarantined, &[&removed, &dir.display()])
+        }
+    };
+    println!("{summary}");
+
+    Ok(())
+}
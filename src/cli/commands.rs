@@ -1,9 +1,23 @@
 use std::path::PathBuf;
+use std::time::Duration;
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap::builder::BoolishValueParser;
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
 use url::Url;
 
+pub use crate::core::artwork::{ArtworkPolicy, MediaServer};
+pub use crate::core::download::MissingFormat;
+pub use crate::core::external::Downloader;
 pub use crate::core::library::AudioFormat;
+use crate::core::permissions::{OutputPermissions, lookup_group, lookup_user, parse_mode};
+pub use crate::core::routing::Layout;
+use crate::core::schedule::DownloadWindow;
+pub use crate::core::target::AlbumOutput;
+use crate::core::utils::{
+    SanitizeOptions, Transliterate, is_valid_replacement, parse_duration, parse_size,
+};
+pub use crate::term::spinner::SpinnerStyle;
+pub use crate::term::theme::ThemeName;
 
 #[derive(Parser, Debug)]
 #[command(name = "bannedcamp")]
@@ -16,9 +30,82 @@ pub struct Cli {
     #[arg(short, long, action = clap::ArgAction::Count, global = true)]
     pub verbose: u8,
 
-    /// Suppress output
-    #[arg(short, long, global = true)]
+    /// Suppress output, only errors and --json results are printed
+    #[arg(short, long, global = true, env = "BANNEDCAMP_QUIET", value_parser = BoolishValueParser::new())]
     pub quiet: bool,
+
+    /// Milliseconds between UI refreshes, raise it to lower CPU usage
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_TICK_RATE",
+        default_value = "100",
+        value_name = "MS"
+    )]
+    pub tick_rate: u64,
+
+    /// Spinner frames, `auto` falls back to ASCII when the locale is not UTF-8
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_SPINNER",
+        value_enum,
+        default_value = "auto"
+    )]
+    pub spinner: SpinnerStyle,
+
+    /// Colors for download statuses in the TUI
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_THEME",
+        value_enum,
+        default_value = "default"
+    )]
+    pub theme: ThemeName,
+
+    /// Save every request to Bandcamp and its response in this directory, to reproduce a failure
+    /// with --replay. Cookies and URL signatures are left out, page contents are kept as they are
+    #[arg(long, global = true, value_name = "DIR")]
+    pub record_http: Option<PathBuf>,
+
+    /// Save download pages that can't be parsed into this directory, with the cookie and URL
+    /// signatures blanked, so they can be attached to a bug report
+    #[arg(long, global = true, env = "BANNEDCAMP_DEBUG_DUMP", value_name = "DIR")]
+    pub debug_dump: Option<PathBuf>,
+
+    /// Answer requests from a --record-http directory instead of contacting Bandcamp
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        conflicts_with = "record_http"
+    )]
+    pub replay: Option<PathBuf>,
+
+    /// Collection items requested per page, lower it if Bandcamp times out on large pages
+    #[arg(long, global = true, default_value = "100", value_name = "N")]
+    pub collection_page_size: usize,
+
+    /// Fetch at most this many items of the collection, newest purchases first, e.g. for a
+    /// quick test run on a huge account
+    #[arg(long, global = true, value_name = "N")]
+    pub max_collection_items: Option<usize>,
+
+    /// Let commands run within this many seconds of each other reuse the validated cookie and
+    /// the fetched collection, cached in ~/.cache/bannedcamp. Off by default
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_SESSION_TTL",
+        value_name = "SECS"
+    )]
+    pub session_ttl: Option<u64>,
+
+    /// Run against a local server with a canned library instead of bandcamp.com, any cookie works
+    #[cfg(feature = "mock")]
+    #[arg(long, global = true)]
+    pub mock: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -28,12 +115,217 @@ pub enum Commands {
         /// Output directory for downloads
         #[arg(short, long, default_value = ".")]
         output: std::path::PathBuf,
+
+        /// Format `D` downloads in and the format popup starts on
+        #[arg(short, long, value_enum, default_value = "flac")]
+        format: AudioFormat,
+
+        /// File name template for downloads, see `download --help` for the variables
+        #[arg(long)]
+        custom_format: Option<String>,
+
+        /// Append a JSON line for every download attempt to this file
+        #[arg(long, env = "BANNEDCAMP_AUDIT_LOG")]
+        audit_log: Option<PathBuf>,
+
+        /// Write SHA-256 checksums (SHA256SUMS for albums, .sha256 for tracks)
+        #[arg(long)]
+        checksums: bool,
+
+        /// Write purchase date, price and gift details as JSON next to each download
+        #[arg(long)]
+        purchase_info: bool,
+
+        /// Decode-check every downloaded audio file with ffprobe and mark broken items as failed
+        #[arg(long)]
+        verify_audio: bool,
+
+        /// Browse the collection last saved in the output directory without logging in, e.g.
+        /// on a shared NAS. Nothing can be downloaded or hidden
+        #[arg(long)]
+        read_only: bool,
+
+        #[command(flatten)]
+        filenames: FilenameArgs,
     },
 
     /// Download items from library
+    #[command(subcommand_required = true)]
     Download {
         #[command(flatten)]
-        args: DownloadArgs,
+        args: Box<DownloadArgs>,
+    },
+
+    /// Download what is new in one pass or on an interval, for containers and cron jobs. Nothing
+    /// is ever asked, options can come from BANNEDCAMP_* variables, and the exit code is non-zero
+    /// if any item failed
+    Sync {
+        /// Exit after one pass instead of repeating every --interval
+        #[arg(long, env = "BANNEDCAMP_ONCE", value_parser = BoolishValueParser::new())]
+        once: bool,
+
+        /// Time between passes without --once, e.g. 30m or 6h
+        #[arg(long, env = "BANNEDCAMP_INTERVAL", default_value = "6h", value_parser = parse_duration_arg)]
+        interval: Duration,
+
+        #[command(flatten)]
+        args: Box<DownloadArgs>,
+    },
+
+    /// Print signed download links for other downloaders such as aria2c or curl, once Bandcamp
+    /// has prepared the files. The links expire quickly
+    Resolve {
+        /// Bandcamp URLs (artist, album, track) or item IDs
+        #[arg(required = true, num_args = 1..)]
+        targets: Vec<String>,
+
+        /// Bandcamp identity cookie
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+
+        /// Audio format
+        #[arg(short, long, value_enum, default_value = "flac")]
+        format: AudioFormat,
+    },
+
+    /// Export the collection last saved in the output directory, no Bandcamp login needed
+    #[command(group(
+        ArgGroup::new("exporters")
+            .required(true)
+            .multiple(true)
+            .args(["html", "discogs", "listenbrainz"])
+    ))]
+    Export {
+        /// Output directory the collection was downloaded into
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Write a static page with cover art, purchase dates and links to the downloaded files
+        /// into this directory
+        #[arg(long, value_name = "DIR")]
+        html: Option<PathBuf>,
+
+        /// Add the purchases to your Discogs collection, matched by artist and title. Releases
+        /// already in it are left alone
+        #[arg(long, requires = "discogs_token")]
+        discogs: bool,
+
+        /// Discogs personal access token, from Settings > Developers
+        #[arg(long, env = "BANNEDCAMP_DISCOGS_TOKEN", hide_env_values = true)]
+        discogs_token: Option<String>,
+
+        /// Mark the tracks of the purchases as loved on ListenBrainz
+        #[arg(long, requires = "listenbrainz_token")]
+        listenbrainz: bool,
+
+        /// ListenBrainz user token, from the settings page
+        #[arg(long, env = "BANNEDCAMP_LISTENBRAINZ_TOKEN", hide_env_values = true)]
+        listenbrainz_token: Option<String>,
+
+        /// Show what would be added to Discogs or ListenBrainz without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Remove leftovers from interrupted downloads
+    Clean {
+        /// Output directory to clean
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Show what would be removed without removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Move what is removed to the desktop's trash instead of deleting it
+        #[arg(long, env = "BANNEDCAMP_TRASH", value_parser = BoolishValueParser::new(), conflicts_with = "quarantine")]
+        trash: bool,
+
+        /// Move what is removed into a folder per day below this directory instead of deleting
+        /// it. It has to be on the same filesystem as the output directory
+        #[arg(long, env = "BANNEDCAMP_QUARANTINE", value_name = "DIR")]
+        quarantine: Option<PathBuf>,
+
+        /// How long --quarantine keeps what was moved there, older days are deleted by the next
+        /// clean
+        #[arg(long, env = "BANNEDCAMP_QUARANTINE_TTL", default_value = "30d", value_parser = parse_duration_arg)]
+        quarantine_ttl: Duration,
+    },
+
+    /// Find byte-identical audio files in the output directory, e.g. a single downloaded on its
+    /// own and again as part of an album
+    Dedupe {
+        /// Output directory to search
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Also compare artwork, booklets and other extras, e.g. between the same album
+        /// downloaded in two formats
+        #[arg(long)]
+        extras: bool,
+
+        /// Replace every duplicate with a hardlink to one copy, freeing the space it takes up
+        #[arg(long)]
+        link: bool,
+
+        /// Replace every duplicate with a copy-on-write clone instead, the files stay independent.
+        /// Needs a filesystem with reflink support such as Btrfs or XFS
+        #[arg(long, conflicts_with = "link")]
+        reflink: bool,
+    },
+
+    /// Show statistics about the downloads in the output directory
+    Stats {
+        /// Output directory to report on
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+
+        /// Break the downloaded bytes down by month and list the largest items
+        #[arg(long)]
+        bandwidth: bool,
+    },
+
+    /// Check cookie, connectivity and output directory for common problems
+    Doctor {
+        /// Bandcamp identity cookie
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+
+        /// Output directory to check
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+    },
+
+    /// Hide an item in your collection
+    Hide {
+        /// Item ID, as shown by `{id}` in --custom-format
+        id: String,
+
+        /// Bandcamp identity cookie
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+    },
+
+    /// Unhide a previously hidden item in your collection
+    Unhide {
+        /// Item ID, as shown by `{id}` in --custom-format
+        id: String,
+
+        /// Bandcamp identity cookie
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+    },
+
+    /// Inspect your wishlist
+    Wishlist {
+        #[command(subcommand)]
+        command: WishlistCommand,
+    },
+
+    /// Work with --custom-format templates
+    Template {
+        #[command(subcommand)]
+        command: TemplateCommand,
     },
 
     /// Generate shell completions
@@ -44,31 +336,359 @@ pub enum Commands {
     },
 }
 
-#[derive(Debug, Args)]
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Show where a few items of your collection would be saved with this template and
+    /// report unknown variables, without downloading anything
+    Test {
+        /// Template as passed to --custom-format, e.g. "{artist}/{title}"
+        template: String,
+
+        /// Bandcamp identity cookie, without one the template is rendered for sample items
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+
+        /// Number of collection items to render the template for
+        #[arg(long, default_value = "5")]
+        count: usize,
+
+        /// Audio format, decides what {ext} becomes for tracks
+        #[arg(short, long, value_enum, default_value = "flac")]
+        format: AudioFormat,
+
+        #[command(flatten)]
+        filenames: FilenameArgs,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WishlistCommand {
+    /// List everything on your wishlist
+    List {
+        /// Bandcamp identity cookie
+        #[arg(long, env = "BANDCAMP_COOKIE")]
+        cookie: Option<String>,
+
+        /// Only list items that can currently be downloaded at no cost
+        #[arg(long)]
+        only_free: bool,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
 pub struct DownloadArgs {
     /// Bandcamp identity cookie
     #[arg(long, global = true, env = "BANDCAMP_COOKIE")]
     pub cookie: Option<String>,
 
     /// Audio format
-    #[arg(short, long, value_enum, default_value = "flac", global = true)]
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value = "flac",
+        global = true,
+        env = "BANNEDCAMP_FORMAT"
+    )]
     pub format: AudioFormat,
     /// Output directory
-    #[arg(short, long, default_value = ".", global = true)]
+    #[arg(
+        short,
+        long,
+        default_value = ".",
+        global = true,
+        env = "BANNEDCAMP_OUTPUT"
+    )]
     pub output: PathBuf,
 
     /// Concurrent downloads
-    #[arg(long, default_value = "3", global = true)]
+    #[arg(long, default_value = "3", global = true, env = "BANNEDCAMP_PARALLEL")]
     pub parallel: u8,
 
+    /// Single tracks sharing one --parallel slot, they are one small file each. Raising it
+    /// opens that many more connections
+    #[arg(
+        long,
+        default_value = "1",
+        global = true,
+        env = "BANNEDCAMP_TRACKS_PER_SLOT",
+        value_name = "N",
+        value_parser = clap::value_parser!(u8).range(1..)
+    )]
+    pub tracks_per_slot: u8,
+
+    /// Most items of one artist downloading at once, whatever --parallel allows. Many
+    /// downloads from the same artist at a time can get throttled
+    #[arg(long, global = true, env = "BANNEDCAMP_PER_ARTIST", value_name = "N")]
+    pub per_artist: Option<usize>,
+
     /// Show what would be downloaded without downloading
     #[arg(long, global = true)]
     pub dry_run: bool,
 
+    /// With --dry-run, mark each item as new, already downloaded or downloaded in another
+    /// format, judging by the files in the output directory
+    #[arg(long, global = true, requires = "dry_run")]
+    pub diff: bool,
+
     /// Skip downloads that already exist
-    #[arg(long, global = true)]
+    #[arg(long, global = true, env = "BANNEDCAMP_SKIP_EXISTING", value_parser = BoolishValueParser::new())]
     pub skip_existing: bool,
 
+    /// Download the tracks a preorder already offers. The full album replaces them on the first
+    /// run after release day, `download new` and `sync` keep looking for it
+    #[arg(long, global = true, env = "BANNEDCAMP_PREORDER_TRACKS", value_parser = BoolishValueParser::new())]
+    pub preorder_tracks: bool,
+
+    /// What to do with items not offered in the requested format: skip them, download the
+    /// closest format Bandcamp offers instead, or count them as failed
+    #[arg(
+        long,
+        value_enum,
+        default_value = "skip",
+        global = true,
+        env = "BANNEDCAMP_MISSING_FORMAT"
+    )]
+    pub missing_format: MissingFormat,
+
+    /// Also download items hidden in your collection with `download all` and `artist-all`
+    #[arg(long, global = true, env = "BANNEDCAMP_INCLUDE_HIDDEN", value_parser = BoolishValueParser::new())]
+    pub include_hidden: bool,
+
+    /// File with `item-id-or-artist-glob = format` lines overriding --format per item
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_FORMAT_MAP",
+        value_name = "FILE"
+    )]
+    pub format_map: Option<PathBuf>,
+
+    /// File with `type:|format:|artist: pattern = directory` lines routing items to other
+    /// output directories, relative directories are inside --output
+    #[arg(long, global = true, env = "BANNEDCAMP_ROUTE_MAP", value_name = "FILE")]
+    pub route_map: Option<PathBuf>,
+
+    /// How downloads are arranged in the output directory, purchase-year buckets them into
+    /// folders like `2024/` by when they were bought
+    #[arg(
+        long,
+        value_enum,
+        default_value = "flat",
+        global = true,
+        env = "BANNEDCAMP_LAYOUT"
+    )]
+    pub layout: Layout,
+
+    /// Move items matching these Bandcamp URLs (artist, album or track) to the front of the
+    /// queue, so they finish before the rest of a big batch
+    #[arg(long, global = true, num_args = 1.., value_name = "URL")]
+    pub first: Vec<String>,
+
+    /// Download at most this many items per run. A cursor in the output directory remembers
+    /// finished items, so the next run continues where this one stopped
+    #[arg(long, global = true, env = "BANNEDCAMP_MAX_ITEMS", value_name = "N")]
+    pub max_items: Option<usize>,
+
+    /// Only start downloads during this time of day, e.g. 01:00-07:00, pausing the batch in
+    /// between. Downloads running when the window closes are finished
+    #[arg(long, global = true, env = "BANNEDCAMP_WINDOW", value_name = "HH:MM-HH:MM", value_parser = parse_window_arg)]
+    pub window: Option<DownloadWindow>,
+
+    /// Stop starting downloads once this much was downloaded into the output directory this
+    /// calendar month, e.g. 100G. Items left out are picked up again next month
+    #[arg(long, global = true, env = "BANNEDCAMP_MONTHLY_CAP", value_name = "SIZE", value_parser = parse_size_arg)]
+    pub monthly_cap: Option<u64>,
+
+    /// Pause the batch while the output disk has less than this much free, e.g. 20G, and
+    /// resume once space is freed up. Space is checked before each item starts, downloads
+    /// already running are finished, so leave room for the largest album
+    #[arg(long, global = true, env = "BANNEDCAMP_MIN_FREE_SPACE", value_name = "SIZE", value_parser = parse_size_arg)]
+    pub min_free_space: Option<u64>,
+
+    /// Skip items whose artist matches this glob (case-insensitive, `*` and `?`), repeatable
+    #[arg(long, global = true, value_name = "GLOB")]
+    pub exclude_artist: Vec<String>,
+
+    /// Skip items whose title matches this glob (case-insensitive, `*` and `?`), repeatable
+    #[arg(long, global = true, value_name = "GLOB")]
+    pub exclude_title: Vec<String>,
+
+    /// Append a JSON line for every download attempt to this file
+    #[arg(long, global = true, env = "BANNEDCAMP_AUDIT_LOG")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Write SHA-256 checksums (SHA256SUMS for albums, .sha256 for tracks)
+    #[arg(long, global = true, env = "BANNEDCAMP_CHECKSUMS", value_parser = BoolishValueParser::new())]
+    pub checksums: bool,
+
+    /// Write purchase date, price and gift details as JSON next to each download
+    #[arg(long, global = true, env = "BANNEDCAMP_PURCHASE_INFO", value_parser = BoolishValueParser::new())]
+    pub purchase_info: bool,
+
+    /// Also fetch the booklets, videos and other bonus files some releases offer next to the
+    /// audio, into an Extras folder inside the album or next to the track
+    #[arg(long, global = true, env = "BANNEDCAMP_INCLUDE_BONUS_ITEMS", value_parser = BoolishValueParser::new())]
+    pub include_bonus_items: bool,
+
+    /// Decode-check every downloaded audio file with ffprobe and mark broken items as failed
+    #[arg(long, global = true, env = "BANNEDCAMP_VERIFY_AUDIO", value_parser = BoolishValueParser::new())]
+    pub verify_audio: bool,
+
+    /// Write an album.nfo with title, artist and release date into every album folder, for
+    /// Kodi, Jellyfin and Emby
+    #[arg(long, global = true, env = "BANNEDCAMP_NFO", value_parser = BoolishValueParser::new())]
+    pub nfo: bool,
+
+    /// Set the DATE tag of downloaded tracks and extracted albums to the release date with
+    /// ffmpeg
+    #[arg(long, global = true, env = "BANNEDCAMP_TAG_DATES", value_parser = BoolishValueParser::new())]
+    pub tag_dates: bool,
+
+    /// Extract albums into folders, or keep each one as an uncompressed zip archive with the
+    /// same layout. Tracks are saved as they are either way
+    #[arg(
+        long,
+        value_enum,
+        default_value = "directory",
+        global = true,
+        conflicts_with = "verify_audio",
+        env = "BANNEDCAMP_ALBUM_OUTPUT"
+    )]
+    pub album_output: AlbumOutput,
+
+    /// Handle album artwork the way this media server expects it: folder.jpg for Kodi,
+    /// embedded only for Plex (needs ffmpeg), cover.jpg as Bandcamp names it for Navidrome
+    #[arg(long, value_enum, global = true, env = "BANNEDCAMP_MEDIA_SERVER")]
+    pub media_server: Option<MediaServer>,
+
+    /// What to do with the cover image of albums, overrides --media-server
+    #[arg(long, value_enum, global = true, env = "BANNEDCAMP_ARTWORK")]
+    pub artwork: Option<ArtworkPolicy>,
+
+    /// File with `item-id-or-artist-glob = keep|folder|embedded` lines overriding --artwork per
+    /// item
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_ARTWORK_MAP",
+        value_name = "FILE"
+    )]
+    pub artwork_map: Option<PathBuf>,
+
+    /// Program that transfers the files. Logging in, resolving download links, extraction and
+    /// bookkeeping stay with bannedcamp
+    #[arg(
+        long,
+        value_enum,
+        default_value = "builtin",
+        global = true,
+        env = "BANNEDCAMP_DOWNLOADER"
+    )]
+    pub downloader: Downloader,
+
+    /// Mode for downloaded files, e.g. 664. Without any of the permission flags tracks keep what
+    /// the umask gives them and album contents get 644 and 755
+    #[arg(long, global = true, env = "BANNEDCAMP_FILE_MODE", value_name = "OCTAL", value_parser = parse_mode_arg)]
+    pub file_mode: Option<u32>,
+
+    /// Mode for album directories, e.g. 2775
+    #[arg(long, global = true, env = "BANNEDCAMP_DIR_MODE", value_name = "OCTAL", value_parser = parse_mode_arg)]
+    pub dir_mode: Option<u32>,
+
+    /// User name or id to hand downloads to, needs root, e.g. inside a container
+    #[arg(long, global = true, env = "BANNEDCAMP_OWNER", value_name = "USER", value_parser = parse_owner_arg)]
+    pub owner: Option<u32>,
+
+    /// Group name or id to hand downloads to
+    #[arg(long, global = true, env = "BANNEDCAMP_GROUP", value_name = "GROUP", value_parser = parse_group_arg)]
+    pub group: Option<u32>,
+
+    /// Reuse the id of an earlier batch, skipping every item it already downloaded.
+    /// Without it each run gets a new id, printed at the start and recorded in the audit log
+    #[arg(long, global = true, value_name = "ID")]
+    pub batch_id: Option<String>,
+
+    /// Print the batch summary as JSON on stdout, including skipped items and why
+    #[arg(long, global = true, env = "BANNEDCAMP_JSON", value_parser = BoolishValueParser::new())]
+    pub json: bool,
+
+    /// Keep the progress of the batch (items, bytes, speeds, ETA) as JSON in this file,
+    /// rewritten every few seconds, e.g. for a dashboard or status bar
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_PROGRESS_FILE",
+        value_name = "FILE"
+    )]
+    pub progress_file: Option<PathBuf>,
+
+    /// Open the output directory in the file manager once every item downloaded successfully
+    #[arg(long, global = true)]
+    pub open_on_complete: bool,
+
+    /// Write a report of the batch to this file, HTML for .html files and Markdown otherwise
+    #[arg(long, global = true, env = "BANNEDCAMP_REPORT", value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// List items whose names had to be sanitized or whose paths collide in this file before
+    /// downloading, HTML for .html files and Markdown otherwise. Combine with --dry-run to check
+    /// a naming template against the whole library
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_NAMING_REPORT",
+        value_name = "FILE"
+    )]
+    pub naming_report: Option<PathBuf>,
+
+    /// Send failures and a batch summary to this ntfy topic
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_NTFY_TOPIC",
+        value_name = "TOPIC"
+    )]
+    pub ntfy_topic: Option<String>,
+
+    /// ntfy server to publish to
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_NTFY_SERVER",
+        default_value = "https://ntfy.sh",
+        value_name = "URL"
+    )]
+    pub ntfy_server: String,
+
+    /// Access token for protected ntfy topics
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_NTFY_TOKEN",
+        hide_env_values = true
+    )]
+    pub ntfy_token: Option<String>,
+
+    /// Send failures and a batch summary to this Gotify server, needs --gotify-token
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_GOTIFY_URL",
+        value_name = "URL",
+        requires = "gotify_token"
+    )]
+    pub gotify_url: Option<String>,
+
+    /// Gotify application token
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_GOTIFY_TOKEN",
+        hide_env_values = true
+    )]
+    pub gotify_token: Option<String>,
+
     /**
       Custom name format for download outputs
       Note! This does not modify the files inside album packages, only the top-level folder/file name.
@@ -78,22 +698,117 @@ pub struct DownloadArgs {
       - {title}: Item title (track or album name)
       - {ext}: File extension (e.g., .flac, .mp3), only used for single track downloads, will be empty otherwise. Note that the extension includes the dot!
       - {id}: Item ID
+      - {year}: Release year, looked up on each item's page before the download starts. Empty if Bandcamp doesn't show one
 
       Examples:
       - "{artist} - {title}{ext}" -> "Clark Rainbow - Chainsaw.flac" (when downloading a single track URL https://clarkrainbow.bandcamp.com/track/chainsaw)
       - "{artist} - {title}" -> "Bad Math - Missing Narrative"
       - "{artist}/{title}" -> "Bad Math/Missing Narrative"
+      - "{artist}/{year} - {title}" -> "Bad Math/2021 - Missing Narrative"
+
+      `/` and `\` both start a new directory. Absolute paths and `..` are
+      rejected, downloads always stay inside the output directory.
 
       Default:
         "{artist} - {title}" for albums,
         "{artist} - {title}{ext}" for tracks
 
     */
-    #[arg(long, global = true, verbatim_doc_comment)]
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_CUSTOM_FORMAT",
+        verbatim_doc_comment
+    )]
     pub custom_format: Option<String>,
 
+    /// Name tracks and album folders after the file Bandcamp serves ("Artist - Title.flac")
+    /// instead of --custom-format. --skip-existing and --dry-run still go by the template
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "custom_format",
+        env = "BANNEDCAMP_KEEP_ORIGINAL_NAMES",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub keep_original_names: bool,
+
+    #[command(flatten)]
+    pub filenames: FilenameArgs,
+
+    // Always set for `download`, `sync` defaults to `new`
     #[command(subcommand)]
-    pub target: DownloadTarget,
+    pub target: Option<DownloadTarget>,
+}
+
+/// How artist and title are turned into file names, shared by the TUI and `download`
+#[derive(Args, Debug, Clone)]
+pub struct FilenameArgs {
+    /// Put this in place of characters file names can't contain (/ \ : * ? " < > |), empty
+    /// drops them
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_REPLACE_INVALID",
+        default_value = "_",
+        value_name = "STR",
+        value_parser = parse_replacement_arg
+    )]
+    pub replace_invalid: String,
+
+    /// Spell names in ASCII, only accented letters and punctuation without a value
+    #[arg(
+        long,
+        global = true,
+        env = "BANNEDCAMP_TRANSLITERATE",
+        value_enum,
+        value_name = "CHARS",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "latin"
+    )]
+    pub transliterate: Option<Transliterate>,
+
+    /// Squeeze runs of whitespace into a single space and trim both ends
+    #[arg(long, global = true, env = "BANNEDCAMP_COLLAPSE_WHITESPACE", value_parser = BoolishValueParser::new())]
+    pub collapse_whitespace: bool,
+}
+
+impl DownloadArgs {
+    /// Modes and ownership for downloads, `None` when no permission flag is given
+    pub fn permissions(&self) -> Option<OutputPermissions> {
+        if self.file_mode.is_none()
+            && self.dir_mode.is_none()
+            && self.owner.is_none()
+            && self.group.is_none()
+        {
+            return None;
+        }
+        let defaults = OutputPermissions::default();
+        Some(OutputPermissions {
+            file_mode: self.file_mode.unwrap_or(defaults.file_mode),
+            dir_mode: self.dir_mode.unwrap_or(defaults.dir_mode),
+            owner: self.owner,
+            group: self.group,
+        })
+    }
+
+    /// --artwork, or what --media-server wants
+    pub fn artwork(&self) -> ArtworkPolicy {
+        self.artwork
+            .or(self.media_server.map(MediaServer::artwork))
+            .unwrap_or_default()
+    }
+}
+
+impl FilenameArgs {
+    pub fn sanitize_options(&self) -> SanitizeOptions {
+        SanitizeOptions {
+            replacement: self.replace_invalid.clone(),
+            transliterate: self.transliterate,
+            collapse_whitespace: self.collapse_whitespace,
+        }
+    }
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -101,6 +816,17 @@ pub enum DownloadTarget {
     /// Download all items from your library
     All,
 
+    /// Download all items grouped by artist, each artist into their own directory.
+    /// --custom-format still applies inside the artist directory
+    ArtistAll,
+
+    /// Download items purchased since the last complete `all`, `artist-all` or `new` run, and
+    /// what that run couldn't get yet. The first run downloads everything
+    New,
+
+    /// Download the items earlier runs gave up on because Bandcamp was still preparing them
+    Pending,
+
     /// Download items from urls
     Url {
         /**
@@ -109,6 +835,7 @@ pub enum DownloadTarget {
             https://badmathhk.bandcamp.com                          (all from artist)
             https://badmathhk.bandcamp.com/album/missing-narrative  (specific album)
             https://clarkrainbow.bandcamp.com/track/chainsaw        (specific track)
+            https://bandcamp.com/somefan                            (fan's collection)
         */
         #[arg(required = true, num_args = 1.., verbatim_doc_comment)]
         urls: Vec<String>,
@@ -147,6 +874,38 @@ impl BandcampUrl {
     }
 }
 
+/// Pages on bandcamp.com itself that aren't a fan's collection
+const SITE_PAGES: &[&str] = &[
+    "about", "api", "discover", "download", "fans", "help", "login", "search", "settings",
+    "signup", "tag", "yum",
+];
+
+/// Fan page URL such as https://bandcamp.com/somefan, standing for that fan's collection
+#[derive(Debug)]
+pub struct FanUrl {
+    pub username: String,
+}
+
+impl FanUrl {
+    /// Returns None unless the URL is a fan page on bandcamp.com
+    pub fn parse(input: &str) -> Option<Self> {
+        let url = Url::parse(input).ok()?;
+        if !matches!(url.host_str()?, "bandcamp.com" | "www.bandcamp.com") {
+            return None;
+        }
+
+        let mut segments = url.path_segments()?.filter(|s| !s.is_empty());
+        let username = segments.next()?;
+        if segments.next().is_some() || SITE_PAGES.contains(&username) {
+            return None;
+        }
+
+        Some(Self {
+            username: username.to_string(),
+        })
+    }
+}
+
 #[derive(ValueEnum, Clone, Debug)]
 pub enum Shell {
     Bash,
@@ -163,3 +922,40 @@ impl From<Shell> for clap_complete::Shell {
         }
     }
 }
+
+fn parse_window_arg(window: &str) -> Result<DownloadWindow, String> {
+    DownloadWindow::parse(window)
+        .ok_or_else(|| format!("invalid window {window:?}, expected e.g. 01:00-07:00"))
+}
+
+fn parse_duration_arg(duration: &str) -> Result<Duration, String> {
+    parse_duration(duration)
+        .ok_or_else(|| format!("invalid duration {duration:?}, expected e.g. 30m or 6h"))
+}
+
+fn parse_size_arg(size: &str) -> Result<u64, String> {
+    parse_size(size).ok_or_else(|| format!("invalid size {size:?}, expected e.g. 500M or 100G"))
+}
+
+fn parse_mode_arg(mode: &str) -> Result<u32, String> {
+    parse_mode(mode).ok_or_else(|| format!("invalid mode {mode:?}, expected octal e.g. 644"))
+}
+
+fn parse_replacement_arg(replacement: &str) -> Result<String, String> {
+    if is_valid_replacement(replacement) {
+        Ok(replacement.to_string())
+    } else {
+        Err(format!(
+            "invalid replacement {replacement:?}, it can't contain / \\ : * ? \" < > | or control \
+             characters, or be only dots"
+        ))
+    }
+}
+
+fn parse_owner_arg(user: &str) -> Result<u32, String> {
+    lookup_user(user).ok_or_else(|| format!("no user {user:?}"))
+}
+
+fn parse_group_arg(group: &str) -> Result<u32, String> {
+    lookup_group(group).ok_or_else(|| format!("no group {group:?}"))
+}
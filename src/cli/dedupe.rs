@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use tracing::error;
+
+use crate::core::dedupe::{LinkMode, find_duplicates, link_duplicates};
+use crate::core::utils::format_bytes;
+use crate::error::Result;
+
+pub fn run_dedupe(output: &Path, extras: bool, link: Option<LinkMode>) -> Result<()> {
+    let groups = find_duplicates(output, extras)?;
+
+    if groups.is_empty() {
+        println!("No duplicate files in {}", output.display());
+        return Ok(());
+    }
+
+    let wasted: u64 = groups.iter().map(|g| g.wasted_bytes()).sum();
+    for group in &groups {
+        println!("{}", group.original().display());
+        for duplicate in group.duplicates() {
+            println!("  = {}", duplicate.display());
+        }
+    }
+    println!(
+        "Found {} files with duplicates, {} could be freed.",
+        groups.len(),
+        format_bytes(wasted as f64)
+    );
+
+    let Some(mode) = link else {
+        return Ok(());
+    };
+
+    let mut freed = 0;
+    for group in &groups {
+        match link_duplicates(group, mode) {
+            Ok(bytes) => freed += bytes,
+            Err(e) => error!("Failed to link {}: {e}", group.original().display()),
+        }
+    }
+
+    println!(
+        "Replaced duplicates with {}, freed {}.",
+        match mode {
+            LinkMode::Hardlink => "hardlinks",
+            LinkMode::Reflink => "reflinks",
+        },
+        format_bytes(freed as f64)
+    );
+
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::ExitCode;
+
+use chrono::Utc;
+
+use crate::core::client::BandcampClient;
+use crate::core::download::extract_zip;
+use crate::core::utils::{LOW_SPACE_BYTES, available_space, format_bytes};
+use crate::error::{BandcampError, Result};
+use crate::i18n::{Msg, tr, tr_fmt};
+
+/// Signed download URLs are short-lived, so a skewed clock makes them look expired
+const MAX_CLOCK_SKEW_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Default)]
+struct Report {
+    failures: usize,
+    warnings: usize,
+}
+
+impl Report {
+    fn check(&mut self, status: CheckStatus, name: Msg, detail: &str) {
+        let label = match status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => {
+                self.warnings += 1;
+                "WARN"
+            }
+            CheckStatus::Fail => {
+                self.failures += 1;
+                "FAIL"
+            }
+        };
+        println!("[{label}] {}: {detail}", tr(name));
+    }
+
+    fn hint(&self, hint: Msg) {
+        println!("       -> {}", tr(hint));
+    }
+}
+
+/// Runs every check and prints the report. Fails the process when a check failed, so
+/// scripts can tell a broken setup apart.
+pub async fn run_doctor(cookie: Option<String>, output: &Path) -> Result<ExitCode> {
+    let mut report = Report::default();
+    let mut client = BandcampClient::new();
+
+    // API reachability and clock skew
+    match client.server_time().await {
+        Ok(server_time) => {
+            report.check(CheckStatus::Pass, Msg::DoctorApi, tr(Msg::DoctorApiOk));
+
+            match server_time {
+                Some(server_time) => {
+                    let skew = (Utc::now() - server_time).num_seconds();
+                    if skew.abs() > MAX_CLOCK_SKEW_SECS {
+                        report.check(
+                            CheckStatus::Fail,
+                            Msg::DoctorClockSkew,
+                            &tr_fmt(Msg::DoctorClockOff, &[&skew]),
+                        );
+                        report.hint(Msg::DoctorHintNtp);
+                    } else {
+                        report.check(CheckStatus::Pass, Msg::DoctorClockSkew, &format!("{skew}s"));
+                    }
+                }
+                None => report.check(
+                    CheckStatus::Warn,
+                    Msg::DoctorClockSkew,
+                    tr(Msg::DoctorNoDateHeader),
+                ),
+            }
+        }
+        Err(e) => {
+            report.check(CheckStatus::Fail, Msg::DoctorApi, &e.to_string());
+            report.hint(Msg::DoctorHintConnection);
+        }
+    }
+
+    // Cookie and fan_id
+    match cookie {
+        None => {
+            report.check(
+                CheckStatus::Fail,
+                Msg::DoctorCookie,
+                tr(Msg::DoctorNoCookie),
+            );
+            report.hint(Msg::DoctorHintCookie);
+        }
+        Some(cookie) => match client.validate_cookie(&cookie).await {
+            Ok(creds) => {
+                report.check(
+                    CheckStatus::Pass,
+                    Msg::DoctorCookie,
+                    tr(Msg::DoctorCookieOk),
+                );
+                report.check(
+                    CheckStatus::Pass,
+                    Msg::DoctorFanId,
+                    &tr_fmt(Msg::DoctorFanIdResolved, &[&creds.fan_id]),
+                );
+
+                let token = BandcampClient::initial_collection_token();
+                match client.get_collection_page(&token).await {
+                    Ok(page) => report.check(
+                        CheckStatus::Pass,
+                        Msg::DoctorCollection,
+                        &tr_fmt(Msg::DoctorCollectionOk, &[&page.items.len()]),
+                    ),
+                    Err(e) => {
+                        report.check(CheckStatus::Fail, Msg::DoctorCollection, &e.to_string());
+                        report.hint(Msg::DoctorHintLogInAgain);
+                    }
+                }
+            }
+            Err(e) => {
+                report.check(CheckStatus::Fail, Msg::DoctorCookie, &e.to_string());
+                report.hint(Msg::DoctorHintFreshCookie);
+            }
+        },
+    }
+
+    // Output directory
+    match check_writable(output) {
+        Ok(()) => {
+            report.check(
+                CheckStatus::Pass,
+                Msg::DoctorOutputDir,
+                &tr_fmt(Msg::DoctorWritable, &[&output.display()]),
+            );
+
+            match available_space(output) {
+                Ok(free) if free < LOW_SPACE_BYTES => {
+                    report.check(
+                        CheckStatus::Warn,
+                        Msg::DoctorFreeSpace,
+                        &tr_fmt(Msg::DoctorLowSpace, &[&format_bytes(free as f64)]),
+                    );
+                    report.hint(Msg::DoctorHintLossless);
+                }
+                Ok(free) => report.check(
+                    CheckStatus::Pass,
+                    Msg::DoctorFreeSpace,
+                    &tr_fmt(Msg::DoctorSpace, &[&format_bytes(free as f64)]),
+                ),
+                Err(e) => report.check(CheckStatus::Warn, Msg::DoctorFreeSpace, &e.to_string()),
+            }
+
+            match check_zip_extraction(output) {
+                Ok(()) => report.check(CheckStatus::Pass, Msg::DoctorZip, tr(Msg::DoctorZipOk)),
+                Err(e) => {
+                    report.check(CheckStatus::Fail, Msg::DoctorZip, &e.to_string());
+                    report.hint(Msg::DoctorHintZip);
+                }
+            }
+        }
+        Err(e) => {
+            report.check(
+                CheckStatus::Fail,
+                Msg::DoctorOutputDir,
+                &format!("{}: {e}", output.display()),
+            );
+            report.hint(Msg::DoctorHintOutputDir);
+        }
+    }
+
+    println!();
+    println!(
+        "{}",
+        tr_fmt(Msg::DoctorSummary, &[&report.failures, &report.warnings])
+    );
+
+    Ok(if report.failures > 0 {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn check_writable(output: &Path) -> Result<()> {
+    std::fs::create_dir_all(output)?;
+    let probe = output.join(".bannedcamp-doctor.tmp");
+    std::fs::File::create(&probe)?.write_all(b"ok")?;
+    std::fs::remove_file(&probe)?;
+    Ok(())
+}
+
+fn check_zip_extraction(output: &Path) -> Result<()> {
+    let zip_path = output.join(".bannedcamp-doctor-zip.tmp");
+    let extract_dir = output.join(".bannedcamp-doctor.partial");
+
+    let result = (|| -> Result<()> {
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&zip_path)?);
+        zip.start_file("doctor.txt", zip::write::SimpleFileOptions::default())
+            .map_err(|e| BandcampError::DownloadError(e.to_string()))?;
+        zip.write_all(b"ok")?;
+        zip.finish()
+            .map_err(|e| BandcampError::DownloadError(e.to_string()))?;
+
+        extract_zip(&zip_path, &extract_dir)?;
+
+        if std::fs::read(extract_dir.join("doctor.txt"))? != b"ok" {
+            return Err(BandcampError::DownloadError(
+                "extracted file content mismatch".to_string(),
+            ));
+        }
+        Ok(())
+    })();
+
+    let _ = std::fs::remove_file(&zip_path);
+    let _ = std::fs::remove_dir_all(&extract_dir);
+
+    result
+}
@@ -1,121 +1,294 @@
 use std::{
+    collections::HashMap,
     path::{Path, PathBuf},
     pin::Pin,
     sync::{
-        Arc,
-        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
     },
+    time::{Duration, Instant},
 };
 
 use crate::error::Result;
+use crate::i18n::{Msg, tr, tr_fmt};
 use crate::{
-    cli::commands::AudioFormat,
+    cli::{
+        commands::AudioFormat,
+        progress::{multi_progress, tick_strings},
+    },
     core::{
         client::BandcampClient,
-        download::{DownloadProgressReporter, DownloadSummary, download_item},
+        download::{DownloadOptions, DownloadProgressReporter, DownloadSummary},
+        format_map::FormatMap,
         library::LibraryItem,
-        utils::truncate_str,
+        queue::{BatchProgressReporter, DownloadQueue, ReporterRegistry, SilentReporter},
+        routing::{Layout, RouteMap},
+        schedule::DownloadWindow,
+        utils::{format_bytes, truncate_str},
     },
+    term::title::TitleGuard,
 };
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use tokio::sync::Semaphore;
-use tracing::{error, info};
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
 
 pub struct DownloadManager {
-    client: Arc<BandcampClient>,
-    output_dir: PathBuf,
-    format: AudioFormat,
-    name_format: Option<String>,
-    parallel: usize,
+    queue: DownloadQueue,
     progress: MultiProgress,
+    reporters: ReporterRegistry,
 }
 
 impl DownloadManager {
     pub fn new(
-        client: BandcampClient,
+        client: Arc<BandcampClient>,
         output_dir: PathBuf,
         format: AudioFormat,
         name_format: Option<String>,
         parallel: usize,
+        options: DownloadOptions,
     ) -> Self {
         Self {
-            client: Arc::new(client),
-            output_dir,
-            format,
-            name_format,
-            parallel,
-            progress: MultiProgress::new(),
+            queue: DownloadQueue::new(client, output_dir, format, name_format, options, parallel),
+            progress: multi_progress().clone(),
+            reporters: ReporterRegistry::new(),
         }
     }
 
+    /// Resolve each item's format through `format_map` instead of using one format for all
+    pub fn set_format_map(&mut self, format_map: FormatMap) {
+        self.queue.set_format_map(format_map);
+    }
+
+    /// Pick each item's output directory through `route_map` instead of the output directory
+    pub fn set_route_map(&mut self, route_map: RouteMap) {
+        self.queue.set_route_map(route_map);
+    }
+
+    /// Arrange items below their output directory by `layout`
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.queue.set_layout(layout);
+    }
+
+    /// Only start downloads inside `window`, waiting for it to open again in between
+    pub fn set_window(&mut self, window: DownloadWindow) {
+        self.queue.set_window(window);
+    }
+
+    /// Only start downloads while their disk has at least `min_free` bytes available
+    pub fn set_min_free_space(&mut self, min_free: u64) {
+        self.queue.set_min_free_space(min_free);
+    }
+
+    /// Let `tracks` single tracks share each slot
+    pub fn set_tracks_per_slot(&mut self, tracks: usize) {
+        self.queue.set_tracks_per_slot(tracks);
+    }
+
+    /// Hold items back while `limit` items of the same artist are in flight
+    pub fn set_per_artist(&mut self, limit: usize) {
+        self.queue.set_per_artist(limit);
+    }
+
+    /// Register an extra observer that receives batch events next to the progress bars
+    pub fn register_reporter(&mut self, reporter: Arc<dyn BatchProgressReporter>) {
+        self.reporters.register(reporter);
+    }
+
     pub async fn download_items(&self, items: Vec<LibraryItem>) -> Result<DownloadSummary> {
         let total = items.len();
-        info!("Starting download of {total} items");
 
         let header_pb = self.progress.add(ProgressBar::new_spinner());
-        header_pb.set_style(ProgressStyle::default_spinner().template("{msg}").unwrap());
-        header_pb.set_message(format!("{total} items remaining"));
+        header_pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{msg} [{elapsed_precise}]")
+                .unwrap(),
+        );
+        header_pb.enable_steady_tick(Duration::from_secs(1));
+
+        let batch_reporter = CliBatchReporter {
+            progress: self.progress.clone(),
+            header_pb: header_pb.clone(),
+            total,
+            finished: AtomicUsize::new(0),
+            transferred: Arc::new(AtomicU64::new(0)),
+            started: Instant::now(),
+            title: TitleGuard::new(),
+        };
+        batch_reporter.update_header(0);
+
+        let mut reporters = ReporterRegistry::new();
+        reporters.register(Arc::new(batch_reporter));
+        for reporter in self.reporters.iter() {
+            reporters.register(reporter.clone());
+        }
+
+        let summary = self.queue.run(items, Arc::new(reporters)).await;
 
-        let remaining = Arc::new(AtomicUsize::new(total));
-        let semaphore = Arc::new(Semaphore::new(self.parallel));
-        let mut handles = Vec::new();
+        header_pb.finish_and_clear();
 
-        let mut summary = DownloadSummary::default();
+        Ok(summary)
+    }
+}
 
+/// Prints an artist's progress, e.g. "Bad Math: 2/5 items", each time one of their items finishes
+pub struct ArtistProgressReporter {
+    progress: MultiProgress,
+    /// Finished and total item counts per artist
+    counts: Mutex<HashMap<String, (usize, usize)>>,
+}
+
+impl ArtistProgressReporter {
+    pub fn new(items: &[LibraryItem]) -> Self {
+        let mut counts = HashMap::new();
         for item in items {
-            let permit = semaphore.clone().acquire_owned().await.unwrap();
-            let client = self.client.clone();
-            let output_dir = self.output_dir.clone();
-            let format = self.format;
-            let progress = self.progress.clone();
-            let header_pb = header_pb.clone();
-            let remaining = remaining.clone();
-            let name_format = self.name_format.clone();
-
-            let handle = tokio::spawn(async move {
-                let result = cli_download(&client, &item, &output_dir, format, name_format.as_deref(), &progress).await;
-                let new_remaining = remaining.fetch_sub(1, Ordering::SeqCst) - 1;
-                header_pb.set_message(format!("{new_remaining} items remaining"));
-                drop(permit);
-                (item, result)
-            });
-
-            handles.push(handle);
+            counts.entry(item.artist.clone()).or_insert((0, 0)).1 += 1;
         }
 
-        for handle in handles {
-            match handle.await {
-                Ok((item, Ok(path))) => {
-                    summary.succeeded.push((item, path));
-                }
-                Ok((item, Err(e))) => {
-                    error!("Failed to download {}: {e}", item.title);
-                    summary.failed.push((item, e.to_string()));
-                }
-                Err(e) => {
-                    error!("Task panicked: {e}");
-                }
-            }
+        Self {
+            progress: multi_progress().clone(),
+            counts: Mutex::new(counts),
         }
+    }
+}
 
-        header_pb.finish_and_clear();
+impl BatchProgressReporter for ArtistProgressReporter {
+    fn on_item_started(
+        &self,
+        _index: usize,
+        _item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        Box::pin(async { Box::new(SilentReporter) as Box<dyn DownloadProgressReporter> })
+    }
 
-        Ok(summary)
+    fn on_item_finished(
+        &self,
+        _index: usize,
+        item: &LibraryItem,
+        _result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let line = {
+            let mut counts = self.counts.lock().unwrap();
+            let (finished, total) = counts.entry(item.artist.clone()).or_insert((0, 1));
+            *finished += 1;
+            tr_fmt(Msg::ArtistProgress, &[&item.artist, finished, total])
+        };
+
+        Box::pin(async move {
+            let _ = self.progress.println(line);
+        })
+    }
+}
+
+/// Shows one progress bar per active item below a header with the remaining count,
+/// aggregate speed and an ETA based on how long finished items took
+struct CliBatchReporter {
+    progress: MultiProgress,
+    header_pb: ProgressBar,
+    total: usize,
+    finished: AtomicUsize,
+    /// Bytes received across all items
+    transferred: Arc<AtomicU64>,
+    started: Instant,
+    title: TitleGuard,
+}
+
+impl CliBatchReporter {
+    fn update_header(&self, finished: usize) {
+        let remaining = self.total - finished;
+        let elapsed = self.started.elapsed();
+
+        let speed =
+            self.transferred.load(Ordering::Relaxed) as f64 / elapsed.as_secs_f64().max(1.0);
+        let eta = if finished == 0 {
+            tr(Msg::EtaEstimating).to_string()
+        } else {
+            // Finished items already reflect the parallelism, so this is the batch's pace
+            let per_item = elapsed / finished as u32;
+            tr_fmt(Msg::Eta, &[&HumanDuration(per_item * remaining as u32)])
+        };
+
+        self.title.set_progress(finished, self.total, speed);
+        self.header_pb.set_message(format!(
+            "{} · {}/s · {eta}",
+            tr_fmt(Msg::ItemsRemaining, &[&remaining]),
+            format_bytes(speed)
+        ));
+    }
+}
+
+impl BatchProgressReporter for CliBatchReporter {
+    fn on_item_started(
+        &self,
+        _index: usize,
+        item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        let reporter = CliProgressReporter::new(
+            &self.progress,
+            &item.artist,
+            &item.title,
+            self.transferred.clone(),
+        );
+        Box::pin(async move { Box::new(reporter) as Box<dyn DownloadProgressReporter> })
+    }
+
+    fn on_item_finished(
+        &self,
+        _index: usize,
+        _item: &LibraryItem,
+        _result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            let finished = self.finished.fetch_add(1, Ordering::SeqCst) + 1;
+            self.update_header(finished);
+        })
+    }
+
+    fn on_space_low(
+        &self,
+        dir: &Path,
+        free: u64,
+        min_free: u64,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let line = tr_fmt(
+            Msg::SpaceLow,
+            &[
+                &format_bytes(free as f64),
+                &dir.display(),
+                &format_bytes(min_free as f64),
+            ],
+        );
+        Box::pin(async move {
+            let _ = self.progress.println(line);
+        })
+    }
+
+    fn on_space_freed(&self, dir: &Path) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let line = tr_fmt(Msg::SpaceFreed, &[&dir.display()]);
+        Box::pin(async move {
+            let _ = self.progress.println(line);
+        })
     }
 }
 
 pub struct CliProgressReporter {
     pb: ProgressBar,
     display_name: String,
+    /// Batch-wide byte counter, fed with this item's increments
+    transferred: Arc<AtomicU64>,
+    position: AtomicU64,
 }
 
 impl CliProgressReporter {
-    pub fn new(progress: &MultiProgress, artist: &str, title: &str) -> Self {
+    pub fn new(
+        progress: &MultiProgress,
+        artist: &str,
+        title: &str,
+        transferred: Arc<AtomicU64>,
+    ) -> Self {
         let pb = progress.add(ProgressBar::new(0));
         pb.set_style(
             ProgressStyle::default_bar()
                 .template("{spinner:.green} {msg} [{bar:30.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec})")
                 .unwrap()
+                .tick_strings(&tick_strings())
                 .progress_chars("#>-"),
         );
 
@@ -125,6 +298,8 @@ impl CliProgressReporter {
         Self {
             pb,
             display_name: short_name,
+            transferred,
+            position: AtomicU64::new(0),
         }
     }
 }
@@ -146,12 +321,29 @@ impl DownloadProgressReporter for CliProgressReporter {
         })
     }
 
+    fn on_encoding_wait(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async move {
+            self.pb.set_message(format!(
+                "{} (preparing, attempt {attempt}, ~{}s elapsed)",
+                self.display_name,
+                elapsed.as_secs()
+            ));
+        })
+    }
+
     fn on_progress(
         &self,
         downloaded: u64,
         _total: Option<u64>,
     ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
         Box::pin(async move {
+            let previous = self.position.swap(downloaded, Ordering::Relaxed);
+            self.transferred
+                .fetch_add(downloaded.saturating_sub(previous), Ordering::Relaxed);
             self.pb.set_position(downloaded);
         })
     }
@@ -163,6 +355,22 @@ impl DownloadProgressReporter for CliProgressReporter {
         })
     }
 
+    fn on_extract_progress(
+        &self,
+        entry: &str,
+        done: usize,
+        total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let entry = truncate_str(entry, 30);
+        Box::pin(async move {
+            self.pb.set_message(format!(
+                "{} (extracting {}%: {entry})",
+                self.display_name,
+                done * 100 / total.max(1)
+            ));
+        })
+    }
+
     fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
         Box::pin(async move {
             self.pb.finish_and_clear();
@@ -175,16 +383,3 @@ impl DownloadProgressReporter for CliProgressReporter {
         })
     }
 }
-
-// Usage in CLI:
-async fn cli_download(
-    client: &BandcampClient,
-    item: &LibraryItem,
-    output_dir: &Path,
-    format: AudioFormat,
-    name_format: Option<&str>,
-    progress: &MultiProgress,
-) -> Result<PathBuf> {
-    let reporter = CliProgressReporter::new(progress, &item.artist, &item.title);
-    download_item(client, item, output_dir, format, name_format, reporter).await
-}
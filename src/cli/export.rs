@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use tracing::warn;
+
+use crate::core::client::BandcampClient;
+use crate::core::discogs::Discogs;
+use crate::core::gallery::write_gallery;
+use crate::core::library::{ItemType, LibraryItem};
+use crate::core::listenbrainz::ListenBrainz;
+use crate::core::state::{CollectionSnapshot, Manifest};
+use crate::error::{BandcampError, Result};
+use crate::i18n::{Msg, tr_fmt};
+
+/// Where `export` sends the collection, tokens only for the services asked for
+pub struct ExportTargets<'a> {
+    pub html: Option<&'a Path>,
+    pub discogs_token: Option<&'a str>,
+    pub listenbrainz_token: Option<&'a str>,
+    pub dry_run: bool,
+}
+
+pub async fn run_export(output: &Path, targets: ExportTargets<'_>) -> Result<()> {
+    let snapshot = CollectionSnapshot::load(output)?;
+    if snapshot.items.is_empty() {
+        return Err(BandcampError::NoSnapshot(output.to_path_buf()));
+    }
+
+    if let Some(html) = targets.html {
+        export_html(output, html, &snapshot)?;
+    }
+    if let Some(token) = targets.discogs_token {
+        export_discogs(token, &snapshot.items, targets.dry_run).await?;
+    }
+    if let Some(token) = targets.listenbrainz_token {
+        export_listenbrainz(token, &snapshot.items, targets.dry_run).await?;
+    }
+
+    Ok(())
+}
+
+fn export_html(output: &Path, html: &Path, snapshot: &CollectionSnapshot) -> Result<()> {
+    let manifest = Manifest::load(output)?;
+
+    let entries = write_gallery(html, output, &snapshot.items, &manifest)?;
+    let downloaded = entries.iter().filter(|e| e.local.is_some()).count();
+    println!(
+        "{}",
+        tr_fmt(
+            Msg::ExportWroteGallery,
+            &[
+                &html.join("index.html").display(),
+                &entries.len(),
+                &downloaded
+            ]
+        )
+    );
+    if let Some(fetched_at) = snapshot.fetched_at {
+        println!(
+            "{}",
+            tr_fmt(
+                Msg::ExportSnapshotAge,
+                &[&fetched_at.format("%Y-%m-%d %H:%M UTC")]
+            )
+        );
+    }
+
+    Ok(())
+}
+
+async fn export_discogs(token: &str, items: &[LibraryItem], dry_run: bool) -> Result<()> {
+    let discogs = Discogs::connect(token).await?;
+    let (mut added, mut present, mut failed) = (0, 0, 0);
+    let mut not_found = Vec::new();
+
+    for item in items {
+        let result = async {
+            let Some(release) = discogs.find_release(item).await? else {
+                return Ok(None);
+            };
+            if discogs.in_collection(release).await? {
+                return Ok(Some(false));
+            }
+            if !dry_run {
+                discogs.add(release).await?;
+            }
+            println!(
+                "Added {} - {} (https://www.discogs.com/release/{release})",
+                item.artist, item.title
+            );
+            Ok::<_, BandcampError>(Some(true))
+        }
+        .await;
+
+        match result {
+            Ok(Some(true)) => added += 1,
+            Ok(Some(false)) => present += 1,
+            Ok(None) => not_found.push(item),
+            Err(e) => {
+                warn!(
+                    "Couldn't export {} - {} to Discogs: {e}",
+                    item.artist, item.title
+                );
+                failed += 1;
+            }
+        }
+    }
+
+    if !not_found.is_empty() {
+        println!();
+        println!("Not found on Discogs:");
+        for item in &not_found {
+            println!("  {} - {}", item.artist, item.title);
+        }
+    }
+    println!();
+    println!(
+        "Discogs: {added} added{}, {present} already in the collection, {} not found, {failed} failed.",
+        if dry_run { " (dry run)" } else { "" },
+        not_found.len()
+    );
+    Ok(())
+}
+
+async fn export_listenbrainz(token: &str, items: &[LibraryItem], dry_run: bool) -> Result<()> {
+    let listenbrainz = ListenBrainz::connect(token).await?;
+    // Track lists come from the public album pages, no login needed
+    let bandcamp = BandcampClient::new();
+    let (mut loved, mut unknown, mut failed) = (0, 0, 0);
+
+    for item in items {
+        let (tracks, release) = match item.item_type {
+            ItemType::Track => (vec![item.title.clone()], None),
+            ItemType::Album | ItemType::Package => match bandcamp.get_item_details(item).await {
+                Ok(details) => (
+                    details
+                        .tracks
+                        .into_iter()
+                        .map(|track| track.title)
+                        .collect(),
+                    Some(item.title.as_str()),
+                ),
+                Err(e) => {
+                    warn!(
+                        "Couldn't read the tracks of {} - {}: {e}",
+                        item.artist, item.title
+                    );
+                    failed += 1;
+                    continue;
+                }
+            },
+        };
+
+        for track in tracks {
+            let result = async {
+                let Some(mbid) = listenbrainz
+                    .find_recording(&item.artist, &track, release)
+                    .await?
+                else {
+                    return Ok(false);
+                };
+                if !dry_run {
+                    listenbrainz.love(&mbid).await?;
+                }
+                println!("Loved {} - {track}", item.artist);
+                Ok::<_, BandcampError>(true)
+            }
+            .await;
+
+            match result {
+                Ok(true) => loved += 1,
+                Ok(false) => unknown += 1,
+                Err(e) => {
+                    warn!(
+                        "Couldn't love {} - {track} on ListenBrainz: {e}",
+                        item.artist
+                    );
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "ListenBrainz: {loved} recordings loved{}, {unknown} not found, {failed} failed.",
+        if dry_run { " (dry run)" } else { "" },
+    );
+    Ok(())
+}
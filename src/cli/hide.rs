@@ -0,0 +1,41 @@
+use crate::core::client::BandcampClient;
+use crate::error::{BandcampError, Result};
+use crate::i18n::{Msg, tr_fmt};
+
+pub async fn run_set_hidden(cookie: Option<String>, id: &str, hidden: bool) -> Result<()> {
+    let cookie = cookie.ok_or_else(|| {
+        BandcampError::AuthError(
+            "No cookie provided. Set --cookie flag or BANDCAMP_COOKIE env var".to_string(),
+        )
+    })?;
+
+    let mut client = BandcampClient::new();
+    client.validate_cookie(&cookie).await?;
+
+    // The API needs the item type as well, so look the item up first
+    let item = client
+        .get_collection()
+        .await?
+        .into_iter()
+        .find(|item| item.id == id)
+        .ok_or_else(|| {
+            BandcampError::ParseError(format!("No item with ID {id} in your library"))
+        })?;
+
+    if item.is_hidden == hidden {
+        let msg = if hidden {
+            Msg::HideAlreadyHidden
+        } else {
+            Msg::HideAlreadyVisible
+        };
+        println!("{}", tr_fmt(msg, &[&item.artist, &item.title]));
+        return Ok(());
+    }
+
+    client.set_item_hidden(&item, hidden).await?;
+
+    let msg = if hidden { Msg::HideHid } else { Msg::HideUnhid };
+    println!("{}", tr_fmt(msg, &[&item.artist, &item.title]));
+
+    Ok(())
+}
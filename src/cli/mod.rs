@@ -1,6 +1,17 @@
+pub mod clean;
 pub mod commands;
 pub mod completions;
+pub mod dedupe;
+pub mod doctor;
 pub mod download;
+pub mod export;
+pub mod hide;
+pub mod progress;
+pub mod resolve;
 pub mod run;
+pub mod stats;
+pub mod sync;
+pub mod template;
+pub mod wishlist;
 
 pub use commands::Cli;
@@ -0,0 +1,53 @@
+use std::io::{self, Write};
+use std::sync::LazyLock;
+
+use indicatif::{MultiProgress, ProgressDrawTarget};
+
+use crate::term::logs::remember_log;
+use crate::term::spinner;
+
+static MULTI_PROGRESS: LazyLock<MultiProgress> = LazyLock::new(MultiProgress::new);
+
+/// Container for every CLI progress bar, so log output can be printed without tearing them
+pub fn multi_progress() -> &'static MultiProgress {
+    &MULTI_PROGRESS
+}
+
+/// Stops every progress bar from being drawn, for `--quiet`
+pub fn hide_progress() {
+    MULTI_PROGRESS.set_draw_target(ProgressDrawTarget::hidden());
+}
+
+/// Spinner frames for indicatif, which shows the last entry once a spinner finishes
+pub fn tick_strings() -> Vec<&'static str> {
+    let mut ticks = spinner::frames().to_vec();
+    ticks.push(" ");
+    ticks
+}
+
+/// tracing writer that hides the progress bars while a log line is printed and redraws them after
+pub struct LogWriter {
+    /// Log to stderr instead of stdout, which is left to machine-readable output
+    pub stderr: bool,
+}
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        remember_log(buf);
+        MULTI_PROGRESS.suspend(|| {
+            if self.stderr {
+                io::stderr().write(buf)
+            } else {
+                io::stdout().write(buf)
+            }
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.stderr {
+            io::stderr().flush()
+        } else {
+            io::stdout().flush()
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+use crate::cli::commands::BandcampUrl;
+use crate::cli::run::item_matches_urls;
+use crate::core::client::BandcampClient;
+use crate::core::library::{AudioFormat, LibraryItem};
+use crate::error::{BandcampError, Result};
+
+/// Encoding polls before an item is given up on, the same as for downloads
+const ENCODING_ATTEMPTS: u32 = 30;
+
+/// Items waited for at once, each one polls Bandcamp until its encoding is ready
+const RESOLVE_CONCURRENCY: usize = 4;
+
+/// Prints the signed download link of every item matching `targets`, one per line, for
+/// downloading with other tools. Fails when any of them has no link.
+pub async fn run_resolve(
+    cookie: Option<String>,
+    targets: &[String],
+    format: AudioFormat,
+) -> Result<()> {
+    let cookie = cookie.ok_or_else(|| {
+        BandcampError::AuthError(
+            "No cookie provided. Set --cookie flag or BANDCAMP_COOKIE env var".to_string(),
+        )
+    })?;
+
+    let (ids, urls): (Vec<_>, Vec<_>) = targets
+        .iter()
+        .partition(|target| target.chars().all(|c| c.is_ascii_digit()));
+    let urls = urls
+        .into_iter()
+        .map(|url| {
+            BandcampUrl::parse(url)
+                .ok_or_else(|| BandcampError::ParseError(format!("Not a Bandcamp URL: {url}")))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut client = BandcampClient::new();
+    client.validate_cookie(&cookie).await?;
+
+    let items: Vec<_> = client
+        .get_collection()
+        .await?
+        .into_iter()
+        .filter(|item| ids.contains(&&item.id) || item_matches_urls(item, &urls))
+        .collect();
+    if items.is_empty() {
+        return Err(BandcampError::ParseError(format!(
+            "Nothing in your library matches {}",
+            targets.join(", ")
+        )));
+    }
+
+    // Links are printed as they come in, so a long encoding wait doesn't hold up the others
+    let total = items.len();
+    let mut resolved = 0;
+    let failed = resolve_links(Arc::new(client), items, format, |_, url| {
+        println!("{url}");
+        resolved += 1;
+    })
+    .await;
+    for (item, e) in &failed {
+        warn!("{} - {}: {e}", item.artist, item.title);
+    }
+
+    // stderr, so stdout stays a plain list of links
+    eprintln!(
+        "Resolved {resolved} of {total} links. The links are signed and stop working after a short while, start the downloads right away.",
+    );
+
+    if resolved == total {
+        Ok(())
+    } else {
+        Err(BandcampError::DownloadError(format!(
+            "{} of {total} links couldn't be resolved",
+            total - resolved
+        )))
+    }
+}
+
+/// Waits for the signed links of `items`, a few at a time, handing each to `on_link` as it
+/// comes in. Returns the items without a link and why.
+pub async fn resolve_links(
+    client: Arc<BandcampClient>,
+    items: Vec<LibraryItem>,
+    format: AudioFormat,
+    mut on_link: impl FnMut(&LibraryItem, &str),
+) -> Vec<(LibraryItem, BandcampError)> {
+    let permits = Arc::new(Semaphore::new(RESOLVE_CONCURRENCY));
+    let mut lookups = JoinSet::new();
+    let mut failed = Vec::new();
+
+    for item in items {
+        if item.is_stream_only() {
+            failed.push((item, BandcampError::StreamOnly));
+            continue;
+        }
+        let client = client.clone();
+        let permits = permits.clone();
+        lookups.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let url = client
+                .get_download_url_with_retry(&item, format, ENCODING_ATTEMPTS)
+                .await;
+            (item, url)
+        });
+    }
+
+    while let Some(lookup) = lookups.join_next().await {
+        match lookup {
+            Ok((item, Ok(url))) => on_link(&item, &url),
+            Ok((item, Err(e))) => failed.push((item, e)),
+            Err(e) => warn!("Resolving a link stopped: {e}"),
+        }
+    }
+    failed
+}
@@ -1,127 +1,807 @@
+use std::sync::Arc;
 use std::time::Duration;
 
-use crate::error::Result;
+use crate::error::{BandcampError, Result};
+use chrono::{DateTime, Utc};
 use indicatif::{ProgressBar, ProgressStyle};
-use tracing::info;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::{debug, info, warn};
 
-use crate::cli::commands::{BandcampUrl, DownloadArgs, DownloadTarget};
-use crate::cli::download::DownloadManager;
-use crate::core::client::BandcampClient;
-use crate::core::library::LibraryItem;
+use crate::cli::commands::{BandcampUrl, DownloadArgs, DownloadTarget, FanUrl};
+use crate::cli::download::{ArtistProgressReporter, DownloadManager};
+use crate::cli::progress::{multi_progress, tick_strings};
+use crate::core::artwork::ArtworkPolicy;
+use crate::core::audit::AuditLog;
+use crate::core::client::{BandcampClient, RequestCounter, RequestCounts};
+use crate::core::download::{DownloadOptions, DownloadSummary, SkipReason};
+use crate::core::format_map::{ArtworkMap, FormatMap};
+use crate::core::library::{
+    DEFAULT_TRACK_FORMAT, ItemDetails, LibraryItem, PurchaseInfo, unknown_template_variables,
+    validate_name_format,
+};
+use crate::core::notify::{Notifier, NotifyTarget};
+use crate::core::plan::{PlanStatus, audit_names, recorded_status};
+use crate::core::progress_file::ProgressFile;
+use crate::core::report::{write_naming_report, write_report};
+use crate::core::routing::RouteMap;
+use crate::core::state::{
+    BandwidthMeter, BatchRecord, CollectionSnapshot, LastRun, Manifest, PendingEncodings,
+    PreorderTracks, ResumeCursor,
+};
+use crate::core::tags::ffmpeg_available;
+use crate::core::utils::{check_output_dir, glob_match, open_in_file_manager};
+use crate::core::verify::ffprobe_available;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+/// Release pages of another fan's collection fetched at once, to see which are free
+const FAN_ITEM_LOOKUPS: usize = 4;
+
+/// How a download run ended, a batch with failures still counts as a successful run
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+    pub failed: usize,
+    /// Items still waiting on Bandcamp's encoding, see [`PendingEncodings`]
+    pub pending: usize,
+}
+
+pub async fn run_download(args: DownloadArgs, tick_rate: Duration) -> Result<RunOutcome> {
+    let target = args.target.clone().unwrap_or(DownloadTarget::New);
+    let notifier = Arc::new(Notifier::new(notify_targets(&args))?);
+    let covers_collection = covers_collection(&args, &target);
+    let artwork = args.artwork();
+    let permissions = args.permissions();
 
-pub async fn run_download(args: DownloadArgs) -> Result<()> {
     let cookie = args.cookie.ok_or_else(|| {
         crate::error::BandcampError::AuthError(
             "No cookie provided. Set --cookie flag or BANDCAMP_COOKIE env var".to_string(),
         )
     })?;
 
+    // Read before going online so a typo in the file fails fast
+    let format_map = args
+        .format_map
+        .as_deref()
+        .map(FormatMap::load)
+        .transpose()?;
+    let format_for = |item: &LibraryItem| match &format_map {
+        Some(format_map) => format_map.resolve(item, args.format),
+        None => args.format,
+    };
+    let route_map = args.route_map.as_deref().map(RouteMap::load).transpose()?;
+    let artwork_map = args
+        .artwork_map
+        .as_deref()
+        .map(ArtworkMap::load)
+        .transpose()?;
+    let output_dir_for = |item: &LibraryItem| {
+        let output_dir = match &route_map {
+            Some(route_map) => route_map.resolve(item, format_for(item), &args.output),
+            None => args.output.clone(),
+        };
+        args.layout.resolve(item, &output_dir)
+    };
+
+    if args.verify_audio {
+        ffprobe_available()?;
+    }
+    let embeds_artwork = artwork == ArtworkPolicy::Embedded
+        || artwork_map
+            .as_ref()
+            .is_some_and(|map| map.uses(ArtworkPolicy::Embedded));
+    if args.tag_dates || embeds_artwork {
+        ffmpeg_available()?;
+    }
+    args.downloader.check_available()?;
+    if !args.dry_run {
+        check_output_dir(&args.output)?;
+    }
+
+    let first = args
+        .first
+        .iter()
+        .map(|url| {
+            BandcampUrl::parse(url).ok_or_else(|| {
+                BandcampError::ParseError(format!("--first: not a Bandcamp URL: {url}"))
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let batch_id = args.batch_id.clone().unwrap_or_else(BatchRecord::new_id);
+    let mut batch = BatchRecord::load(&args.output, &batch_id)?;
+
+    if let Some(format) = &args.custom_format {
+        validate_name_format(format)?;
+        for name in unknown_template_variables(format) {
+            warn!(
+                "Unknown variable {{{name}}} in --custom-format, check it with `bannedcamp template test`"
+            );
+        }
+    }
+
+    // artist-all nests every download in a directory named after its artist
+    let custom_format = match target {
+        DownloadTarget::ArtistAll => Some(format!(
+            "{{artist}}/{}",
+            args.custom_format
+                .as_deref()
+                .unwrap_or(DEFAULT_TRACK_FORMAT)
+        )),
+        _ => args.custom_format.clone(),
+    };
+
+    let sanitize = args.filenames.sanitize_options();
+
     let mut client = BandcampClient::new();
+    let requests = client.requests();
 
-    let spinner = ProgressBar::new_spinner();
+    // Purchases made while this run is going count as new for the next one
+    let run_started = Utc::now();
+    let mut last_run = LastRun::load(&args.output)?;
+    let mut pending = PendingEncodings::load(&args.output)?;
+    let mut preorders = PreorderTracks::load(&args.output)?;
+
+    let spinner = multi_progress().add(ProgressBar::new_spinner());
     spinner.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner} {msg}")
-            .unwrap(),
+            .unwrap()
+            .tick_strings(&tick_strings()),
     );
-    spinner.enable_steady_tick(Duration::from_millis(80));
+    spinner.enable_steady_tick(tick_rate);
 
-    spinner.set_message("Validating session...");
+    spinner.set_message(tr(Msg::ValidatingSession));
     info!("Validating session cookie...");
-    client.validate_cookie(&cookie).await?;
+    let credentials = client.validate_cookie(&cookie).await?;
+    let client = Arc::new(client);
 
-    spinner.set_message("Loading library...");
+    spinner.set_message(tr(Msg::LoadingLibrary));
     info!("Fetching library...");
     let items = client.get_collection().await?;
     info!("Found {} items in library", items.len());
+    pending.forget_missing(&items);
+    if !args.dry_run {
+        CollectionSnapshot::new(&items).save(&args.output)?;
+    }
+
+    // Fan pages in a `url` target, the logged in fan's own page stands for the whole library
+    let mut own_fan_page = false;
+    let mut from_fans = Vec::new();
+    if let DownloadTarget::Url { urls } = &target {
+        for fan in urls.iter().filter_map(|u| FanUrl::parse(u)) {
+            let fan_id = client.get_fan_id(&fan.username).await?;
+            if fan_id == credentials.fan_id {
+                info!(
+                    "{} is your own fan page, downloading your whole library",
+                    fan.username
+                );
+                own_fan_page = true;
+            } else {
+                from_fans.extend(fan_items(&client, &fan.username, fan_id, &items).await?);
+            }
+        }
+    }
 
     spinner.finish_and_clear();
 
-    let items_to_download = match &args.target {
-        DownloadTarget::All => items,
-        DownloadTarget::Url { urls } => {
+    // Everything left out of the batch, so the summary accounts for every item
+    let mut skipped = Vec::new();
+
+    let items_to_download = match &target {
+        DownloadTarget::Pending => {
+            info!("{} items are waiting on Bandcamp", pending.len());
+            items
+                .into_iter()
+                .filter(|item| pending.contains(item))
+                .collect()
+        }
+        DownloadTarget::Url { urls } if !own_fan_page => {
             info!("Filtering by {} URL(s)", urls.len());
             let parsed: Vec<_> = urls.iter().filter_map(|u| BandcampUrl::parse(u)).collect();
-            items
+            let mut selected: Vec<_> = items
                 .into_iter()
                 .filter(|item| item_matches_urls(item, &parsed))
-                .collect()
+                .collect();
+            for item in from_fans {
+                if !selected.iter().any(|s| s.id == item.id) {
+                    selected.push(item);
+                }
+            }
+            selected
         }
+        // Hidden items are hidden for a reason, explicit URLs still download them
+        _ if !args.include_hidden => {
+            let (hidden, visible): (Vec<_>, Vec<_>) =
+                items.into_iter().partition(|item| item.is_hidden);
+            if !hidden.is_empty() {
+                info!(
+                    "Skipping {} hidden items, use --include-hidden to download them",
+                    hidden.len()
+                );
+            }
+            skipped.extend(hidden.into_iter().map(|item| (item, SkipReason::Hidden)));
+            visible
+        }
+        _ => items,
     };
 
-    // Filter out existing downloads if skip_existing is set
-    let items_to_download = if args.skip_existing {
-        let before_count = items_to_download.len();
-        let filtered: Vec<_> = items_to_download
+    let items_to_download = if matches!(target, DownloadTarget::New) {
+        match last_run.started_at {
+            Some(started_at) => info!("Looking for items purchased since {started_at}"),
+            None => info!("No earlier complete run, downloading everything"),
+        }
+        // Whatever Bandcamp was still encoding is retried on every pass, also after runs that
+        // didn't cover the collection and so left `last_run` as it was
+        let (waiting, rest): (Vec<_>, Vec<_>) = items_to_download
             .into_iter()
-            .filter(|item| {
-                let path = args
-                    .output
-                    .join(item.construct_filename(args.format, args.custom_format.as_deref()));
-                !path.exists()
-            })
-            .collect();
-        let skipped = before_count - filtered.len();
-        if skipped > 0 {
-            info!("Skipping {skipped} existing downloads");
+            .partition(|item| pending.contains(item));
+        if !waiting.is_empty() {
+            info!("Retrying {} items waiting on Bandcamp", waiting.len());
         }
+        waiting
+            .into_iter()
+            .chain(last_run.new_items(rest))
+            .collect()
+    } else {
+        items_to_download
+    };
+
+    // Preorders have nothing to download until release day unless they offer some tracks early,
+    // and then nothing more once those are here. Stream-only items never do.
+    let (unavailable, mut items_to_download): (Vec<_>, Vec<_>) =
+        items_to_download.into_iter().partition(|item| {
+            (item.is_preorder && !args.preorder_tracks)
+                || preorders.has_early_tracks(item)
+                || item.is_stream_only()
+        });
+    skipped.extend(unavailable.into_iter().map(|item| {
+        let reason = if item.is_preorder {
+            SkipReason::Preorder
+        } else {
+            SkipReason::StreamOnly
+        };
+        (item, reason)
+    }));
+
+    // Keep each artist's items together so their directories fill up one after another
+    if matches!(target, DownloadTarget::ArtistAll) {
+        items_to_download.sort_by_cached_key(|item| item.artist.to_lowercase());
+    }
+
+    // --first items jump the queue, otherwise keeping their order
+    let items_to_download = if first.is_empty() {
+        items_to_download
+    } else {
+        let (priority, rest): (Vec<_>, Vec<_>) = items_to_download
+            .into_iter()
+            .partition(|item| item_matches_urls(item, &first));
+        if priority.is_empty() {
+            warn!("No items to download match --first");
+        } else {
+            info!("Downloading {} items first", priority.len());
+        }
+        priority.into_iter().chain(rest).collect()
+    };
+
+    let items_to_download = if args.exclude_artist.is_empty() && args.exclude_title.is_empty() {
+        items_to_download
+    } else {
+        let (excluded, filtered): (Vec<_>, Vec<_>) = items_to_download
+            .into_iter()
+            .partition(|item| is_excluded(item, &args.exclude_artist, &args.exclude_title));
+        info!("Excluding {} items", excluded.len());
+        skipped.extend(
+            excluded
+                .into_iter()
+                .map(|item| (item, SkipReason::Excluded)),
+        );
+        filtered
+    };
+
+    let manifest = Manifest::load(&args.output)?;
+    let planned_path = |item: &LibraryItem| {
+        let path_name =
+            item.construct_filename(format_for(item), custom_format.as_deref(), &sanitize);
+        args.album_output
+            .path(item, &output_dir_for(item), &path_name)
+    };
+    let status = |item: &LibraryItem| {
+        recorded_status(
+            item,
+            &planned_path(item),
+            format_for(item),
+            &manifest,
+            &args.output,
+        )
+    };
+
+    // Filter out existing downloads if skip_existing is set, released preorders only have their
+    // early tracks there
+    let items_to_download = if args.skip_existing {
+        let (existing, filtered): (Vec<_>, Vec<_>) =
+            items_to_download.into_iter().partition(|item| {
+                if preorders.is_released(item) {
+                    info!(
+                        "{} - {} is out, downloading the full release",
+                        item.artist, item.title
+                    );
+                    return false;
+                }
+                status(item) == PlanStatus::Exists
+            });
+        if !existing.is_empty() {
+            info!("Skipping {} existing downloads", existing.len());
+        }
+        skipped.extend(
+            existing
+                .into_iter()
+                .map(|item| (item, SkipReason::AlreadyExists)),
+        );
         filtered
     } else {
         items_to_download
     };
 
+    // Re-running a batch only picks up what it didn't finish
+    let (completed, items_to_download): (Vec<_>, Vec<_>) = items_to_download
+        .into_iter()
+        .partition(|item| batch.is_completed(item));
+    if !completed.is_empty() {
+        info!(
+            "Skipping {} items already completed in batch {}",
+            completed.len(),
+            batch.id
+        );
+    }
+    skipped.extend(
+        completed
+            .into_iter()
+            .map(|item| (item, SkipReason::CompletedInBatch)),
+    );
+
+    let mut cursor = args
+        .max_items
+        .map(|_| ResumeCursor::load(&args.output))
+        .transpose()?;
+
+    // Items left for later --max-items runs
+    let mut deferred = 0;
+    let items_to_download = match (&cursor, args.max_items) {
+        (Some(cursor), Some(max)) => {
+            let (batch, remaining) = cursor.next_batch(items_to_download, max);
+            deferred = remaining;
+            if batch.is_empty() {
+                if !args.json {
+                    println!("{}", tr(Msg::CursorFinished));
+                }
+                return Ok(RunOutcome {
+                    failed: 0,
+                    pending: pending.len(),
+                });
+            }
+            if !args.json {
+                println!("{}", tr_fmt(Msg::CursorBatch, &[&batch.len(), &remaining]));
+            }
+            batch
+        }
+        _ => items_to_download,
+    };
+
     if items_to_download.is_empty() {
-        match &args.target {
-            DownloadTarget::All => {
+        let summary = DownloadSummary {
+            skipped,
+            ..Default::default()
+        };
+        log_requests(&requests, run_started);
+        // Pending items held back for good, e.g. hidden since, aren't waited for any longer
+        if !args.dry_run {
+            pending.record(&summary);
+            pending.save(&args.output)?;
+        }
+        let outcome = RunOutcome {
+            failed: 0,
+            pending: pending.len(),
+        };
+        if args.json {
+            println!("{}", summary_json(&summary, &batch, requests.counts()));
+            return Ok(outcome);
+        }
+
+        match &target {
+            DownloadTarget::New => println!("{}", tr(Msg::NothingNew)),
+            DownloadTarget::Pending => println!("{}", tr(Msg::NothingPending)),
+            DownloadTarget::All | DownloadTarget::ArtistAll => {
                 if args.skip_existing {
-                    println!("All items already downloaded");
+                    println!("{}", tr(Msg::AllDownloaded));
                 } else {
-                    println!("No items found in library");
+                    println!("{}", tr(Msg::LibraryEmpty));
                 }
             }
             DownloadTarget::Url { urls } => {
                 if args.skip_existing {
-                    println!("All matching items already downloaded");
+                    println!("{}", tr(Msg::AllMatchingDownloaded));
                 } else {
-                    println!("No items found matching URL(s): {}", urls.join(", "));
+                    println!("{}", tr_fmt(Msg::NoUrlMatches, &[&urls.join(", ")]));
                 }
             }
         }
-        return Ok(());
+        print_skipped(&summary);
+        if !args.dry_run && covers_collection {
+            last_run.record(run_started, &summary);
+            last_run.save(&args.output)?;
+        }
+        return Ok(outcome);
     }
 
+    if let Some(naming_report) = &args.naming_report {
+        let planned: Vec<_> = items_to_download
+            .iter()
+            .map(|item| (item.clone(), planned_path(item)))
+            .collect();
+        let audit = audit_names(&planned, custom_format.as_deref(), &sanitize);
+        write_naming_report(naming_report, &audit)?;
+        info!("Wrote naming report to {}", naming_report.display());
+        if !args.json && !audit.is_empty() {
+            println!(
+                "{}",
+                tr_fmt(
+                    Msg::NamingIssues,
+                    &[
+                        &audit.sanitized.len(),
+                        &audit.collisions.len(),
+                        &naming_report.display()
+                    ]
+                )
+            );
+        }
+    }
+
+    let mut failed = 0;
     if args.dry_run {
-        println!("Would download {} items.", items_to_download.len());
+        println!(
+            "{}",
+            tr_fmt(Msg::WouldDownload, &[&items_to_download.len()])
+        );
+        let mut plan = Vec::new();
         for item in &items_to_download {
-            let path_name = item.construct_filename(args.format, args.custom_format.as_deref());
-            println!("{}", args.output.join(path_name).display());
+            let path = planned_path(item);
+            if !args.diff {
+                println!("{}", path.display());
+                continue;
+            }
+
+            let status = status(item);
+            let (marker, label) = match &status {
+                PlanStatus::New => ('+', tr(Msg::PlanNew).to_string()),
+                PlanStatus::Exists => ('=', tr(Msg::SkipAlreadyExists).to_string()),
+                PlanStatus::OtherFormat(ext) => ('~', tr_fmt(Msg::PlanOtherFormat, &[ext])),
+            };
+            println!("{marker} {}  ({label})", path.display());
+            plan.push(status);
+        }
+        if args.diff {
+            let count = |f: fn(&PlanStatus) -> bool| plan.iter().filter(|s| f(s)).count();
+            println!(
+                "{}",
+                tr_fmt(
+                    Msg::PlanSummary,
+                    &[
+                        &count(|s| *s == PlanStatus::New),
+                        &count(|s| *s == PlanStatus::Exists),
+                        &count(|s| matches!(s, PlanStatus::OtherFormat(_))),
+                    ]
+                )
+            );
         }
+        log_requests(&requests, run_started);
     } else {
-        let manager = DownloadManager::new(
+        let audit_log = args
+            .audit_log
+            .as_deref()
+            .map(AuditLog::open)
+            .transpose()?
+            .map(|audit_log| Arc::new(audit_log.with_batch_id(batch.id.clone())));
+        let bandwidth = Arc::new(BandwidthMeter::open(&args.output, args.monthly_cap)?);
+
+        if !args.json {
+            println!("{}", tr_fmt(Msg::BatchId, &[&batch.id]));
+        }
+
+        let mut manager = DownloadManager::new(
             client,
-            args.output,
+            args.output.clone(),
             args.format,
-            args.custom_format,
+            custom_format,
             args.parallel as usize,
+            DownloadOptions {
+                audit_log,
+                checksums: args.checksums,
+                purchase_info: args.purchase_info,
+                include_bonus_items: args.include_bonus_items,
+                verify_audio: args.verify_audio,
+                nfo: args.nfo,
+                tag_dates: args.tag_dates,
+                sanitize: sanitize.clone(),
+                missing_format: args.missing_format,
+                bandwidth: Some(bandwidth),
+                album_output: args.album_output,
+                keep_original_names: args.keep_original_names,
+                artwork,
+                artwork_map,
+                downloader: args.downloader,
+                permissions,
+                output_root: Some(args.output.clone()),
+            },
         );
 
-        let summary = manager.download_items(items_to_download).await?;
+        if let Some(format_map) = format_map.clone() {
+            manager.set_format_map(format_map);
+        }
 
-        println!(
-            "Downloaded {} items, {} failed.",
-            summary.success_count(),
-            summary.failure_count()
-        );
+        if let Some(route_map) = route_map.clone() {
+            manager.set_route_map(route_map);
+        }
+        manager.set_layout(args.layout);
+        if let Some(window) = args.window {
+            manager.set_window(window);
+        }
+        manager.set_tracks_per_slot(args.tracks_per_slot as usize);
+        if let Some(per_artist) = args.per_artist {
+            manager.set_per_artist(per_artist);
+        }
+        if let Some(min_free) = args.min_free_space {
+            manager.set_min_free_space(min_free);
+        }
+
+        if !notifier.is_empty() {
+            manager.register_reporter(notifier.clone());
+        }
+
+        if matches!(target, DownloadTarget::ArtistAll) {
+            manager.register_reporter(Arc::new(ArtistProgressReporter::new(&items_to_download)));
+        }
+
+        if let Some(path) = &args.progress_file {
+            manager.register_reporter(Arc::new(ProgressFile::new(path, items_to_download.len())));
+        }
+
+        let mut summary = manager.download_items(items_to_download).await?;
+        summary.skipped.extend(skipped);
+        notifier.send_summary(&summary).await;
+
+        batch.record(&summary);
+        if !batch.is_empty() {
+            batch.save(&args.output)?;
+        }
 
-        for (_, path) in &summary.succeeded {
-            println!("{}", path.display());
+        if let Some(cursor) = &mut cursor {
+            cursor.record(&summary);
+            cursor.save(&args.output)?;
         }
+
+        pending.record(&summary);
+        pending.save(&args.output)?;
+
+        preorders.record(&summary);
+        preorders.save(&args.output)?;
+
+        if deferred == 0 && !summary.site_down && covers_collection {
+            last_run.record(run_started, &summary);
+            last_run.save(&args.output)?;
+        }
+
+        if let Some(report) = &args.report {
+            write_report(report, &summary)?;
+            info!("Wrote report to {}", report.display());
+        }
+
+        log_requests(&requests, run_started);
+        if args.json {
+            println!("{}", summary_json(&summary, &batch, requests.counts()));
+        } else {
+            println!(
+                "{}",
+                tr_fmt(
+                    Msg::DownloadedSummary,
+                    &[&summary.success_count(), &summary.failure_count()]
+                )
+            );
+            print_skipped(&summary);
+
+            let counts = requests.counts();
+            println!(
+                "{}",
+                tr_fmt(
+                    Msg::RequestSummary,
+                    &[
+                        &counts.total(),
+                        &counts.collection_pages,
+                        &counts.download_pages,
+                        &counts.item_pages,
+                        &counts.status_polls
+                    ]
+                )
+            );
+
+            let not_ready = summary
+                .skipped
+                .iter()
+                .filter(|(_, reason)| *reason == SkipReason::EncodingPending)
+                .count();
+            if not_ready > 0 {
+                println!("{}", tr_fmt(Msg::PendingHint, &[&not_ready]));
+            }
+
+            for (item, format) in &summary.fallbacks {
+                println!(
+                    "{}",
+                    tr_fmt(
+                        Msg::DownloadedAsFallback,
+                        &[&item.artist, &item.title, &format.display_name()]
+                    )
+                );
+            }
+
+            for (_, path) in &summary.succeeded {
+                println!("{}", path.display());
+            }
+        }
+
+        if summary.site_down {
+            return Err(BandcampError::SiteDown);
+        }
+
+        if args.open_on_complete
+            && summary.failed.is_empty()
+            && let Err(e) = open_in_file_manager(&args.output)
+        {
+            warn!("Failed to open {}: {e}", args.output.display());
+        }
+        failed = summary.failure_count();
+    }
+
+    Ok(RunOutcome {
+        failed,
+        pending: pending.len(),
+    })
+}
+
+/// Whether the run went over the whole collection, so it can serve as the starting point for
+/// `download new`
+fn covers_collection(args: &DownloadArgs, target: &DownloadTarget) -> bool {
+    let filtered = !args.exclude_artist.is_empty() || !args.exclude_title.is_empty();
+    !filtered
+        && matches!(
+            target,
+            DownloadTarget::All | DownloadTarget::ArtistAll | DownloadTarget::New
+        )
+}
+
+fn summary_json(
+    summary: &DownloadSummary,
+    batch: &BatchRecord,
+    requests: RequestCounts,
+) -> serde_json::Value {
+    let mut json = summary.to_json();
+    json["batch_id"] = batch.id.clone().into();
+    json["requests"] = serde_json::to_value(requests).unwrap_or_default();
+    json
+}
+
+/// Logs the requests the run sent with `-v`, to set against throttling and e.g. `--parallel`
+fn log_requests(requests: &RequestCounter, run_started: DateTime<Utc>) {
+    let counts = requests.counts();
+    info!(
+        "Sent {} requests to Bandcamp in {}s: {counts}",
+        counts.total(),
+        (Utc::now() - run_started).num_seconds()
+    );
+}
+
+/// One line per skip reason, e.g. "3 skipped (already downloaded)"
+fn print_skipped(summary: &DownloadSummary) {
+    for (reason, count) in summary.skipped_by_reason() {
+        println!("{}", tr_fmt(Msg::SkippedReasonCount, &[&count, &reason]));
+    }
+}
+
+fn notify_targets(args: &DownloadArgs) -> Vec<NotifyTarget> {
+    let mut targets = Vec::new();
+    if let Some(topic) = &args.ntfy_topic {
+        targets.push(NotifyTarget::Ntfy {
+            server: args.ntfy_server.clone(),
+            topic: topic.clone(),
+            token: args.ntfy_token.clone(),
+        });
+    }
+    if let (Some(server), Some(token)) = (&args.gotify_url, &args.gotify_token) {
+        targets.push(NotifyTarget::Gotify {
+            server: server.clone(),
+            token: token.clone(),
+        });
+    }
+    targets
+}
+
+/// What can be downloaded from another fan's collection: the releases also in `library` and
+/// the free ones. Whether a release is free takes a look at its page, a few at a time.
+async fn fan_items(
+    client: &Arc<BandcampClient>,
+    username: &str,
+    fan_id: u64,
+    library: &[LibraryItem],
+) -> Result<Vec<LibraryItem>> {
+    let collection = client.get_fan_collection(fan_id).await?;
+    let mut found = Vec::new();
+    let mut lookups = JoinSet::new();
+    let permits = Arc::new(Semaphore::new(FAN_ITEM_LOOKUPS));
+
+    for (index, item) in collection.iter().enumerate() {
+        if let Some(owned) = library.iter().find(|owned| same_release(owned, item)) {
+            found.push((index, owned.clone()));
+            continue;
+        }
+        let client = client.clone();
+        let permits = permits.clone();
+        let item = item.clone();
+        lookups.spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let details = client.get_item_details(&item).await;
+            (index, item, details)
+        });
     }
 
-    Ok(())
+    while let Some(lookup) = lookups.join_next().await {
+        let Ok((index, item, details)) = lookup else {
+            continue;
+        };
+        match details {
+            Ok(ItemDetails {
+                free_download_url: Some(url),
+                ..
+            }) => found.push((
+                index,
+                LibraryItem {
+                    download_url: url,
+                    // The purchase was theirs, nothing was bought here
+                    purchase: PurchaseInfo::default(),
+                    ..item
+                },
+            )),
+            Ok(_) => debug!("{} - {} is neither free nor yours", item.artist, item.title),
+            Err(e) => warn!(
+                "Couldn't check whether {} - {} is free: {e}",
+                item.artist, item.title
+            ),
+        }
+    }
+    // In the order of their collection, whichever page came back first
+    found.sort_by_key(|(index, _)| *index);
+
+    info!(
+        "{} of {username}'s {} items are free or yours",
+        found.len(),
+        collection.len()
+    );
+    Ok(found.into_iter().map(|(_, item)| item).collect())
+}
+
+/// Matched by artist and slug, collection item IDs are per purchase rather than per release
+fn same_release(a: &LibraryItem, b: &LibraryItem) -> bool {
+    match (&a.artist_subdomain, &a.slug, &b.artist_subdomain, &b.slug) {
+        (Some(a_artist), Some(a_slug), Some(b_artist), Some(b_slug)) => {
+            a_artist.eq_ignore_ascii_case(b_artist) && a_slug.eq_ignore_ascii_case(b_slug)
+        }
+        _ => a.item_url.is_some() && a.item_url == b.item_url,
+    }
+}
+
+fn is_excluded(item: &LibraryItem, artists: &[String], titles: &[String]) -> bool {
+    artists.iter().any(|glob| glob_match(glob, &item.artist))
+        || titles.iter().any(|glob| glob_match(glob, &item.title))
 }
 
-fn item_matches_urls(item: &LibraryItem, urls: &[BandcampUrl]) -> bool {
+pub(crate) fn item_matches_urls(item: &LibraryItem, urls: &[BandcampUrl]) -> bool {
     urls.iter().any(|url| {
         if url.is_artist_url() {
             item.artist_subdomain
@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use chrono::Utc;
+
+use crate::core::state::BandwidthLog;
+use crate::core::utils::format_bytes;
+use crate::error::Result;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+/// Items listed by `stats --bandwidth`
+const LARGEST_ITEMS: usize = 10;
+
+pub fn run_stats(output: &Path, bandwidth: bool) -> Result<()> {
+    let log = BandwidthLog::load(output)?;
+    let total: u64 = log.months().map(|(_, bytes)| bytes).sum();
+
+    if total == 0 {
+        println!(
+            "{}",
+            tr_fmt(Msg::StatsNothingDownloaded, &[&output.display()])
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        tr_fmt(
+            Msg::StatsDownloaded,
+            &[
+                &format_bytes(log.month_total(Utc::now()) as f64),
+                &format_bytes(total as f64)
+            ]
+        )
+    );
+
+    if !bandwidth {
+        return Ok(());
+    }
+
+    println!();
+    for (month, bytes) in log.months() {
+        println!("{month}  {:>12}", format_bytes(bytes as f64));
+    }
+
+    println!();
+    println!("{}", tr(Msg::StatsLargestItems));
+    for (id, bytes) in log.largest_items().into_iter().take(LARGEST_ITEMS) {
+        println!("{id:>12}  {:>12}", format_bytes(bytes as f64));
+    }
+
+    Ok(())
+}
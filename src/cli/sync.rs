@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use tracing::{error, info, warn};
+
+use crate::cli::commands::DownloadArgs;
+use crate::cli::run::{RunOutcome, run_download};
+use crate::error::{BandcampError, Result};
+
+/// Runs the download passes of `sync`, each one retrying the items still waiting on Bandcamp.
+/// With `once` the only pass decides the exit code, failed or still pending items make it
+/// fail. Otherwise a failed pass is logged and the next one starts `interval` later.
+pub async fn run_sync(
+    args: DownloadArgs,
+    once: bool,
+    interval: Duration,
+    tick_rate: Duration,
+) -> Result<()> {
+    loop {
+        let result = run_download(args.clone(), tick_rate).await;
+        if once {
+            return match result? {
+                RunOutcome {
+                    failed: 0,
+                    pending: 0,
+                } => Ok(()),
+                RunOutcome { failed: 0, pending } => Err(BandcampError::DownloadError(format!(
+                    "{pending} items are still waiting on Bandcamp"
+                ))),
+                RunOutcome { failed, .. } => Err(BandcampError::DownloadError(format!(
+                    "{failed} items failed"
+                ))),
+            };
+        }
+
+        match result {
+            Ok(RunOutcome {
+                failed: 0,
+                pending: 0,
+            }) => info!("Sync done, next one in {}s", interval.as_secs()),
+            Ok(RunOutcome { failed, pending }) => warn!(
+                "Sync done with {failed} failed and {pending} pending items, next one in {}s",
+                interval.as_secs()
+            ),
+            Err(e) => error!("Sync failed: {e}, next one in {}s", interval.as_secs()),
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
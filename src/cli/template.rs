@@ -0,0 +1,113 @@
+use chrono::NaiveDate;
+
+use crate::cli::commands::{FilenameArgs, TemplateCommand};
+use crate::core::client::BandcampClient;
+use crate::core::library::{
+    AudioFormat, ItemType, LibraryItem, TEMPLATE_VARIABLES, unknown_template_variables,
+    uses_release_date, validate_name_format,
+};
+use crate::core::session;
+use crate::error::{BandcampError, Result};
+
+pub async fn run_template(command: TemplateCommand) -> Result<()> {
+    match command {
+        TemplateCommand::Test {
+            template,
+            cookie,
+            count,
+            format,
+            filenames,
+        } => test_template(&template, cookie, count, format, &filenames).await,
+    }
+}
+
+async fn test_template(
+    template: &str,
+    cookie: Option<String>,
+    count: usize,
+    format: AudioFormat,
+    filenames: &FilenameArgs,
+) -> Result<()> {
+    validate_name_format(template)?;
+
+    let items = match cookie {
+        Some(cookie) => {
+            let mut client = BandcampClient::new();
+            client.validate_cookie(&cookie).await?;
+            // A single page is plenty unless --session-ttl kept the whole collection around
+            let items = match session::cached_collection(client.base_url(), &cookie) {
+                Some(items) => items,
+                None => {
+                    client
+                        .get_collection_page(&BandcampClient::initial_collection_token())
+                        .await?
+                        .items
+                }
+            };
+            let mut items: Vec<_> = items.into_iter().take(count).collect();
+            if uses_release_date(template) {
+                client.fill_release_dates(&mut items).await;
+            }
+            println!("Rendering \"{template}\" for items of your collection:");
+            items
+        }
+        None => {
+            println!("No cookie given, rendering \"{template}\" for sample items:");
+            sample_items()
+        }
+    };
+
+    let sanitize = filenames.sanitize_options();
+    for item in &items {
+        println!("  {} - {} ({:?})", item.artist, item.title, item.item_type);
+        println!(
+            "    -> {}",
+            item.construct_filename(format, Some(template), &sanitize)
+        );
+    }
+
+    let unknown = unknown_template_variables(template);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+
+    let list = |names: &[String]| {
+        names
+            .iter()
+            .map(|name| format!("{{{name}}}"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let known: Vec<_> = TEMPLATE_VARIABLES.iter().map(|v| v.to_string()).collect();
+    Err(BandcampError::InvalidTemplate(format!(
+        "unknown variables {}, they would be kept as they are. Known ones are {}",
+        list(&unknown),
+        list(&known)
+    )))
+}
+
+/// Stand-ins covering an album, a track and names that need sanitizing
+fn sample_items() -> Vec<LibraryItem> {
+    [
+        ("1", ItemType::Album, "Bad Math", "Missing Narrative", 2021),
+        ("2", ItemType::Track, "Clark Rainbow", "Chainsaw", 2019),
+        ("3", ItemType::Album, "AC/DC", "Live: At River Plate?", 2011),
+    ]
+    .into_iter()
+    .map(|(id, item_type, artist, title, year)| LibraryItem {
+        id: id.to_string(),
+        item_type,
+        title: title.to_string(),
+        artist: artist.to_string(),
+        artist_id: id.to_string(),
+        artist_subdomain: None,
+        slug: None,
+        item_url: None,
+        download_url: String::new(),
+        is_preorder: false,
+        is_hidden: false,
+        purchase: Default::default(),
+        release_date: NaiveDate::from_ymd_opt(year, 1, 1),
+    })
+    .collect()
+}
@@ -0,0 +1,65 @@
+use crate::cli::commands::WishlistCommand;
+use crate::core::client::BandcampClient;
+use crate::core::library::WishlistItem;
+use crate::error::{BandcampError, Result};
+use crate::i18n::{Msg, tr, tr_fmt};
+
+pub async fn run_wishlist(command: WishlistCommand) -> Result<()> {
+    match command {
+        WishlistCommand::List { cookie, only_free } => list_wishlist(cookie, only_free).await,
+    }
+}
+
+async fn list_wishlist(cookie: Option<String>, only_free: bool) -> Result<()> {
+    let cookie = cookie.ok_or_else(|| {
+        BandcampError::AuthError(
+            "No cookie provided. Set --cookie flag or BANDCAMP_COOKIE env var".to_string(),
+        )
+    })?;
+
+    let mut client = BandcampClient::new();
+    client.validate_cookie(&cookie).await?;
+
+    let items: Vec<_> = client
+        .get_wishlist()
+        .await?
+        .into_iter()
+        .filter(|item| !only_free || item.is_free())
+        .collect();
+
+    if items.is_empty() {
+        if only_free {
+            println!("{}", tr(Msg::WishlistNoFree));
+        } else {
+            println!("{}", tr(Msg::WishlistEmpty));
+        }
+        return Ok(());
+    }
+
+    for item in &items {
+        println!("{}", format_item(item));
+    }
+
+    let free = items.iter().filter(|item| item.is_free()).count();
+    println!();
+    println!("{}", tr_fmt(Msg::WishlistSummary, &[&items.len(), &free]));
+
+    Ok(())
+}
+
+fn format_item(item: &WishlistItem) -> String {
+    let price = match (item.price, &item.currency) {
+        _ if item.is_free() => format!("[{}]", tr(Msg::WishlistFree)),
+        (Some(price), Some(currency)) => format!("[{price:.2} {currency}]"),
+        _ => format!("[{}]", tr(Msg::WishlistPriceUnknown)),
+    };
+
+    let mut line = format!(
+        "{price} {} - {} ({:?})",
+        item.artist, item.title, item.item_type
+    );
+    if let Some(url) = &item.item_url {
+        line.push_str(&format!("  {url}"));
+    }
+    line
+}
@@ -0,0 +1,117 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+
+use crate::core::target::ExtractTarget;
+use crate::error::Result;
+
+/// Extensions of the cover image Bandcamp puts at the top of album archives
+pub(crate) const COVER_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// What happens to the cover image that comes with an album
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ArtworkPolicy {
+    /// Keep it as Bandcamp names it
+    #[default]
+    Keep,
+    /// Rename it to `folder.<ext>`
+    Folder,
+    /// Embed it into every track with ffmpeg, then leave it out. Albums kept as zip archives
+    /// and formats ffmpeg can't attach a picture to keep the file.
+    Embedded,
+}
+
+/// Media servers with their own idea of where album art belongs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MediaServer {
+    Kodi,
+    Plex,
+    Navidrome,
+}
+
+impl MediaServer {
+    /// Artwork handling the server picks up without any setup
+    pub fn artwork(self) -> ArtworkPolicy {
+        match self {
+            MediaServer::Kodi => ArtworkPolicy::Folder,
+            MediaServer::Plex => ArtworkPolicy::Embedded,
+            // Bandcamp already calls it cover.jpg
+            MediaServer::Navidrome => ArtworkPolicy::Keep,
+        }
+    }
+}
+
+impl ArtworkPolicy {
+    /// Where the archive entry at `relative` is written to. Only a cover image at the top of the
+    /// album is touched.
+    pub fn rename(self, relative: &Path) -> PathBuf {
+        let is_cover = relative.parent() == Some(Path::new(""))
+            && relative
+                .file_stem()
+                .is_some_and(|stem| stem.eq_ignore_ascii_case("cover"));
+        let extension = relative
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .filter(|ext| COVER_EXTENSIONS.contains(&ext.as_str()));
+
+        let Some(extension) = extension.filter(|_| is_cover) else {
+            return relative.to_path_buf();
+        };
+        match self {
+            // Embedding needs the file, it is removed once the tracks carry it
+            ArtworkPolicy::Keep | ArtworkPolicy::Embedded => relative.to_path_buf(),
+            ArtworkPolicy::Folder => PathBuf::from(format!("folder.{extension}")),
+        }
+    }
+}
+
+/// The cover image at the top of an album folder, whatever the artwork policy renamed it to
+pub(crate) fn find_cover(album: &Path) -> Option<PathBuf> {
+    let mut images: Vec<PathBuf> = std::fs::read_dir(album)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+                .is_some_and(|ext| COVER_EXTENSIONS.contains(&ext.as_str()))
+        })
+        .collect();
+    images.sort();
+
+    let named = |name: &str| {
+        images.iter().position(|path| {
+            path.file_stem()
+                .is_some_and(|stem| stem.eq_ignore_ascii_case(name))
+        })
+    };
+    let position = named("cover").or_else(|| named("folder")).unwrap_or(0);
+    (position < images.len()).then(|| images.swap_remove(position))
+}
+
+/// Applies an [`ArtworkPolicy`] to the files of an album on their way into `inner`
+pub struct ArtworkTarget<'a> {
+    inner: &'a mut dyn ExtractTarget,
+    policy: ArtworkPolicy,
+}
+
+impl<'a> ArtworkTarget<'a> {
+    pub fn new(inner: &'a mut dyn ExtractTarget, policy: ArtworkPolicy) -> Self {
+        Self { inner, policy }
+    }
+}
+
+impl ExtractTarget for ArtworkTarget<'_> {
+    fn create_dir(&mut self, relative: &Path) -> Result<()> {
+        self.inner.create_dir(relative)
+    }
+
+    fn create_file(&mut self, relative: &Path) -> Result<Box<dyn Write + '_>> {
+        self.inner.create_file(&self.policy.rename(relative))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::core::download::TransferStats;
+use crate::core::library::{AudioFormat, LibraryItem};
+use crate::error::{BandcampError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+/// A single line of the audit log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// Batch the attempt belonged to, see `--batch-id`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_id: Option<String>,
+    pub item_id: String,
+    pub artist: String,
+    pub title: String,
+    pub format: String,
+    /// Bytes received from the server
+    pub bytes: u64,
+    /// SHA-256 of the downloaded file, as received
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    pub duration_secs: f64,
+    pub outcome: AuditOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+/// Append-only JSONL log with one entry per download attempt
+#[derive(Debug)]
+pub struct AuditLog {
+    file: Mutex<File>,
+    batch_id: Option<String>,
+}
+
+impl AuditLog {
+    /// Opens the log for appending, creating it (and its parent directory) if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            batch_id: None,
+        })
+    }
+
+    /// Tags every entry written from now on with `batch_id`
+    pub fn with_batch_id(mut self, batch_id: String) -> Self {
+        self.batch_id = Some(batch_id);
+        self
+    }
+
+    pub fn append(&self, entry: &AuditEntry) -> Result<()> {
+        let mut line =
+            serde_json::to_string(entry).map_err(|e| BandcampError::ParseError(e.to_string()))?;
+        line.push('\n');
+
+        // Single write per entry so concurrent downloads never interleave lines
+        let mut file = self.file.lock().unwrap();
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Records the outcome of a download, logging instead of failing if the write fails
+    pub fn record(
+        &self,
+        item: &LibraryItem,
+        format: AudioFormat,
+        stats: &TransferStats,
+        duration: Duration,
+        result: &Result<PathBuf>,
+    ) {
+        let (outcome, error, path) = match result {
+            Ok(path) => (AuditOutcome::Success, None, Some(path.clone())),
+            Err(e) => (AuditOutcome::Failure, Some(e.to_string()), None),
+        };
+
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            batch_id: self.batch_id.clone(),
+            item_id: item.id.clone(),
+            artist: item.artist.clone(),
+            title: item.title.clone(),
+            format: format.bandcamp_encoding().to_string(),
+            bytes: stats.bytes,
+            sha256: stats.sha256.clone(),
+            duration_secs: duration.as_secs_f64(),
+            outcome,
+            error,
+            path,
+        };
+
+        if let Err(e) = self.append(&entry) {
+            warn!("Failed to write audit log entry for {}: {e}", item.title);
+        }
+    }
+}
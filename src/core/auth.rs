@@ -5,4 +5,3 @@ pub struct Credentials {
     pub identity_cookie: String,
     pub fan_id: u64,
 }
-
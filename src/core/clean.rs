@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate};
+use tracing::{debug, info};
+
+use crate::core::state::{STATE_DIR, Scratch};
+use crate::error::{BandcampError, Result};
+use crate::i18n::{Msg, tr};
+
+/// Leftovers from interrupted downloads that are safe to remove. Only paths the output's
+/// [`Scratch`] list still names count, anything else in the directory is left alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanTarget {
+    /// Temporary file a download or retag was writing
+    TempFile(PathBuf),
+    /// Album folder whose extraction never finished
+    PartialDir(PathBuf),
+    /// Folder with nothing left in it once the leftovers inside are gone
+    EmptyDir(PathBuf),
+}
+
+impl CleanTarget {
+    pub fn path(&self) -> &Path {
+        match self {
+            CleanTarget::TempFile(p) | CleanTarget::PartialDir(p) | CleanTarget::EmptyDir(p) => p,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            CleanTarget::TempFile(_) => tr(Msg::CleanTempFile),
+            CleanTarget::PartialDir(_) => tr(Msg::CleanPartialDir),
+            CleanTarget::EmptyDir(_) => tr(Msg::CleanEmptyDir),
+        }
+    }
+}
+
+/// Finds everything `clean` would remove below `root`. The root and its state directory are
+/// never included. A directory that only contains removable entries is reported once
+/// instead of listing its contents.
+pub fn find_clean_targets(root: &Path) -> Result<Vec<CleanTarget>> {
+    let scratch: HashSet<PathBuf> = Scratch::load(root)?.paths(root).collect();
+    let (targets, _) = scan_dir(root, &scratch)?;
+    Ok(targets)
+}
+
+/// Returns the removable entries of `dir` and whether they are all it holds. A directory
+/// without any is never removable, even when it is empty.
+fn scan_dir(dir: &Path, scratch: &HashSet<PathBuf>) -> Result<(Vec<CleanTarget>, bool)> {
+    let mut targets = Vec::new();
+    let mut only_leftovers = true;
+
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if scratch.contains(&path) {
+            targets.push(if file_type.is_dir() {
+                CleanTarget::PartialDir(path)
+            } else {
+                CleanTarget::TempFile(path)
+            });
+        } else if file_type.is_dir() && entry.file_name() != STATE_DIR {
+            let (child_targets, child_only_leftovers) = scan_dir(&path, scratch)?;
+            if child_only_leftovers {
+                targets.push(CleanTarget::EmptyDir(path));
+            } else {
+                targets.extend(child_targets);
+                only_leftovers = false;
+            }
+        } else {
+            only_leftovers = false;
+        }
+    }
+
+    let removable = only_leftovers && !targets.is_empty();
+    Ok((targets, removable))
+}
+
+/// What happens to whatever `clean` removes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Disposal {
+    #[default]
+    Delete,
+    /// The desktop's trash, where a file manager can restore it from
+    Trash,
+    /// A folder per day below `dir`, deleted once it is older than `ttl`
+    Quarantine { dir: PathBuf, ttl: Duration },
+}
+
+/// Date format of the quarantine's day folders, anything else in there is left alone
+const QUARANTINE_DAY: &str = "%Y-%m-%d";
+
+impl Disposal {
+    /// Gets `path`, somewhere below `root`, out of the way. Quarantined entries keep their
+    /// path relative to `root`, so they can be moved back by hand.
+    pub fn remove(&self, path: &Path, root: &Path) -> Result<()> {
+        match self {
+            Disposal::Delete => {
+                debug!("Removing {path:?}");
+                if path.is_dir() {
+                    std::fs::remove_dir_all(path)?;
+                } else {
+                    std::fs::remove_file(path)?;
+                }
+            }
+            Disposal::Trash => {
+                debug!("Moving {path:?} to the trash");
+                trash::delete(path).map_err(|e| {
+                    BandcampError::IoError(std::io::Error::other(format!(
+                        "can't move {} to the trash: {e}",
+                        path.display()
+                    )))
+                })?;
+            }
+            Disposal::Quarantine { dir, .. } => {
+                let relative = path
+                    .strip_prefix(root)
+                    .ok()
+                    .filter(|relative| !relative.as_os_str().is_empty())
+                    .or_else(|| path.file_name().map(Path::new))
+                    .ok_or_else(|| {
+                        BandcampError::IoError(std::io::Error::other(format!(
+                            "can't quarantine {}",
+                            path.display()
+                        )))
+                    })?;
+                let day = dir.join(Local::now().format(QUARANTINE_DAY).to_string());
+                let mut destination = day.join(relative);
+                let mut copy = 1;
+                while destination.exists() {
+                    let mut name = day.join(relative).into_os_string();
+                    name.push(format!(".{copy}"));
+                    destination = PathBuf::from(name);
+                    copy += 1;
+                }
+
+                debug!("Moving {path:?} to {destination:?}");
+                if let Some(parent) = destination.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::rename(path, &destination)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the quarantine's day folders older than its TTL, returns how many went
+    pub fn purge_expired(&self) -> Result<usize> {
+        let Disposal::Quarantine { dir, ttl } = self else {
+            return Ok(0);
+        };
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+
+        let today = Local::now().date_naive();
+        let ttl_days = (ttl.as_secs() / (24 * 60 * 60)) as i64;
+        let mut purged = 0;
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(day) = name
+                .to_str()
+                .and_then(|name| NaiveDate::parse_from_str(name, QUARANTINE_DAY).ok())
+            else {
+                continue;
+            };
+            if (today - day).num_days() > ttl_days && entry.file_type()?.is_dir() {
+                info!("Deleting quarantined files from {day}");
+                std::fs::remove_dir_all(entry.path())?;
+                purged += 1;
+            }
+        }
+        Ok(purged)
+    }
+}
+
+pub fn remove_target(target: &CleanTarget, root: &Path, disposal: &Disposal) -> Result<()> {
+    disposal.remove(target.path(), root)
+}
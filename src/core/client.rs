@@ -1,16 +1,277 @@
-use std::collections::{HashMap, HashSet};
-
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use chrono::NaiveDate;
 use reqwest::header::{COOKIE, HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, warn};
+use tracing::{debug, info, trace, warn};
 
 use crate::core::auth::Credentials;
-use crate::core::library::{AudioFormat, ItemType, LibraryItem};
+use crate::core::download::DownloadProgressReporter;
+use crate::core::library::{
+    AudioFormat, BonusItem, ItemDetails, ItemType, LibraryItem, PurchaseInfo, TrackInfo,
+    WishlistItem, parse_release_date,
+};
+use crate::core::{recording, session};
 use crate::error::{BandcampError, Result};
 
 const BANDCAMP_BASE: &str = "https://bandcamp.com";
 const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0";
 
+static BASE_URL: OnceLock<String> = OnceLock::new();
+static PAGING: OnceLock<CollectionPaging> = OnceLock::new();
+static DEBUG_DUMP_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Points every client created afterwards at `url` instead of bandcamp.com, for `--mock`.
+/// A single client is pointed elsewhere with [`BandcampClient::with_base_url`].
+pub fn set_base_url(url: String) {
+    let _ = BASE_URL.set(url);
+}
+
+fn default_base_url() -> &'static str {
+    BASE_URL.get().map(String::as_str).unwrap_or(BANDCAMP_BASE)
+}
+
+/// Saves every download page that can't be parsed into `dir`, for `--debug-dump`
+pub fn set_debug_dump_dir(dir: PathBuf) {
+    let _ = DEBUG_DUMP_DIR.set(dir);
+}
+
+/// Release date a download page lists for its item, in the escaped or plain JSON of its data
+/// blob. Preorders only list the date of their package, if any.
+pub fn download_page_release_date(html: &str) -> Option<NaiveDate> {
+    ["release_date", "package_release_date"]
+        .iter()
+        .flat_map(|key| {
+            [
+                (format!("&quot;{key}&quot;:&quot;"), "&quot;"),
+                (format!("\"{key}\":\""), "\""),
+            ]
+        })
+        .find_map(|(pattern, end)| {
+            let start = html.find(&pattern)? + pattern.len();
+            let len = html[start..].find(end)?;
+            parse_release_date(&html[start..start + len])
+        })
+}
+
+/// Copy of a download page that is safe to attach to a bug report: the identity cookie and
+/// the signatures and tokens of download URLs are blanked, everything the parser looks at is
+/// kept
+pub fn scrub_page(html: &str, identity_cookie: &str) -> String {
+    let mut page = html.to_string();
+    if !identity_cookie.is_empty() {
+        let encoded: String =
+            url::form_urlencoded::byte_serialize(identity_cookie.as_bytes()).collect();
+        page = page
+            .replace(identity_cookie, "REDACTED")
+            .replace(&encoded, "REDACTED");
+    }
+
+    // Signed URLs would let anyone download the release until they expire
+    recording::redact_text(&page)
+}
+
+/// Writes a scrubbed copy of a page that couldn't be parsed into the `--debug-dump` directory
+fn dump_page(dir: &Path, item: &LibraryItem, html: &str, identity_cookie: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    let path = dir.join(format!("download-page-{}-{stamp}.html", item.id));
+    std::fs::write(&path, scrub_page(html, identity_cookie))?;
+    Ok(path)
+}
+
+/// Phrases of the page Bandcamp serves in place of everything while it is down
+const MAINTENANCE_MARKERS: &[&str] = &[
+    "down for maintenance",
+    "scheduled maintenance",
+    "bandcamp is currently down",
+    "we'll be back shortly",
+];
+
+/// Maintenance pages are a few KB, real pages are far larger and may quote any of the markers
+/// in an album description
+const MAINTENANCE_PAGE_MAX_BYTES: usize = 32 * 1024;
+
+/// Whether `body` is Bandcamp's maintenance page rather than the page or file asked for
+pub fn is_maintenance_page(body: &[u8]) -> bool {
+    if body.len() > MAINTENANCE_PAGE_MAX_BYTES {
+        return false;
+    }
+    let page = String::from_utf8_lossy(body).to_lowercase();
+    MAINTENANCE_MARKERS
+        .iter()
+        .any(|marker| page.contains(marker))
+}
+
+/// Phrases of the page Bandcamp shows instead of a download once an account made too many
+/// download attempts in a short time
+const DOWNLOAD_LIMIT_MARKERS: &[&str] = &["too many download attempts", "too many downloads"];
+
+/// Wait when the limit page doesn't say how long, it usually clears within the hour
+const DEFAULT_LIMIT_COOLDOWN: Duration = Duration::from_secs(60 * 60);
+
+/// How long a ready download page is reused. Its links are signed for longer than this, but
+/// nothing tells when exactly they expire.
+const DOWNLOAD_PAGE_TTL: Duration = Duration::from_secs(10 * 60);
+/// Download pages kept at most, the oldest goes first
+const DOWNLOAD_PAGES_KEPT: usize = 64;
+
+/// The cool-down if `body` is Bandcamp's download limit page, as stated on the page
+/// ("try again in 30 minutes") or an hour when it doesn't say
+pub fn download_limit_cooldown(body: &[u8]) -> Option<Duration> {
+    if body.len() > MAINTENANCE_PAGE_MAX_BYTES {
+        return None;
+    }
+    let page = String::from_utf8_lossy(body).to_lowercase();
+    if !DOWNLOAD_LIMIT_MARKERS
+        .iter()
+        .any(|marker| page.contains(marker))
+    {
+        return None;
+    }
+
+    let words: Vec<&str> = page
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let stated = words.windows(2).find_map(|pair| {
+        let count: u64 = pair[0].parse().ok()?;
+        let unit = match pair[1].trim_end_matches('s') {
+            "second" => 1,
+            "minute" => 60,
+            "hour" => 60 * 60,
+            _ => return None,
+        };
+        Some(Duration::from_secs(count * unit))
+    });
+    Some(stated.unwrap_or(DEFAULT_LIMIT_COOLDOWN))
+}
+
+/// Error for a response with an unsuccessful status, keeping the status, URL and any
+/// `Retry-After` so callers can decide without parsing the message. Signatures are blanked
+/// from the URL, it ends up in logs, reports and `--json` output.
+pub fn http_error(response: &reqwest::Response) -> BandcampError {
+    BandcampError::HttpStatus {
+        status: response.status().as_u16(),
+        url: recording::redact_url(response.url()),
+        retry_after: response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after),
+    }
+}
+
+/// `Retry-After` is either a number of seconds or an HTTP date
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means no waiting at all
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// File name from a `Content-Disposition` header, preferring the UTF-8 `filename*` form over
+/// the plain `filename`. Only the last path component is kept, whatever the server sent.
+pub fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+
+    for param in value.split(';').skip(1) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename" => {
+                let unquoted = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .map(|v| v.replace("\\\"", "\""));
+                plain = Some(unquoted.unwrap_or_else(|| value.to_string()));
+            }
+            // charset'language'percent-encoded
+            "filename*" => {
+                extended = value
+                    .splitn(3, '\'')
+                    .nth(2)
+                    .map(percent_decode)
+                    .filter(|name| !name.is_empty());
+            }
+            _ => {}
+        }
+    }
+
+    let name = extended.or(plain)?;
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// How collection and wishlist pages are requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollectionPaging {
+    /// Items asked for per request
+    pub page_size: usize,
+    /// Stop walking the collection once this many items were fetched
+    pub max_items: Option<usize>,
+}
+
+impl Default for CollectionPaging {
+    fn default() -> Self {
+        Self {
+            page_size: 100,
+            max_items: None,
+        }
+    }
+}
+
+/// Applies `--collection-page-size` and `--max-collection-items` to every client
+pub fn set_collection_paging(paging: CollectionPaging) {
+    let _ = PAGING.set(CollectionPaging {
+        page_size: paging.page_size.max(1),
+        ..paging
+    });
+}
+
+pub fn collection_paging() -> CollectionPaging {
+    PAGING.get().copied().unwrap_or_default()
+}
+
 /// Response from the collection_items API endpoint
 #[derive(Debug, Deserialize)]
 struct CollectionResponse {
@@ -50,6 +311,184 @@ struct CollectionItem {
     band_name: String,
     #[serde(default)]
     is_preorder: bool,
+
+    /// Purchase date, e.g. "14 Mar 2024 18:02:11 GMT"
+    #[serde(default)]
+    purchased: Option<String>,
+    #[serde(default)]
+    price: Option<f64>,
+    #[serde(default)]
+    currency: Option<String>,
+    #[serde(default)]
+    gift_sender_name: Option<String>,
+    #[serde(default)]
+    gift_sender_note: Option<String>,
+    /// Fan's note on the item
+    #[serde(default)]
+    why: Option<String>,
+}
+
+/// Set once a collection page had to be read leniently, so the upgrade hint shows up once a run
+static SCHEMA_WARNED: AtomicBool = AtomicBool::new(false);
+
+impl CollectionResponse {
+    /// Reads a collection page, falling back to picking out the fields bannedcamp needs one by
+    /// one when Bandcamp changed the response in a way the strict types don't accept. Items
+    /// without an ID or title are left out, every other missing field gets a default.
+    fn parse(page: serde_json::Value) -> Result<Self> {
+        let error = match serde_json::from_value(page.clone()) {
+            Ok(response) => return Ok(response),
+            Err(e) => e,
+        };
+
+        let Some(raw_items) = page.get("items").and_then(|items| items.as_array()) else {
+            return Err(BandcampError::ParseError(format!(
+                "collection page without items: {error}"
+            )));
+        };
+        let mut missing = BTreeSet::new();
+        let items: Vec<_> = raw_items
+            .iter()
+            .filter_map(|item| lenient_collection_item(item, &mut missing))
+            .collect();
+        let last_token = lenient_field::<String>(&page, "last_token");
+        let more_available = lenient_field(&page, "more_available").unwrap_or_else(|| {
+            missing.insert("more_available");
+            // Keep paging as long as there is somewhere to continue from
+            last_token.is_some() && !raw_items.is_empty()
+        });
+        let redownload_urls = lenient_field(&page, "redownload_urls").unwrap_or_else(|| {
+            missing.insert("redownload_urls");
+            HashMap::new()
+        });
+
+        let skipped = raw_items.len() - items.len();
+        if SCHEMA_WARNED.swap(true, Ordering::Relaxed) {
+            debug!(missing = ?missing, skipped, "Read another collection page leniently: {error}");
+        } else {
+            warn!(
+                error = %error,
+                missing = ?missing,
+                skipped,
+                "Bandcamp changed its collection API, reading it leniently. Some details may be \
+                 missing, upgrade bannedcamp if a newer version is out"
+            );
+        }
+        Ok(Self {
+            items,
+            more_available,
+            last_token,
+            redownload_urls,
+        })
+    }
+}
+
+/// `name` of `value` if it is there and has the expected type
+fn lenient_field<T: DeserializeOwned>(value: &serde_json::Value, name: &str) -> Option<T> {
+    value
+        .get(name)
+        .filter(|field| !field.is_null())
+        .and_then(|field| serde_json::from_value(field.clone()).ok())
+}
+
+/// A collection item from whatever fields are left, `None` without an ID or title. Fields the
+/// strict types require but weren't found are added to `missing`.
+fn lenient_collection_item(
+    value: &serde_json::Value,
+    missing: &mut BTreeSet<&'static str>,
+) -> Option<CollectionItem> {
+    // IDs have been seen as strings in other Bandcamp APIs
+    let id = |name: &'static str, missing: &mut BTreeSet<&'static str>| {
+        let id = lenient_field::<u64>(value, name)
+            .or_else(|| lenient_field::<String>(value, name)?.parse().ok());
+        if id.is_none() {
+            missing.insert(name);
+        }
+        id
+    };
+    let sale_item_id = id("sale_item_id", missing)?;
+    let band_id = id("band_id", missing).unwrap_or_default();
+    let Some(item_title) = lenient_field::<String>(value, "item_title") else {
+        missing.insert("item_title");
+        return None;
+    };
+
+    let tralbum_type = lenient_field::<CollectionSummaryItemType>(value, "tralbum_type");
+    let sale_item_type = lenient_field::<String>(value, "sale_item_type");
+    if tralbum_type.is_none() {
+        missing.insert("tralbum_type");
+    }
+    if sale_item_type.is_none() {
+        missing.insert("sale_item_type");
+    }
+    // Each one stands in for the other, the redownload URL is looked up by the sale type
+    let tralbum_type = tralbum_type
+        .or_else(|| serde_json::from_value(serde_json::Value::String(sale_item_type.clone()?)).ok())
+        .unwrap_or(CollectionSummaryItemType::Album);
+    let sale_item_type = sale_item_type.unwrap_or_else(|| {
+        match tralbum_type {
+            CollectionSummaryItemType::Album => "a",
+            CollectionSummaryItemType::Track => "t",
+            CollectionSummaryItemType::Package => "p",
+        }
+        .to_string()
+    });
+
+    Some(CollectionItem {
+        band_id,
+        sale_item_id,
+        sale_item_type,
+        tralbum_type,
+        hidden: lenient_field(value, "hidden"),
+        url_hints: lenient_field(value, "url_hints"),
+        item_title,
+        item_url: lenient_field(value, "item_url"),
+        band_name: lenient_field(value, "band_name").unwrap_or_default(),
+        is_preorder: lenient_field(value, "is_preorder").unwrap_or_default(),
+        purchased: lenient_field(value, "purchased"),
+        price: lenient_field(value, "price"),
+        currency: lenient_field(value, "currency"),
+        gift_sender_name: lenient_field(value, "gift_sender_name"),
+        gift_sender_note: lenient_field(value, "gift_sender_note"),
+        why: lenient_field(value, "why"),
+    })
+}
+
+/// Response from the wishlist_items API endpoint
+#[derive(Debug, Deserialize)]
+struct WishlistResponse {
+    items: Vec<WishlistApiItem>,
+    more_available: bool,
+    last_token: Option<String>,
+}
+
+/// Individual item from wishlist response
+#[derive(Debug, Deserialize)]
+struct WishlistApiItem {
+    tralbum_id: u64,
+    tralbum_type: CollectionSummaryItemType,
+    item_title: String,
+    #[serde(default)]
+    band_name: String,
+    #[serde(default)]
+    item_url: Option<String>,
+    /// Minimum price, 0 for name-your-price items that can be downloaded for free
+    #[serde(default)]
+    price: Option<f64>,
+    #[serde(default)]
+    currency: Option<String>,
+}
+
+/// Response from the hide_unhide_item API endpoint
+#[derive(Debug, Deserialize)]
+struct HideUnhideResponse {
+    #[serde(default)]
+    ok: bool,
+    #[serde(default)]
+    error: Option<String>,
+    /// Sent along with an `invalid_crumb` error, valid for the retry
+    #[serde(default)]
+    crumb: Option<String>,
 }
 
 /// A page of library items from the collection API
@@ -59,9 +498,89 @@ pub struct CollectionPage {
     pub next_token: Option<String>,
 }
 
+/// What a request to Bandcamp was for, see [`RequestCounts`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    /// A page of the collection or wishlist API
+    CollectionPage,
+    DownloadPage,
+    /// A release's public page, read for its details
+    ItemPage,
+    /// Asking whether Bandcamp has finished encoding a download
+    StatusPoll,
+    Other,
+}
+
+/// Requests a client sent to Bandcamp, not counting the downloads themselves. Set against the
+/// run's settings they show what ran into Bandcamp's throttling.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct RequestCounts {
+    pub collection_pages: u64,
+    pub download_pages: u64,
+    pub item_pages: u64,
+    pub status_polls: u64,
+    pub other: u64,
+}
+
+impl RequestCounts {
+    pub fn total(&self) -> u64 {
+        self.collection_pages
+            + self.download_pages
+            + self.item_pages
+            + self.status_polls
+            + self.other
+    }
+}
+
+impl fmt::Display for RequestCounts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "collection pages: {}, download pages: {}, item pages: {}, status polls: {}, other: {}",
+            self.collection_pages,
+            self.download_pages,
+            self.item_pages,
+            self.status_polls,
+            self.other
+        )
+    }
+}
+
+/// Shared view of a client's [`RequestCounts`] that stays readable after the client moved on,
+/// e.g. into a download queue
+#[derive(Debug, Clone, Default)]
+pub struct RequestCounter(Arc<Mutex<RequestCounts>>);
+
+impl RequestCounter {
+    fn record(&self, kind: RequestKind) -> RequestCounts {
+        let mut counts = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match kind {
+            RequestKind::CollectionPage => counts.collection_pages += 1,
+            RequestKind::DownloadPage => counts.download_pages += 1,
+            RequestKind::ItemPage => counts.item_pages += 1,
+            RequestKind::StatusPoll => counts.status_polls += 1,
+            RequestKind::Other => counts.other += 1,
+        }
+        *counts
+    }
+
+    pub fn counts(&self) -> RequestCounts {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
 pub struct BandcampClient {
     http: reqwest::Client,
+    /// Server of the API and fan pages, bandcamp.com unless testing against a mock
+    base_url: String,
     credentials: Option<Credentials>,
+    requests: RequestCounter,
+    /// Ready download pages fetched this session by item ID, with when they were fetched, so
+    /// downloading an item again, e.g. in another format, skips the page. Its links are
+    /// signed, the page is dropped after [`DOWNLOAD_PAGE_TTL`] or once Bandcamp rejects them.
+    download_pages: Mutex<HashMap<String, (std::time::Instant, String)>>,
+    /// Release dates the download pages fetched this session list, by item ID
+    release_dates: Mutex<HashMap<String, NaiveDate>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -105,10 +624,65 @@ impl BandcampClient {
                 .user_agent(USER_AGENT)
                 .build()
                 .expect("Failed to create HTTP client"),
+            base_url: default_base_url().to_string(),
             credentials: None,
+            requests: RequestCounter::default(),
+            download_pages: Mutex::default(),
+            release_dates: Mutex::default(),
         }
     }
 
+    /// Sends the API requests of this client to `url` instead, like a local mock server
+    pub fn with_base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Counts of the requests this client sent so far, and keeps sending
+    pub fn requests(&self) -> RequestCounter {
+        self.requests.clone()
+    }
+
+    /// Every request goes through here so `--record-http` and `--replay` see it.
+    /// Maintenance pages come back as [`BandcampError::SiteDown`] instead of the parse error
+    /// reading them as JSON or as a download page would end in.
+    async fn send(
+        &self,
+        kind: RequestKind,
+        request: reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let counts = self.requests.record(kind);
+        trace!("Request {} to Bandcamp this run: {counts}", counts.total());
+        let response = recording::send(&self.http, request, true).await?;
+
+        let is_html = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("text/html"));
+        if !is_html {
+            return Ok(response);
+        }
+
+        let status = response.status();
+        let version = response.version();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        if is_maintenance_page(&body) {
+            return Err(BandcampError::SiteDown);
+        }
+
+        let mut rebuilt = http::Response::new(body);
+        *rebuilt.status_mut() = status;
+        *rebuilt.version_mut() = version;
+        *rebuilt.headers_mut() = headers;
+        Ok(reqwest::Response::from(rebuilt))
+    }
+
     fn cookie_headers(identity_cookie: &str) -> Result<HeaderMap> {
         let mut headers = HeaderMap::new();
         let cookie_value = format!("identity={identity_cookie}");
@@ -128,23 +702,35 @@ impl BandcampClient {
         Self::cookie_headers(&creds.identity_cookie)
     }
 
+    /// Headers another program needs to fetch a download link the way [`Self::download`] does
+    pub fn download_headers(&self) -> Result<Vec<String>> {
+        let mut headers = vec![format!("User-Agent: {USER_AGENT}")];
+        for (name, value) in &self.auth_headers()? {
+            let value = value
+                .to_str()
+                .map_err(|e| BandcampError::AuthError(e.to_string()))?;
+            headers.push(format!("{name}: {value}"));
+        }
+        Ok(headers)
+    }
+
     pub async fn fetch_collection_summary(
         &self,
         identity_cookie: &str,
     ) -> Result<CollectionSummary> {
         let response = self
-            .http
-            .get(format!("{BANDCAMP_BASE}/api/fan/2/collection_summary"))
-            .headers(Self::cookie_headers(identity_cookie)?)
-            .send()
+            .send(
+                RequestKind::Other,
+                self.http
+                    .get(format!("{}/api/fan/2/collection_summary", self.base_url))
+                    .headers(Self::cookie_headers(identity_cookie)?),
+            )
             .await?;
 
         if response.status().is_success() {
             Ok(response.json().await?)
         } else {
-            Err(BandcampError::NetworkError(
-                response.error_for_status().unwrap_err(),
-            ))
+            Err(http_error(&response))
         }
     }
 
@@ -153,22 +739,37 @@ impl BandcampClient {
         fan_id: u64,
         older_than_token: &str,
     ) -> Result<CollectionResponse> {
-        let url = format!("{BANDCAMP_BASE}/api/fancollection/1/collection_items");
+        let page = self
+            .fancollection_page("collection_items", fan_id, older_than_token)
+            .await?;
+        CollectionResponse::parse(page)
+    }
+
+    /// Fetch one page from a paginated fancollection endpoint (collection_items, wishlist_items)
+    async fn fancollection_page<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        fan_id: u64,
+        older_than_token: &str,
+    ) -> Result<T> {
+        let url = format!("{}/api/fancollection/1/{endpoint}", self.base_url);
 
         let body = serde_json::json!({
             "fan_id": fan_id,
-            "count": 100,
+            "count": collection_paging().page_size,
             "older_than_token": older_than_token
         });
 
-        debug!("Fetching collection page: {url} with body: {body:?}");
+        debug!("Fetching {endpoint} page: {url} with body: {body:?}");
 
         let response = self
-            .http
-            .post(&url)
-            .headers(self.auth_headers()?)
-            .json(&body)
-            .send()
+            .send(
+                RequestKind::CollectionPage,
+                self.http
+                    .post(&url)
+                    .headers(self.auth_headers()?)
+                    .json(&body),
+            )
             .await?;
 
         if response.status() == 401 {
@@ -176,21 +777,48 @@ impl BandcampClient {
         } else if response.status() == 503 {
             Err(BandcampError::SiteDown)
         } else if !response.status().is_success() {
-            Err(BandcampError::NetworkError(
-                response.error_for_status().unwrap_err(),
-            ))
+            Err(http_error(&response))
         } else {
             Ok(response.json().await?)
         }
     }
 
+    /// Check that bandcamp.com is reachable, returning the server clock from the `Date` header
+    pub async fn server_time(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let response = self
+            .send(RequestKind::Other, self.http.head(&self.base_url))
+            .await?;
+
+        if response.status() == 503 {
+            return Err(BandcampError::SiteDown);
+        }
+
+        Ok(response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|date| date.to_str().ok())
+            .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+            .map(|date| date.with_timezone(&chrono::Utc)))
+    }
+
     /// Validate a session cookie by attempting to fetch the user's fan ID.
     pub async fn validate_cookie(&mut self, identity_cookie: &str) -> Result<Credentials> {
+        if let Some(fan_id) = session::cached_fan_id(&self.base_url, identity_cookie) {
+            info!("Cookie validated by an earlier command, fan_id: {fan_id}");
+            let credentials = Credentials {
+                identity_cookie: identity_cookie.to_string(),
+                fan_id,
+            };
+            self.credentials = Some(credentials.clone());
+            return Ok(credentials);
+        }
+
         info!("Validating session cookie...");
 
         let summary = self.fetch_collection_summary(identity_cookie).await?;
 
         info!("Cookie validated, fan_id: {}", summary.fan_id);
+        session::store_fan_id(&self.base_url, identity_cookie, summary.fan_id);
 
         let credentials = Credentials {
             identity_cookie: identity_cookie.to_string(),
@@ -220,23 +848,48 @@ impl BandcampClient {
             collection.items.len(),
             collection.more_available
         );
+        Ok(self.collection_page(collection))
+    }
+
+    /// Reads a page as the collection API returns it, leniently if Bandcamp changed its shape
+    pub fn parse_collection_page(&self, page: serde_json::Value) -> Result<CollectionPage> {
+        CollectionResponse::parse(page).map(|collection| self.collection_page(collection))
+    }
 
+    fn collection_page(&self, collection: CollectionResponse) -> CollectionPage {
         let items: Vec<LibraryItem> = collection
             .items
             .into_iter()
-            .filter(|item| !item.is_preorder)
             .map(|item| self.convert_collection_item(item, &collection.redownload_urls))
             .collect();
 
-        Ok(CollectionPage {
+        CollectionPage {
             items,
             more_available: collection.more_available,
             next_token: collection.last_token,
-        })
+        }
     }
 
     /// Fetch the user's entire library collection
     pub async fn get_collection(&self) -> Result<Vec<LibraryItem>> {
+        let creds = self
+            .credentials
+            .as_ref()
+            .ok_or(BandcampError::NotLoggedIn)?;
+        let max_items = collection_paging().max_items;
+        if let Some(mut items) = session::cached_collection(&self.base_url, &creds.identity_cookie)
+        {
+            info!("Using {} items cached by an earlier command", items.len());
+            items.truncate(max_items.unwrap_or(usize::MAX));
+            return Ok(items);
+        }
+
+        let items = self.fetch_collection(max_items).await?;
+        session::store_collection(&self.base_url, &creds.identity_cookie, &items);
+        Ok(items)
+    }
+
+    async fn fetch_collection(&self, max_items: Option<usize>) -> Result<Vec<LibraryItem>> {
         info!("Fetching collection...");
 
         let mut token = Self::initial_collection_token();
@@ -252,6 +905,14 @@ impl BandcampClient {
                 }
             }
 
+            if let Some(max) = max_items
+                && items.len() >= max
+            {
+                info!("Stopping at --max-collection-items {max}");
+                items.truncate(max);
+                return Ok(items);
+            }
+
             if !page.more_available {
                 break;
             }
@@ -266,6 +927,246 @@ impl BandcampClient {
         Ok(items)
     }
 
+    /// Looks up the fan behind a fan page like `bandcamp.com/<username>`
+    pub async fn get_fan_id(&self, username: &str) -> Result<u64> {
+        let url = format!("{}/{username}", self.base_url);
+        debug!("Fetching fan page: {url}");
+
+        let response = self.send(RequestKind::Other, self.http.get(&url)).await?;
+        if response.status() == 404 {
+            return Err(BandcampError::ParseError(format!(
+                "No Bandcamp fan called {username}"
+            )));
+        } else if !response.status().is_success() {
+            return Err(http_error(&response));
+        }
+
+        let html = response.text().await?;
+        self.attribute_json(&html, "data-blob")?
+            .and_then(|blob| blob.get("fan_data")?.get("fan_id")?.as_u64())
+            .ok_or_else(|| BandcampError::ParseError(format!("{url} is not a fan page")))
+    }
+
+    /// Everything another fan shows on their collection page. Bandcamp only hands out
+    /// redownload links for the logged in fan's own purchases, none of these items have one.
+    pub async fn get_fan_collection(&self, fan_id: u64) -> Result<Vec<LibraryItem>> {
+        info!("Fetching collection of fan {fan_id}...");
+
+        let mut token = Self::initial_collection_token();
+        let mut seen: HashSet<String> = HashSet::new();
+        let mut items = Vec::new();
+
+        loop {
+            let page = self.collection_items(fan_id, &token).await?;
+            for item in page.items {
+                let item = self.convert_collection_item(item, &HashMap::new());
+                if seen.insert(item.id.clone()) {
+                    items.push(item);
+                }
+            }
+
+            match page.last_token {
+                Some(t) if page.more_available => token = t,
+                _ => break,
+            }
+        }
+
+        info!("Fan {fan_id} shows {} items", items.len());
+        Ok(items)
+    }
+
+    /// Hide or unhide an item in the user's collection, same as the eye icon on the website
+    pub async fn set_item_hidden(&self, item: &LibraryItem, hidden: bool) -> Result<()> {
+        let creds = self
+            .credentials
+            .as_ref()
+            .ok_or(BandcampError::NotLoggedIn)?;
+
+        let url = format!("{}/api/collectionowner/1/hide_unhide_item", self.base_url);
+        let item_type = match item.item_type {
+            ItemType::Album => "a",
+            ItemType::Track => "t",
+            ItemType::Package => "p",
+        };
+        let mut body = serde_json::json!({
+            "fan_id": creds.fan_id,
+            "item_type": item_type,
+            "item_id": item.id,
+            "action": if hidden { "hide" } else { "unhide" },
+        });
+
+        // The first request usually lacks a valid crumb, Bandcamp hands one out with the error
+        for _ in 0..2 {
+            debug!("Posting to {url} with body: {body:?}");
+
+            let response = self
+                .send(
+                    RequestKind::Other,
+                    self.http
+                        .post(&url)
+                        .headers(self.auth_headers()?)
+                        .json(&body),
+                )
+                .await?;
+
+            if response.status() == 401 {
+                return Err(BandcampError::SessionExpired);
+            } else if response.status() == 503 {
+                return Err(BandcampError::SiteDown);
+            } else if !response.status().is_success() {
+                return Err(http_error(&response));
+            }
+
+            let result: HideUnhideResponse = response.json().await?;
+            if result.ok {
+                info!(
+                    "{} {} - {}",
+                    if hidden { "Hid" } else { "Unhid" },
+                    item.artist,
+                    item.title
+                );
+                session::forget_collection(&self.base_url, &creds.identity_cookie);
+                return Ok(());
+            }
+
+            match (result.error.as_deref(), result.crumb) {
+                (Some("invalid_crumb"), Some(crumb)) => body["crumb"] = crumb.into(),
+                (error, _) => {
+                    return Err(BandcampError::ParseError(format!(
+                        "Bandcamp rejected the request: {}",
+                        error.unwrap_or("unknown error")
+                    )));
+                }
+            }
+        }
+
+        Err(BandcampError::ParseError(
+            "Bandcamp kept rejecting the request crumb".to_string(),
+        ))
+    }
+
+    /// Fetch every item on the user's wishlist
+    pub async fn get_wishlist(&self) -> Result<Vec<WishlistItem>> {
+        info!("Fetching wishlist...");
+
+        let creds = self
+            .credentials
+            .as_ref()
+            .ok_or(BandcampError::NotLoggedIn)?;
+
+        let mut token = Self::initial_collection_token();
+        let mut items = Vec::new();
+
+        loop {
+            let page: WishlistResponse = self
+                .fancollection_page("wishlist_items", creds.fan_id, &token)
+                .await?;
+
+            items.extend(page.items.into_iter().map(|item| WishlistItem {
+                id: item.tralbum_id.to_string(),
+                item_type: match item.tralbum_type {
+                    CollectionSummaryItemType::Album => ItemType::Album,
+                    CollectionSummaryItemType::Track => ItemType::Track,
+                    CollectionSummaryItemType::Package => ItemType::Package,
+                },
+                title: item.item_title,
+                artist: item.band_name,
+                item_url: item.item_url,
+                price: item.price,
+                currency: item.currency,
+            }));
+
+            if !page.more_available {
+                break;
+            }
+
+            match page.last_token {
+                Some(t) => token = t,
+                None => break,
+            }
+        }
+
+        info!("Fetched {} wishlist items", items.len());
+        Ok(items)
+    }
+
+    /// Fetches release date and track list from the item's public page
+    pub async fn get_item_details(&self, item: &LibraryItem) -> Result<ItemDetails> {
+        let url = item.item_url.as_deref().ok_or_else(|| {
+            BandcampError::ParseError(format!("No page URL known for {}", item.title))
+        })?;
+        debug!("Fetching item details: {url}");
+
+        let html = self
+            .send(RequestKind::ItemPage, self.http.get(url))
+            .await?
+            .text()
+            .await?;
+        self.parse_item_details(&html)
+    }
+
+    /// Parses the JSON in the first `attribute` of the page, e.g. `data-tralbum`. `None` when
+    /// the page has no such attribute, an error when its JSON doesn't parse.
+    fn attribute_json(&self, html: &str, attribute: &str) -> Result<Option<serde_json::Value>> {
+        let Some(blob) = ['"', '\''].iter().find_map(|quote| {
+            let pattern = format!("{attribute}={quote}");
+            let start = html.find(&pattern)? + pattern.len();
+            let end = html[start..].find(*quote)?;
+            Some(&html[start..start + end])
+        }) else {
+            return Ok(None);
+        };
+        serde_json::from_str(&self.unescape_html(blob))
+            .map(Some)
+            .map_err(|e| BandcampError::ParseError(format!("{attribute}: {e}")))
+    }
+
+    /// Reads the `data-tralbum` blob that album and track pages embed
+    pub fn parse_item_details(&self, html: &str) -> Result<ItemDetails> {
+        let value = self
+            .attribute_json(html, "data-tralbum")?
+            .ok_or_else(|| BandcampError::ParseError("No release data in page".to_string()))?;
+
+        let release_date = value
+            .get("album_release_date")
+            .or_else(|| value.get("current")?.get("release_date"))
+            .and_then(|date| date.as_str())
+            .map(str::to_string);
+
+        let tracks = value
+            .get("trackinfo")
+            .and_then(|tracks| tracks.as_array())
+            .map(|tracks| {
+                tracks
+                    .iter()
+                    .map(|track| TrackInfo {
+                        title: track
+                            .get("title")
+                            .and_then(|t| t.as_str())
+                            .unwrap_or_default()
+                            .to_string(),
+                        duration_secs: track
+                            .get("duration")
+                            .and_then(|d| d.as_f64())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Only set for releases anyone can download without paying or leaving an email
+        let free_download_url = value
+            .get("freeDownloadPage")
+            .and_then(|url| url.as_str())
+            .map(str::to_string);
+
+        Ok(ItemDetails {
+            release_date,
+            tracks,
+            free_download_url,
+        })
+    }
+
     /// Convert API collection item to our LibraryItem type
     fn convert_collection_item(
         &self,
@@ -291,8 +1192,8 @@ impl BandcampClient {
         let download_url = match redownload_urls.get(&redownload_key).cloned() {
             Some(url) => url,
             None => {
-                warn!(
-                    "No redownload URL found for {} (key: {redownload_key}), item may not be downloadable",
+                debug!(
+                    "No redownload URL found for {} (key: {redownload_key}), treating it as stream-only",
                     item.item_title,
                 );
                 String::new()
@@ -316,18 +1217,55 @@ impl BandcampClient {
             download_url,
             is_preorder: item.is_preorder,
             is_hidden: item.hidden.unwrap_or(false),
+            purchase: PurchaseInfo {
+                purchased_at: item.purchased.as_deref().and_then(parse_purchase_date),
+                price: item.price,
+                currency: item.currency,
+                gift_from: item.gift_sender_name.filter(|s| !s.is_empty()),
+                gift_note: item.gift_sender_note.filter(|s| !s.is_empty()),
+                note: item.why.filter(|s| !s.is_empty()),
+            },
+            release_date: None,
+        }
+    }
+
+    /// Release date of `item`, from the item itself or the download page fetched for it
+    pub fn release_date(&self, item: &LibraryItem) -> Option<NaiveDate> {
+        item.release_date.or_else(|| {
+            self.release_dates
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(&item.id)
+                .copied()
+        })
+    }
+
+    /// Looks up the release date of every item that has none yet, one page request per item,
+    /// for previews of a few items. Downloads take it from the download page instead.
+    pub async fn fill_release_dates(&self, items: &mut [LibraryItem]) {
+        for item in items
+            .iter_mut()
+            .filter(|item| item.release_date.is_none() && item.item_url.is_some())
+        {
+            match self.get_item_details(item).await {
+                Ok(details) => {
+                    item.release_date =
+                        details.release_date.as_deref().and_then(parse_release_date);
+                }
+                Err(e) => debug!("No release date for {}: {e}", item.title),
+            }
         }
     }
 
     /// Make an authenticated GET request for downloading files
     pub async fn download(&self, url: &str) -> Result<reqwest::Response> {
-        let response = self
-            .http
-            .get(url)
-            .headers(self.auth_headers()?)
-            .send()
-            .await?;
-        Ok(response)
+        // File bodies are streamed to disk by the caller, recordings only keep their headers
+        recording::send(
+            &self.http,
+            self.http.get(url).headers(self.auth_headers()?),
+            false,
+        )
+        .await
     }
 
     /// Get download URL with retry logic for pending encodings
@@ -337,6 +1275,18 @@ impl BandcampClient {
         item: &LibraryItem,
         format: AudioFormat,
         max_attempts: u32,
+    ) -> Result<String> {
+        self.get_download_url_reporting(item, format, max_attempts, None)
+            .await
+    }
+
+    /// Same as [`Self::get_download_url_with_retry`], but reports each encoding poll to `reporter`
+    pub async fn get_download_url_reporting(
+        &self,
+        item: &LibraryItem,
+        format: AudioFormat,
+        max_attempts: u32,
+        reporter: Option<&dyn DownloadProgressReporter>,
     ) -> Result<String> {
         info!(
             "Getting download URL for {} - {} ({})",
@@ -345,56 +1295,31 @@ impl BandcampClient {
             format.bandcamp_encoding()
         );
 
-        // First, fetch the download page to get the format-specific URL
-        debug!("Fetching download page: {}", item.download_url);
-
-        let response = self
-            .http
-            .get(&item.download_url)
-            .headers(self.auth_headers()?)
-            .send()
-            .await?;
-
-        if response.status() == 401 {
-            return Err(BandcampError::SessionExpired);
-        } else if response.status() == 503 {
-            return Err(BandcampError::SiteDown);
-        }
-
-        if !response.status().is_success() {
-            return Err(BandcampError::DownloadError(format!(
-                "Failed to fetch download page: HTTP {}",
-                response.status()
-            )));
-        }
-
-        let html = response.text().await?;
+        let html = self.fetch_download_page(item).await?;
 
         // Check if already ready
-        let is_ready = html.contains("\"ready\":true") || html.contains("\"ready\": true");
-        if is_ready {
+        if Self::is_ready(&html) {
             debug!("Download is already ready");
-            return self.extract_download_url(&html, format);
+            return self.parse_download_page(item, &html, format);
         }
 
         // Extract the download URL for this format
-        let download_url = self.extract_download_url(&html, format)?;
+        let download_url = self.parse_download_page(item, &html, format)?;
         debug!("Download URL: {download_url}");
 
-        // Trigger encoding by requesting the download URL
-        // This will return HTML (preparing page) but triggers the encoding process
-        debug!("Triggering encoding by requesting download URL...");
-        let _ = self
-            .http
-            .get(&download_url)
-            .headers(self.auth_headers()?)
-            .send()
-            .await;
+        self.trigger_encoding(&download_url).await?;
 
         // Now poll statdownload until ready
         let stat_url_base = download_url.replace("/download/", "/statdownload/");
+        let wait_start = std::time::Instant::now();
 
         for attempt in 1..=max_attempts {
+            if let Some(reporter) = reporter {
+                reporter
+                    .on_encoding_wait(attempt, wait_start.elapsed())
+                    .await;
+            }
+
             let stat_url = format!(
                 "{stat_url_base}&.rand={}&.vrs=1",
                 std::time::SystemTime::now()
@@ -406,10 +1331,10 @@ impl BandcampClient {
             debug!("Polling statdownload (attempt {attempt}): {stat_url}");
 
             let stat_response = self
-                .http
-                .get(&stat_url)
-                .headers(self.auth_headers()?)
-                .send()
+                .send(
+                    RequestKind::StatusPoll,
+                    self.http.get(&stat_url).headers(self.auth_headers()?),
+                )
                 .await?;
 
             let stat_text = stat_response.text().await.unwrap_or_default();
@@ -433,20 +1358,12 @@ impl BandcampClient {
             if stat_text.contains("\"errortype\":\"ExpirationError\"") {
                 // Signature expired, need to get fresh URLs
                 debug!("Signature expired, refreshing download page...");
+                self.forget_download_page(&item.id);
 
-                // Re-fetch download page for fresh URLs
-                let response = self
-                    .http
-                    .get(&item.download_url)
-                    .headers(self.auth_headers()?)
-                    .send()
-                    .await?;
-
-                if response.status().is_success() {
-                    let html = response.text().await?;
-                    if html.contains("\"ready\":true") || html.contains("\"ready\": true") {
-                        return self.extract_download_url(&html, format);
-                    }
+                if let Ok(html) = self.fetch_download_page(item).await
+                    && Self::is_ready(&html)
+                {
+                    return self.parse_download_page(item, &html, format);
                 }
             }
 
@@ -459,10 +1376,195 @@ impl BandcampClient {
             }
         }
 
-        Err(BandcampError::DownloadError(format!(
-            "Download for {} - {} not ready after {} attempts. The encoding may take longer - try again in a few minutes.",
-            item.artist, item.title, max_attempts
-        )))
+        info!(
+            "Download for {} - {} still not ready after {max_attempts} attempts",
+            item.artist, item.title
+        );
+        Err(BandcampError::EncodingPending {
+            attempts: max_attempts,
+        })
+    }
+
+    /// Fetches the download page and starts encoding if Bandcamp has not prepared the file yet.
+    /// Returns whether the download is ready right away.
+    pub async fn request_encoding(&self, item: &LibraryItem, format: AudioFormat) -> Result<bool> {
+        let html = self.fetch_download_page(item).await?;
+        if Self::is_ready(&html) {
+            return Ok(true);
+        }
+
+        let download_url = self.parse_download_page(item, &html, format)?;
+        self.trigger_encoding(&download_url).await?;
+        Ok(false)
+    }
+
+    /// Booklets, videos and other files the download page offers next to the audio
+    pub async fn get_bonus_items(&self, item: &LibraryItem) -> Result<Vec<BonusItem>> {
+        let html = self.fetch_download_page(item).await?;
+        Ok(self.parse_bonus_items(&html))
+    }
+
+    /// Reads the `bonus_items` of every digital item in the page data, skipping entries without
+    /// a link
+    fn parse_bonus_items(&self, html: &str) -> Vec<BonusItem> {
+        let Ok(Some(page)) = self.attribute_json(html, "data-blob") else {
+            return Vec::new();
+        };
+        let digital_items = page.get("digital_items").and_then(|items| items.as_array());
+
+        digital_items
+            .into_iter()
+            .flatten()
+            .filter_map(|item| item.get("bonus_items")?.as_array())
+            .flatten()
+            .filter_map(|bonus| {
+                let text = |key: &str| Some(bonus.get(key)?.as_str()?.to_string());
+                Some(BonusItem {
+                    url: text("url")?,
+                    title: text("title").unwrap_or_default(),
+                    filename: text("filename"),
+                })
+            })
+            .collect()
+    }
+
+    /// Drops the cached download page of the item, its signed links are no longer accepted
+    pub fn forget_download_page(&self, item_id: &str) {
+        let mut pages = self
+            .download_pages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if pages.remove(item_id).is_some() {
+            debug!("Dropped the cached download page of {item_id}");
+        }
+    }
+
+    fn keep_download_page(&self, item_id: &str, html: &str) {
+        let mut pages = self
+            .download_pages
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if pages.len() >= DOWNLOAD_PAGES_KEPT
+            && !pages.contains_key(item_id)
+            && let Some(oldest) = pages
+                .iter()
+                .min_by_key(|(_, (fetched_at, _))| *fetched_at)
+                .map(|(id, _)| id.clone())
+        {
+            pages.remove(&oldest);
+        }
+        pages.insert(
+            item_id.to_string(),
+            (std::time::Instant::now(), html.to_string()),
+        );
+    }
+
+    async fn fetch_download_page(&self, item: &LibraryItem) -> Result<String> {
+        let cached = {
+            let mut pages = self
+                .download_pages
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            pages.retain(|_, (fetched_at, _)| fetched_at.elapsed() < DOWNLOAD_PAGE_TTL);
+            pages.get(&item.id).map(|(_, html)| html.clone())
+        };
+        if let Some(html) = cached {
+            debug!("Using the download page fetched earlier for {}", item.title);
+            return Ok(html);
+        }
+
+        debug!("Fetching download page: {}", item.download_url);
+
+        let response = self
+            .send(
+                RequestKind::DownloadPage,
+                self.http
+                    .get(&item.download_url)
+                    .headers(self.auth_headers()?),
+            )
+            .await?;
+
+        if response.status() == 401 {
+            return Err(BandcampError::SessionExpired);
+        } else if response.status() == 503 {
+            return Err(BandcampError::SiteDown);
+        }
+
+        let status = response.status();
+        let error = (!status.is_success()).then(|| http_error(&response));
+        let html = response.text().await?;
+        // The limit page may come with 200 or 429
+        if let Some(cooldown) = download_limit_cooldown(html.as_bytes()) {
+            warn!(
+                "Bandcamp refuses more downloads for {}s",
+                cooldown.as_secs()
+            );
+            return Err(BandcampError::DownloadLimited { cooldown });
+        }
+        match error {
+            Some(error) => Err(error),
+            None => {
+                if let Some(date) = download_page_release_date(&html) {
+                    self.release_dates
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .insert(item.id.clone(), date);
+                }
+                // A page still preparing the download has to be fetched again anyway
+                if Self::is_ready(&html) {
+                    self.keep_download_page(&item.id, &html);
+                }
+                Ok(html)
+            }
+        }
+    }
+
+    fn is_ready(html: &str) -> bool {
+        html.contains("\"ready\":true")
+            || html.contains("\"ready\": true")
+            || html.contains("&quot;ready&quot;:true")
+    }
+
+    /// Requesting the download URL returns the "preparing" page, but starts the encoding
+    async fn trigger_encoding(&self, download_url: &str) -> Result<()> {
+        debug!("Triggering encoding by requesting download URL...");
+        let _ = self
+            .send(
+                RequestKind::Other,
+                self.http.get(download_url).headers(self.auth_headers()?),
+            )
+            .await;
+        Ok(())
+    }
+
+    /// [`Self::extract_download_url`], saving the page with `--debug-dump` when it can't be
+    /// parsed and naming the file in the error
+    fn parse_download_page(
+        &self,
+        item: &LibraryItem,
+        html: &str,
+        format: AudioFormat,
+    ) -> Result<String> {
+        match self.extract_download_url(html, format) {
+            Err(BandcampError::ParseError(msg)) if let Some(dir) = DEBUG_DUMP_DIR.get() => {
+                let cookie = self
+                    .credentials
+                    .as_ref()
+                    .map(|creds| creds.identity_cookie.as_str())
+                    .unwrap_or_default();
+                match dump_page(dir, item, html, cookie) {
+                    Ok(path) => Err(BandcampError::ParseError(format!(
+                        "{msg}, page saved to {}",
+                        path.display()
+                    ))),
+                    Err(e) => {
+                        warn!("Failed to save the download page to {}: {e}", dir.display());
+                        Err(BandcampError::ParseError(msg))
+                    }
+                }
+            }
+            result => result,
+        }
     }
 
     /// Extract download URL from download page HTML
@@ -491,6 +1593,19 @@ impl BandcampClient {
             return Ok(url);
         }
 
+        // Subscription releases can have a download page with nothing on it
+        if ["\"digital_items\":[]", "&quot;digital_items&quot;:[]"]
+            .iter()
+            .any(|empty| html.contains(empty))
+        {
+            return Err(BandcampError::StreamOnly);
+        }
+
+        // The page does list downloads, just none in the requested format
+        if html.contains("\"downloads\"") || html.contains("&quot;downloads&quot;") {
+            return Err(BandcampError::FormatUnavailable(format_str.to_string()));
+        }
+
         Err(BandcampError::ParseError(format!(
             "Could not find download URL for format '{format_str}' in page",
         )))
@@ -773,6 +1888,14 @@ impl BandcampClient {
     }
 }
 
+/// Parses the collection API's date format, e.g. "14 Mar 2024 18:02:11 GMT"
+fn parse_purchase_date(date: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::NaiveDateTime::parse_from_str(date, "%d %b %Y %H:%M:%S GMT")
+        .map(|date| date.and_utc())
+        .map_err(|e| debug!("Unparseable purchase date {date:?}: {e}"))
+        .ok()
+}
+
 impl Default for BandcampClient {
     fn default() -> Self {
         Self::new()
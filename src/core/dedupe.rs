@@ -0,0 +1,162 @@
+use std::collections::{HashMap, HashSet};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::core::state::Scratch;
+use crate::core::verify::collect_audio_files;
+use crate::error::Result;
+
+/// Byte-identical files, the first one is kept and the rest are the duplicates
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    pub size: u64,
+    pub files: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    pub fn original(&self) -> &Path {
+        &self.files[0]
+    }
+
+    pub fn duplicates(&self) -> &[PathBuf] {
+        &self.files[1..]
+    }
+
+    /// Space the duplicates take up on top of the original
+    pub fn wasted_bytes(&self) -> u64 {
+        self.size * self.duplicates().len() as u64
+    }
+}
+
+/// How a duplicate is made to share the original's data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMode {
+    /// Both names point at the same file, editing one edits the other
+    Hardlink,
+    /// Copy-on-write clone on filesystems that support it (Btrfs, XFS), the files stay
+    /// independent and only share blocks until one of them is changed
+    Reflink,
+}
+
+/// Finds audio files below `root` with identical contents, and with `extras` also artwork,
+/// booklets and every other file that came with a download. Files that are already hardlinks
+/// of each other count as one, so running this again after `--link` reports nothing. Reflinked
+/// files can't be told apart from copies and are reported again.
+pub fn find_duplicates(root: &Path, extras: bool) -> Result<Vec<DuplicateGroup>> {
+    let mut files = Vec::new();
+    if extras {
+        collect_files(root, &mut files)?;
+    } else {
+        collect_audio_files(root, &mut files)?;
+    }
+    // Files still being written and leftovers of interrupted downloads are `clean`'s business
+    let scratch: HashSet<PathBuf> = Scratch::load(root)?.paths(root).collect();
+    files.retain(|path| !path.ancestors().any(|path| scratch.contains(path)));
+    files.sort();
+
+    // Only files of the same size can be identical, everything else is never hashed
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut seen_inodes = HashSet::new();
+    for path in files {
+        let metadata = std::fs::metadata(&path)?;
+        if !seen_inodes.insert((metadata.dev(), metadata.ino())) {
+            continue;
+        }
+        by_size.entry(metadata.len()).or_default().push(path);
+    }
+
+    let mut groups = Vec::new();
+    for (size, paths) in by_size {
+        if paths.len() < 2 || size == 0 {
+            continue;
+        }
+
+        let mut by_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+        for path in paths {
+            by_hash.entry(sha256_file(&path)?).or_default().push(path);
+        }
+        groups.extend(
+            by_hash
+                .into_values()
+                .filter(|files| files.len() > 1)
+                .map(|files| DuplicateGroup { size, files }),
+        );
+    }
+
+    groups.sort_by(|a, b| a.files.cmp(&b.files));
+    Ok(groups)
+}
+
+/// Replaces every duplicate in `group` with a hardlink or reflink of the original and returns
+/// the bytes freed. Each duplicate is swapped in with a rename, so it never goes missing halfway.
+pub fn link_duplicates(group: &DuplicateGroup, mode: LinkMode) -> Result<u64> {
+    let mut freed = 0;
+    for duplicate in group.duplicates() {
+        let name = duplicate.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = duplicate.with_file_name(format!(".{name}.dedupe.tmp"));
+
+        debug!(
+            "Linking {:?} to {:?} ({mode:?})",
+            duplicate,
+            group.original()
+        );
+        let linked = match mode {
+            LinkMode::Hardlink => std::fs::hard_link(group.original(), &temp_path),
+            LinkMode::Reflink => reflink(group.original(), &temp_path),
+        }
+        .and_then(|()| std::fs::rename(&temp_path, duplicate));
+        if let Err(e) = linked {
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        freed += group.size;
+    }
+    Ok(freed)
+}
+
+#[cfg(target_os = "linux")]
+fn reflink(source: &Path, target: &Path) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let source = std::fs::File::open(source)?;
+    let target = std::fs::File::create_new(target)?;
+    if unsafe { libc::ioctl(target.as_raw_fd(), libc::FICLONE, source.as_raw_fd()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn reflink(_source: &Path, _target: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "reflinks are only supported on Linux",
+    ))
+}
+
+/// Every regular file below `path`, except hidden ones such as temp files and download state
+fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else if file_type.is_file() {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
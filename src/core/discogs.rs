@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use tracing::{debug, info};
+use url::Url;
+
+use crate::core::client::http_error;
+use crate::core::library::{ItemType, LibraryItem};
+use crate::error::{BandcampError, Result};
+
+const API: &str = "https://api.discogs.com";
+
+/// Discogs allows 60 requests a minute with a token
+const REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// New releases land in the "Uncategorized" folder, the one every collection has
+const UNCATEGORIZED_FOLDER: u32 = 1;
+
+#[derive(Debug, Deserialize)]
+struct Identity {
+    username: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchResult>,
+}
+
+/// A release as the database search lists it
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResult {
+    pub id: u64,
+    /// "Artist - Title"
+    pub title: String,
+    #[serde(default)]
+    pub format: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionReleases {
+    releases: Vec<serde_json::Value>,
+}
+
+/// Adds Bandcamp purchases to a Discogs collection, matched by artist and title
+pub struct Discogs {
+    http: Client,
+    token: String,
+    username: String,
+}
+
+impl Discogs {
+    /// Checks the personal access token and looks up whose collection it opens
+    pub async fn connect(token: &str) -> Result<Self> {
+        let http = Client::builder()
+            .user_agent(concat!("bannedcamp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let mut discogs = Self {
+            http,
+            token: token.to_string(),
+            username: String::new(),
+        };
+
+        let response = discogs
+            .send(discogs.http.get(format!("{API}/oauth/identity")))
+            .await?;
+        if response.status() == 401 {
+            return Err(BandcampError::AuthError(
+                "Discogs rejected the token, create one under Settings > Developers".to_string(),
+            ));
+        }
+        discogs.username = response.json::<Identity>().await?.username;
+        info!(
+            "Exporting to the Discogs collection of {}",
+            discogs.username
+        );
+        Ok(discogs)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        tokio::time::sleep(REQUEST_INTERVAL).await;
+        let response = request
+            .header("Authorization", format!("Discogs token={}", self.token))
+            .send()
+            .await?;
+        if response.status().is_success() || response.status() == 401 {
+            Ok(response)
+        } else {
+            Err(http_error(&response))
+        }
+    }
+
+    /// The Discogs release the item most likely is, `None` if the search finds nothing fitting
+    pub async fn find_release(&self, item: &LibraryItem) -> Result<Option<u64>> {
+        let mut query = vec![
+            ("type", "release"),
+            ("artist", item.artist.as_str()),
+            ("release_title", item.title.as_str()),
+        ];
+        if item.item_type == ItemType::Track {
+            query.push(("format", "Single"));
+        }
+        let url = Url::parse_with_params(&format!("{API}/database/search"), &query)
+            .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+
+        let response = self.send(self.http.get(url)).await?;
+        let results = response.json::<SearchResponse>().await?.results;
+        debug!(
+            "Discogs search for {} - {}: {} results",
+            item.artist,
+            item.title,
+            results.len()
+        );
+        Ok(pick_release(&results, item))
+    }
+
+    pub async fn in_collection(&self, release_id: u64) -> Result<bool> {
+        let response = self
+            .send(self.http.get(format!(
+                "{API}/users/{}/collection/releases/{release_id}",
+                self.username
+            )))
+            .await?;
+        Ok(!response
+            .json::<CollectionReleases>()
+            .await?
+            .releases
+            .is_empty())
+    }
+
+    pub async fn add(&self, release_id: u64) -> Result<()> {
+        self.send(self.http.post(format!(
+            "{API}/users/{}/collection/folders/{UNCATEGORIZED_FOLDER}/releases/{release_id}",
+            self.username
+        )))
+        .await?;
+        Ok(())
+    }
+}
+
+/// Picks the search result whose artist and title match the item, preferring a digital
+/// release as that is what was bought on Bandcamp
+pub fn pick_release(results: &[SearchResult], item: &LibraryItem) -> Option<u64> {
+    let wanted = normalize(&format!("{} - {}", item.artist, item.title));
+    let matching: Vec<_> = results
+        .iter()
+        .filter(|result| normalize(&result.title) == wanted)
+        .collect();
+
+    matching
+        .iter()
+        .find(|result| result.format.iter().any(|f| f == "File"))
+        .or_else(|| matching.first())
+        .map(|result| result.id)
+}
+
+/// Lowercase with Discogs' artist numbering dropped, e.g. "Bad Math (2)" becomes "bad math"
+fn normalize(title: &str) -> String {
+    let mut words: Vec<&str> = title.split_whitespace().collect();
+    words.retain(|word| {
+        !(word.starts_with('(')
+            && word.ends_with(')')
+            && word[1..word.len() - 1].chars().all(|c| c.is_ascii_digit())
+            && word.len() > 2)
+    });
+    words.join(" ").to_lowercase()
+}
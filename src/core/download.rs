@@ -1,25 +1,136 @@
-use std::io::{BufWriter, Write};
+use std::collections::HashMap;
+use std::io::{BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-use tokio_stream::StreamExt;
+use clap::ValueEnum;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::future::Future;
 use std::pin::Pin;
-use tracing::{debug, info};
+use tokio_stream::StreamExt;
+use tracing::{debug, info, warn};
+use zip::read::root_dir_common_filter;
+
+use crate::core::artwork::{ArtworkPolicy, ArtworkTarget};
+use crate::core::audit::AuditLog;
+use crate::core::client::{
+    BandcampClient, content_disposition_filename, download_limit_cooldown, http_error,
+    is_maintenance_page,
+};
+use crate::core::dedupe::sha256_file;
+use crate::core::external::{self, Downloader};
+use crate::core::format_map::ArtworkMap;
+use crate::core::library::{AudioFormat, BonusItem, ItemType, LibraryItem, PurchaseInfo};
+use crate::core::permissions::OutputPermissions;
+use crate::core::state::{BandwidthMeter, ItemClaim, Manifest, Scratch};
+use crate::core::tags::{embed_cover, tag_release_date, tagging_paths, write_album_nfo};
+use crate::core::target::{AlbumOutput, DirectoryTarget, ExtractTarget};
+use crate::core::utils::{SanitizeOptions, sanitize_filename};
+use crate::core::verify::find_undecodable;
+use crate::error::{BandcampError, ErrorInfo, Result};
+use crate::i18n::{Msg, tr};
+
+/// Why an item was left out of a batch without being counted as a failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The download is already in the output directory
+    AlreadyExists,
+    /// Not released yet, there is nothing to download
+    Preorder,
+    Hidden,
+    /// Bandcamp doesn't offer the item in the requested format
+    NoMatchingFormat,
+    /// Matched an --exclude-artist or --exclude-title glob
+    Excluded,
+    /// An earlier run with the same `--batch-id` already downloaded it
+    CompletedInBatch,
+    /// Bandcamp offers no download at all, only streaming
+    StreamOnly,
+    /// The TUI or another CLI run is downloading it into the same directory right now
+    InProgressElsewhere,
+    /// This month's downloads used up `--monthly-cap`
+    BandwidthCap,
+    /// Bandcamp took too long to prepare the download, `download pending` tries it again
+    EncodingPending,
+}
+
+impl SkipReason {
+    pub const ALL: [SkipReason; 10] = [
+        SkipReason::AlreadyExists,
+        SkipReason::Preorder,
+        SkipReason::Hidden,
+        SkipReason::NoMatchingFormat,
+        SkipReason::Excluded,
+        SkipReason::CompletedInBatch,
+        SkipReason::StreamOnly,
+        SkipReason::BandwidthCap,
+        SkipReason::InProgressElsewhere,
+        SkipReason::EncodingPending,
+    ];
+
+    /// Errors that mean the item can't be downloaded as requested rather than that it failed
+    pub fn from_error(error: &BandcampError) -> Option<Self> {
+        match error {
+            BandcampError::FormatUnavailable(_) => Some(SkipReason::NoMatchingFormat),
+            BandcampError::StreamOnly => Some(SkipReason::StreamOnly),
+            BandcampError::BandwidthCapReached => Some(SkipReason::BandwidthCap),
+            BandcampError::InProgressElsewhere => Some(SkipReason::InProgressElsewhere),
+            BandcampError::EncodingPending { .. } => Some(SkipReason::EncodingPending),
+            _ => None,
+        }
+    }
+}
 
-use crate::core::client::BandcampClient;
-use crate::core::library::{AudioFormat, ItemType, LibraryItem};
-use crate::error::{BandcampError, Result};
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            SkipReason::AlreadyExists => Msg::SkipAlreadyExists,
+            SkipReason::Preorder => Msg::SkipPreorder,
+            SkipReason::Hidden => Msg::SkipHidden,
+            SkipReason::NoMatchingFormat => Msg::SkipNoMatchingFormat,
+            SkipReason::Excluded => Msg::SkipExcluded,
+            SkipReason::CompletedInBatch => Msg::SkipCompletedInBatch,
+            SkipReason::StreamOnly => Msg::SkipStreamOnly,
+            SkipReason::BandwidthCap => Msg::SkipBandwidthCap,
+            SkipReason::InProgressElsewhere => Msg::SkipInProgressElsewhere,
+            SkipReason::EncodingPending => Msg::SkipEncodingPending,
+        };
+        f.write_str(tr(msg))
+    }
+}
+
+/// What to do with items Bandcamp doesn't offer in the requested format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MissingFormat {
+    /// Leave the item out and list it as skipped
+    #[default]
+    Skip,
+    /// Download the closest format that is offered instead
+    Fallback,
+    /// Count the item as failed
+    Fail,
+}
 
 /// Summary of download results
 #[derive(Debug, Default)]
 pub struct DownloadSummary {
     pub succeeded: Vec<(LibraryItem, PathBuf)>,
     pub failed: Vec<(LibraryItem, String)>,
+    pub skipped: Vec<(LibraryItem, SkipReason)>,
+    /// Succeeded items that were downloaded in another format than requested
+    pub fallbacks: Vec<(LibraryItem, AudioFormat)>,
+    /// Bandcamp went down during the batch and the items not started yet were given up
+    pub site_down: bool,
+    /// Details of each failure by item id, for tools reading `--json`
+    pub errors: HashMap<String, ErrorInfo>,
 }
 
 impl DownloadSummary {
     pub fn total(&self) -> usize {
-        self.succeeded.len() + self.failed.len()
+        self.succeeded.len() + self.failed.len() + self.skipped.len()
     }
 
     pub fn success_count(&self) -> usize {
@@ -29,33 +140,311 @@ impl DownloadSummary {
     pub fn failure_count(&self) -> usize {
         self.failed.len()
     }
+
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.len()
+    }
+
+    /// Number of skipped items per reason, leaving out reasons with none
+    pub fn skipped_by_reason(&self) -> Vec<(SkipReason, usize)> {
+        SkipReason::ALL
+            .into_iter()
+            .map(|reason| {
+                let count = self.skipped.iter().filter(|(_, r)| *r == reason).count();
+                (reason, count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
+    /// Machine-readable form printed by `--json`
+    pub fn to_json(&self) -> serde_json::Value {
+        let entry = |item: &LibraryItem, key: &str, value: serde_json::Value| {
+            let mut entry = serde_json::json!({
+                "id": item.id,
+                "artist": item.artist,
+                "title": item.title,
+                "url": item.item_url,
+            });
+            entry[key] = value;
+            entry
+        };
+
+        serde_json::json!({
+            "succeeded": self
+                .succeeded
+                .iter()
+                .map(|(item, path)| entry(item, "path", serde_json::json!(path)))
+                .collect::<Vec<_>>(),
+            "failed": self
+                .failed
+                .iter()
+                .map(|(item, error)| {
+                    let mut entry = entry(item, "error", serde_json::json!(error));
+                    if let Some(info) = self.errors.get(&item.id) {
+                        entry["error_info"] = serde_json::json!(info);
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>(),
+            "skipped": self
+                .skipped
+                .iter()
+                .map(|(item, reason)| entry(item, "reason", serde_json::json!(reason)))
+                .collect::<Vec<_>>(),
+            "fallbacks": self
+                .fallbacks
+                .iter()
+                .map(|(item, format)| entry(item, "format", serde_json::json!(format)))
+                .collect::<Vec<_>>(),
+            "site_down": self.site_down,
+        })
+    }
 }
 
-/// Trait for reporting download progress
+/// Trait for reporting download progress, every method does nothing unless overridden
 pub trait DownloadProgressReporter: Send + Sync {
     /// Called when download starts (returns total size if known)
-    fn on_start(&self, total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn on_start(&self, _total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
 
     /// Called when fetching download URL
-    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    /// Called on each poll while Bandcamp is still encoding the download
+    fn on_encoding_wait(
+        &self,
+        _attempt: u32,
+        _elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
 
     /// Called during download with current progress
     fn on_progress(
         &self,
-        downloaded: u64,
-        total: Option<u64>,
-    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+        _downloaded: u64,
+        _total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
 
     /// Called when extracting (for albums/packages)
-    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    /// Called before each archive entry is extracted, `done` entries out of `total` are finished
+    fn on_extract_progress(
+        &self,
+        _entry: &str,
+        _done: usize,
+        _total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
 
     /// Called when download completes successfully
-    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
 
     /// Called when download fails
-    fn on_error(&self, error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn on_error(&self, _error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+}
+
+impl<T: DownloadProgressReporter + ?Sized> DownloadProgressReporter for Box<T> {
+    fn on_start(&self, total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_start(total_size)
+    }
+
+    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_fetching_url()
+    }
+
+    fn on_encoding_wait(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_encoding_wait(attempt, elapsed)
+    }
+
+    fn on_progress(
+        &self,
+        downloaded: u64,
+        total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_progress(downloaded, total)
+    }
+
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_extracting()
+    }
+
+    fn on_extract_progress(
+        &self,
+        entry: &str,
+        done: usize,
+        total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_extract_progress(entry, done, total)
+    }
+
+    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_complete()
+    }
+
+    fn on_error(&self, error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_error(error)
+    }
+}
+
+impl<T: DownloadProgressReporter + ?Sized> DownloadProgressReporter for &T {
+    fn on_start(&self, total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_start(total_size)
+    }
+
+    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_fetching_url()
+    }
+
+    fn on_encoding_wait(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_encoding_wait(attempt, elapsed)
+    }
+
+    fn on_progress(
+        &self,
+        downloaded: u64,
+        total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_progress(downloaded, total)
+    }
+
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_extracting()
+    }
+
+    fn on_extract_progress(
+        &self,
+        entry: &str,
+        done: usize,
+        total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_extract_progress(entry, done, total)
+    }
+
+    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_complete()
+    }
+
+    fn on_error(&self, error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        (**self).on_error(error)
+    }
+}
+
+/// Optional behaviour shared by every frontend calling [`download_item`]
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    /// Every download attempt is appended here when set
+    pub audit_log: Option<Arc<AuditLog>>,
+    /// Write SHA-256 checksums next to downloaded tracks and inside extracted albums
+    pub checksums: bool,
+    /// Write the item's purchase details as JSON next to tracks and inside albums
+    pub purchase_info: bool,
+    /// Fetch the bonus files of the download page into [`EXTRAS_DIR`]
+    pub include_bonus_items: bool,
+    /// Check every audio file with ffprobe and fail the item if any can't be decoded
+    pub verify_audio: bool,
+    /// Write an [`album.nfo`](crate::core::tags::NFO_FILE) into extracted albums
+    pub nfo: bool,
+    /// Set the DATE tag of every audio file to the release date with ffmpeg
+    pub tag_dates: bool,
+    /// How artist and title are cleaned up for the file name
+    pub sanitize: SanitizeOptions,
+    /// What happens to items that aren't offered in the requested format
+    pub missing_format: MissingFormat,
+    /// Counts transferred bytes and stops new downloads once the monthly cap is used up
+    pub bandwidth: Option<Arc<BandwidthMeter>>,
+    /// Whether albums are extracted into folders or kept as zip archives
+    pub album_output: AlbumOutput,
+    /// Name tracks and album folders after the file Bandcamp serves instead of the template
+    pub keep_original_names: bool,
+    /// Whether the cover image of albums is kept, renamed or embedded into the tracks
+    pub artwork: ArtworkPolicy,
+    /// Per-item overrides of [`Self::artwork`]
+    pub artwork_map: Option<ArtworkMap>,
+    /// What transfers the files once their download link is resolved
+    pub downloader: Downloader,
+    /// Modes and ownership set on everything a download leaves behind, instead of what the
+    /// umask gives tracks and 0644/0755 inside albums
+    pub permissions: Option<OutputPermissions>,
+    /// Output root the queue routes items below. Its state directory holds the claims and
+    /// the list of unfinished files, and every finished download is added to its manifest.
+    pub output_root: Option<PathBuf>,
+}
+
+impl DownloadOptions {
+    /// Directory whose state directory is shared by every item, the item's own output
+    /// directory when no [`Self::output_root`] is set
+    pub fn state_root<'a>(&'a self, output_dir: &'a Path) -> &'a Path {
+        self.output_root.as_deref().unwrap_or(output_dir)
+    }
+
+    /// Artwork handling for `item`, from [`Self::artwork_map`] when a rule matches
+    pub fn artwork_for(&self, item: &LibraryItem) -> ArtworkPolicy {
+        match &self.artwork_map {
+            Some(artwork_map) => artwork_map.resolve(item, self.artwork),
+            None => self.artwork,
+        }
+    }
 }
 
+/// What was transferred for a single download attempt
+#[derive(Debug, Clone, Default)]
+pub struct TransferStats {
+    /// Bytes received from the server
+    pub bytes: u64,
+    /// SHA-256 of the downloaded file, only set once the transfer completed
+    pub sha256: Option<String>,
+}
+
+/// Attempts per item when the download server answers with something that isn't the file
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry, growing with each further attempt
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest a `Retry-After` is honoured for, a batch shouldn't sit idle for an hour
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Name of the checksum file written into extracted albums
+pub const CHECKSUM_FILE: &str = "SHA256SUMS";
+
+/// Appended to a track's file name for its checksum sidecar
+const TRACK_CHECKSUM_SUFFIX: &str = ".sha256";
+
+/// Appended to a track's file name for its purchase details sidecar
+const TRACK_PURCHASE_INFO_SUFFIX: &str = ".purchase.json";
+
+/// Bytes of a file handed over by an external downloader that are checked for what it is
+const SNIFF_LENGTH: u64 = 64 * 1024;
+
+/// Name of the purchase details file written into extracted albums
+pub const PURCHASE_INFO_FILE: &str = "purchase.json";
+
+/// Folder bonus items go in, inside the album folder or next to a track
+pub const EXTRAS_DIR: &str = "Extras";
+
 /// Generic download function that works for both CLI and TUI
 pub async fn download_item<P: DownloadProgressReporter>(
     client: &BandcampClient,
@@ -63,128 +452,862 @@ pub async fn download_item<P: DownloadProgressReporter>(
     output_dir: &Path,
     format: AudioFormat,
     name_format: Option<&str>,
+    options: &DownloadOptions,
     reporter: P,
 ) -> Result<PathBuf> {
-    info!("Downloading: {} - {}", item.artist, item.title);
+    if let Some(bandwidth) = &options.bandwidth
+        && bandwidth.cap_reached()
+    {
+        return Err(BandcampError::BandwidthCapReached);
+    }
+    // Held until this function returns, retries included. Taken under the root so routed
+    // runs and the TUI see each other's claims.
+    let _claim = ItemClaim::acquire(options.state_root(output_dir), &item.id)?;
 
-    // Fetch download URL
-    reporter.on_fetching_url().await;
-    let download_url = client.get_download_url_with_retry(item, format, 30).await?;
-    debug!("Download URL: {download_url}");
+    let started = Instant::now();
+    let mut stats = TransferStats::default();
+    // Over every attempt, the audit log only sees the last one
+    let mut transferred = 0;
+    let mut attempt = 1;
 
-    // Start download
-    let response = client.download(&download_url).await?;
+    let result = loop {
+        let result = fetch_and_store(
+            client,
+            item,
+            output_dir,
+            format,
+            name_format,
+            options,
+            &reporter,
+            &mut stats,
+        )
+        .await;
 
-    if !response.status().is_success() {
-        let error_msg = format!(
-            "HTTP {}: {}",
-            response.status(),
-            response.status().canonical_reason().unwrap_or("Unknown")
-        );
-        reporter.on_error(&error_msg).await;
-        return Err(BandcampError::DownloadError(error_msg));
+        match result {
+            Err(e) if e.is_retryable() && attempt < DOWNLOAD_ATTEMPTS => {
+                warn!(
+                    "{} - {}: {e}, retrying ({attempt}/{DOWNLOAD_ATTEMPTS})",
+                    item.artist, item.title
+                );
+                let delay = e.retry_after().unwrap_or(RETRY_DELAY * attempt);
+                tokio::time::sleep(delay.min(MAX_RETRY_DELAY)).await;
+                transferred += stats.bytes;
+                stats = TransferStats::default();
+                attempt += 1;
+            }
+            result => break result,
+        }
+    };
+
+    if let Err(e) = &result
+        && e.is_retryable()
+    {
+        reporter.on_error(&e.to_string()).await;
     }
 
-    let total_size = response.content_length();
-    reporter.on_start(total_size).await;
+    if let Some(audit_log) = &options.audit_log {
+        audit_log.record(item, format, &stats, started.elapsed(), &result);
+    }
 
-    // Create temporary file
-    let temp_path = output_dir.join(format!(".{}.tmp", item.id));
-    std::fs::create_dir_all(output_dir)?;
-    let mut file = BufWriter::new(std::fs::File::create(&temp_path)?);
+    if let Some(bandwidth) = &options.bandwidth {
+        bandwidth.record(&item.id, transferred + stats.bytes);
+    }
 
-    // Download with progress reporting
-    let mut downloaded: u64 = 0;
-    let mut stream = response.bytes_stream();
+    if let (Some(root), Ok(path)) = (&options.output_root, &result)
+        && let Err(e) = Manifest::add(root, item, path)
+    {
+        warn!("Failed to add {} to the manifest: {e}", item.title);
+    }
+
+    result
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn fetch_and_store<P: DownloadProgressReporter>(
+    client: &BandcampClient,
+    item: &LibraryItem,
+    output_dir: &Path,
+    format: AudioFormat,
+    name_format: Option<&str>,
+    options: &DownloadOptions,
+    reporter: &P,
+    stats: &mut TransferStats,
+) -> Result<PathBuf> {
+    info!("Downloading: {} - {}", item.artist, item.title);
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| BandcampError::DownloadError(e.to_string()))?;
-        file.write_all(&chunk)?;
-        downloaded += chunk.len() as u64;
-        reporter.on_progress(downloaded, total_size).await;
+    // Nothing to fetch, and polling for an encoding that never comes would take minutes
+    if item.is_stream_only() {
+        return Err(BandcampError::StreamOnly);
     }
 
-    file.flush()?;
-    drop(file);
+    // Fetch download URL
+    reporter.on_fetching_url().await;
+    let download_url = client
+        .get_download_url_reporting(item, format, 30, Some(reporter))
+        .await?;
+    debug!("Download URL: {download_url}");
+
+    // The download page lists the release date the collection leaves out
+    let dated;
+    let item = match client.release_date(item) {
+        Some(date) if item.release_date.is_none() => {
+            dated = LibraryItem {
+                release_date: Some(date),
+                ..item.clone()
+            };
+            &dated
+        }
+        _ => item,
+    };
+
+    let temp_path = output_dir.join(format!(".{}.tmp", item.id));
+    let state_root = options.state_root(output_dir);
+    Scratch::track(state_root, &[&temp_path])?;
+    let Fetched {
+        server_filename,
+        served_extension,
+        is_archive,
+        sha256,
+    } = match options.downloader {
+        Downloader::Builtin => {
+            match fetch_to(client, item, &download_url, &temp_path, reporter, stats).await {
+                // Links of a reused download page can be rejected before it expires here, a
+                // fresh page is worth one more try before the attempt counts as failed
+                Err(BandcampError::HttpStatus {
+                    status: 403 | 410, ..
+                }) => {
+                    debug!("Signed link of {} was rejected, refetching", item.title);
+                    let download_url = client
+                        .get_download_url_reporting(item, format, 30, Some(reporter))
+                        .await?;
+                    fetch_to(client, item, &download_url, &temp_path, reporter, stats).await?
+                }
+                result => result?,
+            }
+        }
+        Downloader::Aria2c => {
+            fetch_with_aria2c(client, item, &download_url, &temp_path, reporter, stats).await?
+        }
+    };
 
-    let filename = item.construct_filename(format, name_format);
+    debug!("SHA-256 of {}: {sha256}", item.title);
+    stats.sha256 = Some(sha256.clone());
+
+    let filename = match server_filename.filter(|_| options.keep_original_names) {
+        Some(name) => original_filename(&name, is_archive, &options.sanitize),
+        None => {
+            let extension = match &served_extension {
+                _ if is_archive => None,
+                Some(ext) => Some(ext.as_str()),
+                None => Some(format.extension()),
+            };
+            item.construct_filename_with(extension, name_format, &options.sanitize)
+        }
+    };
 
-    let output_path = if item.item_type == ItemType::Track {
-        // For tracks, rename the temp file
+    let artwork = options.artwork_for(item);
+    let output_path = if !is_archive {
+        // For single files, rename the temp file
         let final_path = output_dir.join(&filename);
         let tp = temp_path.clone();
         let fp = final_path.clone();
+        let checksums = options.checksums;
 
         tokio::task::spawn_blocking(move || -> Result<()> {
             if let Some(parent) = fp.parent() {
                 std::fs::create_dir_all(parent)?;
             }
             std::fs::rename(&tp, &fp)?;
+            if checksums {
+                write_track_checksum(&fp, &sha256)?;
+            }
             Ok(())
         })
         .await
         .map_err(|e| BandcampError::DownloadError(format!("Task join error: {e}")))??;
+        release_scratch(state_root, &[&temp_path]);
 
         final_path
     } else {
-        // For albums and packages, extract the zip archive
+        // For albums, packages and tracks with extras, extract the zip archive
         reporter.on_extracting().await;
-        let extract_path = output_dir.join(&filename);
+        let extract_path = options.album_output.archive_path(output_dir, &filename);
+        let unfinished = options.album_output.unfinished_path(&extract_path);
+        Scratch::track(state_root, &[&unfinished])?;
         let tp = temp_path.clone();
         let ep = extract_path.clone();
+        let checksums = options.checksums;
+        let album_output = options.album_output;
+        let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
 
-        tokio::task::spawn_blocking(move || -> Result<()> {
-            std::fs::create_dir_all(&ep)?;
-            extract_zip(&tp, &ep)?;
+        let extraction = tokio::task::spawn_blocking(move || -> Result<()> {
+            let mut target = album_output.open(&ep)?;
+            let mut artwork_target = ArtworkTarget::new(target.as_mut(), artwork);
+            let sums =
+                extract_zip_into(&tp, &mut artwork_target, checksums, |entry, done, total| {
+                    let _ = progress_tx.send((entry.to_string_lossy().into_owned(), done, total));
+                })?;
+            // Under the name each file was written as
+            let sums: Vec<_> = sums
+                .into_iter()
+                .map(|(path, hash)| (artwork.rename(&path), hash))
+                .collect();
+            if checksums {
+                write_checksum_file(target.as_mut(), &sums)?;
+            }
+            target.finish()?;
             std::fs::remove_file(&tp)?;
             Ok(())
+        });
+
+        // Ends once the extraction drops its sender
+        while let Some((entry, done, total)) = progress_rx.recv().await {
+            reporter.on_extract_progress(&entry, done, total).await;
+        }
+
+        extraction
+            .await
+            .map_err(|e| BandcampError::DownloadError(format!("Task join error: {e}")))??;
+        release_scratch(state_root, &[&temp_path, &unfinished]);
+
+        extract_path
+    };
+
+    if options.verify_audio {
+        let path = output_path.clone();
+        let bad = tokio::task::spawn_blocking(move || -> Result<Vec<PathBuf>> {
+            let bad = find_undecodable(&path)?;
+            if !bad.is_empty() {
+                discard_output(&path)?;
+            }
+            Ok(bad)
         })
         .await
         .map_err(|e| BandcampError::DownloadError(format!("Task join error: {e}")))??;
+        if !bad.is_empty() {
+            // Failing the item gets it retried by --max-items and --batch-id runs, and with
+            // the files gone --skip-existing doesn't pass over it either
+            let names: Vec<_> = bad
+                .iter()
+                .map(|p| p.file_name().unwrap_or_default().to_string_lossy())
+                .collect();
+            let error_msg = format!("Undecodable audio: {}", names.join(", "));
+            reporter.on_error(&error_msg).await;
+            return Err(BandcampError::DownloadError(error_msg));
+        }
+    }
 
-        extract_path
+    if options.purchase_info {
+        write_purchase_info(item, &output_path)?;
+    }
+    if options.nfo && output_path.is_dir() {
+        write_album_nfo(item, &output_path)?;
+    }
+    // Bandcamp's own tags and cover are still there, a failure only leaves them as they came
+    let date = item.release_date.filter(|_| options.tag_dates);
+    let embed = artwork == ArtworkPolicy::Embedded && output_path.is_dir();
+    if date.is_some() || embed {
+        let path = output_path.clone();
+        let root = state_root.to_path_buf();
+        let checksums = options.checksums;
+        let retagged = tokio::task::spawn_blocking(move || -> Result<()> {
+            let scratch = tagging_paths(&path)?;
+            Scratch::track(&root, &scratch)?;
+            let dated = date.map_or(Ok(()), |date| tag_release_date(&path, date));
+            let embedded = if embed {
+                embed_cover(&path).map(drop)
+            } else {
+                Ok(())
+            };
+            release_scratch(&root, &scratch);
+            if checksums {
+                refresh_checksums(&path)?;
+            }
+            dated.and(embedded)
+        })
+        .await;
+        match retagged {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Couldn't retag {}: {e}", item.title),
+            Err(e) => warn!("Retagging {} panicked: {e}", item.title),
+        }
+    }
+
+    let extras = if options.include_bonus_items {
+        download_bonus_items(client, item, &output_path, options, stats).await
+    } else {
+        None
     };
 
+    if let Some(permissions) = &options.permissions {
+        permissions.apply(&output_path)?;
+        if let Some(extras) = extras.filter(|_| output_path.is_file()) {
+            permissions.apply(&extras)?;
+        }
+        for suffix in [TRACK_CHECKSUM_SUFFIX, TRACK_PURCHASE_INFO_SUFFIX] {
+            let sidecar = sidecar_path(&output_path, suffix);
+            if output_path.is_file() && sidecar.is_file() {
+                permissions.apply(&sidecar)?;
+            }
+        }
+    }
+
     reporter.on_complete().await;
     info!("Completed: {filename}");
 
     Ok(output_path)
 }
 
+/// Fetches the item's bonus items into [`EXTRAS_DIR`], returning the folder if anything is in
+/// it. They are extras, a failure is only logged and the audio still counts as downloaded.
+async fn download_bonus_items(
+    client: &BandcampClient,
+    item: &LibraryItem,
+    output_path: &Path,
+    options: &DownloadOptions,
+    stats: &mut TransferStats,
+) -> Option<PathBuf> {
+    let bonus_items = match client.get_bonus_items(item).await {
+        Ok(bonus_items) => bonus_items,
+        Err(e) => {
+            warn!("No bonus items for {} - {}: {e}", item.artist, item.title);
+            return None;
+        }
+    };
+    if bonus_items.is_empty() {
+        return None;
+    }
+
+    // Tracks share their folder, their extras are told apart by the track's name
+    let (extras, prefix) = if output_path.is_dir() {
+        (output_path.join(EXTRAS_DIR), None)
+    } else {
+        let parent = output_path.parent().unwrap_or(output_path);
+        let stem = output_path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        (parent.join(EXTRAS_DIR), Some(stem.into_owned()))
+    };
+
+    for bonus in &bonus_items {
+        match download_bonus_item(client, bonus, &extras, prefix.as_deref(), options, stats).await {
+            Ok(path) => info!("Bonus item saved to {}", path.display()),
+            Err(e) => warn!("Failed to download the bonus item {}: {e}", bonus.title),
+        }
+    }
+    extras.is_dir().then_some(extras)
+}
+
+async fn download_bonus_item(
+    client: &BandcampClient,
+    bonus: &BonusItem,
+    extras: &Path,
+    prefix: Option<&str>,
+    options: &DownloadOptions,
+    stats: &mut TransferStats,
+) -> Result<PathBuf> {
+    let target = |name: &str| {
+        let name = sanitize_filename(name, &options.sanitize);
+        match prefix {
+            Some(prefix) => extras.join(format!("{prefix} - {name}")),
+            None => extras.join(name),
+        }
+    };
+    if let Some(filename) = &bonus.filename
+        && target(filename).exists()
+    {
+        return Ok(target(filename));
+    }
+
+    let response = client.download(&bonus.url).await?;
+    if !response.status().is_success() {
+        return Err(http_error(&response));
+    }
+    let served = response
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_disposition_filename);
+    let path = target(
+        bonus
+            .filename
+            .as_deref()
+            .or(served.as_deref())
+            .unwrap_or(&bonus.title),
+    );
+
+    std::fs::create_dir_all(extras)?;
+    let temp_path = sidecar_path(&path, ".tmp");
+    let mut file = BufWriter::new(std::fs::File::create(&temp_path)?);
+    let mut stream = response.bytes_stream();
+    while let Some(bytes) = stream.next().await {
+        let bytes = bytes.map_err(BandcampError::from)?;
+        file.write_all(&bytes)?;
+        stats.bytes += bytes.len() as u64;
+    }
+    file.flush()?;
+    drop(file);
+    std::fs::rename(&temp_path, &path)?;
+    Ok(path)
+}
+
+/// What a finished transfer left in the temporary file
+struct Fetched {
+    /// Name from the Content-Disposition header
+    server_filename: Option<String>,
+    served_extension: Option<String>,
+    is_archive: bool,
+    sha256: String,
+}
+
+/// Streams `download_url` into `temp_path`, hashing each chunk as it is written
+async fn fetch_to<P: DownloadProgressReporter>(
+    client: &BandcampClient,
+    item: &LibraryItem,
+    download_url: &str,
+    temp_path: &Path,
+    reporter: &P,
+    stats: &mut TransferStats,
+) -> Result<Fetched> {
+    let response = client.download(download_url).await?;
+
+    if !response.status().is_success() {
+        let error = http_error(&response);
+        // A rejected signature, the caller tries again with a fresh download page
+        if matches!(response.status().as_u16(), 403 | 410) {
+            client.forget_download_page(&item.id);
+        } else {
+            reporter.on_error(&error.to_string()).await;
+        }
+        return Err(error);
+    }
+
+    let total_size = response.content_length();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let server_filename = response
+        .headers()
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(content_disposition_filename);
+    let mut stream = response.bytes_stream();
+    let mut chunk = stream
+        .next()
+        .await
+        .transpose()
+        .map_err(BandcampError::from)?;
+
+    // What the bytes are beats what Bandcamp says it sent, which beats guessing from the item
+    // type: a track can come with bonus material in a zip and a one-track release as a bare file
+    let first_chunk = chunk.as_deref().unwrap_or_default();
+    let served_extension = sniff_extension(first_chunk)
+        .map(str::to_string)
+        .or_else(|| server_filename.as_deref().and_then(file_extension));
+    let is_archive = match &served_extension {
+        Some(ext) => ext == "zip",
+        None => item.item_type != ItemType::Track,
+    };
+
+    // Reported by download_item once it gives up retrying
+    check_download_start(first_chunk, content_type.as_deref(), is_archive)?;
+
+    reporter.on_start(total_size).await;
+
+    if let Some(parent) = temp_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = BufWriter::new(std::fs::File::create(temp_path)?);
+
+    // Download with progress reporting, hashing each chunk as it is written
+    let mut hasher = Sha256::new();
+
+    while let Some(bytes) = chunk {
+        file.write_all(&bytes)?;
+        hasher.update(&bytes);
+        stats.bytes += bytes.len() as u64;
+        reporter.on_progress(stats.bytes, total_size).await;
+
+        chunk = stream
+            .next()
+            .await
+            .transpose()
+            .map_err(BandcampError::from)?;
+    }
+
+    file.flush()?;
+    drop(file);
+
+    Ok(Fetched {
+        server_filename,
+        served_extension,
+        is_archive,
+        sha256: format!("{:x}", hasher.finalize()),
+    })
+}
+
+/// Has aria2c download `download_url` into `temp_path`. It doesn't tell what the server called
+/// the file, so the type is told from the first bytes and the item type alone.
+async fn fetch_with_aria2c<P: DownloadProgressReporter>(
+    client: &BandcampClient,
+    item: &LibraryItem,
+    download_url: &str,
+    temp_path: &Path,
+    reporter: &P,
+    stats: &mut TransferStats,
+) -> Result<Fetched> {
+    reporter.on_start(None).await;
+
+    let headers = client.download_headers()?;
+    if let Some(parent) = temp_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    external::aria2c(download_url, &headers, temp_path).await?;
+
+    let path = temp_path.to_path_buf();
+    let (first_chunk, bytes, sha256) = tokio::task::spawn_blocking(move || -> Result<_> {
+        let mut file = std::fs::File::open(&path)?;
+        let mut first_chunk = Vec::new();
+        (&mut file)
+            .take(SNIFF_LENGTH)
+            .read_to_end(&mut first_chunk)?;
+        let mut hasher = Sha256::new();
+        hasher.update(&first_chunk);
+        let bytes = first_chunk.len() as u64 + std::io::copy(&mut file, &mut hasher)?;
+        Ok((first_chunk, bytes, format!("{:x}", hasher.finalize())))
+    })
+    .await
+    .map_err(|e| BandcampError::DownloadError(format!("Task join error: {e}")))??;
+
+    stats.bytes += bytes;
+    reporter.on_progress(stats.bytes, Some(bytes)).await;
+
+    let served_extension = sniff_extension(&first_chunk).map(str::to_string);
+    let is_archive = match &served_extension {
+        Some(ext) => ext == "zip",
+        None => item.item_type != ItemType::Track,
+    };
+    check_download_start(&first_chunk, None, is_archive)?;
+
+    Ok(Fetched {
+        server_filename: None,
+        served_extension,
+        is_archive,
+        sha256,
+    })
+}
+
+/// `served` as a name below the output directory. Archives arrive as `Artist - Album.zip`,
+/// their folder drops the extension again.
+fn original_filename(served: &str, is_archive: bool, sanitize: &SanitizeOptions) -> String {
+    let name = match served.rsplit_once('.') {
+        Some((stem, _)) if is_archive && file_extension(served).as_deref() == Some("zip") => stem,
+        _ => served,
+    };
+    sanitize_filename(name, sanitize)
+}
+
+/// Extension of a download recognised by its first bytes, `zip` for archives
+pub fn sniff_extension(first_chunk: &[u8]) -> Option<&'static str> {
+    let at =
+        |offset: usize, magic: &[u8]| first_chunk.get(offset..offset + magic.len()) == Some(magic);
+
+    if at(0, b"PK\x03\x04") {
+        Some("zip")
+    } else if at(0, b"fLaC") {
+        Some("flac")
+    } else if at(0, b"ID3") || at(0, b"\xff\xfb") || at(0, b"\xff\xf3") || at(0, b"\xff\xf2") {
+        Some("mp3")
+    } else if at(0, b"OggS") {
+        Some("ogg")
+    } else if at(0, b"RIFF") && at(8, b"WAVE") {
+        Some("wav")
+    } else if at(0, b"FORM") && (at(8, b"AIFF") || at(8, b"AIFC")) {
+        Some("aiff")
+    } else if at(4, b"ftyp") {
+        Some("m4a")
+    } else {
+        None
+    }
+}
+
+/// Lowercase extension of a served file name, if it looks like one
+fn file_extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    let plausible = !stem.is_empty()
+        && (1..=5).contains(&ext.len())
+        && ext.chars().all(|c| c.is_ascii_alphanumeric())
+        && ext.chars().any(|c| c.is_ascii_alphabetic());
+    plausible.then(|| ext.to_ascii_lowercase())
+}
+
+/// Rejects a download whose first bytes are no file at all. A signed URL that expired or hit a
+/// CDN hiccup can still answer 200, with an empty body or an HTML error page that would only
+/// fail later during extraction with a confusing zip error.
+pub fn check_download_start(
+    first_chunk: &[u8],
+    content_type: Option<&str>,
+    expect_zip: bool,
+) -> Result<()> {
+    if first_chunk.is_empty() {
+        return Err(BandcampError::BadDownload("empty response".to_string()));
+    }
+
+    let is_html = content_type.is_some_and(|t| t.trim_start().starts_with("text/html"))
+        || first_chunk.trim_ascii_start().starts_with(b"<");
+    if is_html {
+        if is_maintenance_page(first_chunk) {
+            return Err(BandcampError::SiteDown);
+        }
+        if let Some(cooldown) = download_limit_cooldown(first_chunk) {
+            return Err(BandcampError::DownloadLimited { cooldown });
+        }
+        let page = String::from_utf8_lossy(first_chunk);
+        let title = page
+            .split_once("<title>")
+            .and_then(|(_, rest)| rest.split_once("</title>"))
+            .map(|(title, _)| title.trim())
+            .filter(|title| !title.is_empty());
+        return Err(BandcampError::BadDownload(match title {
+            Some(title) => format!("got an HTML page ({title}) instead of the file"),
+            None => "got an HTML page instead of the file".to_string(),
+        }));
+    }
+
+    if expect_zip && !first_chunk.starts_with(b"PK") {
+        return Err(BandcampError::BadDownload(
+            "album download is not a zip archive".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Extracts a ZIP archive to the specified directory.
-///
-/// Returns the `output_dir` path on success.
+/// A single top-level folder inside the archive is unwrapped into `output_dir`.
 pub fn extract_zip(zip_path: &Path, output_dir: &Path) -> Result<()> {
+    extract_zip_with_progress(zip_path, output_dir, false, |_, _, _| {})?;
+    Ok(())
+}
+
+/// Same as [`extract_zip`], but hashes every file while it is being written.
+///
+/// Returns the SHA-256 of each extracted file, relative to `output_dir`.
+pub fn extract_zip_with_checksums(
+    zip_path: &Path,
+    output_dir: &Path,
+) -> Result<Vec<(PathBuf, String)>> {
+    extract_zip_with_progress(zip_path, output_dir, true, |_, _, _| {})
+}
+
+/// Extracts a ZIP archive like [`extract_zip`], calling `on_entry` with the relative path,
+/// the number of files written so far and the archive's file count before each file is
+/// written. Folders don't count.
+///
+/// Returns the SHA-256 of each extracted file when `checksums` is set, otherwise nothing.
+pub fn extract_zip_with_progress(
+    zip_path: &Path,
+    output_dir: &Path,
+    checksums: bool,
+    on_entry: impl FnMut(&Path, usize, usize),
+) -> Result<Vec<(PathBuf, String)>> {
     debug!("Extracting {zip_path:?} to {output_dir:?}");
 
+    let mut target = DirectoryTarget::new(output_dir)?;
+    let sums = extract_zip_into(zip_path, &mut target, checksums, on_entry)?;
+    target.finish()?;
+    Ok(sums)
+}
+
+/// Extracts a ZIP archive into any [`ExtractTarget`], flattening a single top-level folder
+/// like [`extract_zip`]. The target is left for the caller to finish.
+pub fn extract_zip_into(
+    zip_path: &Path,
+    target: &mut dyn ExtractTarget,
+    checksums: bool,
+    mut on_entry: impl FnMut(&Path, usize, usize),
+) -> Result<Vec<(PathBuf, String)>> {
     let file = std::fs::File::open(zip_path)?;
 
-    zip::ZipArchive::new(file)
-        .map_err(|e| BandcampError::DownloadError(format!("Invalid ZIP file: {e}")))?
-        .extract(output_dir)
-        .map_err(|e| BandcampError::DownloadError(format!("ZIP extraction failed: {e}")))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| BandcampError::DownloadError(format!("Invalid ZIP file: {e}")))?;
+
+    // Some archives wrap everything in a folder named after the release, which would
+    // otherwise end up as `Artist - Album/Artist - Album/...`
+    let root = archive.root_dir(root_dir_common_filter).ok().flatten();
+    if let Some(root) = &root {
+        debug!("Flattening single root folder {root:?}");
+    }
+
+    let total = archive
+        .file_names()
+        .filter(|name| !name.ends_with('/'))
+        .count();
+    let mut done = 0;
+    let mut sums = Vec::new();
+
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| BandcampError::DownloadError(format!("ZIP extraction failed: {e}")))?;
 
-    fix_permissions(output_dir)?;
+        let Some(name) = entry.enclosed_name() else {
+            warn!("Skipping ZIP entry with unsafe path: {}", entry.name());
+            continue;
+        };
 
+        let relative = match &root {
+            Some(root) => name.strip_prefix(root).unwrap_or(&name).to_path_buf(),
+            None => name,
+        };
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        if entry.is_dir() {
+            target.create_dir(&relative)?;
+            continue;
+        }
+
+        on_entry(&relative, done, total);
+        done += 1;
+
+        let mut writer = target.create_file(&relative)?;
+        let mut hasher = checksums.then(Sha256::new);
+        let mut buf = [0u8; 64 * 1024];
+        loop {
+            let n = entry.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            writer.write_all(&buf[..n])?;
+            if let Some(hasher) = &mut hasher {
+                hasher.update(&buf[..n]);
+            }
+        }
+        writer.flush()?;
+        drop(writer);
+
+        if let Some(hasher) = hasher {
+            sums.push((relative, format!("{:x}", hasher.finalize())));
+        }
+    }
+
+    Ok(sums)
+}
+
+/// Writes `sha256sum`-compatible lines for the given files into [`CHECKSUM_FILE`]
+fn write_checksum_file(target: &mut dyn ExtractTarget, sums: &[(PathBuf, String)]) -> Result<()> {
+    let mut contents = String::new();
+    for (path, hash) in sums {
+        contents.push_str(&format!("{hash}  {}\n", path.to_string_lossy()));
+    }
+    let mut file = target.create_file(Path::new(CHECKSUM_FILE))?;
+    file.write_all(contents.as_bytes())?;
+    file.flush()?;
     Ok(())
 }
 
-/// Recursively resets permissions to 0755 for directories and 0644 for files.
-fn fix_permissions(path: &Path) -> Result<()> {
-    use std::fs::Permissions;
-    use std::os::unix::fs::PermissionsExt;
+/// Drops `paths` from the output's [`Scratch`] list once they are finished. Failing that
+/// only leaves a stale entry, `clean` skips paths that are gone.
+fn release_scratch<P: AsRef<Path>>(root: &Path, paths: &[P]) {
+    if let Err(e) = Scratch::release(root, paths) {
+        warn!("Couldn't update the list of unfinished files: {e}");
+    }
+}
+
+/// Removes a download along with its sidecars, after it turned out broken
+fn discard_output(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)?;
+        return Ok(());
+    }
+    std::fs::remove_file(path)?;
+    for suffix in [TRACK_CHECKSUM_SUFFIX, TRACK_PURCHASE_INFO_SUFFIX] {
+        let sidecar = sidecar_path(path, suffix);
+        if sidecar.is_file() {
+            std::fs::remove_file(sidecar)?;
+        }
+    }
+    Ok(())
+}
 
-    for entry in std::fs::read_dir(path)? {
-        let entry = entry?;
-        let file_type = entry.file_type()?;
+/// Hashes the files in `path`'s checksums again after their tags changed. Drops files that
+/// are gone, like a cover embedded into the tracks.
+fn refresh_checksums(path: &Path) -> Result<()> {
+    if path.is_file() {
+        if sidecar_path(path, TRACK_CHECKSUM_SUFFIX).is_file() {
+            write_track_checksum(path, &sha256_file(path)?)?;
+        }
+        return Ok(());
+    }
 
-        if file_type.is_dir() {
-            std::fs::set_permissions(entry.path(), Permissions::from_mode(0o755))?;
-            fix_permissions(&entry.path())?;
-        } else {
-            std::fs::set_permissions(entry.path(), Permissions::from_mode(0o644))?;
+    let sums_path = path.join(CHECKSUM_FILE);
+    let Ok(sums) = std::fs::read_to_string(&sums_path) else {
+        return Ok(());
+    };
+    let mut contents = String::new();
+    for line in sums.lines() {
+        let Some((_, relative)) = line.split_once("  ") else {
+            continue;
+        };
+        let file = path.join(relative);
+        if file.is_file() {
+            contents.push_str(&format!("{}  {relative}\n", sha256_file(&file)?));
         }
     }
+    std::fs::write(sums_path, contents)?;
+    Ok(())
+}
+
+/// `track` with `suffix` appended to its full name, e.g. `song.flac.sha256`
+fn sidecar_path(track: &Path, suffix: &str) -> PathBuf {
+    let mut sidecar = track.as_os_str().to_owned();
+    sidecar.push(suffix);
+    PathBuf::from(sidecar)
+}
+
+/// Writes a `<file>.sha256` sidecar next to a downloaded track
+fn write_track_checksum(track: &Path, hash: &str) -> Result<()> {
+    let name = track
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let sidecar = sidecar_path(track, TRACK_CHECKSUM_SUFFIX);
+    std::fs::write(sidecar, format!("{hash}  {name}\n"))?;
+    Ok(())
+}
+
+/// Writes [`PURCHASE_INFO_FILE`] into an album folder, or a `<file>.purchase.json` sidecar for tracks
+fn write_purchase_info(item: &LibraryItem, output_path: &Path) -> Result<()> {
+    #[derive(Serialize)]
+    struct PurchaseSidecar<'a> {
+        id: &'a str,
+        artist: &'a str,
+        title: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        release_date: Option<chrono::NaiveDate>,
+        #[serde(flatten)]
+        purchase: &'a PurchaseInfo,
+    }
+
+    let path = if output_path.is_dir() {
+        output_path.join(PURCHASE_INFO_FILE)
+    } else {
+        sidecar_path(output_path, TRACK_PURCHASE_INFO_SUFFIX)
+    };
 
+    let contents = serde_json::to_string_pretty(&PurchaseSidecar {
+        id: &item.id,
+        artist: &item.artist,
+        title: &item.title,
+        release_date: item.release_date,
+        purchase: &item.purchase,
+    })
+    .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+    std::fs::write(path, contents)?;
     Ok(())
 }
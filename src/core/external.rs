@@ -0,0 +1,120 @@
+//! Handing file transfers to other programs. bannedcamp still logs in, resolves the download
+//! link, and extracts and records what arrives.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use clap::ValueEnum;
+use tokio::io::AsyncWriteExt;
+use tracing::debug;
+
+use crate::error::{BandcampError, Result};
+
+/// Programs run from somewhere else than PATH, by the name they have there
+static PROGRAMS: Mutex<BTreeMap<&'static str, PathBuf>> = Mutex::new(BTreeMap::new());
+
+/// Runs `path` wherever bannedcamp would run `name` from PATH, e.g. `ffprobe`, `ffmpeg` or
+/// `aria2c`. Changing PATH instead would race with everything else reading the environment.
+pub fn set_program(name: &'static str, path: PathBuf) {
+    let mut programs = PROGRAMS.lock().unwrap_or_else(|e| e.into_inner());
+    programs.insert(name, path);
+}
+
+/// What to run for `name`, the override from [`set_program`] or the name looked up on PATH
+pub(crate) fn program(name: &str) -> PathBuf {
+    let programs = PROGRAMS.lock().unwrap_or_else(|e| e.into_inner());
+    programs
+        .get(name)
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+/// What transfers the files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Downloader {
+    /// bannedcamp itself
+    #[default]
+    Builtin,
+    /// aria2c, with several connections per file
+    Aria2c,
+}
+
+impl Downloader {
+    /// Checks the program can be run, so a missing one fails before downloading anything
+    pub fn check_available(self) -> Result<()> {
+        match self {
+            Downloader::Builtin => Ok(()),
+            Downloader::Aria2c => Command::new(program("aria2c"))
+                .arg("--version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|_| ())
+                .map_err(|e| {
+                    BandcampError::DownloadError(format!(
+                        "--downloader aria2c needs aria2c on PATH: {e}"
+                    ))
+                }),
+        }
+    }
+}
+
+/// Downloads `url` to `path` with aria2c. The request goes in through stdin rather than the
+/// command line, where `headers` and their cookie would show up in the process list.
+/// Dropping the future, e.g. when the batch is cancelled, kills aria2c.
+pub async fn aria2c(url: &str, headers: &[String], path: &Path) -> Result<()> {
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(BandcampError::DownloadError(format!(
+            "Not a file path: {}",
+            path.display()
+        )));
+    };
+
+    let mut input = format!(
+        "{url}\n  dir={}\n  out={}\n",
+        dir.display(),
+        name.to_string_lossy()
+    );
+    for header in headers {
+        input.push_str(&format!("  header={header}\n"));
+    }
+
+    debug!("Handing {} to aria2c", path.display());
+    let mut child = tokio::process::Command::new(program("aria2c"))
+        .args([
+            "--input-file=-",
+            "--console-log-level=error",
+            "--summary-interval=0",
+        ])
+        .args(["--download-result=hide", "--allow-overwrite=true"])
+        .args(["--auto-file-renaming=false", "--file-allocation=none"])
+        .args(["--max-connection-per-server=4", "--split=4"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| BandcampError::DownloadError(format!("Could not run aria2c: {e}")))?;
+    // Closed once written, aria2c starts on end of input
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let message = [stderr.trim(), stdout.trim()]
+        .into_iter()
+        .find(|message| !message.is_empty())
+        .unwrap_or("no output");
+    // aria2c already retried on its own, another round only helps when the link went bad
+    Err(BandcampError::BadDownload(format!(
+        "aria2c exited with {}: {message}",
+        output.status
+    )))
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::core::artwork::ArtworkPolicy;
+use crate::core::library::{AudioFormat, LibraryItem};
+use crate::core::utils::glob_match;
+use crate::error::{BandcampError, Result};
+
+/// Per-item overrides of a setting like the format, read from a file with one
+/// `pattern = value` rule per line:
+///
+/// ```text
+/// # exact item IDs win over artist globs listed after them
+/// 1234567890 = wav
+/// *Classical* = flac
+/// ```
+///
+/// A pattern made only of digits matches an item ID, anything else is a case-insensitive
+/// artist glob. The first matching rule wins.
+#[derive(Debug, Clone)]
+pub struct ItemMap<T> {
+    rules: Vec<(Pattern, T)>,
+}
+
+/// `--format-map`, overriding `--format`
+pub type FormatMap = ItemMap<AudioFormat>;
+
+/// `--artwork-map`, overriding `--artwork`
+pub type ArtworkMap = ItemMap<ArtworkPolicy>;
+
+impl<T> Default for ItemMap<T> {
+    fn default() -> Self {
+        Self { rules: Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    ItemId(String),
+    Artist(String),
+}
+
+impl<T: ValueEnum + Copy> ItemMap<T> {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+            .map_err(|e| BandcampError::ParseError(format!("{}: {e}", path.display())))
+    }
+
+    pub fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let mut rules = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (pattern, value) = line
+                .rsplit_once('=')
+                .ok_or_else(|| format!("line {}: expected `pattern = value`", number + 1))?;
+            let (pattern, value) = (pattern.trim(), value.trim());
+
+            let value = T::from_str(value, true)
+                .map_err(|_| format!("line {}: unknown value {value:?}", number + 1))?;
+            let pattern = if !pattern.is_empty() && pattern.chars().all(|c| c.is_ascii_digit()) {
+                Pattern::ItemId(pattern.to_string())
+            } else {
+                Pattern::Artist(pattern.to_string())
+            };
+
+            rules.push((pattern, value));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Whether any rule sets `value`
+    pub fn uses(&self, value: T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.rules.iter().any(|(_, v)| *v == value)
+    }
+
+    /// Value for `item`, falling back to `default` when no rule matches
+    pub fn resolve(&self, item: &LibraryItem, default: T) -> T {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| match pattern {
+                Pattern::ItemId(id) => *id == item.id,
+                Pattern::Artist(glob) => glob_match(glob, &item.artist),
+            })
+            .map(|(_, value)| *value)
+            .unwrap_or(default)
+    }
+}
@@ -0,0 +1,158 @@
+use std::fmt::Write as _;
+use std::path::{Component, Path};
+
+use chrono::{DateTime, Utc};
+
+use crate::core::artwork::find_cover;
+use crate::core::library::LibraryItem;
+use crate::core::report::html_escape;
+use crate::core::state::Manifest;
+use crate::error::Result;
+
+/// Where the gallery keeps its copies of the album covers, next to `index.html`
+const COVERS_DIR: &str = "covers";
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;background:#fafafa;color:#222}\
+.grid{display:grid;grid-template-columns:repeat(auto-fill,minmax(200px,1fr));gap:1.5em}\
+.item img,.item .blank{width:100%;aspect-ratio:1;object-fit:cover;background:#ddd;display:block}\
+.item p{margin:.3em 0}.artist{color:#555}.date{color:#888;font-size:.85em}";
+
+/// One item of the gallery, with the paths relative to the gallery's directory
+#[derive(Debug, Clone)]
+pub struct GalleryEntry {
+    pub item: LibraryItem,
+    /// The downloaded file or album folder, `None` if it isn't downloaded
+    pub local: Option<String>,
+    pub cover: Option<String>,
+}
+
+/// Writes `index.html` with every item of the collection into `gallery_dir`. Covers of
+/// downloaded albums are copied along, so the page keeps its images wherever it is served from.
+pub fn write_gallery(
+    gallery_dir: &Path,
+    output_dir: &Path,
+    items: &[LibraryItem],
+    manifest: &Manifest,
+) -> Result<Vec<GalleryEntry>> {
+    std::fs::create_dir_all(gallery_dir.join(COVERS_DIR))?;
+    let gallery_root = std::fs::canonicalize(gallery_dir)?;
+
+    let mut entries = Vec::with_capacity(items.len());
+    for item in items {
+        let path = manifest
+            .locate(output_dir, item)
+            .and_then(|path| std::fs::canonicalize(path).ok());
+
+        let cover = match path.as_deref().and_then(find_cover) {
+            Some(source) => {
+                let extension = source.extension().unwrap_or_default().to_string_lossy();
+                let name = format!("{}.{}", item.id, extension.to_ascii_lowercase());
+                std::fs::copy(&source, gallery_root.join(COVERS_DIR).join(&name))?;
+                Some(format!("{COVERS_DIR}/{name}"))
+            }
+            None => None,
+        };
+
+        entries.push(GalleryEntry {
+            item: item.clone(),
+            local: path.map(|path| relative_link(&gallery_root, &path)),
+            cover,
+        });
+    }
+
+    std::fs::write(
+        gallery_root.join("index.html"),
+        render_gallery(&entries, Utc::now()),
+    )?;
+    Ok(entries)
+}
+
+pub fn render_gallery(entries: &[GalleryEntry], generated_at: DateTime<Utc>) -> String {
+    let downloaded = entries.iter().filter(|e| e.local.is_some()).count();
+
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html>\n<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>Bandcamp collection</title>");
+    let _ = writeln!(out, "<style>{STYLE}</style>");
+    let _ = writeln!(out, "</head>\n<body>");
+    let _ = writeln!(out, "<h1>Bandcamp collection</h1>");
+    let _ = writeln!(
+        out,
+        "<p>{} items, {downloaded} downloaded. Generated {}</p>",
+        entries.len(),
+        generated_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let _ = writeln!(out, "<div class=\"grid\">");
+
+    for entry in entries {
+        let item = &entry.item;
+        let _ = writeln!(out, "<div class=\"item\">");
+        match &entry.cover {
+            Some(cover) => {
+                let _ = writeln!(
+                    out,
+                    "<img src=\"{}\" alt=\"\" loading=\"lazy\">",
+                    html_escape(cover)
+                );
+            }
+            None => {
+                let _ = writeln!(out, "<div class=\"blank\"></div>");
+            }
+        }
+
+        // The title opens the local copy, the release page otherwise
+        let title = html_escape(&item.title);
+        let title = match (&entry.local, &item.item_url) {
+            (Some(local), _) => format!("<a href=\"{}\">{title}</a>", html_escape(local)),
+            (None, Some(url)) => format!("<a href=\"{}\">{title}</a>", html_escape(url)),
+            (None, None) => title,
+        };
+        let _ = writeln!(out, "<p><strong>{title}</strong></p>");
+        let _ = writeln!(out, "<p class=\"artist\">{}</p>", html_escape(&item.artist));
+
+        let mut details = Vec::new();
+        if let Some(purchased_at) = item.purchase.purchased_at {
+            details.push(purchased_at.format("%Y-%m-%d").to_string());
+        }
+        if entry.local.is_some()
+            && let Some(url) = &item.item_url
+        {
+            details.push(format!("<a href=\"{}\">Bandcamp</a>", html_escape(url)));
+        }
+        if !details.is_empty() {
+            let _ = writeln!(out, "<p class=\"date\">{}</p>", details.join(" · "));
+        }
+        let _ = writeln!(out, "</div>");
+    }
+
+    let _ = writeln!(out, "</div>\n</body>\n</html>");
+    out
+}
+
+/// `path` as a link from a page in `base`, both absolute. Falls back to a `file://` link when
+/// they share no root.
+fn relative_link(base: &Path, path: &Path) -> String {
+    let base: Vec<_> = base.components().collect();
+    let target: Vec<_> = path.components().collect();
+    let common = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    let encode = |component: &Component| {
+        url::form_urlencoded::byte_serialize(component.as_os_str().as_encoded_bytes())
+            .collect::<String>()
+            .replace('+', "%20")
+    };
+    if common == 0 {
+        return url::Url::from_file_path(path)
+            .map(String::from)
+            .unwrap_or_else(|_| path.display().to_string());
+    }
+
+    let mut segments = vec!["..".to_string(); base.len() - common];
+    segments.extend(target[common..].iter().map(encode));
+    if path.is_dir() {
+        segments.push(String::new());
+    }
+    segments.join("/")
+}
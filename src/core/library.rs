@@ -1,6 +1,10 @@
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
+use crate::core::utils::{SanitizeOptions, sanitize_filename};
+use crate::error::{BandcampError, Result};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LibraryItem {
     pub id: String,
@@ -20,35 +24,241 @@ pub struct LibraryItem {
     pub download_url: String,
     pub is_preorder: bool,
     pub is_hidden: bool,
+
+    /// How the item ended up in the collection
+    #[serde(default)]
+    pub purchase: PurchaseInfo,
+
+    /// Not part of the collection API, read from the item's page when a name template uses
+    /// `{year}` or the TUI preview fetched it
+    #[serde(default)]
+    pub release_date: Option<NaiveDate>,
+}
+
+/// Purchase details from the collection API, every field is optional as Bandcamp omits them freely
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PurchaseInfo {
+    /// When the item was bought or received as a gift
+    pub purchased_at: Option<DateTime<Utc>>,
+    /// Amount paid in `currency`
+    pub price: Option<f64>,
+    pub currency: Option<String>,
+    /// Name of the fan who gifted the item
+    pub gift_from: Option<String>,
+    /// Message sent along with the gift
+    pub gift_note: Option<String>,
+    /// The fan's own note on the item, shown on their collection page
+    pub note: Option<String>,
+}
+
+/// An item on the user's wishlist, not owned yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WishlistItem {
+    pub id: String,
+    pub item_type: ItemType,
+    pub title: String,
+    pub artist: String,
+    pub item_url: Option<String>,
+    /// Minimum price in `currency`, if Bandcamp reported one
+    pub price: Option<f64>,
+    pub currency: Option<String>,
+}
+
+impl WishlistItem {
+    /// Name-your-price items with a minimum of zero can be downloaded at no cost
+    pub fn is_free(&self) -> bool {
+        self.price == Some(0.0)
+    }
+}
+
+/// A file a release offers next to its audio on the download page, e.g. a PDF booklet or a video
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BonusItem {
+    pub title: String,
+    pub url: String,
+    /// Name Bandcamp gives the file, if the page lists one
+    pub filename: Option<String>,
+}
+
+/// Release details read from an item's public page, fetched on demand for the TUI preview
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemDetails {
+    /// As Bandcamp prints it, e.g. "14 Mar 2024 00:00:00 GMT"
+    pub release_date: Option<String>,
+    pub tracks: Vec<TrackInfo>,
+    /// Download page of a free release, reachable without buying it
+    pub free_download_url: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackInfo {
+    pub title: String,
+    /// Zero when Bandcamp doesn't report one
+    pub duration_secs: f64,
+}
+
+/// Day part of a date as Bandcamp prints it, e.g. "14 Mar 2024 00:00:00 GMT"
+pub fn parse_release_date(date: &str) -> Option<NaiveDate> {
+    let day = date
+        .trim()
+        .splitn(4, ' ')
+        .take(3)
+        .collect::<Vec<_>>()
+        .join(" ");
+    NaiveDate::parse_from_str(&day, "%d %b %Y").ok()
+}
+
+/// Whether rendering `format` needs [`LibraryItem::release_date`]
+pub fn uses_release_date(format: &str) -> bool {
+    format.contains("{year}")
 }
 
 /// Custom name format for downloads
-/// {artist}, {title}, {id}, {ext}
-const DEFAULT_ALBUM_FORMAT: &str = "{artist} - {title}";
-const DEFAULT_TRACK_FORMAT: &str = "{artist} - {title}{ext}";
+/// {artist}, {title}, {id}, {ext}, {year}
+pub const DEFAULT_ALBUM_FORMAT: &str = "{artist} - {title}";
+pub const DEFAULT_TRACK_FORMAT: &str = "{artist} - {title}{ext}";
 
-impl LibraryItem {
-    /// Constructs the folder or filename it will be downloaded as
-    pub fn construct_filename(&self, format: AudioFormat, custom_format: Option<&str>) -> String {
-        let extension_str = if self.item_type == ItemType::Track {
-            format!(".{}", format.extension())
-        } else {
-            String::new()
+/// Placeholders name templates can use, without their braces
+pub const TEMPLATE_VARIABLES: &[&str] = &["artist", "title", "id", "ext", "year"];
+
+/// Placeholders in `format` that aren't in [`TEMPLATE_VARIABLES`] and would end up in file
+/// names as they are, e.g. `{genre}` or a misspelled `{artst}`
+pub fn unknown_template_variables(format: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
         };
+        let name = &rest[start + 1..start + len];
+        if !TEMPLATE_VARIABLES.contains(&name) && !unknown.iter().any(|u| u == name) {
+            unknown.push(name.to_string());
+        }
+        rest = &rest[start + len + 1..];
+    }
+    unknown
+}
+
+/// Directory separators in name templates, either one works on every platform
+const TEMPLATE_SEPARATORS: [char; 2] = ['/', '\\'];
+
+/// Rejects name templates that would place downloads outside the output directory
+pub fn validate_name_format(format: &str) -> Result<()> {
+    let bytes = format.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if format.starts_with(TEMPLATE_SEPARATORS) || has_drive {
+        return Err(BandcampError::InvalidTemplate(format!(
+            "\"{format}\" is an absolute path, downloads have to stay inside the output directory"
+        )));
+    }
+    if format
+        .split(TEMPLATE_SEPARATORS)
+        .any(|part| part.trim() == "..")
+    {
+        return Err(BandcampError::InvalidTemplate(format!(
+            "\"{format}\" contains `..`, downloads have to stay inside the output directory"
+        )));
+    }
+    Ok(())
+}
 
-        let name_format = if let Some(fmt) = custom_format {
-            fmt
-        } else if self.item_type == ItemType::Track {
-            DEFAULT_TRACK_FORMAT
+/// Punctuation that only sets `{year}` apart in a name template
+const YEAR_SEPARATORS: &[char] = &[' ', '-', '–', '—', '_', ','];
+
+/// `part` of a name template with `{year}` taken out for an item of unknown release date,
+/// together with the brackets and separators around it. `{year} - {title}` leaves `{title}`,
+/// `{artist} - {year} - {title}` leaves `{artist} - {title}`.
+fn without_year(part: &str) -> String {
+    let mut part = part.to_string();
+    while let Some((before, after)) = part.split_once("{year}") {
+        let (before, after) = match (before.chars().last(), after.chars().next()) {
+            (Some('('), Some(')')) | (Some('['), Some(']')) => {
+                (&before[..before.len() - 1], &after[1..])
+            }
+            _ => (before, after),
+        };
+        let kept_before = before.trim_end_matches(YEAR_SEPARATORS);
+        let kept_after = after.trim_start_matches(YEAR_SEPARATORS);
+        let run_before = &before[kept_before.len()..];
+        let run_after = &after[..after.len() - kept_after.len()];
+        // One separator stays between what is left, unless `{year}` was glued to what follows
+        // like `{year}{ext}`
+        let joint = if kept_before.is_empty() || kept_after.is_empty() || run_after.is_empty() {
+            ""
+        } else if run_before.is_empty() {
+            run_after
         } else {
-            DEFAULT_ALBUM_FORMAT
+            run_before
         };
+        part = format!("{kept_before}{joint}{kept_after}");
+    }
+    part
+}
 
-        name_format
-            .replace("{artist}", &self.artist)
-            .replace("{title}", &self.title)
-            .replace("{ext}", &extension_str)
-            .replace("{id}", &self.id)
+impl LibraryItem {
+    /// Constructs the folder or filename it will be downloaded as
+    pub fn construct_filename(
+        &self,
+        format: AudioFormat,
+        custom_format: Option<&str>,
+        sanitize: &SanitizeOptions,
+    ) -> String {
+        let extension = (self.item_type == ItemType::Track).then(|| format.extension());
+        self.construct_filename_with(extension, custom_format, sanitize)
+    }
+
+    /// Same as [`LibraryItem::construct_filename`] for a download that turned out to be a
+    /// single file with `extension`, or an archive unpacked into a folder when `None`
+    pub fn construct_filename_with(
+        &self,
+        extension: Option<&str>,
+        custom_format: Option<&str>,
+        sanitize: &SanitizeOptions,
+    ) -> String {
+        let extension_str = extension.map(|ext| format!(".{ext}")).unwrap_or_default();
+
+        let name_format = match (custom_format, extension) {
+            (Some(fmt), _) => fmt,
+            (None, Some(_)) => DEFAULT_TRACK_FORMAT,
+            (None, None) => DEFAULT_ALBUM_FORMAT,
+        };
+
+        let year = self.release_date.map(|date| date.year().to_string());
+        let mut parts: Vec<_> = name_format
+            .split(TEMPLATE_SEPARATORS)
+            .map(|part| {
+                let part = match &year {
+                    Some(year) => part.replace("{year}", year),
+                    None => without_year(part),
+                };
+                part.replace("{artist}", &sanitize_filename(&self.artist, sanitize))
+                    .replace("{title}", &sanitize_filename(&self.title, sanitize))
+                    .replace("{ext}", &extension_str)
+                    .replace("{id}", &self.id)
+            })
+            .collect();
+
+        // A folder of only an unknown year goes, the name itself falls back to the ID
+        let name = parts.pop().filter(|name| !name.is_empty());
+        parts.retain(|part| !part.is_empty());
+        parts.push(name.unwrap_or_else(|| format!("{}{extension_str}", self.id)));
+
+        // Sanitized values contain no separators, so every one left came from the template
+        parts.join(std::path::MAIN_SEPARATOR_STR)
+    }
+
+    /// Bandcamp lists no download for the item, e.g. a subscription release that can only be streamed
+    pub fn is_stream_only(&self) -> bool {
+        self.download_url.is_empty()
+    }
+
+    /// Copy without the signature and payment id of its download link, for writing to disk
+    pub fn unsigned(&self) -> Self {
+        let mut item = self.clone();
+        if let Some((path, _)) = item.download_url.split_once('?') {
+            item.download_url = path.to_string();
+        }
+        item
     }
 }
 
@@ -86,6 +296,33 @@ impl AudioFormat {
         AudioFormat::Aiff,
     ];
 
+    /// Formats to try when Bandcamp doesn't offer this one, lossless ones first for lossless
+    /// formats and lossy ones first otherwise
+    pub fn fallbacks(self) -> Vec<AudioFormat> {
+        const LOSSLESS: [AudioFormat; 4] = [
+            AudioFormat::Flac,
+            AudioFormat::Alac,
+            AudioFormat::Aiff,
+            AudioFormat::Wav,
+        ];
+        const LOSSY: [AudioFormat; 4] = [
+            AudioFormat::Mp3320,
+            AudioFormat::Mp3V0,
+            AudioFormat::Aac,
+            AudioFormat::OggVorbis,
+        ];
+
+        let (same, other) = if LOSSLESS.contains(&self) {
+            (LOSSLESS, LOSSY)
+        } else {
+            (LOSSY, LOSSLESS)
+        };
+        same.into_iter()
+            .chain(other)
+            .filter(|format| *format != self)
+            .collect()
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             AudioFormat::Flac => "FLAC (lossless)",
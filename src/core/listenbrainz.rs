@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder, Response};
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{debug, info, warn};
+use url::Url;
+
+use crate::core::client::http_error;
+use crate::error::{BandcampError, Result};
+
+const API: &str = "https://api.listenbrainz.org/1";
+
+/// Attempts per request while ListenBrainz answers with 429
+const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Deserialize)]
+struct TokenCheck {
+    valid: bool,
+    #[serde(default)]
+    user_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Lookup {
+    #[serde(default)]
+    recording_mbid: Option<String>,
+}
+
+/// Marks the recordings of Bandcamp purchases as loved on ListenBrainz
+pub struct ListenBrainz {
+    http: Client,
+    token: String,
+}
+
+impl ListenBrainz {
+    /// Checks the user token from the ListenBrainz settings page
+    pub async fn connect(token: &str) -> Result<Self> {
+        let http = Client::builder()
+            .user_agent(concat!("bannedcamp/", env!("CARGO_PKG_VERSION")))
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let listenbrainz = Self {
+            http,
+            token: token.to_string(),
+        };
+
+        let check: TokenCheck = listenbrainz
+            .send(|| listenbrainz.http.get(format!("{API}/validate-token")))
+            .await?
+            .json()
+            .await?;
+        match check.user_name.filter(|_| check.valid) {
+            Some(user) => info!("Exporting to the ListenBrainz account of {user}"),
+            None => {
+                return Err(BandcampError::AuthError(
+                    "ListenBrainz rejected the token, copy it from the settings page".to_string(),
+                ));
+            }
+        }
+        Ok(listenbrainz)
+    }
+
+    /// Sends the request built by `request`, waiting out the rate limit when ListenBrainz asks to
+    async fn send(&self, request: impl Fn() -> RequestBuilder) -> Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let response = request()
+                .header("Authorization", format!("Token {}", self.token))
+                .send()
+                .await?;
+            if response.status() != 429 || attempt == MAX_ATTEMPTS {
+                return if response.status().is_success() {
+                    Ok(response)
+                } else {
+                    Err(http_error(&response))
+                };
+            }
+
+            let wait = response
+                .headers()
+                .get("X-RateLimit-Reset-In")
+                .and_then(|value| value.to_str().ok()?.parse().ok())
+                .unwrap_or(10);
+            warn!("ListenBrainz rate limit reached, waiting {wait}s");
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            attempt += 1;
+        }
+    }
+
+    /// MusicBrainz ID of the recording, `None` if ListenBrainz doesn't know it
+    pub async fn find_recording(
+        &self,
+        artist: &str,
+        recording: &str,
+        release: Option<&str>,
+    ) -> Result<Option<String>> {
+        let mut query = vec![("artist_name", artist), ("recording_name", recording)];
+        if let Some(release) = release {
+            query.push(("release_name", release));
+        }
+        let url = Url::parse_with_params(&format!("{API}/metadata/lookup/"), &query)
+            .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+
+        let lookup: Lookup = self
+            .send(|| self.http.get(url.clone()))
+            .await?
+            .json()
+            .await?;
+        debug!(
+            "ListenBrainz lookup for {artist} - {recording}: {:?}",
+            lookup.recording_mbid
+        );
+        Ok(lookup.recording_mbid)
+    }
+
+    /// Loving a recording twice changes nothing, exports can be repeated
+    pub async fn love(&self, recording_mbid: &str) -> Result<()> {
+        self.send(|| {
+            self.http
+                .post(format!("{API}/feedback/recording-feedback"))
+                .json(&json!({ "recording_mbid": recording_mbid, "score": 1 }))
+        })
+        .await?;
+        Ok(())
+    }
+}
@@ -1,5 +1,28 @@
+pub mod artwork;
+pub mod audit;
 pub mod auth;
+pub mod clean;
 pub mod client;
+pub mod dedupe;
+pub mod discogs;
 pub mod download;
+pub mod external;
+pub mod format_map;
+pub mod gallery;
 pub mod library;
+pub mod listenbrainz;
+pub mod notify;
+pub mod permissions;
+pub mod plan;
+pub mod progress_file;
+pub mod queue;
+pub mod recording;
+pub mod report;
+pub mod routing;
+pub mod schedule;
+pub mod session;
+pub mod state;
+pub mod tags;
+pub mod target;
 pub mod utils;
+pub mod verify;
@@ -0,0 +1,240 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use crate::core::download::{DownloadProgressReporter, DownloadSummary};
+use crate::core::library::LibraryItem;
+use crate::core::queue::{BatchProgressReporter, SilentReporter};
+use crate::core::utils::format_bytes;
+use crate::error::Result;
+use crate::i18n::{Msg, tr, tr_fmt};
+
+/// A push notification service to report batch progress to
+#[derive(Debug, Clone)]
+pub enum NotifyTarget {
+    /// ntfy.sh or a self-hosted ntfy server, `token` is only needed for protected topics
+    Ntfy {
+        server: String,
+        topic: String,
+        token: Option<String>,
+    },
+    /// Gotify server, `token` is an application token
+    Gotify { server: String, token: String },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Priority {
+    Default,
+    High,
+}
+
+#[derive(Serialize)]
+struct GotifyMessage<'a> {
+    title: &'a str,
+    message: &'a str,
+    priority: u8,
+}
+
+/// Sends failures and batch summaries to every configured [`NotifyTarget`].
+/// Delivery problems are logged and never fail the download itself.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    http: Client,
+    targets: Vec<NotifyTarget>,
+    /// Failure notifications still being sent, the summary waits for them
+    in_flight: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl Notifier {
+    pub fn new(targets: Vec<NotifyTarget>) -> Result<Self> {
+        let http = Client::builder().timeout(Duration::from_secs(10)).build()?;
+
+        Ok(Self {
+            http,
+            targets,
+            in_flight: Arc::default(),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub async fn send(&self, title: &str, message: &str, priority: Priority) {
+        for target in &self.targets {
+            if let Err(e) = self.send_to(target, title, message, priority).await {
+                warn!("Failed to send notification: {e}");
+            }
+        }
+    }
+
+    async fn send_to(
+        &self,
+        target: &NotifyTarget,
+        title: &str,
+        message: &str,
+        priority: Priority,
+    ) -> Result<()> {
+        let request = match target {
+            NotifyTarget::Ntfy {
+                server,
+                topic,
+                token,
+            } => {
+                let url = format!("{}/{topic}", server.trim_end_matches('/'));
+                let priority = match priority {
+                    Priority::Default => "default",
+                    Priority::High => "high",
+                };
+                let request = self
+                    .http
+                    .post(url)
+                    .header("Title", title)
+                    .header("Priority", priority)
+                    .header("Tags", "musical_note")
+                    .body(message.to_string());
+                match token {
+                    Some(token) => request.bearer_auth(token),
+                    None => request,
+                }
+            }
+            NotifyTarget::Gotify { server, token } => {
+                let url = format!("{}/message", server.trim_end_matches('/'));
+                let priority = match priority {
+                    Priority::Default => 5,
+                    Priority::High => 8,
+                };
+                self.http
+                    .post(url)
+                    .header("X-Gotify-Key", token)
+                    .json(&GotifyMessage {
+                        title,
+                        message,
+                        priority,
+                    })
+            }
+        };
+
+        request.send().await?.error_for_status()?;
+        debug!("Sent notification: {title}");
+        Ok(())
+    }
+
+    /// Sends without holding up the caller, [`Self::flush`] waits for it
+    fn send_in_background(&self, title: String, message: String, priority: Priority) {
+        let notifier = self.clone();
+        let handle = tokio::spawn(async move {
+            notifier.send(&title, &message, priority).await;
+        });
+
+        let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+        in_flight.retain(|handle| !handle.is_finished());
+        in_flight.push(handle);
+    }
+
+    /// Waits for the notifications still being sent in the background
+    pub async fn flush(&self) {
+        let in_flight =
+            std::mem::take(&mut *self.in_flight.lock().unwrap_or_else(|e| e.into_inner()));
+        for handle in in_flight {
+            if let Err(e) = handle.await {
+                warn!("Failed to send notification: {e}");
+            }
+        }
+    }
+
+    /// Reports the outcome of a finished batch, after the failures reported on the way
+    pub async fn send_summary(&self, summary: &DownloadSummary) {
+        self.flush().await;
+        let title = tr_fmt(
+            Msg::NotifyDownloaded,
+            &[
+                &summary.success_count(),
+                &(summary.success_count() + summary.failure_count()),
+            ],
+        );
+
+        let mut message = if summary.failed.is_empty() {
+            tr(Msg::NotifyAllSucceeded).to_string()
+        } else {
+            tr_fmt(Msg::NotifyFailedCount, &[&summary.failure_count()])
+        };
+        for (item, error) in &summary.failed {
+            message.push_str(&format!("\n{} - {}: {error}", item.artist, item.title));
+        }
+        if !summary.skipped.is_empty() {
+            message.push('\n');
+            message.push_str(&tr_fmt(
+                Msg::NotifySkippedCount,
+                &[&summary.skipped_count()],
+            ));
+        }
+
+        let priority = if summary.failed.is_empty() {
+            Priority::Default
+        } else {
+            Priority::High
+        };
+        self.send(&title, &message, priority).await;
+    }
+}
+
+/// Sends a notification as soon as an item fails, so long batches don't hide errors until the
+/// end. It goes out in the background, a slow server doesn't hold up the next download.
+impl BatchProgressReporter for Notifier {
+    fn on_item_started(
+        &self,
+        _index: usize,
+        _item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        Box::pin(async { Box::new(SilentReporter) as Box<dyn DownloadProgressReporter> })
+    }
+
+    fn on_item_finished(
+        &self,
+        _index: usize,
+        item: &LibraryItem,
+        result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        if let Err(e) = result {
+            let title = tr_fmt(Msg::NotifyFailed, &[&item.artist, &item.title]);
+            self.send_in_background(title, e.to_string(), Priority::High);
+        }
+        Box::pin(async {})
+    }
+
+    fn on_space_low(
+        &self,
+        dir: &Path,
+        free: u64,
+        min_free: u64,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let message = tr_fmt(
+            Msg::SpaceLow,
+            &[
+                &format_bytes(free as f64),
+                &dir.display(),
+                &format_bytes(min_free as f64),
+            ],
+        );
+        self.send_in_background(tr(Msg::NotifySpaceLow).to_string(), message, Priority::High);
+        Box::pin(async {})
+    }
+
+    fn on_space_freed(&self, dir: &Path) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let message = tr_fmt(Msg::SpaceFreed, &[&dir.display()]);
+        self.send_in_background(
+            tr(Msg::NotifySpaceFreed).to_string(),
+            message,
+            Priority::Default,
+        );
+        Box::pin(async {})
+    }
+}
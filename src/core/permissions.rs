@@ -0,0 +1,126 @@
+use std::ffi::CString;
+use std::fs::{FileType, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use crate::error::{BandcampError, Result};
+
+/// Modes and ownership given to everything a download leaves in the output directory, so
+/// e.g. a media server running as another user can read it whatever the umask is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputPermissions {
+    pub file_mode: u32,
+    pub dir_mode: u32,
+    /// Changing the owner needs root, the group also works for groups the user is in
+    pub owner: Option<u32>,
+    pub group: Option<u32>,
+}
+
+impl Default for OutputPermissions {
+    fn default() -> Self {
+        Self {
+            file_mode: 0o644,
+            dir_mode: 0o755,
+            owner: None,
+            group: None,
+        }
+    }
+}
+
+impl OutputPermissions {
+    /// Applies to `path` and, for a directory, everything below it. Symlinks are never
+    /// followed, only their own ownership changes.
+    pub fn apply(&self, path: &Path) -> Result<()> {
+        let file_type = std::fs::symlink_metadata(path)?.file_type();
+        self.apply_to(path, file_type)?;
+        if file_type.is_dir() {
+            self.apply_below(path)?;
+        }
+        Ok(())
+    }
+
+    /// Applies to everything below `dir`, but not `dir` itself
+    pub fn apply_below(&self, dir: &Path) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            self.apply_to(&entry.path(), file_type)?;
+            if file_type.is_dir() {
+                self.apply_below(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_to(&self, path: &Path, file_type: FileType) -> Result<()> {
+        // chmod on a symlink would change whatever it points to, which may be outside
+        // the output directory
+        if !file_type.is_symlink() {
+            let mode = if file_type.is_dir() {
+                self.dir_mode
+            } else {
+                self.file_mode
+            };
+            std::fs::set_permissions(path, Permissions::from_mode(mode))?;
+        }
+        if self.owner.is_some() || self.group.is_some() {
+            std::os::unix::fs::lchown(path, self.owner, self.group).map_err(|e| {
+                BandcampError::DownloadError(format!(
+                    "Could not change owner of {}: {e}",
+                    path.display()
+                ))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Octal mode such as `640` or `0o2775`
+pub fn parse_mode(mode: &str) -> Option<u32> {
+    let digits = mode.strip_prefix("0o").unwrap_or(mode);
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+}
+
+/// User id for a user name or a numeric id
+pub fn lookup_user(user: &str) -> Option<u32> {
+    if let Ok(uid) = user.parse() {
+        return Some(uid);
+    }
+    let name = CString::new(user).ok()?;
+    lookup_entry(|entry: &mut libc::passwd, buf, result| unsafe {
+        libc::getpwnam_r(name.as_ptr(), entry, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|entry| entry.pw_uid)
+}
+
+/// Group id for a group name or a numeric id
+pub fn lookup_group(group: &str) -> Option<u32> {
+    if let Ok(gid) = group.parse() {
+        return Some(gid);
+    }
+    let name = CString::new(group).ok()?;
+    lookup_entry(|entry: &mut libc::group, buf, result| unsafe {
+        libc::getgrnam_r(name.as_ptr(), entry, buf.as_mut_ptr(), buf.len(), result)
+    })
+    .map(|entry| entry.gr_gid)
+}
+
+/// Runs one of the reentrant `get*nam_r` lookups, growing the string buffer until the entry
+/// fits. Only plain ids are read from the result, so it may outlive the buffer.
+fn lookup_entry<T>(
+    lookup: impl Fn(&mut T, &mut [libc::c_char], &mut *mut T) -> libc::c_int,
+) -> Option<T> {
+    let mut buf = vec![0; 1024];
+    loop {
+        // Both entry types are plain C structs, all zeroes is a valid value
+        let mut entry: T = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        match lookup(&mut entry, &mut buf, &mut result) {
+            libc::ERANGE if buf.len() < 1 << 20 => buf.resize(buf.len() * 2, 0),
+            0 if !result.is_null() => return Some(entry),
+            _ => return None,
+        }
+    }
+}
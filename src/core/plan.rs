@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::core::library::{AudioFormat, ItemType, LibraryItem};
+use crate::core::state::Manifest;
+use crate::core::utils::{SanitizeOptions, sanitize_filename};
+use crate::core::verify::{AUDIO_EXTENSIONS, collect_audio_files};
+
+/// How an item compares to what is already in the output directory, for `--dry-run --diff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanStatus {
+    /// Nothing there yet
+    New,
+    /// Already downloaded in the requested format
+    Exists,
+    /// Downloaded before, but only in the format with this file extension
+    OtherFormat(String),
+}
+
+/// Compares `path`, where `item` would be downloaded as `format`, against the files on disk
+pub fn plan_status(item: &LibraryItem, path: &Path, format: AudioFormat) -> PlanStatus {
+    let wanted = format.extension();
+
+    if item.item_type == ItemType::Track {
+        if path.exists() {
+            return PlanStatus::Exists;
+        }
+        // Only the extension changes between formats of a track
+        if let Some(ext) = AUDIO_EXTENSIONS
+            .iter()
+            .filter(|ext| **ext != wanted)
+            .find(|ext| path.with_extension(ext).exists())
+        {
+            return PlanStatus::OtherFormat(ext.to_string());
+        }
+        // A track served as a zip with extras, extracted into a folder
+        return folder_status(&path.with_extension(""), wanted);
+    }
+
+    // Kept as a zip archive with `--album-output zip`, which isn't looked into
+    if path.is_file() {
+        return PlanStatus::Exists;
+    }
+
+    // A one-track release Bandcamp served as a bare file, named like a track
+    let mut bare = path.as_os_str().to_owned();
+    bare.push(format!(".{wanted}"));
+    if Path::new(&bare).is_file() {
+        return PlanStatus::Exists;
+    }
+
+    folder_status(path, wanted)
+}
+
+/// Like [`plan_status`], but also looks where the manifest says an earlier run put `item`.
+/// That differs from `path` after `--keep-original-names` or a change of the name template.
+pub fn recorded_status(
+    item: &LibraryItem,
+    path: &Path,
+    format: AudioFormat,
+    manifest: &Manifest,
+    output_dir: &Path,
+) -> PlanStatus {
+    let status = plan_status(item, path, format);
+    let Some(located) = manifest.locate(output_dir, item) else {
+        return status;
+    };
+    if status == PlanStatus::Exists || located == path {
+        return status;
+    }
+
+    let wanted = format.extension();
+    let recorded = if located.is_file() {
+        match located
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        {
+            Some(ext) if ext != wanted && AUDIO_EXTENSIONS.contains(&ext.as_str()) => {
+                PlanStatus::OtherFormat(ext)
+            }
+            _ => PlanStatus::Exists,
+        }
+    } else {
+        folder_status(&located, wanted)
+    };
+    match (status, recorded) {
+        (_, PlanStatus::Exists) => PlanStatus::Exists,
+        (PlanStatus::New, recorded) => recorded,
+        (status, _) => status,
+    }
+}
+
+/// Albums keep their folder name whatever the format, so look at what is inside
+fn folder_status(path: &Path, wanted: &str) -> PlanStatus {
+    let mut files = Vec::new();
+    if !path.is_dir() || collect_audio_files(path, &mut files).is_err() {
+        return PlanStatus::New;
+    }
+    let extensions: Vec<String> = files
+        .iter()
+        .filter_map(|file| file.extension())
+        .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+        .collect();
+
+    if extensions.iter().any(|ext| ext == wanted) {
+        PlanStatus::Exists
+    } else {
+        extensions
+            .into_iter()
+            .next()
+            .map_or(PlanStatus::New, PlanStatus::OtherFormat)
+    }
+}
+
+/// A name from Bandcamp that had to change to be used in a path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameChange {
+    pub before: String,
+    pub after: String,
+}
+
+/// An item whose planned path only works with some of its names sanitized
+#[derive(Debug, Clone)]
+pub struct SanitizedItem {
+    pub item: LibraryItem,
+    pub path: PathBuf,
+    pub changes: Vec<NameChange>,
+}
+
+/// Items of a batch that would be written to the same path
+#[derive(Debug, Clone)]
+pub struct NameCollision {
+    pub path: PathBuf,
+    pub items: Vec<LibraryItem>,
+}
+
+/// What the naming template and sanitizing do to a batch, for `--naming-report`
+#[derive(Debug, Clone, Default)]
+pub struct NamingAudit {
+    pub sanitized: Vec<SanitizedItem>,
+    pub collisions: Vec<NameCollision>,
+}
+
+impl NamingAudit {
+    pub fn is_empty(&self) -> bool {
+        self.sanitized.is_empty() && self.collisions.is_empty()
+    }
+}
+
+/// Looks through the `planned` path of every item for names the template could only use
+/// sanitized and for paths several items would share. Paths are compared case-insensitively,
+/// as the output directory may well be on a filesystem that does.
+pub fn audit_names(
+    planned: &[(LibraryItem, PathBuf)],
+    name_format: Option<&str>,
+    sanitize: &SanitizeOptions,
+) -> NamingAudit {
+    let mut audit = NamingAudit::default();
+    let mut by_path: HashMap<String, usize> = HashMap::new();
+    let mut groups: Vec<NameCollision> = Vec::new();
+
+    for (item, path) in planned {
+        let changes: Vec<_> = [("{artist}", &item.artist), ("{title}", &item.title)]
+            .into_iter()
+            .filter(|(variable, _)| name_format.is_none_or(|format| format.contains(variable)))
+            .filter_map(|(_, name)| {
+                let after = sanitize_filename(name, sanitize);
+                (after != *name).then(|| NameChange {
+                    before: name.clone(),
+                    after,
+                })
+            })
+            .collect();
+        if !changes.is_empty() {
+            audit.sanitized.push(SanitizedItem {
+                item: item.clone(),
+                path: path.clone(),
+                changes,
+            });
+        }
+
+        let key = path.to_string_lossy().to_lowercase();
+        match by_path.get(&key) {
+            Some(&group) => groups[group].items.push(item.clone()),
+            None => {
+                by_path.insert(key, groups.len());
+                groups.push(NameCollision {
+                    path: path.clone(),
+                    items: vec![item.clone()],
+                });
+            }
+        }
+    }
+
+    audit.collisions = groups
+        .into_iter()
+        .filter(|group| group.items.len() > 1)
+        .collect();
+    audit
+}
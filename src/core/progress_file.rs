@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::core::download::DownloadProgressReporter;
+use crate::core::library::LibraryItem;
+use crate::core::queue::BatchProgressReporter;
+use crate::core::state::save_json;
+use crate::error::Result;
+
+/// Least time between two writes, the last one of a batch is always written
+const WRITE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether the batch is still going
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchState {
+    Running,
+    Finished,
+}
+
+/// What an item being downloaded is waiting on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStage {
+    FetchingUrl,
+    /// Bandcamp is still encoding the requested format
+    Preparing,
+    Downloading,
+    Extracting,
+}
+
+/// An item of the batch that is being downloaded right now
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveItem {
+    pub id: String,
+    pub artist: String,
+    pub title: String,
+    pub stage: ItemStage,
+    pub bytes: u64,
+    /// Unknown until the server sent the size
+    pub total_bytes: Option<u64>,
+    pub bytes_per_sec: f64,
+}
+
+/// Contents of the `--progress-file`, for dashboards and status bars
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchProgress {
+    pub state: BatchState,
+    pub updated_at: DateTime<Utc>,
+    pub total: usize,
+    pub finished: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Received across the whole batch
+    pub bytes: u64,
+    /// Average since the batch started
+    pub bytes_per_sec: f64,
+    /// Estimated from how long finished items took, `None` before the first one
+    pub eta_secs: Option<u64>,
+    pub active: Vec<ActiveItem>,
+}
+
+struct ItemState {
+    item: ActiveItem,
+    started: Instant,
+}
+
+struct Tracker {
+    path: PathBuf,
+    started: Instant,
+    last_write: Option<Instant>,
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    /// Bytes of items that are done, active items count their own
+    finished_bytes: u64,
+    /// By index in the batch, so they are listed in queue order
+    active: BTreeMap<usize, ItemState>,
+    /// Only the first failing write is logged
+    warned: bool,
+}
+
+impl Tracker {
+    fn snapshot(&self) -> BatchProgress {
+        let finished = self.succeeded + self.failed;
+        let elapsed = self.started.elapsed();
+        let bytes = self.finished_bytes + self.active.values().map(|s| s.item.bytes).sum::<u64>();
+        let active = self
+            .active
+            .values()
+            .map(|state| {
+                let mut item = state.item.clone();
+                item.bytes_per_sec = rate(item.bytes, state.started.elapsed());
+                item
+            })
+            .collect();
+
+        BatchProgress {
+            state: if finished == self.total {
+                BatchState::Finished
+            } else {
+                BatchState::Running
+            },
+            updated_at: Utc::now(),
+            total: self.total,
+            finished,
+            succeeded: self.succeeded,
+            failed: self.failed,
+            bytes,
+            bytes_per_sec: rate(bytes, elapsed),
+            // Finished items already reflect the parallelism, so this is the batch's pace
+            eta_secs: (finished > 0)
+                .then(|| (elapsed / finished as u32 * (self.total - finished) as u32).as_secs()),
+            active,
+        }
+    }
+
+    /// Writes the file unless it was written less than [`WRITE_INTERVAL`] ago
+    fn write(&mut self, force: bool) {
+        if !force
+            && self
+                .last_write
+                .is_some_and(|at| at.elapsed() < WRITE_INTERVAL)
+        {
+            return;
+        }
+        self.last_write = Some(Instant::now());
+        if let Err(e) = save_json(&self.path, &self.snapshot())
+            && !self.warned
+        {
+            warn!("Could not write {}: {e}", self.path.display());
+            self.warned = true;
+        }
+    }
+}
+
+fn rate(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / elapsed.as_secs_f64().max(1.0)
+}
+
+/// Keeps a JSON file with the progress of a batch up to date, replacing it as a whole on every
+/// write so readers never see half of it
+#[derive(Clone)]
+pub struct ProgressFile {
+    tracker: Arc<Mutex<Tracker>>,
+}
+
+impl ProgressFile {
+    /// Starts tracking a batch of `total` items, the file is written right away
+    pub fn new(path: &Path, total: usize) -> Self {
+        let tracker = Tracker {
+            path: path.to_path_buf(),
+            started: Instant::now(),
+            last_write: None,
+            total,
+            succeeded: 0,
+            failed: 0,
+            finished_bytes: 0,
+            active: BTreeMap::new(),
+            warned: false,
+        };
+        let file = Self {
+            tracker: Arc::new(Mutex::new(tracker)),
+        };
+        file.update(true, |_| {});
+        file
+    }
+
+    /// The batch as it would be written now
+    pub fn snapshot(&self) -> BatchProgress {
+        self.tracker.lock().unwrap().snapshot()
+    }
+
+    fn update(&self, force: bool, f: impl FnOnce(&mut Tracker)) {
+        let mut tracker = self.tracker.lock().unwrap();
+        f(&mut tracker);
+        tracker.write(force);
+    }
+}
+
+impl BatchProgressReporter for ProgressFile {
+    fn on_item_started(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        let state = ItemState {
+            item: ActiveItem {
+                id: item.id.clone(),
+                artist: item.artist.clone(),
+                title: item.title.clone(),
+                stage: ItemStage::FetchingUrl,
+                bytes: 0,
+                total_bytes: None,
+                bytes_per_sec: 0.0,
+            },
+            started: Instant::now(),
+        };
+        self.update(false, |tracker| {
+            tracker.active.insert(index, state);
+        });
+
+        let reporter = ItemProgress {
+            file: self.clone(),
+            index,
+        };
+        Box::pin(async move { Box::new(reporter) as Box<dyn DownloadProgressReporter> })
+    }
+
+    fn on_item_finished(
+        &self,
+        index: usize,
+        _item: &LibraryItem,
+        result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let succeeded = result.is_ok();
+        self.update(true, |tracker| {
+            if let Some(state) = tracker.active.remove(&index) {
+                tracker.finished_bytes += state.item.bytes;
+            }
+            if succeeded {
+                tracker.succeeded += 1;
+            } else {
+                tracker.failed += 1;
+            }
+        });
+        Box::pin(async {})
+    }
+}
+
+/// Feeds the events of one item into its [`ProgressFile`]
+struct ItemProgress {
+    file: ProgressFile,
+    index: usize,
+}
+
+impl ItemProgress {
+    fn update(
+        &self,
+        f: impl FnOnce(&mut ActiveItem),
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.file.update(false, |tracker| {
+            if let Some(state) = tracker.active.get_mut(&self.index) {
+                f(&mut state.item);
+            }
+        });
+        Box::pin(async {})
+    }
+}
+
+impl DownloadProgressReporter for ItemProgress {
+    fn on_start(&self, total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.update(|item| {
+            item.stage = ItemStage::Downloading;
+            item.total_bytes = total_size;
+        })
+    }
+
+    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.update(|item| item.stage = ItemStage::FetchingUrl)
+    }
+
+    fn on_encoding_wait(
+        &self,
+        _attempt: u32,
+        _elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.update(|item| item.stage = ItemStage::Preparing)
+    }
+
+    fn on_progress(
+        &self,
+        downloaded: u64,
+        total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.update(|item| {
+            item.bytes = downloaded;
+            item.total_bytes = total.or(item.total_bytes);
+        })
+    }
+
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.update(|item| item.stage = ItemStage::Extracting)
+    }
+
+    fn on_extract_progress(
+        &self,
+        _entry: &str,
+        _done: usize,
+        _total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    fn on_error(&self, _error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+}
@@ -0,0 +1,776 @@
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
+
+use crate::core::client::BandcampClient;
+use crate::core::download::{
+    DownloadOptions, DownloadProgressReporter, DownloadSummary, MissingFormat, SkipReason,
+    download_item,
+};
+use crate::core::format_map::FormatMap;
+use crate::core::library::{AudioFormat, ItemType, LibraryItem};
+use crate::core::routing::{Layout, RouteMap};
+use crate::core::schedule::{DownloadWindow, FreeSpace};
+use crate::error::{BandcampError, Result};
+
+/// Receives batch-level events from a [`DownloadQueue`]
+pub trait BatchProgressReporter: Send + Sync {
+    /// Called when an item leaves the queue, returns the reporter for that item's download
+    fn on_item_started(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>>;
+
+    /// Called once an item has finished, successfully or not
+    fn on_item_finished(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+        result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+
+    /// Called when the queue stops starting items because `dir` has only `free` bytes left
+    fn on_space_low(
+        &self,
+        _dir: &Path,
+        _free: u64,
+        _min_free: u64,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    /// Called when enough space is free again after [`Self::on_space_low`]
+    fn on_space_freed(&self, _dir: &Path) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+}
+
+/// Fans batch events out to every registered reporter, so frontends and embedders
+/// can plug in extra observers next to their own progress display
+#[derive(Default, Clone)]
+pub struct ReporterRegistry {
+    reporters: Vec<Arc<dyn BatchProgressReporter>>,
+}
+
+impl ReporterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, reporter: Arc<dyn BatchProgressReporter>) {
+        self.reporters.push(reporter);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn BatchProgressReporter>> {
+        self.reporters.iter()
+    }
+}
+
+impl BatchProgressReporter for ReporterRegistry {
+    fn on_item_started(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        let item = item.clone();
+        Box::pin(async move {
+            let mut reporters = Vec::with_capacity(self.reporters.len());
+            for reporter in &self.reporters {
+                reporters.push(reporter.on_item_started(index, &item).await);
+            }
+            Box::new(ReporterSet(reporters)) as Box<dyn DownloadProgressReporter>
+        })
+    }
+
+    fn on_item_finished(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+        result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let futures: Vec<_> = self
+            .reporters
+            .iter()
+            .map(|reporter| reporter.on_item_finished(index, item, result))
+            .collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+
+    fn on_space_low(
+        &self,
+        dir: &Path,
+        free: u64,
+        min_free: u64,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let futures: Vec<_> = self
+            .reporters
+            .iter()
+            .map(|reporter| reporter.on_space_low(dir, free, min_free))
+            .collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+
+    fn on_space_freed(&self, dir: &Path) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let futures: Vec<_> = self
+            .reporters
+            .iter()
+            .map(|reporter| reporter.on_space_freed(dir))
+            .collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+}
+
+/// Item-level counterpart of [`ReporterRegistry`]
+struct ReporterSet(Vec<Box<dyn DownloadProgressReporter>>);
+
+impl ReporterSet {
+    fn each<'a>(
+        &'a self,
+        f: impl Fn(&'a dyn DownloadProgressReporter) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        let futures: Vec<_> = self.0.iter().map(|r| f(r.as_ref())).collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+}
+
+impl DownloadProgressReporter for ReporterSet {
+    fn on_start(&self, total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_start(total_size))
+    }
+
+    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_fetching_url())
+    }
+
+    fn on_encoding_wait(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_encoding_wait(attempt, elapsed))
+    }
+
+    fn on_progress(
+        &self,
+        downloaded: u64,
+        total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_progress(downloaded, total))
+    }
+
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_extracting())
+    }
+
+    fn on_extract_progress(
+        &self,
+        entry: &str,
+        done: usize,
+        total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let entry = entry.to_string();
+        let futures: Vec<_> = self
+            .0
+            .iter()
+            .map(|r| r.on_extract_progress(&entry, done, total))
+            .collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+
+    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.each(|r| r.on_complete())
+    }
+
+    fn on_error(&self, error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let error = error.to_string();
+        let futures: Vec<_> = self.0.iter().map(|r| r.on_error(&error)).collect();
+        Box::pin(async move {
+            for future in futures {
+                future.await;
+            }
+        })
+    }
+}
+
+/// What a probe or download in flight holds up
+#[derive(Debug, Clone)]
+pub struct Slot {
+    track: bool,
+    artist: String,
+}
+
+impl Slot {
+    pub fn of(item: &LibraryItem) -> Self {
+        Self {
+            track: item.item_type == ItemType::Track,
+            artist: artist_key(item),
+        }
+    }
+}
+
+/// How many probes or downloads a [`DownloadQueue`] keeps in flight at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotLimits {
+    parallel: usize,
+    tracks_per_slot: usize,
+    per_artist: Option<usize>,
+}
+
+impl SlotLimits {
+    /// `parallel` slots, each taken by one item
+    pub fn new(parallel: usize) -> Self {
+        Self {
+            parallel: parallel.max(1),
+            tracks_per_slot: 1,
+            per_artist: None,
+        }
+    }
+
+    /// Let this many single tracks share a slot, they are one small file each
+    pub fn with_tracks_per_slot(self, tracks: usize) -> Self {
+        Self {
+            tracks_per_slot: tracks.max(1),
+            ..self
+        }
+    }
+
+    /// Hold items back while this many of the same artist are in flight, whatever
+    /// `parallel` allows
+    pub fn with_per_artist(self, limit: usize) -> Self {
+        Self {
+            per_artist: Some(limit.max(1)),
+            ..self
+        }
+    }
+
+    /// Share of a slot an item takes, counted in single tracks
+    fn cost(&self, track: bool) -> usize {
+        if track { 1 } else { self.tracks_per_slot }
+    }
+
+    fn capacity(&self) -> usize {
+        self.parallel * self.tracks_per_slot
+    }
+
+    /// Whether all of `items` get a slot right away
+    pub fn fit_at_once<'a>(&self, items: impl IntoIterator<Item = &'a LibraryItem>) -> bool {
+        let used: usize = items
+            .into_iter()
+            .map(|item| self.cost(item.item_type == ItemType::Track))
+            .sum();
+        used <= self.capacity()
+    }
+
+    /// Whether `item` may start while `stage`, the probes or the downloads, hold their slots.
+    /// An album takes a slot of its own, single tracks share one `tracks_per_slot` at a time.
+    /// Either way no more than `per_artist` of the same artist are `in_flight` at once, which
+    /// counts both stages.
+    pub fn has_slot<'a, 'b>(
+        &self,
+        item: &LibraryItem,
+        stage: impl IntoIterator<Item = &'a Slot>,
+        in_flight: impl IntoIterator<Item = &'b Slot>,
+    ) -> bool {
+        let slot = Slot::of(item);
+        let used: usize = stage.into_iter().map(|taken| self.cost(taken.track)).sum();
+        let same_artist = in_flight
+            .into_iter()
+            .filter(|taken| taken.artist == slot.artist)
+            .count();
+        used + self.cost(slot.track) <= self.capacity()
+            && self
+                .per_artist
+                .is_none_or(|per_artist| same_artist < per_artist)
+    }
+}
+
+/// The items of a batch that haven't started downloading, each with its index in the queue.
+///
+/// Items are probed first, which also asks Bandcamp to start encoding them. Those that are
+/// ready right away move to the ready lane, the rest wait in a low-priority pending lane so
+/// slots are not held up by transcoding.
+#[derive(Debug, Default)]
+pub struct Lanes {
+    unprobed: VecDeque<(usize, LibraryItem)>,
+    ready: VecDeque<(usize, LibraryItem)>,
+    pending: VecDeque<(usize, LibraryItem)>,
+}
+
+impl Lanes {
+    /// Every item waiting for its probe, or ready right away without `probe`
+    pub fn new(items: Vec<LibraryItem>, probe: bool) -> Self {
+        let items = items.into_iter().enumerate().collect();
+        if probe {
+            Self {
+                unprobed: items,
+                ..Default::default()
+            }
+        } else {
+            Self {
+                ready: items,
+                ..Default::default()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.unprobed.is_empty() && self.ready.is_empty() && self.pending.is_empty()
+    }
+
+    /// Takes the first unprobed item that `fits`
+    pub fn next_probe(
+        &mut self,
+        fits: impl Fn(&LibraryItem) -> bool,
+    ) -> Option<(usize, LibraryItem)> {
+        take_first(&mut self.unprobed, fits)
+    }
+
+    /// Files a probed item into the ready or the pending lane
+    pub fn probed(&mut self, index: usize, item: LibraryItem, ready: bool) {
+        if ready {
+            self.ready.push_back((index, item));
+        } else {
+            self.pending.push_back((index, item));
+        }
+    }
+
+    /// Takes the first ready item that `fits`. Encoding-pending items only get a slot once no
+    /// ready item can take it and no probe, unstarted or `in_flight`, may still turn one up.
+    pub fn next_download(
+        &mut self,
+        probes_in_flight: bool,
+        fits: impl Fn(&LibraryItem) -> bool,
+    ) -> Option<(usize, LibraryItem)> {
+        match take_first(&mut self.ready, &fits) {
+            Some(next) => Some(next),
+            None if self.unprobed.is_empty() && !probes_in_flight => {
+                take_first(&mut self.pending, fits)
+            }
+            None => None,
+        }
+    }
+
+    /// Takes every item left
+    pub fn drain(&mut self) -> Vec<(usize, LibraryItem)> {
+        self.unprobed
+            .drain(..)
+            .chain(self.ready.drain(..))
+            .chain(self.pending.drain(..))
+            .collect()
+    }
+}
+
+fn take_first(
+    lane: &mut VecDeque<(usize, LibraryItem)>,
+    fits: impl Fn(&LibraryItem) -> bool,
+) -> Option<(usize, LibraryItem)> {
+    let position = lane.iter().position(|(_, item)| fits(item))?;
+    lane.remove(position)
+}
+
+/// Who an item counts against for `per_artist`, the subdomain when known since several
+/// artists can share a name
+fn artist_key(item: &LibraryItem) -> String {
+    match &item.artist_subdomain {
+        Some(subdomain) => subdomain.to_ascii_lowercase(),
+        None => item.artist_id.clone(),
+    }
+}
+
+/// Frees the slot of a task that left its [`JoinSet`], whether it finished or panicked
+fn finished<T>(
+    in_flight: &mut HashMap<tokio::task::Id, Slot>,
+    joined: std::result::Result<(tokio::task::Id, T), tokio::task::JoinError>,
+) -> std::result::Result<T, tokio::task::JoinError> {
+    match joined {
+        Ok((id, output)) => {
+            in_flight.remove(&id);
+            Ok(output)
+        }
+        Err(e) => {
+            in_flight.remove(&e.id());
+            Err(e)
+        }
+    }
+}
+
+/// Times an item may run into Bandcamp's download limit before it counts as failed
+const LIMIT_PAUSES: u32 = 3;
+
+/// Shared by the downloads of a queue, so the first one to hit Bandcamp's download limit holds
+/// all of them back for the cool-down instead of each failing on its own
+#[derive(Debug, Clone, Default)]
+struct Pause(Arc<Mutex<Option<Instant>>>);
+
+impl Pause {
+    fn extend(&self, cooldown: Duration) {
+        let until = Instant::now() + cooldown;
+        let mut paused_until = self.0.lock().unwrap();
+        if paused_until.is_none_or(|paused_until| paused_until < until) {
+            *paused_until = Some(until);
+        }
+    }
+
+    async fn wait(&self) {
+        loop {
+            let paused_until = *self.0.lock().unwrap();
+            match paused_until {
+                Some(until) if until > Instant::now() => tokio::time::sleep_until(until).await,
+                _ => return,
+            }
+        }
+    }
+}
+
+/// Concurrent batch downloader shared by the CLI and the TUI
+pub struct DownloadQueue {
+    client: Arc<BandcampClient>,
+    output_dir: PathBuf,
+    format: AudioFormat,
+    format_map: Option<FormatMap>,
+    route_map: Option<RouteMap>,
+    layout: Layout,
+    window: Option<DownloadWindow>,
+    free_space: Option<FreeSpace>,
+    name_format: Option<String>,
+    options: DownloadOptions,
+    limits: SlotLimits,
+    pause: Pause,
+}
+
+impl DownloadQueue {
+    pub fn new(
+        client: Arc<BandcampClient>,
+        output_dir: PathBuf,
+        format: AudioFormat,
+        name_format: Option<String>,
+        options: DownloadOptions,
+        parallel: usize,
+    ) -> Self {
+        Self {
+            client,
+            output_dir,
+            format,
+            format_map: None,
+            route_map: None,
+            layout: Layout::default(),
+            window: None,
+            free_space: None,
+            name_format,
+            options,
+            limits: SlotLimits::new(parallel),
+            pause: Pause::default(),
+        }
+    }
+
+    /// Override the format per item, items without a matching rule keep the queue's format
+    pub fn set_format_map(&mut self, format_map: FormatMap) {
+        self.format_map = Some(format_map);
+    }
+
+    pub fn set_route_map(&mut self, route_map: RouteMap) {
+        self.route_map = Some(route_map);
+    }
+
+    /// Arrange items below their (routed) output directory, e.g. by purchase year
+    pub fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+
+    /// Only start downloads inside this time of day, the queue waits for it to open again
+    pub fn set_window(&mut self, window: DownloadWindow) {
+        self.window = Some(window);
+    }
+
+    /// Only start downloads while the disk they go to has at least `min_free` bytes available,
+    /// the queue waits for space to be freed up otherwise
+    pub fn set_min_free_space(&mut self, min_free: u64) {
+        self.free_space = Some(FreeSpace::new(min_free));
+    }
+
+    /// Hold items back while this many of the same artist are probed or downloaded, whatever
+    /// `parallel` allows
+    pub fn set_per_artist(&mut self, limit: usize) {
+        self.limits = self.limits.with_per_artist(limit);
+    }
+
+    /// Let this many single tracks share one of the `parallel` slots, one each by default
+    pub fn set_tracks_per_slot(&mut self, tracks: usize) {
+        self.limits = self.limits.with_tracks_per_slot(tracks);
+    }
+
+    fn output_dir_for(&self, item: &LibraryItem, format: AudioFormat) -> PathBuf {
+        let output_dir = match &self.route_map {
+            Some(route_map) => route_map.resolve(item, format, &self.output_dir),
+            None => self.output_dir.clone(),
+        };
+        self.layout.resolve(item, &output_dir)
+    }
+
+    fn format_for(&self, item: &LibraryItem) -> AudioFormat {
+        match &self.format_map {
+            Some(format_map) => format_map.resolve(item, self.format),
+            None => self.format,
+        }
+    }
+
+    /// Downloads every item, at most `parallel` at a time.
+    /// Dropping the returned future aborts all in-flight downloads.
+    ///
+    /// Items are scheduled in two tiers through [`Lanes`], ready ones first and those still
+    /// encoding once nothing else can take their slot.
+    pub async fn run(
+        &self,
+        items: Vec<LibraryItem>,
+        reporter: Arc<dyn BatchProgressReporter>,
+    ) -> DownloadSummary {
+        info!("Starting download of {} items", items.len());
+        if let Some(window) = self.window
+            && !window.contains(chrono::Local::now().time())
+        {
+            info!("Waiting for the download window {window} to open");
+        }
+
+        // Nothing to reorder when every item gets a slot right away
+        let probe = !self.limits.fit_at_once(&items);
+        let mut lanes = Lanes::new(items, probe);
+
+        let mut probes = JoinSet::new();
+        let mut tasks = JoinSet::new();
+        // Probes and downloads in flight, with the slot each one holds
+        let mut probing = HashMap::new();
+        let mut running = HashMap::new();
+        let mut results = Vec::new();
+        let mut site_down = false;
+
+        loop {
+            // Every further request would hit the same maintenance page, so the rest of the
+            // batch fails right away instead of item by item
+            if site_down {
+                for (index, item) in lanes.drain() {
+                    let result = Err(BandcampError::SiteDown);
+                    reporter.on_item_finished(index, &item, &result).await;
+                    let format = self.format_for(&item);
+                    results.push((index, item, result, format));
+                }
+            }
+
+            while let Some((index, item)) = lanes.next_probe(|item| {
+                let in_flight = probing.values().chain(running.values());
+                self.limits.has_slot(item, probing.values(), in_flight)
+            }) {
+                let slot = Slot::of(&item);
+                let client = self.client.clone();
+                let format = self.format_for(&item);
+                let pause = self.pause.clone();
+                let window = self.window;
+                let handle = probes.spawn(async move {
+                    if let Some(window) = window {
+                        window.wait().await;
+                    }
+                    pause.wait().await;
+                    let probe = client.request_encoding(&item, format).await;
+                    (index, item, probe)
+                });
+                probing.insert(handle.id(), slot);
+            }
+
+            while let Some((index, item)) = lanes.next_download(!probes.is_empty(), |item| {
+                let in_flight = probing.values().chain(running.values());
+                self.limits.has_slot(item, running.values(), in_flight)
+            }) {
+                let slot = Slot::of(&item);
+                let handle = tasks.spawn(self.download(index, item, reporter.clone()));
+                running.insert(handle.id(), slot);
+            }
+
+            if probes.is_empty() && tasks.is_empty() {
+                break;
+            }
+
+            tokio::select! {
+                Some(probed) = probes.join_next_with_id(), if !probes.is_empty() => {
+                    match finished(&mut probing, probed) {
+                        Ok((index, item, Ok(ready))) => {
+                            if !ready {
+                                debug!(
+                                    "{} is still encoding, moving it to the pending lane",
+                                    item.title
+                                );
+                            }
+                            lanes.probed(index, item, ready);
+                        }
+                        Ok((index, item, Err(BandcampError::SiteDown))) => {
+                            error!("Bandcamp is down, not starting the remaining downloads");
+                            site_down = true;
+                            lanes.probed(index, item, true);
+                        }
+                        // The download itself will run into and report the same error
+                        Ok((index, item, Err(e))) => {
+                            debug!("Failed to probe {}: {e}", item.title);
+                            lanes.probed(index, item, true);
+                        }
+                        Err(e) => error!("Task panicked: {e}"),
+                    }
+                }
+                Some(joined) = tasks.join_next_with_id(), if !tasks.is_empty() => {
+                    match finished(&mut running, joined) {
+                        Ok(result) => {
+                            if matches!(result.2, Err(BandcampError::SiteDown)) && !site_down {
+                                error!("Bandcamp is down, not starting the remaining downloads");
+                                site_down = true;
+                            }
+                            results.push(result);
+                        }
+                        Err(e) => error!("Task panicked: {e}"),
+                    }
+                }
+            }
+        }
+
+        // Report in queue order rather than completion order
+        results.sort_by_key(|(index, _, _, _)| *index);
+
+        let fail_missing = self.options.missing_format == MissingFormat::Fail;
+        let mut summary = DownloadSummary {
+            site_down,
+            ..Default::default()
+        };
+        for (_, item, result, format) in results {
+            match result {
+                Ok(path) => {
+                    if format != self.format_for(&item) {
+                        summary.fallbacks.push((item.clone(), format));
+                    }
+                    summary.succeeded.push((item, path));
+                }
+                Err(e)
+                    if let Some(reason) = SkipReason::from_error(&e)
+                        && !(fail_missing && reason == SkipReason::NoMatchingFormat) =>
+                {
+                    info!("Skipping {}: {e}", item.title);
+                    summary.skipped.push((item, reason));
+                }
+                Err(e) => {
+                    error!("Failed to download {}: {e}", item.title);
+                    summary.errors.insert(item.id.clone(), e.info());
+                    summary.failed.push((item, e.to_string()));
+                }
+            }
+        }
+
+        summary
+    }
+
+    fn download(
+        &self,
+        index: usize,
+        item: LibraryItem,
+        reporter: Arc<dyn BatchProgressReporter>,
+    ) -> impl Future<Output = (usize, LibraryItem, Result<PathBuf>, AudioFormat)> + Send + 'static
+    {
+        let client = self.client.clone();
+        let requested = self.format_for(&item);
+        let formats: Vec<_> = match self.options.missing_format {
+            MissingFormat::Fallback => std::iter::once(requested)
+                .chain(requested.fallbacks())
+                .map(|format| (format, self.output_dir_for(&item, format)))
+                .collect(),
+            _ => vec![(requested, self.output_dir_for(&item, requested))],
+        };
+        let name_format = self.name_format.clone();
+        let options = self.options.clone();
+        let pause = self.pause.clone();
+        let window = self.window;
+        let free_space = self.free_space.clone();
+
+        async move {
+            if let Some(window) = window {
+                window.wait().await;
+            }
+            // Held back before it starts, so a paused item doesn't show up as downloading
+            if let Some(free_space) = &free_space {
+                free_space.wait(&formats[0].1, reporter.as_ref()).await;
+            }
+            let item_reporter = reporter.on_item_started(index, &item).await;
+            let mut formats = formats.into_iter().peekable();
+            let mut format_dir = formats.next().unwrap();
+            let mut limit_pauses = 0;
+            let (result, format) = loop {
+                pause.wait().await;
+                let (format, output_dir) = format_dir.clone();
+                // Checked again for fallback formats and retries, the disk may have filled up
+                // since this item started
+                if let Some(free_space) = &free_space {
+                    free_space.wait(&output_dir, reporter.as_ref()).await;
+                }
+                let result = download_item(
+                    &client,
+                    &item,
+                    &output_dir,
+                    format,
+                    name_format.as_deref(),
+                    &options,
+                    &item_reporter,
+                )
+                .await;
+
+                match result {
+                    Err(BandcampError::FormatUnavailable(_)) if formats.peek().is_some() => {
+                        debug!(
+                            "{} is not offered as {format:?}, trying the next format",
+                            item.title
+                        );
+                        format_dir = formats.next().unwrap();
+                    }
+                    Err(BandcampError::DownloadLimited { cooldown })
+                        if limit_pauses < LIMIT_PAUSES =>
+                    {
+                        warn!(
+                            "Bandcamp refuses more downloads, pausing the queue for {} minutes",
+                            cooldown.as_secs().div_ceil(60)
+                        );
+                        pause.extend(cooldown);
+                        limit_pauses += 1;
+                    }
+                    result => break (result, format),
+                }
+            };
+            reporter.on_item_finished(index, &item, &result).await;
+            (index, item, result, format)
+        }
+    }
+}
+
+/// Per-item reporter for observers that only care about batch events
+pub struct SilentReporter;
+
+impl DownloadProgressReporter for SilentReporter {}
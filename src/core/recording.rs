@@ -0,0 +1,306 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+use url::Url;
+
+use crate::error::{BandcampError, Result};
+
+/// Query parameters that carry signatures or account ids, their values never hit the disk.
+/// `.rand` changes on every statdownload poll and would keep replayed URLs from matching.
+const REDACTED_PARAMS: &[&str] = &["sig", "token", "payment_id", "fan_id", ".rand"];
+
+/// Response headers worth keeping, cookies and tracking headers are dropped
+const KEPT_HEADERS: &[&str] = &["content-type", "content-length", "date", "location"];
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+enum Mode {
+    Record(Recorder),
+    Replay(Replayer),
+}
+
+/// One request/response pair, stored as `NNNN.json` with the body next to it in `NNNN.body`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Exchange {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<String>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// None for file downloads, whose bodies are too large to keep around
+    body_file: Option<String>,
+}
+
+struct Recorder {
+    dir: PathBuf,
+    next: AtomicUsize,
+}
+
+struct Replayer {
+    dir: PathBuf,
+    /// Exchanges per `METHOD url`, in recording order. The last one is repeated once the rest
+    /// are used up, so a replay that polls more often than the recording still gets an answer.
+    exchanges: Mutex<HashMap<String, VecDeque<Exchange>>>,
+}
+
+/// Saves every Bandcamp request and response made by this process under `dir`
+pub fn record_to(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    info!("Recording HTTP traffic to {}", dir.display());
+    set_mode(Mode::Record(Recorder {
+        dir: dir.to_path_buf(),
+        next: AtomicUsize::new(1),
+    }))
+}
+
+/// Answers every Bandcamp request from a `--record-http` directory instead of the network
+pub fn replay_from(dir: &Path) -> Result<()> {
+    let mut exchanges: HashMap<String, VecDeque<Exchange>> = HashMap::new();
+
+    let mut files: Vec<_> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+
+    for file in &files {
+        let exchange: Exchange = serde_json::from_str(&std::fs::read_to_string(file)?)
+            .map_err(|e| BandcampError::ParseError(format!("{}: {e}", file.display())))?;
+        exchanges
+            .entry(key(&exchange.method, &exchange.url))
+            .or_default()
+            .push_back(exchange);
+    }
+
+    info!(
+        "Replaying {} recorded requests from {}",
+        files.len(),
+        dir.display()
+    );
+    set_mode(Mode::Replay(Replayer {
+        dir: dir.to_path_buf(),
+        exchanges: Mutex::new(exchanges),
+    }))
+}
+
+fn set_mode(mode: Mode) -> Result<()> {
+    MODE.set(mode).map_err(|_| {
+        BandcampError::ParseError("--record-http and --replay can't be combined".to_string())
+    })
+}
+
+/// Sends `request` like [`reqwest::RequestBuilder::send`], recording or replaying it when
+/// `--record-http` or `--replay` is active. Bodies of requests with `keep_body` unset are
+/// streamed through unrecorded, for file downloads.
+pub(crate) async fn send(
+    http: &reqwest::Client,
+    request: reqwest::RequestBuilder,
+    keep_body: bool,
+) -> Result<reqwest::Response> {
+    match MODE.get() {
+        None => Ok(request.send().await?),
+        Some(Mode::Record(recorder)) => recorder.send(http, request.build()?, keep_body).await,
+        Some(Mode::Replay(replayer)) => replayer.respond(&request.build()?),
+    }
+}
+
+impl Recorder {
+    async fn send(
+        &self,
+        http: &reqwest::Client,
+        request: reqwest::Request,
+        keep_body: bool,
+    ) -> Result<reqwest::Response> {
+        let number = self.next.fetch_add(1, Ordering::Relaxed);
+        let method = request.method().to_string();
+        let url = redact_url(request.url());
+        let request_body = request
+            .body()
+            .and_then(|body| body.as_bytes())
+            .map(|bytes| redact_body(&String::from_utf8_lossy(bytes)));
+
+        let response = http.execute(request).await?;
+        let status = response.status();
+        let headers = kept_headers(response.headers());
+
+        let mut exchange = Exchange {
+            method,
+            url,
+            request_body,
+            status: status.as_u16(),
+            headers,
+            body_file: None,
+        };
+
+        if !keep_body {
+            self.save(number, &exchange)?;
+            return Ok(response);
+        }
+
+        let version = response.version();
+        let body = response.bytes().await?.to_vec();
+        let body_file = format!("{number:04}.body");
+        // Download pages and status polls carry signed links, the caller still gets them intact
+        let saved = match std::str::from_utf8(&body) {
+            Ok(text) => redact_text(text).into_bytes(),
+            Err(_) => body.clone(),
+        };
+        std::fs::write(self.dir.join(&body_file), saved)?;
+        exchange.body_file = Some(body_file);
+        self.save(number, &exchange)?;
+
+        build_response(status.as_u16(), &exchange.headers, body, version)
+    }
+
+    fn save(&self, number: usize, exchange: &Exchange) -> Result<()> {
+        debug!("Recorded {} {}", exchange.method, exchange.url);
+        let json = serde_json::to_string_pretty(exchange)
+            .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+        std::fs::write(self.dir.join(format!("{number:04}.json")), json)?;
+        Ok(())
+    }
+}
+
+impl Replayer {
+    fn respond(&self, request: &reqwest::Request) -> Result<reqwest::Response> {
+        let url = redact_url(request.url());
+        let key = key(request.method().as_str(), &url);
+
+        let mut exchanges = self.exchanges.lock().unwrap();
+        let queue = exchanges
+            .get_mut(&key)
+            .ok_or_else(|| BandcampError::ParseError(format!("No recorded response for {key}")))?;
+        let exchange = if queue.len() > 1 {
+            queue.pop_front().unwrap()
+        } else {
+            queue[0].clone()
+        };
+        drop(exchanges);
+
+        debug!("Replaying {key}");
+        let body = match &exchange.body_file {
+            Some(file) => std::fs::read(self.dir.join(file))?,
+            None => {
+                return Err(BandcampError::DownloadError(format!(
+                    "{url} was recorded without its body, file downloads can't be replayed"
+                )));
+            }
+        };
+
+        build_response(
+            exchange.status,
+            &exchange.headers,
+            body,
+            reqwest::Version::HTTP_11,
+        )
+    }
+}
+
+fn key(method: &str, url: &str) -> String {
+    format!("{method} {url}")
+}
+
+/// Blanks the values of [`REDACTED_PARAMS`], keeping the rest of the URL intact
+pub fn redact_url(url: &Url) -> String {
+    let mut url = url.clone();
+    if url.query().is_none() {
+        return url.to_string();
+    }
+
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(name, value)| {
+            let value = if REDACTED_PARAMS.contains(&name.as_ref()) {
+                "REDACTED".to_string()
+            } else {
+                value.into_owned()
+            };
+            (name.into_owned(), value)
+        })
+        .collect();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    url.to_string()
+}
+
+/// Blanks the values of [`REDACTED_PARAMS`] in every `name=value` pair found in `text`,
+/// for URLs embedded in HTML or JSON, whether escaped as `&amp;`, `\u0026` or not at all
+pub fn redact_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some((start, name)) = next_param(rest) {
+        let value_start = start + name.len() + 1;
+        out.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+        let value_end = rest
+            .find(['&', '"', '\'', '\\', '<', ' '])
+            .unwrap_or(rest.len());
+        if value_end > 0 {
+            out.push_str("REDACTED");
+        }
+        rest = &rest[value_end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Position and name of the first redacted parameter in `text`, skipping longer names that
+/// merely end in one, like `xsig=`
+fn next_param(text: &str) -> Option<(usize, &'static str)> {
+    REDACTED_PARAMS
+        .iter()
+        .filter_map(|name| {
+            let pattern = format!("{name}=");
+            text.match_indices(&pattern)
+                .map(|(pos, _)| pos)
+                .find(|&pos| {
+                    !text[..pos]
+                        .chars()
+                        .next_back()
+                        .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+                })
+                .map(|pos| (pos, *name))
+        })
+        .min_by_key(|(pos, _)| *pos)
+}
+
+/// Blanks [`REDACTED_PARAMS`] in JSON request bodies, such as the fan id of collection requests
+fn redact_body(body: &str) -> String {
+    let Ok(serde_json::Value::Object(mut object)) = serde_json::from_str(body) else {
+        return body.to_string();
+    };
+    for name in REDACTED_PARAMS {
+        if let Some(value) = object.get_mut(*name) {
+            *value = "REDACTED".into();
+        }
+    }
+    serde_json::Value::Object(object).to_string()
+}
+
+fn kept_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| KEPT_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+fn build_response(
+    status: u16,
+    headers: &[(String, String)],
+    body: Vec<u8>,
+    version: reqwest::Version,
+) -> Result<reqwest::Response> {
+    let mut builder = http::Response::builder().status(status).version(version);
+    for (name, value) in headers {
+        builder = builder.header(name, value);
+    }
+    let response = builder
+        .body(body)
+        .map_err(|e| BandcampError::ParseError(format!("Invalid recorded response: {e}")))?;
+    Ok(reqwest::Response::from(response))
+}
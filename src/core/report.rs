@@ -0,0 +1,354 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+
+use crate::core::download::DownloadSummary;
+use crate::core::library::LibraryItem;
+use crate::core::plan::NamingAudit;
+use crate::core::utils::format_bytes;
+use crate::error::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// HTML for `.html`/`.htm` files, Markdown for everything else
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                Self::Html
+            }
+            _ => Self::Markdown,
+        }
+    }
+}
+
+/// Writes a human-readable report of `summary` to `path`, formatted by its extension
+pub fn write_report(path: &Path, summary: &DownloadSummary) -> Result<()> {
+    let report = render_report(summary, ReportFormat::from_path(path), Utc::now());
+    write_file(path, &report)
+}
+
+/// Writes the renamed and colliding items of `audit` to `path`, formatted by its extension
+pub fn write_naming_report(path: &Path, audit: &NamingAudit) -> Result<()> {
+    let report = render_naming_report(audit, ReportFormat::from_path(path), Utc::now());
+    write_file(path, &report)
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+pub fn render_report(
+    summary: &DownloadSummary,
+    format: ReportFormat,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let sizes: Vec<u64> = summary
+        .succeeded
+        .iter()
+        .map(|(_, path)| disk_size(path))
+        .collect();
+    let total_size = sizes.iter().sum::<u64>();
+    let generated_at = generated_at.format("%Y-%m-%d %H:%M UTC");
+
+    let mut out = String::new();
+    match format {
+        ReportFormat::Markdown => {
+            let _ = writeln!(out, "# bannedcamp download report\n");
+            let _ = writeln!(out, "Generated {generated_at}\n");
+            let _ = writeln!(out, "- Succeeded: {}", summary.success_count());
+            let _ = writeln!(out, "- Failed: {}", summary.failure_count());
+            let _ = writeln!(out, "- Skipped: {}", summary.skipped_count());
+            let _ = writeln!(out, "- Downloaded: {}", format_bytes(total_size as f64));
+
+            if !summary.succeeded.is_empty() {
+                let _ = writeln!(out, "\n## Succeeded\n");
+                let _ = writeln!(out, "| Artist | Title | Size | Path |");
+                let _ = writeln!(out, "| --- | --- | --- | --- |");
+                for ((item, path), size) in summary.succeeded.iter().zip(&sizes) {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} | `{}` |",
+                        markdown_cell(&item.artist),
+                        markdown_title(item),
+                        format_bytes(*size as f64),
+                        markdown_cell(&path.display().to_string()),
+                    );
+                }
+            }
+
+            if !summary.failed.is_empty() {
+                let _ = writeln!(out, "\n## Failed\n");
+                let _ = writeln!(out, "| Artist | Title | Error |");
+                let _ = writeln!(out, "| --- | --- | --- |");
+                for (item, error) in &summary.failed {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        markdown_cell(&item.artist),
+                        markdown_title(item),
+                        markdown_cell(error),
+                    );
+                }
+            }
+
+            if !summary.skipped.is_empty() {
+                let _ = writeln!(out, "\n## Skipped\n");
+                let _ = writeln!(out, "| Artist | Title | Reason |");
+                let _ = writeln!(out, "| --- | --- | --- |");
+                for (item, reason) in &summary.skipped {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        markdown_cell(&item.artist),
+                        markdown_title(item),
+                        markdown_cell(&reason.to_string()),
+                    );
+                }
+            }
+        }
+        ReportFormat::Html => {
+            let _ = writeln!(out, "<!DOCTYPE html>");
+            let _ = writeln!(out, "<html>\n<head>");
+            let _ = writeln!(out, "<meta charset=\"utf-8\">");
+            let _ = writeln!(out, "<title>bannedcamp download report</title>");
+            let _ = writeln!(out, "</head>\n<body>");
+            let _ = writeln!(out, "<h1>bannedcamp download report</h1>");
+            let _ = writeln!(out, "<p>Generated {generated_at}</p>");
+            let _ = writeln!(out, "<ul>");
+            let _ = writeln!(out, "<li>Succeeded: {}</li>", summary.success_count());
+            let _ = writeln!(out, "<li>Failed: {}</li>", summary.failure_count());
+            let _ = writeln!(out, "<li>Skipped: {}</li>", summary.skipped_count());
+            let _ = writeln!(
+                out,
+                "<li>Downloaded: {}</li>",
+                format_bytes(total_size as f64)
+            );
+            let _ = writeln!(out, "</ul>");
+
+            if !summary.succeeded.is_empty() {
+                let _ = writeln!(out, "<h2>Succeeded</h2>\n<table>");
+                let _ = writeln!(
+                    out,
+                    "<tr><th>Artist</th><th>Title</th><th>Size</th><th>Path</th></tr>"
+                );
+                for ((item, path), size) in summary.succeeded.iter().zip(&sizes) {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td><td><code>{}</code></td></tr>",
+                        html_escape(&item.artist),
+                        html_title(item),
+                        format_bytes(*size as f64),
+                        html_escape(&path.display().to_string()),
+                    );
+                }
+                let _ = writeln!(out, "</table>");
+            }
+
+            if !summary.failed.is_empty() {
+                let _ = writeln!(out, "<h2>Failed</h2>\n<table>");
+                let _ = writeln!(out, "<tr><th>Artist</th><th>Title</th><th>Error</th></tr>");
+                for (item, error) in &summary.failed {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        html_escape(&item.artist),
+                        html_title(item),
+                        html_escape(error),
+                    );
+                }
+                let _ = writeln!(out, "</table>");
+            }
+
+            if !summary.skipped.is_empty() {
+                let _ = writeln!(out, "<h2>Skipped</h2>\n<table>");
+                let _ = writeln!(out, "<tr><th>Artist</th><th>Title</th><th>Reason</th></tr>");
+                for (item, reason) in &summary.skipped {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        html_escape(&item.artist),
+                        html_title(item),
+                        html_escape(&reason.to_string()),
+                    );
+                }
+                let _ = writeln!(out, "</table>");
+            }
+
+            let _ = writeln!(out, "</body>\n</html>");
+        }
+    }
+
+    out
+}
+
+pub fn render_naming_report(
+    audit: &NamingAudit,
+    format: ReportFormat,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let generated_at = generated_at.format("%Y-%m-%d %H:%M UTC");
+    let names = |items: &[LibraryItem]| -> Vec<String> {
+        items
+            .iter()
+            .map(|item| format!("{} - {}", item.artist, item.title))
+            .collect()
+    };
+
+    let mut out = String::new();
+    match format {
+        ReportFormat::Markdown => {
+            let _ = writeln!(out, "# bannedcamp naming report\n");
+            let _ = writeln!(out, "Generated {generated_at}\n");
+            let _ = writeln!(out, "- Sanitized: {}", audit.sanitized.len());
+            let _ = writeln!(out, "- Collisions: {}", audit.collisions.len());
+
+            if !audit.sanitized.is_empty() {
+                let _ = writeln!(out, "\n## Sanitized\n");
+                let _ = writeln!(out, "| Title | Before | After | Path |");
+                let _ = writeln!(out, "| --- | --- | --- | --- |");
+                for sanitized in &audit.sanitized {
+                    for change in &sanitized.changes {
+                        let _ = writeln!(
+                            out,
+                            "| {} | `{}` | `{}` | `{}` |",
+                            markdown_title(&sanitized.item),
+                            markdown_cell(&change.before),
+                            markdown_cell(&change.after),
+                            markdown_cell(&sanitized.path.display().to_string()),
+                        );
+                    }
+                }
+            }
+
+            if !audit.collisions.is_empty() {
+                let _ = writeln!(out, "\n## Collisions\n");
+                let _ = writeln!(out, "| Path | Items |");
+                let _ = writeln!(out, "| --- | --- |");
+                for collision in &audit.collisions {
+                    let _ = writeln!(
+                        out,
+                        "| `{}` | {} |",
+                        markdown_cell(&collision.path.display().to_string()),
+                        markdown_cell(&names(&collision.items).join("; ")),
+                    );
+                }
+            }
+        }
+        ReportFormat::Html => {
+            let _ = writeln!(out, "<!DOCTYPE html>");
+            let _ = writeln!(out, "<html>\n<head>");
+            let _ = writeln!(out, "<meta charset=\"utf-8\">");
+            let _ = writeln!(out, "<title>bannedcamp naming report</title>");
+            let _ = writeln!(out, "</head>\n<body>");
+            let _ = writeln!(out, "<h1>bannedcamp naming report</h1>");
+            let _ = writeln!(out, "<p>Generated {generated_at}</p>");
+            let _ = writeln!(out, "<ul>");
+            let _ = writeln!(out, "<li>Sanitized: {}</li>", audit.sanitized.len());
+            let _ = writeln!(out, "<li>Collisions: {}</li>", audit.collisions.len());
+            let _ = writeln!(out, "</ul>");
+
+            if !audit.sanitized.is_empty() {
+                let _ = writeln!(out, "<h2>Sanitized</h2>\n<table>");
+                let _ = writeln!(
+                    out,
+                    "<tr><th>Title</th><th>Before</th><th>After</th><th>Path</th></tr>"
+                );
+                for sanitized in &audit.sanitized {
+                    for change in &sanitized.changes {
+                        let _ = writeln!(
+                            out,
+                            "<tr><td>{}</td><td><code>{}</code></td><td><code>{}</code></td><td><code>{}</code></td></tr>",
+                            html_title(&sanitized.item),
+                            html_escape(&change.before),
+                            html_escape(&change.after),
+                            html_escape(&sanitized.path.display().to_string()),
+                        );
+                    }
+                }
+                let _ = writeln!(out, "</table>");
+            }
+
+            if !audit.collisions.is_empty() {
+                let _ = writeln!(out, "<h2>Collisions</h2>\n<table>");
+                let _ = writeln!(out, "<tr><th>Path</th><th>Items</th></tr>");
+                for collision in &audit.collisions {
+                    let items: Vec<_> = names(&collision.items)
+                        .iter()
+                        .map(|name| html_escape(name))
+                        .collect();
+                    let _ = writeln!(
+                        out,
+                        "<tr><td><code>{}</code></td><td>{}</td></tr>",
+                        html_escape(&collision.path.display().to_string()),
+                        items.join("<br>"),
+                    );
+                }
+                let _ = writeln!(out, "</table>");
+            }
+
+            let _ = writeln!(out, "</body>\n</html>");
+        }
+    }
+
+    out
+}
+
+/// Size of a downloaded track, or of every file in an extracted album folder
+fn disk_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| disk_size(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn markdown_title(item: &LibraryItem) -> String {
+    let title = markdown_cell(&item.title)
+        .replace('[', "\\[")
+        .replace(']', "\\]");
+    match &item.item_url {
+        Some(url) => format!("[{title}]({url})"),
+        None => title,
+    }
+}
+
+pub(crate) fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_title(item: &LibraryItem) -> String {
+    let title = html_escape(&item.title);
+    match &item.item_url {
+        Some(url) => format!("<a href=\"{}\">{title}</a>", html_escape(url)),
+        None => title,
+    }
+}
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use chrono::Datelike;
+use clap::ValueEnum;
+
+use crate::core::library::{AudioFormat, ItemType, LibraryItem};
+use crate::core::utils::glob_match;
+use crate::error::{BandcampError, Result};
+
+/// Output directory rules, read from a file with one `condition = directory` rule per line:
+///
+/// ```text
+/// # WAV masters go to the archive disk whatever they are
+/// format:wav = /mnt/archive/bandcamp
+/// type:track = Singles
+/// type:album = Albums
+/// artist:*Orchestra* = Classical
+/// ```
+///
+/// Relative directories are resolved against the output directory. The first matching rule
+/// wins and items matching none stay in the output directory.
+#[derive(Debug, Clone, Default)]
+pub struct RouteMap {
+    rules: Vec<(Condition, PathBuf)>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    ItemType(ItemType),
+    Format(AudioFormat),
+    Artist(String),
+}
+
+impl RouteMap {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&contents)
+            .map_err(|e| BandcampError::ParseError(format!("{}: {e}", path.display())))
+    }
+
+    pub fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let mut rules = Vec::new();
+
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| format!("line {}: {message}", number + 1);
+
+            let (condition, directory) = line
+                .split_once('=')
+                .ok_or_else(|| error("expected `condition = directory`".to_string()))?;
+            let (condition, directory) = (condition.trim(), directory.trim());
+            if directory.is_empty() {
+                return Err(error("missing directory".to_string()));
+            }
+
+            let (kind, value) = condition.split_once(':').ok_or_else(|| {
+                error(format!(
+                    "expected type:, format: or artist:, got {condition:?}"
+                ))
+            })?;
+            let value = value.trim();
+            let condition = match kind.trim() {
+                "type" => Condition::ItemType(match value.to_ascii_lowercase().as_str() {
+                    "album" => ItemType::Album,
+                    "track" => ItemType::Track,
+                    "package" => ItemType::Package,
+                    _ => return Err(error(format!("unknown item type {value:?}"))),
+                }),
+                "format" => Condition::Format(
+                    AudioFormat::from_str(value, true)
+                        .map_err(|_| error(format!("unknown format {value:?}")))?,
+                ),
+                "artist" => Condition::Artist(value.to_string()),
+                kind => return Err(error(format!("unknown condition {kind:?}"))),
+            };
+
+            rules.push((condition, PathBuf::from(directory)));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Directory `item` is downloaded into when fetched as `format`
+    pub fn resolve(&self, item: &LibraryItem, format: AudioFormat, output_dir: &Path) -> PathBuf {
+        self.rules
+            .iter()
+            .find(|(condition, _)| match condition {
+                Condition::ItemType(item_type) => *item_type == item.item_type,
+                Condition::Format(rule_format) => *rule_format == format,
+                Condition::Artist(glob) => glob_match(glob, &item.artist),
+            })
+            .map(|(_, directory)| output_dir.join(directory))
+            .unwrap_or_else(|| output_dir.to_path_buf())
+    }
+}
+
+/// How downloads are arranged below their output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    /// Everything directly in the output directory
+    #[default]
+    Flat,
+    /// A folder per purchase year like `2024/`, items without a purchase date go to `Unknown/`
+    PurchaseYear,
+}
+
+impl Layout {
+    /// Directory `item` goes to inside `output_dir`
+    pub fn resolve(self, item: &LibraryItem, output_dir: &Path) -> PathBuf {
+        match self {
+            Layout::Flat => output_dir.to_path_buf(),
+            Layout::PurchaseYear => match item.purchase.purchased_at {
+                Some(purchased_at) => output_dir.join(purchased_at.year().to_string()),
+                None => output_dir.join("Unknown"),
+            },
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::{Local, NaiveTime, Timelike};
+use tracing::{debug, info};
+
+use crate::core::queue::BatchProgressReporter;
+use crate::core::utils::{available_space, format_bytes};
+
+/// Time of day downloads may start in, e.g. `01:00-07:00`. A window ending before it starts
+/// runs past midnight. Downloads already running when it closes are finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+impl DownloadWindow {
+    pub fn parse(window: &str) -> Option<Self> {
+        let (start, end) = window.split_once('-')?;
+        let time = |time: &str| NaiveTime::parse_from_str(time.trim(), "%H:%M").ok();
+        let (start, end) = (time(start)?, time(end)?);
+        (start != end).then_some(Self { start, end })
+    }
+
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+
+    /// How long until the window opens at `time`, zero while it is open
+    pub fn until_open(&self, time: NaiveTime) -> Duration {
+        if self.contains(time) {
+            return Duration::ZERO;
+        }
+        let (start, now) = (
+            self.start.num_seconds_from_midnight(),
+            time.num_seconds_from_midnight(),
+        );
+        Duration::from_secs(u64::from((start + SECONDS_PER_DAY - now) % SECONDS_PER_DAY))
+    }
+
+    /// Returns once the window is open in local time
+    pub async fn wait(&self) {
+        let wait = self.until_open(Local::now().time());
+        if !wait.is_zero() {
+            debug!(
+                "Outside the download window {self}, waiting {}s",
+                wait.as_secs()
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+impl fmt::Display for DownloadWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M")
+        )
+    }
+}
+
+/// How often a queue held back by [`FreeSpace`] looks at the disk again
+const SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Holds downloads back while the disk they go to has less than `min_free` available, e.g.
+/// until a sync job has moved finished albums off it. Clones share whether the queue is
+/// waiting, so running low is announced once rather than for every item.
+///
+/// Space is only checked before an item starts. A download that is already running isn't
+/// paused, so one large album can still fill the disk: the threshold needs to leave room for
+/// the biggest item in the batch.
+#[derive(Debug, Clone)]
+pub struct FreeSpace {
+    min_free: u64,
+    waiting: Arc<AtomicBool>,
+}
+
+impl FreeSpace {
+    pub fn new(min_free: u64) -> Self {
+        Self {
+            min_free,
+            waiting: Arc::default(),
+        }
+    }
+
+    /// Bytes available to `dir` while they are below the threshold, `None` once there is
+    /// enough. Directories not created yet are measured at their closest existing parent,
+    /// filesystems that don't answer never hold anything back.
+    pub fn shortage(&self, dir: &Path) -> Option<u64> {
+        let existing = dir.ancestors().find(|dir| dir.exists())?;
+        available_space(existing)
+            .ok()
+            .filter(|free| *free < self.min_free)
+    }
+
+    /// Returns once `dir` has at least the threshold available, telling `reporter` when the
+    /// queue starts and stops waiting
+    pub async fn wait(&self, dir: &Path, reporter: &dyn BatchProgressReporter) {
+        loop {
+            let Some(free) = self.shortage(dir) else {
+                if self.waiting.swap(false, Ordering::Relaxed) {
+                    info!(
+                        "Enough space free in {} again, resuming downloads",
+                        dir.display()
+                    );
+                    reporter.on_space_freed(dir).await;
+                }
+                return;
+            };
+            if !self.waiting.swap(true, Ordering::Relaxed) {
+                info!(
+                    "Only {} free in {}, pausing downloads until {} are free",
+                    format_bytes(free as f64),
+                    dir.display(),
+                    format_bytes(self.min_free as f64)
+                );
+                reporter.on_space_low(dir, free, self.min_free).await;
+            }
+            tokio::time::sleep(SPACE_CHECK_INTERVAL).await;
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, info};
+
+use crate::core::library::LibraryItem;
+use crate::core::state::{load_json, save_private_json};
+
+static SESSION: OnceLock<SessionCache> = OnceLock::new();
+
+struct SessionCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+/// What one command leaves for the next. The cookie itself is never written, only a hash of
+/// it, so a cache entry can't be reused with another cookie or after logging in again. The
+/// collection keeps its signed download links, which work without the cookie, so the file is
+/// only readable by its owner.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionFile {
+    fingerprint: String,
+    fan_id: u64,
+    validated_at: Option<DateTime<Utc>>,
+    collection: Option<Vec<LibraryItem>>,
+    collection_fetched_at: Option<DateTime<Utc>>,
+}
+
+/// `$XDG_CACHE_HOME/bannedcamp/session.json`, falling back to `~/.cache`
+pub fn default_path() -> Option<PathBuf> {
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(cache_dir.join("bannedcamp").join("session.json"))
+}
+
+/// Lets cookie validations and collection fetches of this process be answered from `path`
+/// for `ttl` after an earlier command made them
+pub fn enable(path: PathBuf, ttl: Duration) {
+    info!(
+        "Using session cache {} ({}s)",
+        path.display(),
+        ttl.as_secs()
+    );
+    let _ = SESSION.set(SessionCache { path, ttl });
+}
+
+/// Fan id of `cookie` on `server` if it was validated within the TTL
+pub fn cached_fan_id(server: &str, cookie: &str) -> Option<u64> {
+    let (cache, file) = load(server, cookie)?;
+    cache.is_fresh(file.validated_at).then_some(file.fan_id)
+}
+
+pub fn store_fan_id(server: &str, cookie: &str, fan_id: u64) {
+    update(server, cookie, |file| {
+        if file.fan_id != fan_id {
+            file.collection = None;
+        }
+        file.fan_id = fan_id;
+        file.validated_at = Some(Utc::now());
+    });
+}
+
+/// Whole collection of `cookie`'s account if it was fetched within the TTL, download links
+/// included
+pub fn cached_collection(server: &str, cookie: &str) -> Option<Vec<LibraryItem>> {
+    let (cache, file) = load(server, cookie)?;
+    if !cache.is_fresh(file.collection_fetched_at) {
+        return None;
+    }
+    debug!(
+        "Using the collection cached at {:?}",
+        file.collection_fetched_at
+    );
+    file.collection
+}
+
+pub fn store_collection(server: &str, cookie: &str, items: &[LibraryItem]) {
+    update(server, cookie, |file| {
+        file.collection = Some(items.to_vec());
+        file.collection_fetched_at = Some(Utc::now());
+    });
+}
+
+/// Drops the cached collection after changing it, e.g. by hiding an item
+pub fn forget_collection(server: &str, cookie: &str) {
+    update(server, cookie, |file| {
+        file.collection = None;
+        file.collection_fetched_at = None;
+    });
+}
+
+impl SessionCache {
+    fn is_fresh(&self, at: Option<DateTime<Utc>>) -> bool {
+        at.and_then(|at| (Utc::now() - at).to_std().ok())
+            .is_some_and(|age| age < self.ttl)
+    }
+}
+
+/// Hash of the cookie and the server it belongs to, `--mock` sessions never mix with real ones
+fn fingerprint(server: &str, cookie: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(server);
+    hasher.update([0]);
+    hasher.update(cookie);
+    format!("{:x}", hasher.finalize())
+}
+
+/// The cache and its contents, when enabled and written for the same cookie
+fn load(server: &str, cookie: &str) -> Option<(&'static SessionCache, SessionFile)> {
+    let cache = SESSION.get()?;
+    let file: SessionFile = load_json(&cache.path)
+        .inspect_err(|e| debug!("Ignoring unreadable session cache: {e}"))
+        .ok()?;
+    (file.fingerprint == fingerprint(server, cookie)).then_some((cache, file))
+}
+
+/// A broken cache only costs the requests it would have saved, so errors are just logged
+fn update(server: &str, cookie: &str, change: impl FnOnce(&mut SessionFile)) {
+    let Some(cache) = SESSION.get() else {
+        return;
+    };
+    let mut file = load(server, cookie)
+        .map(|(_, file)| file)
+        .unwrap_or_else(|| SessionFile {
+            fingerprint: fingerprint(server, cookie),
+            ..Default::default()
+        });
+    change(&mut file);
+    if let Err(e) = save_private_json(&cache.path, &file) {
+        debug!(
+            "Failed to write session cache {}: {e}",
+            cache.path.display()
+        );
+    }
+}
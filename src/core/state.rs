@@ -0,0 +1,757 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::core::download::{DownloadSummary, SkipReason};
+use crate::core::library::LibraryItem;
+use crate::error::{BandcampError, Result};
+
+/// Folder in the output root holding bannedcamp's own bookkeeping
+pub const STATE_DIR: &str = ".bannedcamp";
+
+pub fn state_dir(output_dir: &Path) -> PathBuf {
+    output_dir.join(STATE_DIR)
+}
+
+/// Reads a JSON state file, returning the default when it doesn't exist yet
+pub(crate) fn load_json<T: for<'de> Deserialize<'de> + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| BandcampError::ParseError(format!("{}: {e}", path.display()))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes a JSON state file through a temporary file, so an interrupted run never leaves it half written
+pub(crate) fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+    write_atomic(path, contents.as_bytes(), false)
+}
+
+/// Like [`save_json`], but only the user can read the file, for caches tied to their account
+pub(crate) fn save_private_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| BandcampError::ParseError(e.to_string()))?;
+    write_atomic(path, contents.as_bytes(), true)
+}
+
+/// Loads the state file at `path`, applies `change` and writes it back, all while holding a
+/// lock on a `.lock` file next to it. Runs sharing an output directory so never lose each
+/// other's updates. Returns the value as written.
+pub(crate) fn update_json<T>(path: &Path, change: impl FnOnce(&mut T)) -> Result<T>
+where
+    T: Serialize + for<'de> Deserialize<'de> + Default,
+{
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let lock = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(path.with_file_name(format!(".{file_name}.lock")))?;
+    lock.lock()?;
+
+    let mut value: T = load_json(path)?;
+    change(&mut value);
+    save_json(path, &value)?;
+    Ok(value)
+}
+
+fn write_atomic(path: &Path, contents: &[u8], private: bool) -> Result<()> {
+    // Unique per write, two writers of the same file never share a temporary file
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = path.with_file_name(format!(
+        ".{file_name}.{}-{}.tmp",
+        std::process::id(),
+        WRITES.fetch_add(1, Ordering::Relaxed)
+    ));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    if private {
+        // The mode only applies to new files, a leftover could be readable by anyone
+        let _ = std::fs::remove_file(&temp_path);
+        options.mode(0o600);
+    }
+    let mut file = options.open(&temp_path)?;
+    std::io::Write::write_all(&mut file, contents)?;
+    drop(file);
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+/// Remembers which items earlier `--max-items` runs got through, so the next run continues after them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ResumeCursor {
+    /// Items downloaded successfully
+    done: BTreeSet<String>,
+    /// Items that failed on their latest attempt
+    failed: BTreeSet<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl ResumeCursor {
+    const FILE: &str = "cursor.json";
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&state_dir(output_dir).join(Self::FILE))
+    }
+
+    pub fn save(&mut self, output_dir: &Path) -> Result<()> {
+        self.updated_at = Some(Utc::now());
+        save_json(&state_dir(output_dir).join(Self::FILE), self)
+    }
+
+    /// Picks up to `max` items that haven't been downloaded yet, in collection order.
+    /// Items that failed before come last, so a few broken items can't stall every run.
+    ///
+    /// Returns the batch and how many items are left for later runs.
+    pub fn next_batch(&self, items: Vec<LibraryItem>, max: usize) -> (Vec<LibraryItem>, usize) {
+        let (retries, fresh): (Vec<_>, Vec<_>) = items
+            .into_iter()
+            .filter(|item| !self.done.contains(&item.id))
+            .partition(|item| self.failed.contains(&item.id));
+
+        let mut pending: Vec<_> = fresh.into_iter().chain(retries).collect();
+        let remaining = pending.len().saturating_sub(max);
+        pending.truncate(max);
+
+        debug!(
+            "Resume cursor picked {} items, {remaining} left",
+            pending.len()
+        );
+        (pending, remaining)
+    }
+
+    pub fn record(&mut self, summary: &DownloadSummary) {
+        for (item, _) in &summary.succeeded {
+            self.failed.remove(&item.id);
+            self.done.insert(item.id.clone());
+        }
+        for (item, _) in &summary.failed {
+            self.failed.insert(item.id.clone());
+        }
+        for (item, reason) in &summary.skipped {
+            match reason {
+                SkipReason::AlreadyExists => {
+                    self.done.insert(item.id.clone());
+                }
+                // The format may show up later, so retry it after everything else
+                SkipReason::NoMatchingFormat => {
+                    self.failed.insert(item.id.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Start of the last complete `download all`, `artist-all` or `new` run, so `download new`
+/// only has to look at what was bought after it
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct LastRun {
+    pub started_at: Option<DateTime<Utc>>,
+    /// Items a run couldn't get yet, like preorders and failures, the next run tries them again
+    pending: BTreeSet<String>,
+}
+
+impl LastRun {
+    const FILE: &str = "last_run.json";
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&state_dir(output_dir).join(Self::FILE))
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        save_json(&state_dir(output_dir).join(Self::FILE), self)
+    }
+
+    /// Items purchased since the last run started plus the ones it left pending, everything
+    /// if there hasn't been a run yet. Items without a purchase date only count on the first run.
+    pub fn new_items(&self, items: Vec<LibraryItem>) -> Vec<LibraryItem> {
+        let Some(started_at) = self.started_at else {
+            return items;
+        };
+        items
+            .into_iter()
+            .filter(|item| {
+                self.pending.contains(&item.id)
+                    || item
+                        .purchase
+                        .purchased_at
+                        .is_some_and(|purchased_at| purchased_at >= started_at)
+            })
+            .collect()
+    }
+
+    /// Records a run that started at `started_at` and went over the whole collection. Preorders
+    /// stay pending whatever happened to their early tracks, until the full release is out.
+    pub fn record(&mut self, started_at: DateTime<Utc>, summary: &DownloadSummary) {
+        self.started_at = Some(started_at);
+        for (item, _) in &summary.succeeded {
+            if item.is_preorder {
+                self.pending.insert(item.id.clone());
+            } else {
+                self.pending.remove(&item.id);
+            }
+        }
+        for (item, _) in &summary.failed {
+            self.pending.insert(item.id.clone());
+        }
+        for (item, reason) in &summary.skipped {
+            match reason {
+                _ if item.is_preorder => {
+                    self.pending.insert(item.id.clone());
+                }
+                SkipReason::Preorder
+                | SkipReason::NoMatchingFormat
+                | SkipReason::BandwidthCap
+                | SkipReason::InProgressElsewhere
+                | SkipReason::EncodingPending => {
+                    self.pending.insert(item.id.clone());
+                }
+                _ => {
+                    self.pending.remove(&item.id);
+                }
+            }
+        }
+    }
+}
+
+/// Items Bandcamp didn't finish preparing in time, for `download pending` to fetch later.
+/// They are almost always ready within the hour.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PendingEncodings {
+    /// When each item was first found not ready, by item ID
+    items: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl PendingEncodings {
+    const FILE: &str = "pending.json";
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&state_dir(output_dir).join(Self::FILE))
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        save_json(&state_dir(output_dir).join(Self::FILE), self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn contains(&self, item: &LibraryItem) -> bool {
+        self.items.contains_key(&item.id)
+    }
+
+    /// Drops items no longer in `collection`, nothing is going to download them
+    pub fn forget_missing(&mut self, collection: &[LibraryItem]) {
+        self.items
+            .retain(|id, _| collection.iter().any(|item| item.id == *id));
+    }
+
+    /// Adds the items `summary` gave up waiting for and drops the ones it dealt with otherwise
+    pub fn record(&mut self, summary: &DownloadSummary) {
+        for (item, _) in &summary.succeeded {
+            self.items.remove(&item.id);
+        }
+        for (item, reason) in &summary.skipped {
+            if *reason == SkipReason::EncodingPending {
+                self.items.entry(item.id.clone()).or_insert_with(Utc::now);
+            } else {
+                self.items.remove(&item.id);
+            }
+        }
+    }
+}
+
+/// Preorders downloaded with only the tracks offered before release day, so the full album
+/// replaces them once it is out
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PreorderTracks {
+    /// When the early tracks were downloaded, by item ID
+    items: BTreeMap<String, DateTime<Utc>>,
+}
+
+impl PreorderTracks {
+    const FILE: &str = "preorders.json";
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&state_dir(output_dir).join(Self::FILE))
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        save_json(&state_dir(output_dir).join(Self::FILE), self)
+    }
+
+    /// Whether only the early tracks of `item` are downloaded although it has been released
+    pub fn is_released(&self, item: &LibraryItem) -> bool {
+        !item.is_preorder && self.items.contains_key(&item.id)
+    }
+
+    /// Whether the early tracks of `item` are downloaded and it still hasn't been released
+    pub fn has_early_tracks(&self, item: &LibraryItem) -> bool {
+        item.is_preorder && self.items.contains_key(&item.id)
+    }
+
+    /// Adds the preorders `summary` downloaded early tracks of and drops the released ones
+    pub fn record(&mut self, summary: &DownloadSummary) {
+        for (item, _) in &summary.succeeded {
+            if item.is_preorder {
+                self.items.entry(item.id.clone()).or_insert_with(Utc::now);
+            } else {
+                self.items.remove(&item.id);
+            }
+        }
+    }
+}
+
+/// Where each downloaded item ended up, relative to the output root so the directory stays
+/// understandable after it was moved, mounted elsewhere or copied to another machine
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of bannedcamp that last wrote the manifest
+    written_by: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+    items: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub artist: String,
+    pub title: String,
+    /// Relative to the output root, absolute only for items routed outside of it
+    pub path: PathBuf,
+    pub downloaded_at: DateTime<Utc>,
+}
+
+impl Manifest {
+    const FILE: &str = "manifest.json";
+
+    fn path(output_dir: &Path) -> PathBuf {
+        state_dir(output_dir).join(Self::FILE)
+    }
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&Self::path(output_dir))
+    }
+
+    pub fn save(&mut self, output_dir: &Path) -> Result<()> {
+        self.stamp();
+        save_json(&Self::path(output_dir), self)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn get(&self, item: &LibraryItem) -> Option<&ManifestEntry> {
+        self.items.get(&item.id)
+    }
+
+    /// IDs of every item downloaded so far
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.items.keys().map(String::as_str)
+    }
+
+    /// Where `item` is now, with the output root at `output_dir`
+    pub fn locate(&self, output_dir: &Path, item: &LibraryItem) -> Option<PathBuf> {
+        self.get(item).map(|entry| output_dir.join(&entry.path))
+    }
+
+    /// Adds the items `summary` downloaded below `output_dir`, replacing earlier downloads
+    pub fn record(&mut self, output_dir: &Path, summary: &DownloadSummary) {
+        let now = Utc::now();
+        for (item, path) in &summary.succeeded {
+            self.insert(output_dir, item, path, now);
+        }
+    }
+
+    /// Adds `item`, just downloaded to `path`, to the manifest of the output root at
+    /// `output_dir`. Merged into the file under a lock, so downloads of the TUI and of
+    /// concurrent runs all end up in it.
+    pub fn add(output_dir: &Path, item: &LibraryItem, path: &Path) -> Result<()> {
+        update_json(&Self::path(output_dir), |manifest: &mut Self| {
+            manifest.insert(output_dir, item, path, Utc::now());
+            manifest.stamp();
+        })?;
+        Ok(())
+    }
+
+    fn insert(&mut self, output_dir: &Path, item: &LibraryItem, path: &Path, at: DateTime<Utc>) {
+        let path = path.strip_prefix(output_dir).unwrap_or(path).to_path_buf();
+        self.items.insert(
+            item.id.clone(),
+            ManifestEntry {
+                artist: item.artist.clone(),
+                title: item.title.clone(),
+                path,
+                downloaded_at: at,
+            },
+        );
+    }
+
+    fn stamp(&mut self) {
+        self.written_by = Some(concat!("bannedcamp ", env!("CARGO_PKG_VERSION")).to_string());
+        self.updated_at = Some(Utc::now());
+    }
+}
+
+/// The collection as it was last fetched into this output directory, so `library --read-only`
+/// can list it without the owner's cookie. Signed download links are cut down to their path,
+/// the file can't be used to download anything.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CollectionSnapshot {
+    pub fetched_at: Option<DateTime<Utc>>,
+    pub items: Vec<LibraryItem>,
+}
+
+impl CollectionSnapshot {
+    const FILE: &str = "collection.json";
+
+    pub fn new(items: &[LibraryItem]) -> Self {
+        Self {
+            fetched_at: Some(Utc::now()),
+            items: items.iter().map(LibraryItem::unsigned).collect(),
+        }
+    }
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&state_dir(output_dir).join(Self::FILE))
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        save_json(&state_dir(output_dir).join(Self::FILE), self)
+    }
+}
+
+/// Items a batch has finished, keyed by its `--batch-id` so re-running the batch skips them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BatchRecord {
+    pub id: String,
+    created_at: Option<DateTime<Utc>>,
+    updated_at: Option<DateTime<Utc>>,
+    /// Items downloaded successfully under this batch
+    completed: BTreeSet<String>,
+}
+
+impl BatchRecord {
+    const DIR: &str = "batches";
+    /// Records kept around, older ones are removed whenever a batch is saved
+    const KEEP: usize = 50;
+
+    /// Generates a fresh batch id
+    pub fn new_id() -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+
+    /// Loads the batch `id`, starting an empty one if it hasn't run before
+    pub fn load(output_dir: &Path, id: &str) -> Result<Self> {
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(BandcampError::ParseError(format!(
+                "Invalid batch id {id:?}, use letters, digits, '-' and '_'"
+            )));
+        }
+
+        let mut record: Self = load_json(&Self::path(output_dir, id))?;
+        if record.id.is_empty() {
+            record.id = id.to_string();
+            record.created_at = Some(Utc::now());
+        }
+        Ok(record)
+    }
+
+    /// Writes the record and removes the oldest ones beyond [`Self::KEEP`]
+    pub fn save(&mut self, output_dir: &Path) -> Result<()> {
+        self.updated_at = Some(Utc::now());
+        let own = Self::path(output_dir, &self.id);
+        save_json(&own, self)?;
+
+        let mut records: Vec<_> = std::fs::read_dir(state_dir(output_dir).join(Self::DIR))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| *path != own && path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some((path.metadata().ok()?.modified().ok()?, path)))
+            .collect();
+        records.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        for (_, path) in records.into_iter().skip(Self::KEEP - 1) {
+            debug!("Removing old batch record {}", path.display());
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn path(output_dir: &Path, id: &str) -> PathBuf {
+        state_dir(output_dir)
+            .join(Self::DIR)
+            .join(format!("{id}.json"))
+    }
+
+    pub fn is_completed(&self, item: &LibraryItem) -> bool {
+        self.completed.contains(&item.id)
+    }
+
+    /// Whether the batch hasn't finished any item yet, such a record isn't worth keeping
+    pub fn is_empty(&self) -> bool {
+        self.completed.is_empty()
+    }
+
+    pub fn record(&mut self, summary: &DownloadSummary) {
+        self.completed
+            .extend(summary.succeeded.iter().map(|(item, _)| item.id.clone()));
+    }
+}
+
+/// Temporary files and unfinished album folders downloads are writing into an output
+/// directory. Entries are added before a file is created and dropped once it is renamed or
+/// complete, so whatever is still listed after a crash is a leftover `clean` may remove.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Scratch {
+    /// Relative to the output root, absolute only for paths outside of it
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Scratch {
+    const FILE: &str = "scratch.json";
+
+    fn path(output_dir: &Path) -> PathBuf {
+        state_dir(output_dir).join(Self::FILE)
+    }
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&Self::path(output_dir))
+    }
+
+    /// Lists `paths` as being written into the output root at `output_dir`
+    pub fn track<P: AsRef<Path>>(output_dir: &Path, paths: &[P]) -> Result<()> {
+        update_json(&Self::path(output_dir), |scratch: &mut Self| {
+            for path in paths {
+                let path = path.as_ref();
+                let path = path.strip_prefix(output_dir).unwrap_or(path);
+                scratch.paths.insert(path.to_path_buf());
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Drops `paths` again once they are finished or gone
+    pub fn release<P: AsRef<Path>>(output_dir: &Path, paths: &[P]) -> Result<()> {
+        update_json(&Self::path(output_dir), |scratch: &mut Self| {
+            for path in paths {
+                let path = path.as_ref();
+                scratch
+                    .paths
+                    .remove(path.strip_prefix(output_dir).unwrap_or(path));
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Drops every path that doesn't exist anymore, e.g. after `clean` removed it
+    pub fn forget_missing(output_dir: &Path) -> Result<()> {
+        update_json(&Self::path(output_dir), |scratch: &mut Self| {
+            scratch
+                .paths
+                .retain(|path| output_dir.join(path).symlink_metadata().is_ok());
+        })?;
+        Ok(())
+    }
+
+    /// Every listed path, below `output_dir`
+    pub fn paths(&self, output_dir: &Path) -> impl Iterator<Item = PathBuf> {
+        self.paths.iter().map(move |path| output_dir.join(path))
+    }
+}
+
+/// Marks an item as being downloaded into an output directory, so the TUI and any number of
+/// CLI runs sharing it never fetch the same item at once. The mark is an advisory lock on
+/// `.bannedcamp/active/<id>.lock` that the OS drops with the process, a crash can't leave an
+/// item stuck as in progress.
+#[derive(Debug)]
+pub struct ItemClaim {
+    _lock: std::fs::File,
+    path: PathBuf,
+}
+
+impl ItemClaim {
+    const DIR: &str = "active";
+
+    /// Claims `item_id`, failing with [`BandcampError::InProgressElsewhere`] while another
+    /// claim on it is held
+    pub fn acquire(output_dir: &Path, item_id: &str) -> Result<Self> {
+        let dir = state_dir(output_dir).join(Self::DIR);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{item_id}.lock"));
+
+        loop {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&path)?;
+            match file.try_lock() {
+                Ok(()) => {}
+                Err(std::fs::TryLockError::WouldBlock) => {
+                    return Err(BandcampError::InProgressElsewhere);
+                }
+                Err(std::fs::TryLockError::Error(e)) => return Err(e.into()),
+            }
+
+            // The previous holder may have removed the file between our open and lock, the
+            // lock only counts if it's on the file that is still there
+            let locked = file.metadata()?;
+            match std::fs::metadata(&path) {
+                Ok(current) if current.dev() == locked.dev() && current.ino() == locked.ino() => {
+                    debug!("Claimed item {item_id} in {}", dir.display());
+                    return Ok(Self { _lock: file, path });
+                }
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl Drop for ItemClaim {
+    fn drop(&mut self) {
+        // Removed while still locked, the lock goes away with the file handle right after
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Bytes received from Bandcamp per calendar month (UTC) and per item, failed attempts included
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BandwidthLog {
+    /// Keyed by `YYYY-MM`
+    months: BTreeMap<String, u64>,
+    /// Keyed by item id, over every download of the item
+    items: BTreeMap<String, u64>,
+}
+
+impl BandwidthLog {
+    const FILE: &str = "bandwidth.json";
+
+    fn path(output_dir: &Path) -> PathBuf {
+        state_dir(output_dir).join(Self::FILE)
+    }
+
+    pub fn load(output_dir: &Path) -> Result<Self> {
+        load_json(&Self::path(output_dir))
+    }
+
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        save_json(&Self::path(output_dir), self)
+    }
+
+    pub fn month_key(at: DateTime<Utc>) -> String {
+        at.format("%Y-%m").to_string()
+    }
+
+    pub fn add(&mut self, item_id: &str, bytes: u64, at: DateTime<Utc>) {
+        *self.months.entry(Self::month_key(at)).or_default() += bytes;
+        *self.items.entry(item_id.to_string()).or_default() += bytes;
+    }
+
+    pub fn month_total(&self, at: DateTime<Utc>) -> u64 {
+        self.months
+            .get(&Self::month_key(at))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// `YYYY-MM` and bytes, oldest month first
+    pub fn months(&self) -> impl Iterator<Item = (&str, u64)> {
+        self.months
+            .iter()
+            .map(|(month, bytes)| (month.as_str(), *bytes))
+    }
+
+    /// Item ids and bytes, most downloaded first
+    pub fn largest_items(&self) -> Vec<(&str, u64)> {
+        let mut items: Vec<_> = self
+            .items
+            .iter()
+            .map(|(id, bytes)| (id.as_str(), *bytes))
+            .collect();
+        items.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+        items
+    }
+}
+
+/// Counts the transfers of running downloads into the [`BandwidthLog`] of an output
+/// directory and enforces `--monthly-cap`. Every transfer is written through right away, so
+/// an interrupted batch is still accounted for.
+#[derive(Debug)]
+pub struct BandwidthMeter {
+    output_dir: PathBuf,
+    log: Mutex<BandwidthLog>,
+    monthly_cap: Option<u64>,
+}
+
+impl BandwidthMeter {
+    pub fn open(output_dir: &Path, monthly_cap: Option<u64>) -> Result<Self> {
+        Ok(Self {
+            output_dir: output_dir.to_path_buf(),
+            log: Mutex::new(BandwidthLog::load(output_dir)?),
+            monthly_cap,
+        })
+    }
+
+    pub fn record(&self, item_id: &str, bytes: u64) {
+        if bytes == 0 {
+            return;
+        }
+        let now = Utc::now();
+        let mut log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+        // Merged into what is on disk, other runs into the same directory count towards the cap
+        match update_json(
+            &BandwidthLog::path(&self.output_dir),
+            |on_disk: &mut BandwidthLog| on_disk.add(item_id, bytes, now),
+        ) {
+            Ok(updated) => *log = updated,
+            Err(e) => {
+                warn!("Failed to update bandwidth statistics: {e}");
+                log.add(item_id, bytes, now);
+            }
+        }
+    }
+
+    /// Whether this month's downloads used up `--monthly-cap`. Downloads already running are
+    /// finished, so a month can end up slightly above the cap.
+    pub fn cap_reached(&self) -> bool {
+        self.monthly_cap.is_some_and(|cap| {
+            let log = self.log.lock().unwrap_or_else(|e| e.into_inner());
+            log.month_total(Utc::now()) >= cap
+        })
+    }
+}
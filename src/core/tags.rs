@@ -0,0 +1,171 @@
+//! Release details for media servers: an `album.nfo` next to the audio, and the release date
+//! and cover in the audio files' own tags.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use chrono::{Datelike, NaiveDate};
+use tracing::debug;
+
+use crate::core::artwork::find_cover;
+use crate::core::external::program;
+use crate::core::library::LibraryItem;
+use crate::core::report::html_escape;
+use crate::core::verify::collect_audio_files;
+use crate::error::{BandcampError, Result};
+
+/// Album details in Kodi's format, which Jellyfin and Emby read as well
+pub const NFO_FILE: &str = "album.nfo";
+
+/// Writes [`NFO_FILE`] into `album_dir`
+pub fn write_album_nfo(item: &LibraryItem, album_dir: &Path) -> Result<()> {
+    std::fs::write(album_dir.join(NFO_FILE), render_album_nfo(item))?;
+    Ok(())
+}
+
+pub fn render_album_nfo(item: &LibraryItem) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>"
+    );
+    let _ = writeln!(out, "<album>");
+    let _ = writeln!(out, "  <title>{}</title>", html_escape(&item.title));
+    let _ = writeln!(out, "  <artist>{}</artist>", html_escape(&item.artist));
+    if let Some(date) = item.release_date {
+        let _ = writeln!(out, "  <year>{}</year>", date.year());
+        let _ = writeln!(out, "  <releasedate>{date}</releasedate>");
+    }
+    if let Some(url) = &item.item_url {
+        let _ = writeln!(out, "  <review>{}</review>", html_escape(url));
+    }
+    let _ = writeln!(out, "</album>");
+    out
+}
+
+/// Checks that ffmpeg can be run, so `--tag-dates` and `--artwork embedded` fail before
+/// downloading anything
+pub fn ffmpeg_available() -> Result<()> {
+    Command::new(program("ffmpeg"))
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| ())
+        .map_err(|e| BandcampError::DownloadError(format!("ffmpeg isn't on PATH: {e}")))
+}
+
+/// Sets the DATE tag of `path`, a track or an extracted album, to `date` with ffmpeg. The
+/// audio is copied as it is, only the tags are rewritten.
+pub fn tag_release_date(path: &Path, date: NaiveDate) -> Result<()> {
+    let mut files = Vec::new();
+    collect_audio_files(path, &mut files)?;
+
+    for file in files {
+        debug!("Tagging {} with {date}", file.display());
+        rewrite_with_ffmpeg(
+            &file,
+            None,
+            &["-map", "0", "-c", "copy", "-map_metadata", "0"],
+            &["-metadata", &format!("date={date}")],
+        )?;
+    }
+    Ok(())
+}
+
+/// Extensions of the formats ffmpeg can attach a cover picture to
+const EMBEDDABLE_EXTENSIONS: &[&str] = &["flac", "mp3", "m4a"];
+
+/// Embeds the cover image of `album_dir` into each of its tracks with ffmpeg, replacing the
+/// picture they came with, and removes the image file once every track carries it. Returns
+/// whether it was removed, albums in formats without cover pictures keep it.
+pub fn embed_cover(album_dir: &Path) -> Result<bool> {
+    let Some(cover) = find_cover(album_dir) else {
+        return Ok(false);
+    };
+    let mut files = Vec::new();
+    collect_audio_files(album_dir, &mut files)?;
+
+    let mut embedded_all = true;
+    for file in files {
+        let extension = file
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_ascii_lowercase())
+            .unwrap_or_default();
+        if !EMBEDDABLE_EXTENSIONS.contains(&extension.as_str()) {
+            embedded_all = false;
+            continue;
+        }
+        debug!("Embedding {} into {}", cover.display(), file.display());
+        rewrite_with_ffmpeg(
+            &file,
+            Some(&cover),
+            &[
+                "-map",
+                "0:a",
+                "-map",
+                "1",
+                "-c",
+                "copy",
+                "-map_metadata",
+                "0",
+            ],
+            &["-disposition:v:0", "attached_pic"],
+        )?;
+    }
+
+    if embedded_all {
+        std::fs::remove_file(&cover)?;
+    }
+    Ok(embedded_all)
+}
+
+/// Runs ffmpeg over `file`, and `picture` as a second input, and puts what it writes in place
+/// of `file`. The audio is copied as it is.
+fn rewrite_with_ffmpeg(
+    file: &Path,
+    picture: Option<&Path>,
+    mapping: &[&str],
+    metadata: &[&str],
+) -> Result<()> {
+    let rewritten = tagging_path(file);
+    let mut command = Command::new(program("ffmpeg"));
+    command
+        .args(["-v", "error", "-nostdin", "-y", "-i"])
+        .arg(file);
+    if let Some(picture) = picture {
+        command.arg("-i").arg(picture);
+    }
+    let output = command
+        .args(mapping)
+        .args(metadata)
+        .arg(&rewritten)
+        .stdin(Stdio::null())
+        .output()?;
+
+    if !output.status.success() {
+        let _ = std::fs::remove_file(&rewritten);
+        return Err(BandcampError::DownloadError(format!(
+            "ffmpeg couldn't rewrite {}: {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    std::fs::rename(&rewritten, file)?;
+    Ok(())
+}
+
+/// Temporary files retagging `path`, a track or an extracted album, may write
+pub(crate) fn tagging_paths(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_audio_files(path, &mut files)?;
+    Ok(files.iter().map(|file| tagging_path(file)).collect())
+}
+
+/// Where [`rewrite_with_ffmpeg`] writes the new copy of `file` before it replaces it. Keeps
+/// the extension, ffmpeg picks the container by it.
+fn tagging_path(file: &Path) -> PathBuf {
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    file.with_file_name(format!(".tagging.{name}"))
+}
@@ -0,0 +1,200 @@
+use std::io::{BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use clap::ValueEnum;
+use tracing::debug;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use crate::core::library::{ItemType, LibraryItem};
+use crate::core::permissions::OutputPermissions;
+use crate::error::{BandcampError, Result};
+
+/// Where the files of an album archive are written to while it is extracted. Paths handed to
+/// a target are relative to the album and already checked to stay inside it.
+pub trait ExtractTarget: Send {
+    fn create_dir(&mut self, relative: &Path) -> Result<()>;
+
+    /// Writer for a new file, it is finished once the writer is dropped
+    fn create_file(&mut self, relative: &Path) -> Result<Box<dyn Write + '_>>;
+
+    /// Called once after the last file, the album isn't complete before that
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// How albums and packages are stored in the output directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum AlbumOutput {
+    /// Extract into a folder per album
+    #[default]
+    Directory,
+    /// Keep each album as a single uncompressed `.zip`, with the same layout a folder would get
+    Zip,
+}
+
+impl AlbumOutput {
+    /// Path `item` named `filename` ends up at below `output_dir`, tracks are always kept as
+    /// they are
+    pub fn path(self, item: &LibraryItem, output_dir: &Path, filename: &str) -> PathBuf {
+        match item.item_type {
+            ItemType::Track => output_dir.join(filename),
+            ItemType::Album | ItemType::Package => self.archive_path(output_dir, filename),
+        }
+    }
+
+    /// Path a downloaded archive named `filename` ends up at, whatever the item is
+    pub fn archive_path(self, output_dir: &Path, filename: &str) -> PathBuf {
+        match self {
+            AlbumOutput::Directory => output_dir.join(filename),
+            AlbumOutput::Zip => output_dir.join(format!("{filename}.zip")),
+        }
+    }
+
+    /// What an interrupted write of the album at `path` leaves behind: the folder itself, or
+    /// the archive's temporary file
+    pub fn unfinished_path(self, path: &Path) -> PathBuf {
+        match self {
+            AlbumOutput::Directory => path.to_path_buf(),
+            AlbumOutput::Zip => zip_temp_path(path),
+        }
+    }
+
+    /// Target writing an album to `path`, as returned by [`AlbumOutput::path`]
+    pub fn open(self, path: &Path) -> Result<Box<dyn ExtractTarget>> {
+        Ok(match self {
+            AlbumOutput::Directory => Box::new(DirectoryTarget::new(path)?),
+            AlbumOutput::Zip => Box::new(ZipTarget::create(path)?),
+        })
+    }
+}
+
+/// Plain files below a directory on the local filesystem
+pub struct DirectoryTarget {
+    root: PathBuf,
+}
+
+impl DirectoryTarget {
+    pub fn new(root: &Path) -> Result<Self> {
+        std::fs::create_dir_all(root)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+        })
+    }
+}
+
+impl ExtractTarget for DirectoryTarget {
+    fn create_dir(&mut self, relative: &Path) -> Result<()> {
+        std::fs::create_dir_all(self.root.join(relative))?;
+        Ok(())
+    }
+
+    fn create_file(&mut self, relative: &Path) -> Result<Box<dyn Write + '_>> {
+        let path = self.root.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Box::new(BufWriter::new(std::fs::File::create(path)?)))
+    }
+
+    /// Resets everything below the album to 0755 for directories and 0644 for files
+    fn finish(&mut self) -> Result<()> {
+        OutputPermissions::default().apply_below(&self.root)
+    }
+}
+
+/// A new zip archive. Entries are stored without compression since audio and artwork are
+/// compressed already, and the archive only appears at its path once it is complete.
+pub struct ZipTarget {
+    path: PathBuf,
+    temp_path: PathBuf,
+    writer: Option<ZipWriter<BufWriter<std::fs::File>>>,
+}
+
+impl ZipTarget {
+    pub fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temp_path = zip_temp_path(path);
+        let file = BufWriter::new(std::fs::File::create(&temp_path)?);
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            temp_path,
+            writer: Some(ZipWriter::new(file)),
+        })
+    }
+
+    fn writer(&mut self) -> Result<&mut ZipWriter<BufWriter<std::fs::File>>> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| BandcampError::DownloadError("zip archive already finished".into()))
+    }
+}
+
+/// Where a [`ZipTarget`] for `path` is written until it is finished
+fn zip_temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.tmp"))
+}
+
+/// `/`-separated entry name, whatever the platform separator is
+fn entry_name(relative: &Path) -> String {
+    relative
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn zip_error(e: zip::result::ZipError) -> BandcampError {
+    BandcampError::DownloadError(format!("Writing zip archive failed: {e}"))
+}
+
+impl ExtractTarget for ZipTarget {
+    fn create_dir(&mut self, relative: &Path) -> Result<()> {
+        let name = entry_name(relative);
+        self.writer()?
+            .add_directory(name, SimpleFileOptions::default())
+            .map_err(zip_error)
+    }
+
+    fn create_file(&mut self, relative: &Path) -> Result<Box<dyn Write + '_>> {
+        let name = entry_name(relative);
+        let writer = self.writer()?;
+        writer
+            .start_file(
+                name,
+                SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored)
+                    .large_file(true),
+            )
+            .map_err(zip_error)?;
+        Ok(Box::new(writer))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let Some(writer) = self.writer.take() else {
+            return Ok(());
+        };
+        let mut file = writer.finish().map_err(zip_error)?;
+        file.flush()?;
+        drop(file);
+
+        debug!("Moving {:?} to {:?}", self.temp_path, self.path);
+        std::fs::rename(&self.temp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Drop for ZipTarget {
+    fn drop(&mut self) {
+        // An unfinished archive is useless, don't leave it behind for `clean`
+        if self.writer.is_some() {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
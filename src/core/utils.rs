@@ -1,3 +1,10 @@
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use tracing::warn;
+
+use crate::error::{BandcampError, Result};
+
 pub fn format_bytes(bytes: f64) -> String {
     const KB: f64 = 1024.0;
     const MB: f64 = KB * 1024.0;
@@ -14,6 +21,49 @@ pub fn format_bytes(bytes: f64) -> String {
     }
 }
 
+/// Parses sizes like `100G`, `1.5TB` or `500m` in the binary units [`format_bytes`] prints,
+/// a plain number is bytes
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let digits_end = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(digits_end);
+    let number: f64 = number.parse().ok()?;
+
+    let unit = unit.trim().to_ascii_uppercase();
+    let multiplier = match unit.strip_suffix('B').unwrap_or(&unit) {
+        "" => 1u64,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Parses durations like `90s`, `30m`, `6h` or `1d`, a plain number is seconds
+pub fn parse_duration(duration: &str) -> Option<std::time::Duration> {
+    let duration = duration.trim();
+    let digits_end = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(duration.len());
+    let (number, unit) = duration.split_at(digits_end);
+    let number: u64 = number.parse().ok()?;
+
+    let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(std::time::Duration::from_secs(
+        number.checked_mul(multiplier)?,
+    ))
+}
+
 /// Truncate a string
 pub fn truncate_str(s: &str, max_chars: usize) -> String {
     let char_count = s.chars().count();
@@ -25,3 +75,233 @@ pub fn truncate_str(s: &str, max_chars: usize) -> String {
         s.to_string()
     }
 }
+
+/// Case-insensitive shell-style glob match, `*` matches any run of characters and `?` a single one
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it is currently matched up to
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Characters that Windows, macOS or Linux refuse in file names
+const INVALID_FILENAME_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+/// How artist and album names are turned into file names
+#[derive(Debug, Clone)]
+pub struct SanitizeOptions {
+    /// Stands in for every character that can't be used in a file name, empty drops them.
+    /// `_` is used instead when [`is_valid_replacement`] rejects it.
+    pub replacement: String,
+    /// Which characters to spell in ASCII, none when unset
+    pub transliterate: Option<Transliterate>,
+    /// Turn runs of whitespace into a single space and trim both ends
+    pub collapse_whitespace: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            replacement: "_".to_string(),
+            transliterate: None,
+            collapse_whitespace: false,
+        }
+    }
+}
+
+/// Characters [`sanitize_filename`] spells in ASCII
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Transliterate {
+    /// Accented Latin letters and typographic punctuation (é as e, ß as ss), other scripts are
+    /// kept
+    Latin,
+    /// Everything, whatever its script (坂本龍一 as Ban Ben Long Yi), for car stereos and NAS
+    /// software that can't handle anything else. Characters without an ASCII spelling become
+    /// the replacement.
+    All,
+}
+
+/// Whether `replacement` can stand in for invalid characters without bringing in its own,
+/// like a path separator, or turning a name of them into `.` or `..`
+pub fn is_valid_replacement(replacement: &str) -> bool {
+    !replacement
+        .chars()
+        .any(|c| c.is_control() || INVALID_FILENAME_CHARS.contains(&c))
+        && (replacement.is_empty() || !replacement.chars().all(|c| c == '.'))
+}
+
+/// Makes `name` usable as a single path component on every platform
+pub fn sanitize_filename(name: &str, options: &SanitizeOptions) -> String {
+    let replacement = if is_valid_replacement(&options.replacement) {
+        options.replacement.as_str()
+    } else {
+        "_"
+    };
+
+    // Before the checks below, some ASCII spellings are characters file names can't contain
+    let ascii;
+    let name = match options.transliterate {
+        Some(Transliterate::All) => {
+            ascii = deunicode::deunicode_with_tofu(name, replacement);
+            ascii.as_str()
+        }
+        Some(Transliterate::Latin) => {
+            ascii = name
+                .chars()
+                .map(|c| match deunicode::deunicode_char(c) {
+                    Some(spelled) if is_latin_or_punctuation(c) => spelled.to_string(),
+                    _ => c.to_string(),
+                })
+                .collect::<String>();
+            ascii.as_str()
+        }
+        None => name,
+    };
+
+    let mut sanitized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_control() || INVALID_FILENAME_CHARS.contains(&c) {
+            sanitized.push_str(replacement);
+        } else {
+            sanitized.push(c);
+        }
+    }
+
+    if options.collapse_whitespace {
+        sanitized = sanitized.split_whitespace().collect::<Vec<_>>().join(" ");
+    }
+
+    // `.` and `..` would point at the output directory or its parent
+    if !sanitized.is_empty() && sanitized.chars().all(|c| c == '.') {
+        sanitized = "_".repeat(sanitized.len());
+    }
+
+    sanitized
+}
+
+/// Accented Latin letters and typographic punctuation, what [`Transliterate::Latin`] spells
+/// in ASCII
+fn is_latin_or_punctuation(c: char) -> bool {
+    matches!(c, '\u{a0}'..='\u{24f}' | '\u{1e00}'..='\u{1eff}' | '\u{2000}'..='\u{206f}' | '\u{2212}')
+}
+
+/// `$XDG_DATA_HOME/bannedcamp`, falling back to `~/.local/share/bannedcamp`
+pub fn data_dir() -> Option<PathBuf> {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share"))
+        })?;
+    Some(data_dir.join("bannedcamp"))
+}
+
+/// Free space below which doctor and the download screen warn
+pub const LOW_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Free space below which a download can't even get started
+pub const MIN_SPACE_BYTES: u64 = 64 * 1024 * 1024;
+
+fn statvfs(path: &Path) -> std::io::Result<libc::statvfs> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+/// Bytes available to unprivileged users on the filesystem containing `path`
+pub fn available_space(path: &Path) -> std::io::Result<u64> {
+    let stat = statvfs(path)?;
+    // Field widths differ between platforms
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Inodes available to unprivileged users on the filesystem containing `path`, `None` for
+/// filesystems like Btrfs that allocate them as needed
+pub fn available_inodes(path: &Path) -> std::io::Result<Option<u64>> {
+    let stat = statvfs(path)?;
+    #[allow(clippy::unnecessary_cast)]
+    Ok((stat.f_files > 0).then_some(stat.f_favail as u64))
+}
+
+/// Checks that downloads can be written to `output_dir`, creating it if needed, so a bad
+/// directory fails once up front instead of for every item after resolving its download
+pub fn check_output_dir(output_dir: &Path) -> Result<()> {
+    let unusable = |reason: String| BandcampError::OutputUnusable {
+        path: output_dir.to_path_buf(),
+        reason,
+    };
+
+    std::fs::create_dir_all(output_dir).map_err(|e| unusable(e.to_string()))?;
+    let probe = output_dir.join(".bannedcamp-preflight.tmp");
+    std::fs::write(&probe, b"ok").map_err(|e| unusable(e.to_string()))?;
+    let _ = std::fs::remove_file(&probe);
+
+    // Not every filesystem answers, that alone is no reason to stop
+    if let Ok(free) = available_space(output_dir) {
+        if free < MIN_SPACE_BYTES {
+            return Err(unusable(format!("only {} free", format_bytes(free as f64))));
+        }
+        if free < LOW_SPACE_BYTES {
+            warn!(
+                "Only {} free in {}, lossless albums are often several hundred MB each",
+                format_bytes(free as f64),
+                output_dir.display()
+            );
+        }
+    }
+    if let Ok(Some(0)) = available_inodes(output_dir) {
+        return Err(unusable("no free inodes left".to_string()));
+    }
+    Ok(())
+}
+
+/// Opens `path` in the system file manager without waiting for it to close
+pub fn open_in_file_manager(path: &Path) -> std::io::Result<()> {
+    let opener = if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    std::process::Command::new(opener)
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use tracing::{debug, warn};
+
+use crate::core::external::program;
+use crate::error::{BandcampError, Result};
+
+/// Extensions of every format Bandcamp delivers, other files in an album are left alone
+pub(crate) const AUDIO_EXTENSIONS: &[&str] = &["flac", "mp3", "m4a", "ogg", "wav", "aiff", "aif"];
+
+fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Checks that ffprobe can be run, so `--verify-audio` fails before downloading anything
+pub fn ffprobe_available() -> Result<()> {
+    Command::new(program("ffprobe"))
+        .arg("-version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|_| ())
+        .map_err(|e| {
+            BandcampError::DownloadError(format!("--verify-audio needs ffprobe on PATH: {e}"))
+        })
+}
+
+/// Runs ffprobe over `path`, a track or an extracted album, and returns every audio file it
+/// couldn't read. Catches truncated and garbled files that still passed the zip CRC.
+pub fn find_undecodable(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_audio_files(path, &mut files)?;
+
+    let mut bad = Vec::new();
+    for file in files {
+        // Counting packets reads the whole file rather than just the header
+        let output = Command::new(program("ffprobe"))
+            .args(["-v", "error", "-count_packets"])
+            .args(["-show_entries", "stream=nb_read_packets", "-of", "csv=p=0"])
+            .arg(&file)
+            .stdin(Stdio::null())
+            .output()?;
+
+        let errors = String::from_utf8_lossy(&output.stderr);
+        if output.status.success() && errors.trim().is_empty() {
+            debug!("{} decodes fine", file.display());
+        } else {
+            warn!("{} is not decodable: {}", file.display(), errors.trim());
+            bad.push(file);
+        }
+    }
+
+    Ok(bad)
+}
+
+pub(crate) fn collect_audio_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        for entry in std::fs::read_dir(path)? {
+            collect_audio_files(&entry?.path(), files)?;
+        }
+    } else if is_audio_file(path) {
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
@@ -1,30 +1,191 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::Serialize;
 use thiserror::Error;
+use url::Url;
+
+use crate::core::recording::redact_url;
+use crate::i18n::{Msg, tr, tr_fmt};
 
 #[derive(Error, Debug)]
 pub enum BandcampError {
-    #[error("Authentication failed: {0}")]
+    #[error("{}", tr_fmt(Msg::ErrorAuth, &[.0]))]
     AuthError(String),
 
-    #[error("Not logged in")]
+    #[error("{}", tr(Msg::ErrorNotLoggedIn))]
     NotLoggedIn,
 
-    #[error("Session expired")]
+    #[error("{}", tr(Msg::ErrorSessionExpired))]
     SessionExpired,
 
-    #[error("Network error: {0}")]
-    NetworkError(#[from] reqwest::Error),
+    /// Built through `From`, which redacts the signature of a download URL
+    #[error("{}", tr_fmt(Msg::ErrorNetwork, &[.0]))]
+    NetworkError(#[source] reqwest::Error),
+
+    #[error("{}", tr_fmt(Msg::ErrorHttpStatus, &[.status, .url]))]
+    HttpStatus {
+        status: u16,
+        url: String,
+        /// From the `Retry-After` header, when the server asked to wait
+        retry_after: Option<Duration>,
+    },
 
-    #[error("Download failed: {0}")]
+    #[error("{}", tr_fmt(Msg::ErrorDownload, &[.0]))]
     DownloadError(String),
 
-    #[error("IO error: {0}")]
+    #[error("{}", tr_fmt(Msg::ErrorIo, &[.0]))]
     IoError(#[from] std::io::Error),
 
-    #[error("Parse error: {0}")]
+    #[error("{}", tr_fmt(Msg::ErrorParse, &[.0]))]
     ParseError(String),
 
-    #[error("Bandcamp is currently down")]
+    #[error("{}", tr_fmt(Msg::ErrorFormatUnavailable, &[.0]))]
+    FormatUnavailable(String),
+
+    #[error("{}", tr(Msg::ErrorStreamOnly))]
+    StreamOnly,
+
+    #[error("{}", tr(Msg::ErrorSiteDown))]
     SiteDown,
+
+    #[error("{}", tr_fmt(Msg::ErrorBadDownload, &[.0]))]
+    BadDownload(String),
+
+    #[error("{}", tr(Msg::ErrorBandwidthCap))]
+    BandwidthCapReached,
+
+    #[error("{}", tr(Msg::ErrorInProgressElsewhere))]
+    InProgressElsewhere,
+
+    #[error(
+        "{}",
+        tr_fmt(Msg::ErrorDownloadLimited, &[&.cooldown.as_secs().div_ceil(60)])
+    )]
+    DownloadLimited { cooldown: Duration },
+
+    #[error("{}", tr_fmt(Msg::ErrorEncodingPending, &[.attempts]))]
+    EncodingPending { attempts: u32 },
+
+    #[error("{}", tr_fmt(Msg::ErrorInvalidTemplate, &[.0]))]
+    InvalidTemplate(String),
+
+    #[error(
+        "{}",
+        tr_fmt(Msg::ErrorOutputUnusable, &[&.path.display(), .reason])
+    )]
+    OutputUnusable { path: PathBuf, reason: String },
+
+    #[error("{}", tr_fmt(Msg::NoSnapshot, &[&.0.display()]))]
+    NoSnapshot(PathBuf),
+}
+
+impl From<reqwest::Error> for BandcampError {
+    /// reqwest names the URL in its message, so signed download links lose their signature
+    /// before they can reach `--json`, reports, logs or notifications
+    fn from(e: reqwest::Error) -> Self {
+        let redacted = e.url().and_then(|url| Url::parse(&redact_url(url)).ok());
+        BandcampError::NetworkError(match redacted {
+            Some(url) => e.with_url(url),
+            None => e.without_url(),
+        })
+    }
+}
+
+impl BandcampError {
+    /// Worth another attempt with a freshly signed download URL: the server sent something
+    /// unusable, the connection broke or the server asked to come back later
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            BandcampError::BadDownload(_) => true,
+            BandcampError::NetworkError(e) => {
+                e.is_timeout() || e.is_connect() || e.is_body() || e.is_request()
+            }
+            BandcampError::HttpStatus { status, .. } => {
+                matches!(status, 408 | 429) || (500..600).contains(status)
+            }
+            _ => false,
+        }
+    }
+
+    /// How long the server asked to wait before the next attempt
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            BandcampError::HttpStatus { retry_after, .. } => *retry_after,
+            BandcampError::DownloadLimited { cooldown } => Some(*cooldown),
+            _ => None,
+        }
+    }
+
+    /// HTTP status of the response that caused this error, if there was one
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            BandcampError::HttpStatus { status, .. } => Some(*status),
+            BandcampError::NetworkError(e) => e.status().map(|status| status.as_u16()),
+            BandcampError::SessionExpired => Some(401),
+            _ => None,
+        }
+    }
+
+    /// URL of the request that caused this error, if there was one
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            BandcampError::HttpStatus { url, .. } => Some(url),
+            BandcampError::NetworkError(e) => e.url().map(|url| url.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Stable identifier of the variant for machine-readable output
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BandcampError::AuthError(_) => "auth",
+            BandcampError::NotLoggedIn => "not_logged_in",
+            BandcampError::SessionExpired => "session_expired",
+            BandcampError::NetworkError(_) => "network",
+            BandcampError::HttpStatus { .. } => "http_status",
+            BandcampError::DownloadError(_) => "download",
+            BandcampError::IoError(_) => "io",
+            BandcampError::ParseError(_) => "parse",
+            BandcampError::FormatUnavailable(_) => "format_unavailable",
+            BandcampError::StreamOnly => "stream_only",
+            BandcampError::SiteDown => "site_down",
+            BandcampError::BadDownload(_) => "bad_download",
+            BandcampError::BandwidthCapReached => "bandwidth_cap_reached",
+            BandcampError::InProgressElsewhere => "in_progress_elsewhere",
+            BandcampError::DownloadLimited { .. } => "download_limited",
+            BandcampError::EncodingPending { .. } => "encoding_pending",
+            BandcampError::InvalidTemplate(_) => "invalid_template",
+            BandcampError::OutputUnusable { .. } => "output_unusable",
+            BandcampError::NoSnapshot(_) => "no_snapshot",
+        }
+    }
+
+    /// Everything above in one serializable value, for `--json` and other tools
+    pub fn info(&self) -> ErrorInfo {
+        ErrorInfo {
+            kind: self.kind(),
+            message: self.to_string(),
+            retryable: self.is_retryable(),
+            retry_after_secs: self.retry_after().map(|d| d.as_secs()),
+            status: self.status(),
+            url: self.url().map(str::to_string),
+        }
+    }
+}
+
+/// Machine-readable description of a [`BandcampError`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErrorInfo {
+    pub kind: &'static str,
+    pub message: String,
+    pub retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
 }
 
 pub type Result<T> = std::result::Result<T, BandcampError>;
@@ -0,0 +1,630 @@
+use std::fmt::{Display, Write};
+use std::sync::LazyLock;
+
+/// Languages with a translation in the catalog below
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    De,
+    Es,
+}
+
+static LANG: LazyLock<Lang> = LazyLock::new(Lang::from_env);
+
+impl Lang {
+    pub const ALL: [Lang; 3] = [Lang::En, Lang::De, Lang::Es];
+
+    /// Language picked from the environment on first use
+    pub fn current() -> Self {
+        *LANG
+    }
+
+    /// Same precedence as gettext: LC_ALL, then LC_MESSAGES, then LANG
+    fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+            .map(|locale| Self::from_locale(&locale))
+            .unwrap_or(Lang::En)
+    }
+
+    /// Parses a POSIX locale such as `de_AT.UTF-8`, anything unknown falls back to English
+    pub fn from_locale(locale: &str) -> Self {
+        let language = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "de" => Lang::De,
+            "es" => Lang::Es,
+            _ => Lang::En,
+        }
+    }
+}
+
+macro_rules! catalog {
+    ($($msg:ident => [$en:expr, $de:expr, $es:expr $(,)?],)*) => {
+        /// Every user-facing message, `{}` marks where [`tr_fmt`] inserts its arguments
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum Msg {
+            $($msg,)*
+        }
+
+        impl Msg {
+            pub const ALL: &[Msg] = &[$(Msg::$msg,)*];
+
+            pub fn text(self, lang: Lang) -> &'static str {
+                match self {
+                    $(Msg::$msg => match lang {
+                        Lang::En => $en,
+                        Lang::De => $de,
+                        Lang::Es => $es,
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+catalog! {
+    // Login screen
+    ValidatingCookie => ["Validating cookie...", "Cookie wird geprüft...", "Validando la cookie..."],
+    CookieFromEnv => [
+        "Cookie loaded from BANDCAMP_COOKIE",
+        "Cookie aus BANDCAMP_COOKIE geladen",
+        "Cookie cargada desde BANDCAMP_COOKIE",
+    ],
+    PressEnterToContinue => [
+        "Press Enter to continue",
+        "Enter drücken, um fortzufahren",
+        "Pulsa Enter para continuar",
+    ],
+    PasteCookie => [
+        "Paste your Bandcamp identity cookie below:",
+        "Füge unten dein Bandcamp-identity-Cookie ein:",
+        "Pega abajo tu cookie identity de Bandcamp:",
+    ],
+    CookieHint => [
+        "(Browser DevTools -> Application -> Cookies -> identity)",
+        "(Browser-DevTools -> Anwendung -> Cookies -> identity)",
+        "(DevTools del navegador -> Aplicación -> Cookies -> identity)",
+    ],
+    EnterCookie => ["Please enter a cookie", "Bitte gib ein Cookie ein", "Introduce una cookie"],
+    NotLoggedIn => ["Not logged in", "Nicht angemeldet", "No has iniciado sesión"],
+    Submit => ["Submit", "Absenden", "Enviar"],
+    Hide => ["Hide", "Verbergen", "Ocultar"],
+    Show => ["Show", "Anzeigen", "Mostrar"],
+    Quit => ["Quit", "Beenden", "Salir"],
+
+    // Error details popup
+    ErrorDetails => ["Error details", "Fehlerdetails", "Detalles del error"],
+    Details => ["Details", "Details", "Detalles"],
+    Scroll => ["Scroll", "Blättern", "Desplazar"],
+    Copy => ["Copy", "Kopieren", "Copiar"],
+    Copied => ["Copied", "Kopiert", "Copiado"],
+    Close => ["Close", "Schließen", "Cerrar"],
+
+    // Library screen
+    Search => ["Search", "Suche", "Buscar"],
+    Error => ["Error: {}", "Fehler: {}", "Error: {}"],
+    LoadingLibrary => ["Loading library...", "Bibliothek wird geladen...", "Cargando biblioteca..."],
+    LoadingLibraryCount => [
+        "Loading library... ({} items)",
+        "Bibliothek wird geladen... ({} Einträge)",
+        "Cargando biblioteca... ({} elementos)",
+    ],
+    ShowingItems => ["Showing {}/{} items", "{}/{} Einträge angezeigt", "Mostrando {}/{} elementos"],
+    ShowingItemsSelected => [
+        "Showing {}/{} items ({} selected)",
+        "{}/{} Einträge angezeigt ({} ausgewählt)",
+        "Mostrando {}/{} elementos ({} seleccionados)",
+    ],
+    ItemCount => ["{} items", "{} Einträge", "{} elementos"],
+    ItemCountSelected => [
+        "{} items ({} selected)",
+        "{} Einträge ({} ausgewählt)",
+        "{} elementos ({} seleccionados)",
+    ],
+    HiddenShown => ["{} hidden", "{} verborgen", "{} ocultos"],
+    HiddenNotShown => [
+        "{} hidden not shown",
+        "{} verborgene ausgeblendet",
+        "{} ocultos sin mostrar",
+    ],
+    ReadOnly => ["read-only", "nur lesen", "solo lectura"],
+    NoSnapshot => [
+        "No saved collection in {}, run a download there first",
+        "Keine gespeicherte Sammlung in {}, dort erst einmal herunterladen",
+        "No hay colección guardada en {}, descarga allí primero",
+    ],
+    SortNewestFirst => [
+        "newest purchases first",
+        "neueste Käufe zuerst",
+        "compras más recientes primero",
+    ],
+    SortOldestFirst => [
+        "oldest purchases first",
+        "älteste Käufe zuerst",
+        "compras más antiguas primero",
+    ],
+    HiddenTag => ["(hidden)", "(verborgen)", "(oculto)"],
+    TabCollection => ["Collection", "Sammlung", "Colección"],
+    TabWishlist => ["Wishlist", "Wunschliste", "Lista de deseos"],
+    TabFeed => ["Feed", "Feed", "Novedades"],
+    TabHistory => ["History", "Verlauf", "Historial"],
+    Tabs => ["Tabs", "Tabs", "Pestañas"],
+    FeedUnavailable => [
+        "The feed can't be listed yet",
+        "Der Feed kann noch nicht angezeigt werden",
+        "Las novedades aún no se pueden mostrar",
+    ],
+    NoMatches => ["No matches", "Keine Treffer", "Sin resultados"],
+    List => ["List", "Liste", "Lista"],
+    Done => ["Done", "Fertig", "Listo"],
+    Clear => ["Clear", "Leeren", "Borrar"],
+    Nav => ["Nav", "Navigieren", "Navegar"],
+    Select => ["Select", "Auswählen", "Seleccionar"],
+    All => ["All", "Alle", "Todo"],
+    Download => ["Download", "Herunterladen", "Descargar"],
+    QuickDownload => ["Quick download", "Sofort laden", "Descarga rápida"],
+    DownloadNow => ["Download now", "Jetzt laden", "Descargar ya"],
+    DownloadingTag => ["(downloading...)", "(wird geladen...)", "(descargando...)"],
+    DownloadedTag => ["(downloaded)", "(geladen)", "(descargado)"],
+    DownloadFailedTag => ["(failed)", "(fehlgeschlagen)", "(falló)"],
+    ShowHidden => ["Show hidden", "Verborgene zeigen", "Mostrar ocultos"],
+    Sort => ["Sort", "Sortieren", "Ordenar"],
+    SelectFormat => ["Select Format", "Format wählen", "Elegir formato"],
+    Confirm => ["Confirm", "Bestätigen", "Confirmar"],
+    Preview => ["Preview", "Vorschau", "Vista previa"],
+    LoadingDetails => ["Loading details...", "Details werden geladen...", "Cargando detalles..."],
+    PurchasedOn => ["Purchased {}", "Gekauft am {}", "Comprado el {}"],
+    ReleasedOn => ["Released {}", "Erschienen am {}", "Publicado el {}"],
+    TrackCount => ["{} tracks", "{} Titel", "{} pistas"],
+    Cancel => ["Cancel", "Abbrechen", "Cancelar"],
+
+    // Download screen
+    Downloads => ["Downloads", "Downloads", "Descargas"],
+    GroupActive => ["Active", "Aktiv", "Activas"],
+    GroupQueued => ["Queued", "Wartend", "En cola"],
+    GroupCompleted => ["Completed", "Abgeschlossen", "Completadas"],
+    GroupFailed => ["Failed", "Fehlgeschlagen", "Fallidas"],
+    QueuedPosition => ["queued #{}", "wartend #{}", "en cola #{}"],
+    FetchingUrl => ["resolving URL...", "URL wird ermittelt...", "resolviendo URL..."],
+    Preparing => [
+        "preparing (attempt {}, ~{}s elapsed)",
+        "wird vorbereitet (Versuch {}, ~{}s vergangen)",
+        "preparando (intento {}, ~{}s transcurridos)",
+    ],
+    DownloadingStage => ["downloading...", "wird heruntergeladen...", "descargando..."],
+    Extracting => ["extracting...", "wird entpackt...", "extrayendo..."],
+    ExtractingEntry => ["extracting {}%: {}", "entpacke {}%: {}", "extrayendo {}%: {}"],
+    StatusOk => ["OK", "OK", "OK"],
+    StatusFailed => ["FAIL", "FEHLER", "ERROR"],
+    StatusCancelled => ["CANCELLED", "ABGEBROCHEN", "CANCELADA"],
+    StatusSkipped => ["SKIPPED", "ÜBERSPRUNGEN", "OMITIDA"],
+    GroupSkipped => ["Skipped", "Übersprungen", "Omitidas"],
+    SkippedCount => ["{} skipped", "{} übersprungen", "{} omitidas"],
+    CompleteCount => ["{}/{} complete", "{}/{} fertig", "{}/{} completadas"],
+    OkCount => ["{} ok", "{} ok", "{} correctas"],
+    FailedCount => ["{} failed", "{} fehlgeschlagen", "{} fallidas"],
+    NetworkSpeed => ["net {}/s", "Netz {}/s", "red {}/s"],
+    WriteSpeed => ["disk {}/s", "Platte {}/s", "disco {}/s"],
+    FreeSpace => ["{} free", "{} frei", "{} libres"],
+    Collapse => ["Collapse", "Einklappen", "Contraer"],
+    FailuresOnly => ["Failures only", "Nur Fehler", "Solo errores"],
+    ShowAll => ["Show all", "Alle zeigen", "Mostrar todo"],
+    NoFailures => ["No failures", "Keine Fehler", "Sin errores"],
+    AllFinished => [
+        "All downloads finished.",
+        "Alle Downloads abgeschlossen.",
+        "Todas las descargas han terminado.",
+    ],
+    OpenFolder => ["Open folder", "Ordner öffnen", "Abrir carpeta"],
+    BackToLibrary => ["Back to library", "Zurück zur Bibliothek", "Volver a la biblioteca"],
+
+    // CLI
+    ArtistProgress => ["{}: {}/{} items", "{}: {}/{} Einträge", "{}: {}/{} elementos"],
+    ValidatingSession => ["Validating session...", "Sitzung wird geprüft...", "Validando la sesión..."],
+    ItemsRemaining => ["{} items remaining", "{} Einträge verbleibend", "{} elementos restantes"],
+    EtaEstimating => ["ETA estimating...", "Restzeit wird geschätzt...", "calculando tiempo restante..."],
+    Eta => ["ETA {}", "Restzeit {}", "tiempo restante {}"],
+    WouldDownload => [
+        "Would download {} items.",
+        "Würde {} Einträge herunterladen.",
+        "Se descargarían {} elementos.",
+    ],
+    PlanNew => ["new", "neu", "nuevo"],
+    PlanOtherFormat => ["have {} already", "{} schon vorhanden", "ya existe en {}"],
+    PlanSummary => [
+        "{} new, {} already downloaded, {} in another format.",
+        "{} neu, {} bereits heruntergeladen, {} in anderem Format.",
+        "{} nuevos, {} ya descargados, {} en otro formato.",
+    ],
+    RequestSummary => [
+        "Requests to Bandcamp: {} (collection pages: {}, download pages: {}, release pages: {}, status polls: {}).",
+        "Anfragen an Bandcamp: {} (Sammlungsseiten: {}, Downloadseiten: {}, Veröffentlichungsseiten: {}, Statusabfragen: {}).",
+        "Peticiones a Bandcamp: {} (páginas de colección: {}, páginas de descarga: {}, páginas de lanzamiento: {}, consultas de estado: {}).",
+    ],
+    NamingIssues => [
+        "{} items need sanitized names and {} paths are shared by several items, see {}",
+        "{} Einträge brauchen bereinigte Namen und {} Pfade teilen sich mehrere Einträge, siehe {}",
+        "{} elementos necesitan nombres saneados y {} rutas las comparten varios elementos, ver {}",
+    ],
+    DownloadedSummary => [
+        "Downloaded {} items, {} failed.",
+        "{} Einträge heruntergeladen, {} fehlgeschlagen.",
+        "{} elementos descargados, {} fallidos.",
+    ],
+    DownloadedAsFallback => [
+        "{} - {}: downloaded as {} instead",
+        "{} - {}: stattdessen als {} heruntergeladen",
+        "{} - {}: descargado como {} en su lugar",
+    ],
+    SkippedReasonCount => ["{} skipped ({})", "{} übersprungen ({})", "{} omitidos ({})"],
+    SkipAlreadyExists => ["already downloaded", "bereits heruntergeladen", "ya descargado"],
+    SkipPreorder => ["preorder, not released yet", "Vorbestellung, noch nicht erschienen", "preventa, aún sin publicar"],
+    SkipHidden => ["hidden in collection", "in der Sammlung verborgen", "oculto en la colección"],
+    SkipNoMatchingFormat => [
+        "format not offered",
+        "Format nicht angeboten",
+        "formato no disponible",
+    ],
+    SkipExcluded => ["excluded by filter", "durch Filter ausgeschlossen", "excluido por un filtro"],
+    SkipCompletedInBatch => [
+        "already completed in this batch",
+        "in diesem Batch bereits erledigt",
+        "ya completado en este lote",
+    ],
+    SkipBandwidthCap => [
+        "monthly bandwidth cap reached",
+        "monatliches Datenlimit erreicht",
+        "límite mensual de datos alcanzado",
+    ],
+    SkipInProgressElsewhere => [
+        "in progress elsewhere",
+        "wird anderswo heruntergeladen",
+        "en curso en otro proceso",
+    ],
+    SkipEncodingPending => [
+        "still being prepared by Bandcamp",
+        "wird von Bandcamp noch vorbereitet",
+        "Bandcamp aún lo está preparando",
+    ],
+    PendingHint => [
+        "{} items are still being prepared by Bandcamp, `bannedcamp download pending` fetches them once they are ready, usually within an hour",
+        "{} Einträge werden von Bandcamp noch vorbereitet, `bannedcamp download pending` lädt sie, sobald sie fertig sind, meist innerhalb einer Stunde",
+        "Bandcamp aún está preparando {} elementos, `bannedcamp download pending` los descarga cuando estén listos, normalmente en menos de una hora",
+    ],
+    NothingPending => [
+        "No downloads are waiting on Bandcamp",
+        "Keine Downloads warten auf Bandcamp",
+        "Ninguna descarga está esperando a Bandcamp",
+    ],
+    SkipStreamOnly => [
+        "stream-only, no download offered",
+        "nur Stream, kein Download angeboten",
+        "solo streaming, sin descarga",
+    ],
+    BatchId => ["Batch {}", "Batch {}", "Lote {}"],
+    AllDownloaded => [
+        "All items already downloaded",
+        "Alle Einträge sind bereits heruntergeladen",
+        "Todos los elementos ya están descargados",
+    ],
+    LibraryEmpty => [
+        "No items found in library",
+        "Keine Einträge in der Bibliothek gefunden",
+        "No se encontraron elementos en la biblioteca",
+    ],
+    NothingNew => [
+        "Nothing purchased since the last run",
+        "Seit dem letzten Lauf wurde nichts gekauft",
+        "No se ha comprado nada desde la última ejecución",
+    ],
+    AllMatchingDownloaded => [
+        "All matching items already downloaded",
+        "Alle passenden Einträge sind bereits heruntergeladen",
+        "Todos los elementos coincidentes ya están descargados",
+    ],
+    NoUrlMatches => [
+        "No items found matching URL(s): {}",
+        "Keine Einträge zu den URL(s) gefunden: {}",
+        "No se encontraron elementos para las URL: {}",
+    ],
+    CursorFinished => [
+        "Every item has been downloaded by earlier runs",
+        "Alle Einträge wurden bereits in früheren Läufen heruntergeladen",
+        "Todos los elementos se descargaron en ejecuciones anteriores",
+    ],
+    CursorBatch => [
+        "Downloading {} items this run, {} left for later runs",
+        "Lade in diesem Lauf {} Einträge herunter, {} bleiben für spätere Läufe",
+        "Descargando {} elementos en esta ejecución, quedan {} para las siguientes",
+    ],
+    StatsNothingDownloaded => [
+        "Nothing downloaded into {} yet",
+        "Noch nichts nach {} heruntergeladen",
+        "Todavía no se ha descargado nada en {}",
+    ],
+    StatsDownloaded => [
+        "Downloaded {} this month, {} in total.",
+        "Diesen Monat {} heruntergeladen, insgesamt {}.",
+        "Descargado {} este mes, {} en total.",
+    ],
+    StatsLargestItems => [
+        "Largest items:",
+        "Größte Einträge:",
+        "Elementos más grandes:",
+    ],
+    ExportWroteGallery => [
+        "Wrote {} with {} items, {} downloaded.",
+        "{} mit {} Einträgen geschrieben, {} heruntergeladen.",
+        "Se escribió {} con {} elementos, {} descargados.",
+    ],
+    ExportSnapshotAge => [
+        "The collection was last fetched {}, a download refreshes it.",
+        "Die Sammlung wurde zuletzt {} abgerufen, ein Download aktualisiert sie.",
+        "La colección se obtuvo por última vez el {}, una descarga la actualiza.",
+    ],
+    CleanNothing => [
+        "Nothing to clean in {}",
+        "In {} gibt es nichts aufzuräumen",
+        "No hay nada que limpiar en {}",
+    ],
+    CleanWouldRemove => [
+        "Would remove {} items.",
+        "Würde {} Einträge entfernen.",
+        "Se eliminarían {} elementos.",
+    ],
+    CleanTempFile => ["temporary file", "temporäre Datei", "archivo temporal"],
+    CleanPartialDir => ["partial extraction", "unvollständig entpackt", "extracción incompleta"],
+    CleanEmptyDir => ["empty folder", "leerer Ordner", "carpeta vacía"],
+    CleanRemoved => [
+        "Removed {} items.",
+        "{} Einträge entfernt.",
+        "Se eliminaron {} elementos.",
+    ],
+    CleanTrashed => [
+        "Moved {} items to the trash.",
+        "{} Einträge in den Papierkorb verschoben.",
+        "Se movieron {} elementos a la papelera.",
+    ],
+    CleanQuarantined => [
+        "Moved {} items to {}.",
+        "{} Einträge nach {} verschoben.",
+        "Se movieron {} elementos a {}.",
+    ],
+    DoctorApi => ["API reachable", "API erreichbar", "API accesible"],
+    DoctorApiOk => ["bandcamp.com responded", "bandcamp.com hat geantwortet", "bandcamp.com respondió"],
+    DoctorClockSkew => ["Clock skew", "Zeitabweichung", "Desfase del reloj"],
+    DoctorClockOff => [
+        "local clock is {}s off from Bandcamp",
+        "die lokale Uhr weicht {}s von Bandcamp ab",
+        "el reloj local difiere {}s de Bandcamp",
+    ],
+    DoctorNoDateHeader => [
+        "server did not send a Date header",
+        "der Server hat keinen Date-Header gesendet",
+        "el servidor no envió una cabecera Date",
+    ],
+    DoctorCookie => ["Cookie", "Cookie", "Cookie"],
+    DoctorNoCookie => ["no cookie provided", "kein Cookie angegeben", "no se indicó ninguna cookie"],
+    DoctorCookieOk => ["accepted by Bandcamp", "von Bandcamp akzeptiert", "aceptada por Bandcamp"],
+    DoctorFanId => ["Fan ID", "Fan-ID", "ID de fan"],
+    DoctorFanIdResolved => ["resolved to {}", "aufgelöst zu {}", "resuelto como {}"],
+    DoctorCollection => ["Collection", "Sammlung", "Colección"],
+    DoctorCollectionOk => [
+        "first page returned {} items",
+        "die erste Seite lieferte {} Einträge",
+        "la primera página devolvió {} elementos",
+    ],
+    DoctorOutputDir => ["Output directory", "Zielverzeichnis", "Directorio de salida"],
+    DoctorWritable => ["{} is writable", "{} ist beschreibbar", "{} tiene permiso de escritura"],
+    DoctorFreeSpace => ["Free space", "Freier Speicher", "Espacio libre"],
+    DoctorLowSpace => ["only {} available", "nur {} verfügbar", "solo hay {} disponibles"],
+    DoctorSpace => ["{} available", "{} verfügbar", "{} disponibles"],
+    DoctorZip => ["ZIP extraction", "ZIP-Entpacken", "Extracción ZIP"],
+    DoctorZipOk => [
+        "test archive extracted",
+        "Testarchiv entpackt",
+        "archivo de prueba extraído",
+    ],
+    DoctorHintNtp => [
+        "Enable NTP time sync, download links are time-limited",
+        "NTP-Zeitsynchronisation aktivieren, Downloadlinks sind zeitlich begrenzt",
+        "Activa la sincronización NTP, los enlaces de descarga caducan",
+    ],
+    DoctorHintConnection => [
+        "Check your internet connection, proxy settings and https://bandcamp.com",
+        "Internetverbindung, Proxy-Einstellungen und https://bandcamp.com prüfen",
+        "Comprueba la conexión a internet, la configuración del proxy y https://bandcamp.com",
+    ],
+    DoctorHintCookie => [
+        "Set --cookie or the BANDCAMP_COOKIE env var",
+        "--cookie oder die Umgebungsvariable BANDCAMP_COOKIE setzen",
+        "Usa --cookie o la variable de entorno BANDCAMP_COOKIE",
+    ],
+    DoctorHintLogInAgain => [
+        "Log in again on bandcamp.com and copy a fresh identity cookie",
+        "Erneut auf bandcamp.com anmelden und ein frisches identity-Cookie kopieren",
+        "Vuelve a iniciar sesión en bandcamp.com y copia una cookie identity nueva",
+    ],
+    DoctorHintFreshCookie => [
+        "Copy a fresh identity cookie, see the README for instructions",
+        "Ein frisches identity-Cookie kopieren, Anleitung in der README",
+        "Copia una cookie identity nueva, las instrucciones están en el README",
+    ],
+    DoctorHintLossless => [
+        "Lossless albums are often several hundred MB each",
+        "Verlustfreie Alben sind oft mehrere hundert MB groß",
+        "Los álbumes sin pérdida suelen ocupar varios cientos de MB",
+    ],
+    DoctorHintZip => [
+        "Albums are delivered as ZIP files and extracted into the output directory",
+        "Alben kommen als ZIP-Dateien und werden ins Zielverzeichnis entpackt",
+        "Los álbumes llegan como archivos ZIP y se extraen en el directorio de salida",
+    ],
+    DoctorHintOutputDir => [
+        "Choose another directory with -o or fix its permissions",
+        "Mit -o ein anderes Verzeichnis wählen oder die Berechtigungen korrigieren",
+        "Elige otro directorio con -o o corrige sus permisos",
+    ],
+    DoctorSummary => [
+        "{} failed, {} warnings.",
+        "{} fehlgeschlagen, {} Warnungen.",
+        "{} fallidas, {} avisos.",
+    ],
+    WishlistNoFree => [
+        "No free items on your wishlist",
+        "Keine kostenlosen Einträge auf deiner Wunschliste",
+        "No hay elementos gratuitos en tu lista de deseos",
+    ],
+    WishlistEmpty => [
+        "Your wishlist is empty",
+        "Deine Wunschliste ist leer",
+        "Tu lista de deseos está vacía",
+    ],
+    WishlistSummary => [
+        "{} items, {} free.",
+        "{} Einträge, {} kostenlos.",
+        "{} elementos, {} gratuitos.",
+    ],
+    WishlistFree => ["free", "kostenlos", "gratis"],
+    WishlistPriceUnknown => ["price unknown", "Preis unbekannt", "precio desconocido"],
+    HideAlreadyHidden => [
+        "{} - {} is already hidden",
+        "{} - {} ist bereits verborgen",
+        "{} - {} ya está oculto",
+    ],
+    HideAlreadyVisible => [
+        "{} - {} is already visible",
+        "{} - {} ist bereits sichtbar",
+        "{} - {} ya está visible",
+    ],
+    HideHid => ["Hid {} - {}", "{} - {} verborgen", "Se ocultó {} - {}"],
+    HideUnhid => ["Unhid {} - {}", "{} - {} wieder sichtbar", "Se volvió a mostrar {} - {}"],
+
+    // Errors
+    ErrorAuth => [
+        "Authentication failed: {}",
+        "Anmeldung fehlgeschlagen: {}",
+        "Error de autenticación: {}",
+    ],
+    ErrorNotLoggedIn => ["Not logged in", "Nicht angemeldet", "No has iniciado sesión"],
+    ErrorSessionExpired => ["Session expired", "Sitzung abgelaufen", "La sesión ha caducado"],
+    ErrorNetwork => ["Network error: {}", "Netzwerkfehler: {}", "Error de red: {}"],
+    ErrorHttpStatus => ["HTTP {} from {}", "HTTP {} von {}", "HTTP {} de {}"],
+    ErrorDownload => [
+        "Download failed: {}",
+        "Download fehlgeschlagen: {}",
+        "La descarga falló: {}",
+    ],
+    ErrorIo => ["IO error: {}", "E/A-Fehler: {}", "Error de E/S: {}"],
+    ErrorParse => ["Parse error: {}", "Verarbeitungsfehler: {}", "Error de análisis: {}"],
+    ErrorFormatUnavailable => [
+        "Format '{}' is not offered for this item",
+        "Format '{}' wird für diesen Eintrag nicht angeboten",
+        "El formato '{}' no está disponible para este elemento",
+    ],
+    ErrorStreamOnly => [
+        "Item can only be streamed, Bandcamp offers no download for it",
+        "Eintrag kann nur gestreamt werden, Bandcamp bietet keinen Download an",
+        "El elemento solo se puede escuchar en streaming, Bandcamp no ofrece descarga",
+    ],
+    ErrorSiteDown => [
+        "Bandcamp is currently down, try again later",
+        "Bandcamp ist gerade nicht erreichbar, versuche es später erneut",
+        "Bandcamp no está disponible ahora, inténtalo más tarde",
+    ],
+    ErrorBadDownload => [
+        "Download server sent no usable file: {}",
+        "Der Downloadserver hat keine brauchbare Datei geschickt: {}",
+        "El servidor de descargas no envió un archivo válido: {}",
+    ],
+    ErrorBandwidthCap => [
+        "Monthly bandwidth cap reached",
+        "Monatliches Datenlimit erreicht",
+        "Límite mensual de datos alcanzado",
+    ],
+    ErrorInProgressElsewhere => [
+        "Already being downloaded by another bannedcamp process",
+        "Wird bereits von einem anderen bannedcamp-Prozess heruntergeladen",
+        "Otro proceso de bannedcamp ya lo está descargando",
+    ],
+    ErrorDownloadLimited => [
+        "Bandcamp refuses further download attempts for now, wait {} minutes before trying again",
+        "Bandcamp lehnt vorerst weitere Downloadversuche ab, warte {} Minuten, bevor du es erneut versuchst",
+        "Bandcamp rechaza más intentos de descarga por ahora, espera {} minutos antes de volver a intentarlo",
+    ],
+    ErrorEncodingPending => [
+        "Bandcamp is still preparing the download after {} checks, try again later",
+        "Bandcamp bereitet den Download nach {} Prüfungen noch vor, versuche es später erneut",
+        "Bandcamp aún prepara la descarga tras {} comprobaciones, inténtalo más tarde",
+    ],
+    ErrorInvalidTemplate => [
+        "Invalid name template: {}",
+        "Ungültige Namensvorlage: {}",
+        "Plantilla de nombre no válida: {}",
+    ],
+    ErrorOutputUnusable => [
+        "Can't download into {}: {}. Choose another directory with -o, or fix its permissions or free up space",
+        "Download nach {} nicht möglich: {}. Wähle mit -o ein anderes Verzeichnis, korrigiere die Berechtigungen oder schaffe Platz",
+        "No se puede descargar en {}: {}. Elige otro directorio con -o, corrige sus permisos o libera espacio",
+    ],
+
+    // Notifications
+    NotifyFailed => ["Failed: {} - {}", "Fehlgeschlagen: {} - {}", "Falló: {} - {}"],
+    NotifyDownloaded => [
+        "Downloaded {} of {} items",
+        "{} von {} Einträgen heruntergeladen",
+        "Descargados {} de {} elementos",
+    ],
+    NotifyAllSucceeded => [
+        "Everything downloaded successfully",
+        "Alles erfolgreich heruntergeladen",
+        "Todo se descargó correctamente",
+    ],
+    NotifyFailedCount => ["{} failed:", "{} fehlgeschlagen:", "{} fallidos:"],
+    NotifySkippedCount => ["{} skipped", "{} übersprungen", "{} omitidos"],
+    NotifySpaceLow => ["Downloads paused", "Downloads pausiert", "Descargas en pausa"],
+    NotifySpaceFreed => ["Downloads resumed", "Downloads fortgesetzt", "Descargas reanudadas"],
+    SpaceLow => [
+        "Only {} free in {}, pausing downloads until {} are free",
+        "Nur noch {} frei in {}, Downloads pausieren, bis {} frei sind",
+        "Solo quedan {} libres en {}, las descargas se pausan hasta que haya {} libres",
+    ],
+    SpaceFreed => [
+        "Enough space free in {} again, resuming downloads",
+        "Wieder genug Platz frei in {}, Downloads werden fortgesetzt",
+        "Vuelve a haber espacio suficiente en {}, se reanudan las descargas",
+    ],
+}
+
+/// `msg` in the current language
+pub fn tr(msg: Msg) -> &'static str {
+    msg.text(Lang::current())
+}
+
+/// `msg` in the current language with each `{}` replaced by the next argument
+pub fn tr_fmt(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(tr(msg), args)
+}
+
+/// Replaces each `{}` in `template` with the next argument
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+
+    if let Some(first) = parts.next() {
+        out.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            let _ = write!(out, "{arg}");
+        }
+        out.push_str(part);
+    }
+
+    out
+}
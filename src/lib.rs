@@ -1,6 +1,16 @@
+// File claims, permissions, free space checks and dedupe rely on Unix file metadata
+#[cfg(not(unix))]
+compile_error!("bannedcamp only supports Unix-like systems such as Linux and macOS");
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
 pub mod cli;
 pub mod core;
 pub mod error;
+pub mod i18n;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod term;
 pub mod tui;
 
 pub use error::{BandcampError, Result};
@@ -1,15 +1,37 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
 use bannedcamp::error::Result;
 use clap::Parser;
+use tracing::warn;
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
 use bannedcamp::cli::{
+    clean::run_clean,
     commands::{Cli, Commands},
     completions::generate_completions,
+    dedupe::run_dedupe,
+    doctor::run_doctor,
+    export::{ExportTargets, run_export},
+    hide::run_set_hidden,
+    progress::{LogWriter, hide_progress},
+    resolve::run_resolve,
     run::run_download,
+    stats::run_stats,
+    sync::run_sync,
+    template::run_template,
+    wishlist::run_wishlist,
 };
-use bannedcamp::tui;
+use bannedcamp::core::clean::Disposal;
+use bannedcamp::core::client::{self, CollectionPaging};
+use bannedcamp::core::dedupe::LinkMode;
+use bannedcamp::core::library::validate_name_format;
+use bannedcamp::core::verify::ffprobe_available;
+use bannedcamp::core::{recording, session};
+use bannedcamp::term::spinner;
+use bannedcamp::tui::{self, TuiOptions, theme};
 
-fn setup_logging(verbosity: u8, quiet: bool) {
+fn setup_logging(verbosity: u8, quiet: bool, stderr: bool) {
     let filter = if quiet {
         EnvFilter::new("error")
     } else {
@@ -22,24 +44,203 @@ fn setup_logging(verbosity: u8, quiet: bool) {
     };
 
     tracing_subscriber::registry()
-        .with(fmt::layer().with_target(false))
+        .with(
+            fmt::layer()
+                .with_target(false)
+                .with_writer(move || LogWriter { stderr }),
+        )
         .with(filter)
         .init();
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+async fn main() -> ExitCode {
+    // The message rather than the Debug form `Result` would print, it says what to do
+    match run(Cli::parse()).await {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<ExitCode> {
+    // Keeps stdout to the JSON result alone
+    let json = match &cli.command {
+        Commands::Download { args } | Commands::Sync { args, .. } => args.json,
+        _ => false,
+    };
+    setup_logging(cli.verbose, cli.quiet, json);
+    if cli.quiet {
+        hide_progress();
+    }
+    spinner::set_style(cli.spinner);
+    theme::set_theme(cli.theme);
+    let tick_rate = Duration::from_millis(cli.tick_rate.max(1));
+
+    if let Some(dir) = &cli.record_http {
+        recording::record_to(dir)?;
+    }
+    if let Some(dir) = &cli.replay {
+        recording::replay_from(dir)?;
+    }
 
-    setup_logging(cli.verbose, cli.quiet);
+    if let Some(dir) = cli.debug_dump.clone() {
+        client::set_debug_dump_dir(dir);
+    }
+
+    client::set_collection_paging(CollectionPaging {
+        page_size: cli.collection_page_size,
+        max_items: cli.max_collection_items,
+    });
+
+    // doctor is there to check the cookie, an earlier validation proves nothing
+    if let Some(ttl) = cli.session_ttl
+        && !matches!(cli.command, Commands::Doctor { .. })
+    {
+        match session::default_path() {
+            Some(path) => session::enable(path, Duration::from_secs(ttl)),
+            None => warn!("No cache directory found, --session-ttl is ignored"),
+        }
+    }
+
+    // Kept until the command is done, every client it creates talks to the server
+    #[cfg(feature = "mock")]
+    let _mock_server = if cli.mock {
+        let server = bannedcamp::mock::start().await?;
+        bannedcamp::core::client::set_base_url(server.url().to_string());
+        Some(server)
+    } else {
+        None
+    };
 
     match cli.command {
-        Commands::Library { output } => {
-            tui::run(output)?;
+        Commands::Library {
+            output,
+            format,
+            custom_format,
+            audit_log,
+            checksums,
+            purchase_info,
+            verify_audio,
+            read_only,
+            filenames,
+        } => {
+            if verify_audio {
+                ffprobe_available()?;
+            }
+            if let Some(custom_format) = &custom_format {
+                validate_name_format(custom_format)?;
+            }
+            let options = TuiOptions {
+                output_dir: output,
+                format,
+                custom_format,
+                audit_log,
+                checksums,
+                purchase_info,
+                verify_audio,
+                read_only,
+                sanitize: filenames.sanitize_options(),
+            };
+            tui::run(options, tick_rate).await?;
         }
 
         Commands::Download { args } => {
-            run_download(args).await?;
+            run_download(*args, tick_rate).await?;
+        }
+
+        Commands::Sync {
+            once,
+            interval,
+            args,
+        } => {
+            run_sync(*args, once, interval, tick_rate).await?;
+        }
+
+        Commands::Resolve {
+            targets,
+            cookie,
+            format,
+        } => {
+            run_resolve(cookie, &targets, format).await?;
+        }
+
+        Commands::Export {
+            output,
+            html,
+            discogs,
+            discogs_token,
+            listenbrainz,
+            listenbrainz_token,
+            dry_run,
+        } => {
+            let targets = ExportTargets {
+                html: html.as_deref(),
+                discogs_token: discogs_token.as_deref().filter(|_| discogs),
+                listenbrainz_token: listenbrainz_token.as_deref().filter(|_| listenbrainz),
+                dry_run,
+            };
+            run_export(&output, targets).await?;
+        }
+
+        Commands::Clean {
+            output,
+            dry_run,
+            trash,
+            quarantine,
+            quarantine_ttl,
+        } => {
+            let disposal = match quarantine {
+                Some(dir) => Disposal::Quarantine {
+                    dir,
+                    ttl: quarantine_ttl,
+                },
+                None if trash => Disposal::Trash,
+                None => Disposal::Delete,
+            };
+            run_clean(&output, dry_run, &disposal)?;
+        }
+
+        Commands::Dedupe {
+            output,
+            extras,
+            link,
+            reflink,
+        } => {
+            let mode = if link {
+                Some(LinkMode::Hardlink)
+            } else if reflink {
+                Some(LinkMode::Reflink)
+            } else {
+                None
+            };
+            run_dedupe(&output, extras, mode)?;
+        }
+
+        Commands::Stats { output, bandwidth } => {
+            run_stats(&output, bandwidth)?;
+        }
+
+        Commands::Doctor { cookie, output } => {
+            return run_doctor(cookie, &output).await;
+        }
+
+        Commands::Hide { id, cookie } => {
+            run_set_hidden(cookie, &id, true).await?;
+        }
+
+        Commands::Unhide { id, cookie } => {
+            run_set_hidden(cookie, &id, false).await?;
+        }
+
+        Commands::Wishlist { command } => {
+            run_wishlist(command).await?;
+        }
+
+        Commands::Template { command } => {
+            run_template(command).await?;
         }
 
         Commands::Completions { shell } => {
@@ -47,5 +248,5 @@ async fn main() -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,111 @@
+//! Realistic test data for programs built on the crate, so their tests don't need a Bandcamp
+//! account. [`collection_response`] produces the exact JSON the collection API answers with,
+//! [`super::start`] serves a whole fake Bandcamp including downloads.
+
+use chrono::{TimeZone, Utc};
+use serde_json::json;
+
+use crate::core::library::{ItemType, LibraryItem, PurchaseInfo};
+
+/// A purchased album, track or package as the collection API describes it, with URLs on
+/// `bandcamp.com` derived from the artist and title
+pub fn library_item(id: u64, item_type: ItemType, artist: &str, title: &str) -> LibraryItem {
+    let subdomain = slugify(artist).replace('-', "");
+    let slug = slugify(title);
+    let kind = match item_type {
+        ItemType::Track => "track",
+        ItemType::Album | ItemType::Package => "album",
+    };
+
+    LibraryItem {
+        id: id.to_string(),
+        item_url: Some(format!("https://{subdomain}.bandcamp.com/{kind}/{slug}")),
+        download_url: format!(
+            "https://bandcamp.com/download?from=collection&payment_id={id}&sig=fixture&sitem_id={id}"
+        ),
+        item_type,
+        title: title.to_string(),
+        artist: artist.to_string(),
+        artist_id: (id / 2).to_string(),
+        artist_subdomain: Some(subdomain),
+        slug: Some(slug),
+        is_preorder: false,
+        is_hidden: false,
+        purchase: PurchaseInfo {
+            purchased_at: Utc.with_ymd_and_hms(2024, 3, 14, 18, 2, 11).single(),
+            price: Some(9.0),
+            currency: Some("EUR".to_string()),
+            ..Default::default()
+        },
+        release_date: None,
+    }
+}
+
+/// Body of a `collection_items` response listing `items`. `last_token` is handed back by the
+/// client to ask for the next page while `more_available` is set.
+pub fn collection_response(
+    items: &[LibraryItem],
+    more_available: bool,
+    last_token: Option<&str>,
+) -> serde_json::Value {
+    let item_type = |item: &LibraryItem| match item.item_type {
+        ItemType::Album => "a",
+        ItemType::Track => "t",
+        ItemType::Package => "p",
+    };
+
+    let entries: Vec<_> = items
+        .iter()
+        .map(|item| {
+            let purchase = &item.purchase;
+            json!({
+                "band_id": item.artist_id.parse::<u64>().unwrap_or_default(),
+                "sale_item_id": item.id.parse::<u64>().unwrap_or_default(),
+                "sale_item_type": item_type(item),
+                "tralbum_type": item_type(item),
+                "hidden": item.is_hidden,
+                "url_hints": { "subdomain": item.artist_subdomain, "slug": item.slug },
+                "item_title": item.title,
+                "item_url": item.item_url,
+                "band_name": item.artist,
+                "is_preorder": item.is_preorder,
+                "purchased": purchase
+                    .purchased_at
+                    .map(|date| date.format("%d %b %Y %H:%M:%S GMT").to_string()),
+                "price": purchase.price,
+                "currency": purchase.currency,
+                "gift_sender_name": purchase.gift_from,
+                "gift_sender_note": purchase.gift_note,
+                "why": purchase.note,
+            })
+        })
+        .collect();
+
+    // Stream-only items are the ones Bandcamp has no redownload page for
+    let redownload_urls: serde_json::Map<_, _> = items
+        .iter()
+        .filter(|item| !item.is_stream_only())
+        .map(|item| {
+            (
+                format!("{}{}", item_type(item), item.id),
+                item.download_url.clone().into(),
+            )
+        })
+        .collect();
+
+    json!({
+        "items": entries,
+        "more_available": more_available,
+        "last_token": last_token,
+        "redownload_urls": redownload_urls,
+    })
+}
+
+/// Lowercase words joined by `-`, like Bandcamp's URL slugs
+fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
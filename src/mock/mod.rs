@@ -0,0 +1,596 @@
+pub mod fixtures;
+
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info, warn};
+
+use crate::core::client::BandcampClient;
+use crate::core::library::{ItemType, LibraryItem, PurchaseInfo};
+use crate::error::{BandcampError, Result};
+
+/// Canned library served by the mock server
+const LIBRARY: &str = include_str!("library.json");
+
+/// Size of each fake audio file, large enough for the progress bars to move
+const TRACK_BYTES: usize = 512 * 1024;
+
+/// Files are streamed in chunks of this size with a short pause in between
+const CHUNK_BYTES: usize = 64 * 1024;
+const CHUNK_DELAY: Duration = Duration::from_millis(20);
+
+/// Requests bigger than this are cut off, nothing the client sends comes close
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// Fans with a public page: the logged in one, and a friend who owns part of the same library
+const FANS: &[(&str, u64)] = &[("mock", 1), ("friend", 2)];
+
+#[derive(Debug, Deserialize)]
+struct Library {
+    items: Vec<Release>,
+    wishlist: Vec<WishlistEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    id: u64,
+    /// `a`, `t` or `p`, as in the collection API
+    #[serde(rename = "type")]
+    item_type: String,
+    band_id: u64,
+    artist: String,
+    title: String,
+    subdomain: String,
+    slug: String,
+    release_date: String,
+    purchased: String,
+    price: Option<f64>,
+    currency: Option<String>,
+    gift_from: Option<String>,
+    gift_note: Option<String>,
+    note: Option<String>,
+    #[serde(default)]
+    hidden: bool,
+    #[serde(default)]
+    preorder: bool,
+    /// Preorder already offering its first tracks, downloadable before release day
+    #[serde(default)]
+    instant_grat: bool,
+    /// Owned but without any downloads, like some subscription releases
+    #[serde(default)]
+    stream_only: bool,
+    /// Free for anyone to download from its page. Only the friend has it, so fetching their
+    /// collection has something to find that the logged in fan doesn't own.
+    #[serde(default)]
+    free: bool,
+    /// File names of the bonus items offered next to the audio
+    #[serde(default)]
+    bonus: Vec<String>,
+    tracks: Vec<Track>,
+}
+
+impl Release {
+    /// The item as the client reads it from the collection API served at `base`
+    fn library_item(&self, base: &str) -> LibraryItem {
+        let item_type = match self.item_type.as_str() {
+            "t" => ItemType::Track,
+            "p" => ItemType::Package,
+            _ => ItemType::Album,
+        };
+        let download_url = if (self.preorder && !self.instant_grat) || self.stream_only {
+            String::new()
+        } else {
+            format!("{base}/redownload/{}", self.id)
+        };
+
+        LibraryItem {
+            id: self.id.to_string(),
+            item_type,
+            title: self.title.clone(),
+            artist: self.artist.clone(),
+            artist_id: self.band_id.to_string(),
+            artist_subdomain: Some(self.subdomain.clone()),
+            slug: Some(self.slug.clone()),
+            item_url: Some(format!("{base}/release/{}", self.id)),
+            download_url,
+            is_preorder: self.preorder,
+            is_hidden: self.hidden,
+            purchase: PurchaseInfo {
+                purchased_at: NaiveDateTime::parse_from_str(
+                    &self.purchased,
+                    "%d %b %Y %H:%M:%S GMT",
+                )
+                .ok()
+                .map(|date| date.and_utc()),
+                price: self.price,
+                currency: self.currency.clone(),
+                gift_from: self.gift_from.clone(),
+                gift_note: self.gift_note.clone(),
+                note: self.note.clone(),
+            },
+            release_date: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Track {
+    title: String,
+    duration: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct WishlistEntry {
+    id: u64,
+    #[serde(rename = "type")]
+    item_type: String,
+    artist: String,
+    title: String,
+    price: Option<f64>,
+    currency: Option<String>,
+}
+
+/// Encodings offered on every download page, with the file extension Bandcamp uses for each
+const ENCODINGS: &[(&str, &str)] = &[
+    ("flac", "flac"),
+    ("mp3-v0", "mp3"),
+    ("mp3-320", "mp3"),
+    ("aac-hi", "m4a"),
+    ("vorbis", "ogg"),
+    ("alac", "m4a"),
+    ("wav", "wav"),
+    ("aiff-lossless", "aiff"),
+];
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    /// Sent as `Content-Disposition`, like Bandcamp does for downloads
+    filename: Option<String>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(content_type: &'static str, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: "200 OK",
+            content_type,
+            filename: None,
+            body: body.into(),
+        }
+    }
+
+    fn json(value: serde_json::Value) -> Self {
+        Self::ok("application/json", value.to_string())
+    }
+
+    fn html(body: String) -> Self {
+        Self::ok("text/html; charset=utf-8", body)
+    }
+
+    fn attachment(content_type: &'static str, filename: String, body: Vec<u8>) -> Self {
+        Self {
+            filename: Some(filename),
+            ..Self::ok(content_type, body)
+        }
+    }
+
+    fn not_found() -> Self {
+        Self {
+            status: "404 Not Found",
+            content_type: "text/plain",
+            filename: None,
+            body: b"not found".to_vec(),
+        }
+    }
+}
+
+fn load_library() -> Result<Library> {
+    serde_json::from_str(LIBRARY)
+        .map_err(|e| BandcampError::ParseError(format!("mock library: {e}")))
+}
+
+/// The collection [`start`] serves, as the client sees it from the server at `base`
+pub fn library(base: &str) -> Result<Vec<LibraryItem>> {
+    Ok(load_library()?
+        .items
+        .iter()
+        .filter(|r| !r.free)
+        .map(|r| r.library_item(base))
+        .collect())
+}
+
+/// The mock server started by [`start`], it stops when dropped
+pub struct MockServer {
+    url: String,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Base URL of the server, like `http://127.0.0.1:38411`
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// A client sending its requests to this server
+    pub fn client(&self) -> BandcampClient {
+        BandcampClient::new().with_base_url(self.url.clone())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Serves the canned library on a random localhost port. Any cookie is accepted, downloads
+/// are generated on the fly and contain noise, not audio.
+pub async fn start() -> Result<MockServer> {
+    let library = load_library()?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    info!("Mock server listening on {url}");
+
+    let library = Arc::new(library);
+    let base = url.clone();
+    let task = tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let library = library.clone();
+                    let base = base.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(stream, &library, &base).await {
+                            warn!("Mock server connection failed: {e}");
+                        }
+                    });
+                }
+                Err(e) => warn!("Mock server accept failed: {e}"),
+            }
+        }
+    });
+
+    Ok(MockServer { url, task })
+}
+
+async fn serve(mut stream: TcpStream, library: &Library, base: &str) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+
+    // Read the head, then whatever body Content-Length announces
+    let head_end = loop {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        request.extend_from_slice(&buf[..n]);
+        if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if request.len() > MAX_REQUEST_BYTES {
+            return Ok(());
+        }
+    };
+
+    let head = String::from_utf8_lossy(&request[..head_end]).into_owned();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_REQUEST_BYTES);
+    while request.len() - head_end < content_length {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default();
+    let target = request_line.next().unwrap_or_default();
+    debug!("Mock server: {method} {target}");
+
+    let body = String::from_utf8_lossy(&request[head_end..]);
+    let response = route(library, base, target, &body);
+
+    let disposition = match &response.filename {
+        Some(name) => format!(
+            "Content-Disposition: attachment; filename=\"{}\"; filename*=UTF-8''{}\r\n",
+            name.replace(|c: char| !c.is_ascii() || c == '"', "_"),
+            url::form_urlencoded::byte_serialize(name.as_bytes())
+                .collect::<String>()
+                .replace('+', "%20"),
+        ),
+        None => String::new(),
+    };
+    let header = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{disposition}Date: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.content_type,
+        response.body.len(),
+        chrono::Utc::now().to_rfc2822().replace("+0000", "GMT"),
+    );
+    stream.write_all(header.as_bytes()).await?;
+
+    if method != "HEAD" {
+        for chunk in response.body.chunks(CHUNK_BYTES) {
+            stream.write_all(chunk).await?;
+            if response.body.len() > CHUNK_BYTES {
+                tokio::time::sleep(CHUNK_DELAY).await;
+            }
+        }
+    }
+
+    stream.shutdown().await
+}
+
+fn route(library: &Library, base: &str, target: &str, body: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let encoding = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("enc="))
+        .unwrap_or("flac");
+    let release = |id: &str| library.items.iter().find(|r| r.id.to_string() == id);
+
+    match segments.as_slice() {
+        [""] => Response::html("<html><body>mock bandcamp</body></html>".to_string()),
+        ["api", "fan", "2", "collection_summary"] => Response::json(json!({
+            "fan_id": 1,
+            "collection_summary": {
+                "fan_id": 1,
+                "username": "mock",
+                "url": format!("{base}/mock"),
+                "tralbum_lookup": {},
+            },
+        })),
+        ["api", "fancollection", "1", "collection_items"] => collection_items(library, base, body),
+        ["api", "fancollection", "1", "wishlist_items"] => wishlist_items(library, base),
+        ["api", "collectionowner", "1", "hide_unhide_item"] => Response::json(json!({"ok": true})),
+        ["release", id] => match release(id) {
+            Some(release) => Response::html(release_page(release, base)),
+            None => Response::not_found(),
+        },
+        ["redownload", id] => match release(id) {
+            Some(release) => Response::html(download_page(release, base)),
+            None => Response::not_found(),
+        },
+        // Requesting a format starts the encoding on Bandcamp, here it is ready right away
+        ["download", _] => Response::html("<html><body>preparing</body></html>".to_string()),
+        ["statdownload", id] => Response::json(json!({
+            "result": "ok",
+            "download_url": format!("{base}/files/{id}?enc={encoding}"),
+        })),
+        ["files", id] => match release(id) {
+            Some(release) => release_file(release, encoding),
+            None => Response::not_found(),
+        },
+        ["bonus", id, index] => {
+            let filename = release(id).and_then(|r| r.bonus.get(index.parse::<usize>().ok()?));
+            match filename {
+                Some(filename) => Response::attachment(
+                    "application/pdf",
+                    filename.clone(),
+                    b"%PDF-1.4\n% mock booklet\n".to_vec(),
+                ),
+                None => Response::not_found(),
+            }
+        }
+        [username] => match FANS.iter().find(|(name, _)| name == username) {
+            Some((_, fan_id)) => Response::html(fan_page(*fan_id)),
+            None => Response::not_found(),
+        },
+        _ => Response::not_found(),
+    }
+}
+
+/// Pages through the fixture like Bandcamp does, `count` items at a time. The friend's
+/// collection leaves out hidden items, comes without redownload links and adds the free
+/// releases.
+fn collection_items(library: &Library, base: &str, body: &str) -> Response {
+    let request: serde_json::Value = serde_json::from_str(body).unwrap_or_default();
+    let count = request["count"].as_u64().unwrap_or(100).max(1) as usize;
+    let items: Vec<_> = match request["fan_id"].as_u64() {
+        Some(1) => library
+            .items
+            .iter()
+            .filter(|r| !r.free)
+            .map(|r| r.library_item(base))
+            .collect(),
+        _ => library
+            .items
+            .iter()
+            .filter(|r| !r.hidden && !r.preorder && !r.stream_only)
+            .map(|r| LibraryItem {
+                download_url: String::new(),
+                ..r.library_item(base)
+            })
+            .collect(),
+    };
+    // Real tokens are timestamps, the mock hands out offsets instead
+    let offset = request["older_than_token"]
+        .as_str()
+        .and_then(|token| token.strip_prefix("mock:"))
+        .and_then(|offset| offset.parse().ok())
+        .unwrap_or(0usize)
+        .min(items.len());
+    let end = (offset + count).min(items.len());
+    let more_available = end < items.len();
+    let last_token = more_available.then(|| format!("mock:{end}"));
+
+    Response::json(fixtures::collection_response(
+        &items[offset..end],
+        more_available,
+        last_token.as_deref(),
+    ))
+}
+
+fn wishlist_items(library: &Library, base: &str) -> Response {
+    let items: Vec<_> = library
+        .wishlist
+        .iter()
+        .map(|w| {
+            json!({
+                "tralbum_id": w.id,
+                "tralbum_type": w.item_type,
+                "item_title": w.title,
+                "band_name": w.artist,
+                "item_url": format!("{base}/release/{}", w.id),
+                "price": w.price,
+                "currency": w.currency,
+            })
+        })
+        .collect();
+
+    Response::json(json!({
+        "items": items,
+        "more_available": false,
+        "last_token": null,
+    }))
+}
+
+/// Public album or track page, carrying the `data-tralbum` blob the preview pane reads and,
+/// for free releases, the link to their download page
+fn release_page(release: &Release, base: &str) -> String {
+    let mut tralbum = json!({
+        "album_release_date": release.release_date,
+        "trackinfo": release
+            .tracks
+            .iter()
+            .map(|t| json!({ "title": t.title, "duration": t.duration }))
+            .collect::<Vec<_>>(),
+    });
+    if release.free {
+        tralbum["freeDownloadPage"] = json!(format!("{base}/redownload/{}?sig=free", release.id));
+    }
+    format!(
+        "<html><body><script data-tralbum=\"{}\"></script></body></html>",
+        escape_attribute(&tralbum.to_string()),
+    )
+}
+
+/// Public fan page, carrying the fan ID in its `pagedata` blob
+fn fan_page(fan_id: u64) -> String {
+    let blob = json!({ "fan_data": { "fan_id": fan_id } });
+    format!(
+        "<html><body><div id=\"pagedata\" data-blob=\"{}\"></div></body></html>",
+        escape_attribute(&blob.to_string()),
+    )
+}
+
+/// Download page in its "not ready" state, listing a URL for every encoding
+fn download_page(release: &Release, base: &str) -> String {
+    let downloads: serde_json::Map<_, _> = if release.stream_only {
+        serde_json::Map::new()
+    } else {
+        ENCODINGS
+            .iter()
+            .map(|(encoding, _)| {
+                (
+                    encoding.to_string(),
+                    json!({ "url": format!("{base}/download/{}?enc={encoding}", release.id) }),
+                )
+            })
+            .collect()
+    };
+    let bonus_items: Vec<_> = (release.bonus.iter().enumerate())
+        .map(|(i, filename)| {
+            json!({
+                "title": filename.rsplit_once('.').map_or(filename.as_str(), |(stem, _)| stem),
+                "url": format!("{base}/bonus/{}/{i}", release.id),
+                "filename": filename,
+            })
+        })
+        .collect();
+    let digital_items = if release.stream_only {
+        json!([])
+    } else {
+        json!([{
+            "downloads": downloads,
+            "bonus_items": bonus_items,
+            "release_date": release.release_date,
+        }])
+    };
+    let blob = json!({ "digital_items": digital_items });
+
+    format!(
+        "<html><body><div id=\"pagedata\" data-blob=\"{}\"></div></body></html>",
+        escape_attribute(&blob.to_string()),
+    )
+}
+
+/// Tracks are served as a bare file, albums and packages as a zip like Bandcamp does
+fn release_file(release: &Release, encoding: &str) -> Response {
+    let extension = ENCODINGS
+        .iter()
+        .find(|(e, _)| *e == encoding)
+        .map(|(_, ext)| *ext)
+        .unwrap_or("flac");
+
+    let name = format!("{} - {}", release.artist, release.title);
+    if release.item_type == "t" {
+        return Response::attachment(
+            "audio/mpeg",
+            format!("{name}.{extension}"),
+            noise(release.id),
+        );
+    }
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options =
+        zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    for (number, track) in release.tracks.iter().enumerate() {
+        let name = format!(
+            "{} - {} - {:02} {}.{extension}",
+            release.artist,
+            release.title,
+            number + 1,
+            track.title
+        );
+        if zip.start_file(name, options).is_err()
+            || zip.write_all(&noise(release.id + number as u64)).is_err()
+        {
+            return Response::not_found();
+        }
+    }
+    if zip.start_file("cover.jpg", options).is_err() || zip.write_all(&noise(0)[..4096]).is_err() {
+        return Response::not_found();
+    }
+
+    match zip.finish() {
+        Ok(cursor) => Response::attachment(
+            "application/zip",
+            format!("{name}.zip"),
+            cursor.into_inner(),
+        ),
+        Err(_) => Response::not_found(),
+    }
+}
+
+/// Deterministic filler bytes, so repeated downloads of an item produce the same checksum
+fn noise(seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+    (0..TRACK_BYTES)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+fn escape_attribute(s: &str) -> String {
+    s.replace('&', "&amp;").replace('"', "&quot;")
+}
@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::core::recording::redact_text;
+
+/// Log lines kept around for crash reports, with URL signatures and tokens blanked
+const KEPT_LOG_LINES: usize = 200;
+
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Keeps the lines of `buf`, as written to the log, for [`recent_log_lines`]
+pub fn remember_log(buf: &[u8]) {
+    let mut logs = RECENT_LOGS.lock().unwrap_or_else(|e| e.into_inner());
+    for line in String::from_utf8_lossy(buf).lines() {
+        if logs.len() == KEPT_LOG_LINES {
+            logs.pop_front();
+        }
+        logs.push_back(redact_text(&strip_ansi(line)));
+    }
+}
+
+/// The last lines logged, oldest first and without colors
+pub fn recent_log_lines() -> Vec<String> {
+    RECENT_LOGS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .cloned()
+        .collect()
+}
+
+/// Drops the color codes tracing puts around levels and timestamps
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Control sequences end with the first letter after the `[`
+            chars.find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
@@ -0,0 +1,4 @@
+pub mod logs;
+pub mod spinner;
+pub mod theme;
+pub mod title;
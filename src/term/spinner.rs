@@ -0,0 +1,49 @@
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+const BRAILLE_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const ASCII_FRAMES: &[&str] = &["|", "/", "-", "\\"];
+
+static FRAMES: OnceLock<&'static [&'static str]> = OnceLock::new();
+
+/// Frame set used by every spinner, TUI and CLI alike
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpinnerStyle {
+    /// Braille when the locale is UTF-8, ASCII otherwise
+    #[default]
+    Auto,
+    Braille,
+    Ascii,
+}
+
+impl SpinnerStyle {
+    pub fn frames(self) -> &'static [&'static str] {
+        match self {
+            SpinnerStyle::Auto if locale_is_utf8() => BRAILLE_FRAMES,
+            SpinnerStyle::Auto => ASCII_FRAMES,
+            SpinnerStyle::Braille => BRAILLE_FRAMES,
+            SpinnerStyle::Ascii => ASCII_FRAMES,
+        }
+    }
+}
+
+/// Picks the frame set for the rest of the process, only the first call has an effect
+pub fn set_style(style: SpinnerStyle) {
+    let _ = FRAMES.set(style.frames());
+}
+
+pub fn frames() -> &'static [&'static str] {
+    FRAMES.get_or_init(|| SpinnerStyle::Auto.frames())
+}
+
+/// Same precedence as setlocale: LC_ALL, then LC_CTYPE, then LANG
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_ascii_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
@@ -0,0 +1,10 @@
+use clap::ValueEnum;
+
+/// Colors of the TUI, picked on the command line
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThemeName {
+    #[default]
+    Default,
+    /// Bright colors with bold and inverted status styles, readable without telling red from green
+    HighContrast,
+}
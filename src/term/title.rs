@@ -0,0 +1,70 @@
+use std::io::{self, IsTerminal, Write};
+use std::sync::Mutex;
+
+use crossterm::{execute, terminal::SetTitle};
+
+use crate::core::utils::format_bytes;
+
+// xterm window title stack, supported by most terminals and ignored by the rest
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Shows batch progress in the terminal title while alive and restores the previous title
+/// when dropped. Does nothing when stdout is not a terminal.
+pub struct TitleGuard {
+    enabled: bool,
+    /// Last title written, to skip redundant updates
+    current: Mutex<String>,
+}
+
+impl TitleGuard {
+    pub fn new() -> Self {
+        let enabled = io::stdout().is_terminal();
+        if enabled {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(PUSH_TITLE.as_bytes());
+            let _ = stdout.flush();
+        }
+        Self {
+            enabled,
+            current: Mutex::new(String::new()),
+        }
+    }
+
+    pub fn set_progress(&self, finished: usize, total: usize, bytes_per_sec: f64) {
+        if !self.enabled {
+            return;
+        }
+
+        let title = batch_title(finished, total, bytes_per_sec);
+        let mut current = self.current.lock().unwrap();
+        if *current != title {
+            let _ = execute!(io::stdout(), SetTitle(&title));
+            *current = title;
+        }
+    }
+}
+
+impl Default for TitleGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TitleGuard {
+    fn drop(&mut self) {
+        if self.enabled {
+            let mut stdout = io::stdout();
+            let _ = stdout.write_all(POP_TITLE.as_bytes());
+            let _ = stdout.flush();
+        }
+    }
+}
+
+/// e.g. "bannedcamp: 12/40 (3.20 MB/s)"
+pub fn batch_title(finished: usize, total: usize, bytes_per_sec: f64) -> String {
+    format!(
+        "bannedcamp: {finished}/{total} ({}/s)",
+        format_bytes(bytes_per_sec)
+    )
+}
@@ -1,11 +1,22 @@
-use std::collections::HashSet;
-use std::path::PathBuf;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crossterm::{clipboard::CopyToClipboard, execute};
+use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tracing::debug;
 
 use crate::core::auth::Credentials;
-use crate::core::library::{AudioFormat, LibraryItem};
+use crate::core::download::SkipReason;
+use crate::core::library::{AudioFormat, ItemDetails, LibraryItem, parse_release_date};
+use crate::core::state::{CollectionSnapshot, Manifest, state_dir};
+use crate::core::utils::{available_space, open_in_file_manager};
+use crate::i18n::{Msg, tr, tr_fmt};
 use crate::tui::async_bridge::{AsyncRequest, AsyncResponse};
+use crate::tui::cache::LruCache;
+use crate::tui::view_state::LibraryView;
 use crate::tui::widgets::spinner::Spinner;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -49,6 +60,14 @@ pub enum LibraryMode {
     FormatSelection,
 }
 
+/// Where a single item started with "download now" is at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackgroundDownload {
+    Running,
+    Done,
+    Failed,
+}
+
 /// Which element has focus in the library screen
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LibraryFocus {
@@ -57,8 +76,186 @@ pub enum LibraryFocus {
     SearchBar,
 }
 
-/// Library browser state
+/// Lists the library screen switches between, each browsed with the same search, sort and
+/// selection as the collection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LibraryTab {
+    #[default]
+    Collection,
+    Wishlist,
+    Feed,
+    /// Collection items the output directory's manifest lists, latest download first
+    History,
+}
+
+impl LibraryTab {
+    pub const ALL: [LibraryTab; 4] = [
+        LibraryTab::Collection,
+        LibraryTab::Wishlist,
+        LibraryTab::Feed,
+        LibraryTab::History,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LibraryTab::Collection => tr(Msg::TabCollection),
+            LibraryTab::Wishlist => tr(Msg::TabWishlist),
+            LibraryTab::Feed => tr(Msg::TabFeed),
+            LibraryTab::History => tr(Msg::TabHistory),
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self.index() + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(self) -> Self {
+        Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Whether the tab lists purchases, which can be downloaded
+    pub fn is_owned(self) -> bool {
+        matches!(self, LibraryTab::Collection | LibraryTab::History)
+    }
+}
+
+/// Order of the library list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LibrarySort {
+    /// Order returned by the collection API
+    #[default]
+    Collection,
+    NewestFirst,
+    OldestFirst,
+}
+
+impl LibrarySort {
+    pub fn next(self) -> Self {
+        match self {
+            LibrarySort::Collection => LibrarySort::NewestFirst,
+            LibrarySort::NewestFirst => LibrarySort::OldestFirst,
+            LibrarySort::OldestFirst => LibrarySort::Collection,
+        }
+    }
+
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            LibrarySort::Collection => None,
+            LibrarySort::NewestFirst => Some(tr(Msg::SortNewestFirst)),
+            LibrarySort::OldestFirst => Some(tr(Msg::SortOldestFirst)),
+        }
+    }
+}
+
+/// Field a `field:value` search term is restricted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchField {
+    Artist,
+    Title,
+    Subdomain,
+    Slug,
+    Type,
+    Id,
+}
+
+impl SearchField {
+    const ALL: [SearchField; 6] = [
+        SearchField::Artist,
+        SearchField::Title,
+        SearchField::Subdomain,
+        SearchField::Slug,
+        SearchField::Type,
+        SearchField::Id,
+    ];
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "artist" => Some(SearchField::Artist),
+            "title" => Some(SearchField::Title),
+            "subdomain" => Some(SearchField::Subdomain),
+            "slug" => Some(SearchField::Slug),
+            "type" => Some(SearchField::Type),
+            "id" => Some(SearchField::Id),
+            _ => None,
+        }
+    }
+
+    /// Lowercased value of this field for `item`
+    fn value(self, item: &LibraryItem) -> String {
+        match self {
+            SearchField::Artist => item.artist.to_lowercase(),
+            SearchField::Title => item.title.to_lowercase(),
+            SearchField::Subdomain => item
+                .artist_subdomain
+                .as_deref()
+                .unwrap_or_default()
+                .to_lowercase(),
+            SearchField::Slug => item.slug.as_deref().unwrap_or_default().to_lowercase(),
+            SearchField::Type => format!("{:?}", item.item_type).to_lowercase(),
+            SearchField::Id => item.id.clone(),
+        }
+    }
+}
+
+/// Library search, e.g. `artist:hypothermia type:track winter`.
+/// Every `field:value` term has to match its field, the remaining words match any field.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    terms: Vec<(SearchField, String)>,
+    text: String,
+}
+
+impl SearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut terms = Vec::new();
+        let mut words = Vec::new();
+
+        let query = query.to_lowercase();
+        for word in query.split_whitespace() {
+            match word
+                .split_once(':')
+                .and_then(|(field, value)| Some((SearchField::parse(field)?, value)))
+            {
+                Some((field, value)) if !value.is_empty() => terms.push((field, value.to_string())),
+                // A prefix on its own is still being typed, it shouldn't hide everything
+                Some(_) => {}
+                None => words.push(word),
+            }
+        }
+
+        Self {
+            terms,
+            text: words.join(" "),
+        }
+    }
+
+    pub fn matches(&self, item: &LibraryItem) -> bool {
+        self.terms
+            .iter()
+            .all(|(field, value)| field.value(item).contains(value.as_str()))
+            && (self.text.is_empty()
+                || SearchField::ALL
+                    .iter()
+                    .any(|field| field.value(item).contains(&self.text)))
+    }
+}
+
+/// Item details kept around for the preview pane
+const DETAILS_CACHE_SIZE: usize = 256;
+
+/// Rows around the highlighted one whose details are fetched ahead of time
+const PREFETCH_BEHIND: usize = 10;
+const PREFETCH_AHEAD: usize = 30;
+
+/// Library browser state. The active tab's list lives in [`App::library_state`], the other
+/// tabs keep theirs until they are switched to.
 pub struct LibraryState {
+    pub tab: LibraryTab,
     pub items: Vec<LibraryItem>,
     /// Indices of items matching current search (empty = show all)
     pub filtered_indices: Vec<usize>,
@@ -76,6 +273,29 @@ pub struct LibraryState {
     pub selected_format: usize,
     /// Current search query
     pub search_query: String,
+    /// Whether items hidden in the Bandcamp collection are listed
+    pub show_hidden: bool,
+    pub sort: LibrarySort,
+    /// Whether the details pane next to the list is open
+    pub show_preview: bool,
+    /// Preview details by item ID
+    pub details: LruCache<String, ItemDetails>,
+    /// Why details couldn't be fetched, kept while the row stays near the highlighted one so
+    /// it is tried again once scrolled back to
+    pub details_errors: HashMap<String, String>,
+    /// Highlighted item and number of listed rows the last prefetch was for
+    pub prefetched_for: Option<(String, usize)>,
+    /// Items whose details have been requested but not arrived yet
+    pub details_pending: HashSet<String>,
+    /// Remembered item to highlight once its page of the collection arrives, with the rows
+    /// that were listed above it
+    pub pending_highlight: Option<(String, usize)>,
+    /// Items downloaded on their own in the background, by item ID
+    pub background_downloads: HashMap<String, BackgroundDownload>,
+    /// IDs of items the output directory's manifest lists as downloaded
+    pub downloaded: HashSet<String>,
+    /// Browsing a saved collection without credentials, nothing can be downloaded or hidden
+    pub read_only: bool,
     /// Error message to display
     pub error: Option<String>,
 }
@@ -83,6 +303,7 @@ pub struct LibraryState {
 impl Default for LibraryState {
     fn default() -> Self {
         Self {
+            tab: LibraryTab::default(),
             items: Vec::new(),
             filtered_indices: Vec::new(),
             selected: 0,
@@ -94,38 +315,111 @@ impl Default for LibraryState {
             selected_items: HashSet::new(),
             selected_format: 0, // FLAC by default
             search_query: String::new(),
+            show_hidden: false,
+            sort: LibrarySort::default(),
+            show_preview: false,
+            details: LruCache::new(DETAILS_CACHE_SIZE),
+            details_errors: HashMap::new(),
+            prefetched_for: None,
+            details_pending: HashSet::new(),
+            pending_highlight: None,
+            background_downloads: HashMap::new(),
+            downloaded: HashSet::new(),
+            read_only: false,
             error: None,
         }
     }
 }
 
 impl LibraryState {
+    fn for_tab(tab: LibraryTab) -> Self {
+        Self {
+            tab,
+            ..Default::default()
+        }
+    }
+
+    /// Takes over what every tab shares from the tab that was active until now: the popup,
+    /// the preview and what is known about downloads
+    fn share(&mut self, from: &mut LibraryState) {
+        std::mem::swap(&mut self.spinner, &mut from.spinner);
+        std::mem::swap(&mut self.mode, &mut from.mode);
+        std::mem::swap(&mut self.selected_format, &mut from.selected_format);
+        std::mem::swap(&mut self.show_preview, &mut from.show_preview);
+        std::mem::swap(&mut self.details, &mut from.details);
+        std::mem::swap(&mut self.details_errors, &mut from.details_errors);
+        std::mem::swap(&mut self.details_pending, &mut from.details_pending);
+        std::mem::swap(
+            &mut self.background_downloads,
+            &mut from.background_downloads,
+        );
+        std::mem::swap(&mut self.downloaded, &mut from.downloaded);
+        std::mem::swap(&mut self.read_only, &mut from.read_only);
+    }
+
+    /// Whether the listed items can be downloaded
+    pub fn can_download(&self) -> bool {
+        !self.read_only && self.tab.is_owned()
+    }
+
     pub fn set_items(&mut self, items: Vec<LibraryItem>) {
         self.items = items;
         self.update_filter();
     }
 
-    fn item_matches_query(item: &LibraryItem, query: &str) -> bool {
-        item.artist.to_lowercase().contains(query)
-            || item.title.to_lowercase().contains(query)
+    fn item_is_visible(&self, item: &LibraryItem, query: &SearchQuery) -> bool {
+        (self.show_hidden || !item.is_hidden) && query.matches(item)
+    }
+
+    /// Whether `filtered_indices` decides what is visible, instead of listing every item
+    pub fn is_filtered(&self) -> bool {
+        !self.search_query.is_empty() || !self.show_hidden || self.sort != LibrarySort::Collection
+    }
+
+    /// Number of items hidden in the Bandcamp collection
+    pub fn hidden_count(&self) -> usize {
+        self.items.iter().filter(|item| item.is_hidden).count()
     }
 
     pub fn append_items(&mut self, new_items: Vec<LibraryItem>) {
         let old_len = self.items.len();
         self.items.extend(new_items);
 
-        if !self.search_query.is_empty() {
-            let query = self.search_query.to_lowercase();
+        if self.sort != LibrarySort::Collection {
+            // New items can land anywhere in a sorted list
+            self.update_filter();
+        } else if self.is_filtered() {
+            let query = SearchQuery::parse(&self.search_query);
             for (i, item) in self.items[old_len..].iter().enumerate() {
-                if Self::item_matches_query(item, &query) {
+                if self.item_is_visible(item, &query) {
                     self.filtered_indices.push(old_len + i);
                 }
             }
         }
     }
 
+    /// Moves the highlight to the remembered item if it is listed by now. Once the whole
+    /// collection is `loaded` without it the item is forgotten.
+    pub fn restore_highlight(&mut self, loaded: bool) {
+        let Some((id, rows_above)) = &self.pending_highlight else {
+            return;
+        };
+        let position = (0..self.visible_count()).find(|&i| {
+            self.visible_item_at(i)
+                .is_some_and(|(_, item)| &item.id == id)
+        });
+
+        if let Some(position) = position {
+            self.selected = position;
+            self.scroll_offset = position.saturating_sub(*rows_above);
+            self.pending_highlight = None;
+        } else if loaded {
+            self.pending_highlight = None;
+        }
+    }
+
     pub fn visible_item_at(&self, index: usize) -> Option<(usize, &LibraryItem)> {
-        if self.search_query.is_empty() {
+        if !self.is_filtered() {
             self.items.get(index).map(|item| (index, item))
         } else {
             self.filtered_indices
@@ -139,7 +433,7 @@ impl LibraryState {
         skip: usize,
         take: usize,
     ) -> Box<dyn Iterator<Item = (usize, usize, &LibraryItem)> + '_> {
-        if self.search_query.is_empty() {
+        if !self.is_filtered() {
             Box::new(
                 self.items
                     .iter()
@@ -164,7 +458,7 @@ impl LibraryState {
 
     /// Get the number of visible items
     pub fn visible_count(&self) -> usize {
-        if self.search_query.is_empty() {
+        if !self.is_filtered() {
             self.items.len()
         } else {
             self.filtered_indices.len()
@@ -178,17 +472,30 @@ impl LibraryState {
 
     /// Update filtered indices based on search query
     pub fn update_filter(&mut self) {
-        if self.search_query.is_empty() {
+        if !self.is_filtered() {
             self.filtered_indices.clear();
         } else {
-            let query = self.search_query.to_lowercase();
+            let query = SearchQuery::parse(&self.search_query);
             self.filtered_indices = self
                 .items
                 .iter()
                 .enumerate()
-                .filter(|(_, item)| Self::item_matches_query(item, &query))
+                .filter(|(_, item)| self.item_is_visible(item, &query))
                 .map(|(i, _)| i)
                 .collect();
+
+            // Stable sort, items without a date keep their collection order at the end
+            let items = &self.items;
+            match self.sort {
+                LibrarySort::Collection => {}
+                LibrarySort::NewestFirst => self
+                    .filtered_indices
+                    .sort_by_key(|&i| std::cmp::Reverse(items[i].purchase.purchased_at)),
+                LibrarySort::OldestFirst => self.filtered_indices.sort_by_key(|&i| {
+                    let date = items[i].purchase.purchased_at;
+                    (date.is_none(), date)
+                }),
+            }
         }
         // Reset selection if out of bounds
         let count = self.visible_count();
@@ -207,12 +514,66 @@ pub enum DownloadItemStatus {
     #[default]
     Pending,
     FetchingUrl,
+    /// Bandcamp is still encoding the requested format
+    WaitingForEncoding {
+        attempt: u32,
+        elapsed_secs: u64,
+    },
     Downloading,
-    Extracting,
+    /// `entry` is the archive entry being written, empty until the first one starts
+    Extracting {
+        entry: String,
+        done: usize,
+        total: usize,
+    },
     Done(Result<PathBuf, String>),
+    Skipped(SkipReason),
     Cancelled,
 }
 
+impl DownloadItemStatus {
+    /// Section of the download screen this status is listed under
+    pub fn group(&self) -> DownloadGroup {
+        match self {
+            DownloadItemStatus::Pending | DownloadItemStatus::Cancelled => DownloadGroup::Queued,
+            DownloadItemStatus::Done(Ok(_)) => DownloadGroup::Completed,
+            DownloadItemStatus::Done(Err(_)) => DownloadGroup::Failed,
+            DownloadItemStatus::Skipped(_) => DownloadGroup::Skipped,
+            _ => DownloadGroup::Active,
+        }
+    }
+}
+
+/// Sections of the download screen, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DownloadGroup {
+    Active,
+    Queued,
+    Completed,
+    Failed,
+    Skipped,
+}
+
+impl DownloadGroup {
+    pub const ALL: [DownloadGroup; 5] = [
+        DownloadGroup::Active,
+        DownloadGroup::Queued,
+        DownloadGroup::Completed,
+        DownloadGroup::Failed,
+        DownloadGroup::Skipped,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DownloadGroup::Active => tr(Msg::GroupActive),
+            DownloadGroup::Queued => tr(Msg::GroupQueued),
+            DownloadGroup::Completed => tr(Msg::GroupCompleted),
+            DownloadGroup::Failed => tr(Msg::GroupFailed),
+            DownloadGroup::Skipped => tr(Msg::GroupSkipped),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DownloadItem {
     pub item: LibraryItem,
@@ -281,6 +642,51 @@ pub struct DownloadState {
     pub slots: [DownloadSlot; MAX_CONCURRENT_DOWNLOADS],
     /// Spinner for loading states
     pub spinner: Spinner,
+    /// Sections folded down to their header
+    pub collapsed: HashSet<DownloadGroup>,
+    /// Only failed items are listed, with their full error
+    pub failures_only: bool,
+    /// Lines scrolled down in the failures view
+    pub failure_scroll: u16,
+    /// Free space and write rate of the output filesystem
+    pub disk: DiskStats,
+}
+
+/// How often the output filesystem is polled for free space
+const DISK_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Free space on the output filesystem, sampled while a batch runs
+#[derive(Default)]
+pub struct DiskStats {
+    /// Bytes still available, None until the first sample or if statvfs failed
+    pub free_bytes: Option<u64>,
+    /// Rate the free space shrinks at, which also counts extraction and other writers
+    pub write_bytes_per_sec: f64,
+    /// Previous sample the write rate is measured against
+    last_sample: Option<(Instant, u64)>,
+}
+
+impl DiskStats {
+    pub fn sample(&mut self, path: &Path) {
+        let now = Instant::now();
+        if let Some((last_time, _)) = self.last_sample
+            && now.duration_since(last_time) < DISK_SAMPLE_INTERVAL
+        {
+            return;
+        }
+
+        let Ok(free) = available_space(path) else {
+            self.free_bytes = None;
+            return;
+        };
+        if let Some((last_time, last_free)) = self.last_sample {
+            // Removed temp archives free space again, that isn't a negative write rate
+            let written = last_free.saturating_sub(free);
+            self.write_bytes_per_sec = written as f64 / now.duration_since(last_time).as_secs_f64();
+        }
+        self.free_bytes = Some(free);
+        self.last_sample = Some((now, free));
+    }
 }
 
 impl DownloadState {
@@ -302,13 +708,41 @@ impl DownloadState {
             .count()
     }
 
+    pub fn skipped_count(&self) -> usize {
+        self.queue
+            .iter()
+            .filter(|i| matches!(i.status, DownloadItemStatus::Skipped(_)))
+            .count()
+    }
+
     pub fn done_count(&self) -> usize {
         self.queue
             .iter()
-            .filter(|i| matches!(i.status, DownloadItemStatus::Done(_)))
+            .filter(|i| {
+                matches!(
+                    i.status,
+                    DownloadItemStatus::Done(_) | DownloadItemStatus::Skipped(_)
+                )
+            })
             .count()
     }
 
+    /// Items listed under `group`, in queue order
+    pub fn group_items(&self, group: DownloadGroup) -> impl Iterator<Item = &DownloadItem> {
+        self.queue.iter().filter(move |i| i.status.group() == group)
+    }
+
+    /// Whether the batch downloaded every item it could, nothing failed or was cancelled
+    pub fn all_succeeded(&self) -> bool {
+        self.success_count() > 0
+            && self.success_count() + self.skipped_count() == self.total_items()
+    }
+
+    /// Combined speed of every active slot
+    pub fn speed_bytes_per_sec(&self) -> f64 {
+        self.slots.iter().map(|s| s.speed_bytes_per_sec).sum()
+    }
+
     pub fn find_item_mut(&mut self, item_id: &str) -> Option<&mut DownloadItem> {
         self.queue.iter_mut().find(|i| i.item.id == item_id)
     }
@@ -340,23 +774,50 @@ impl DownloadState {
     }
 }
 
+/// Full text of an error that is shown truncated elsewhere, opened with `e`
+#[derive(Debug, Default)]
+pub struct ErrorDetail {
+    pub text: String,
+    /// Lines scrolled down
+    pub scroll: u16,
+    /// Set once the text has been sent to the clipboard
+    pub copied: bool,
+}
+
 pub struct App {
     pub screen: Screen,
     pub should_quit: bool,
 
+    /// Popup over the current screen, takes every key while open
+    pub error_detail: Option<ErrorDetail>,
+
     // Authentication
     pub credentials: Option<Credentials>,
 
     // Screen states
     pub login_state: LoginState,
     pub library_state: LibraryState,
+    /// Lists of the library tabs that aren't active, kept from when they were last open
+    tabs: HashMap<LibraryTab, LibraryState>,
     pub download_state: DownloadState,
 
     // Async communication
     pub async_tx: mpsc::Sender<AsyncRequest>,
+    /// User actions the full request channel couldn't take yet, sent in order on the next
+    /// tick. Prefetches are only sent while this is empty, they can be dropped.
+    unsent: VecDeque<AsyncRequest>,
 
     // Download settings
     pub output_dir: PathBuf,
+    /// Format quick downloads use and the format popup starts on
+    pub default_format: AudioFormat,
+    /// `--custom-format` template, None uses the default names
+    pub name_format: Option<String>,
+
+    /// File the library view is remembered in, None keeps it for this run only
+    pub view_path: Option<PathBuf>,
+    /// Library view as last written, so it is only saved again when it changed
+    saved_view: LibraryView,
 }
 
 impl App {
@@ -364,12 +825,100 @@ impl App {
         Self {
             screen: Screen::Login,
             should_quit: false,
+            error_detail: None,
             credentials: None,
             login_state: LoginState::default(),
             library_state: LibraryState::default(),
+            tabs: HashMap::new(),
             download_state: DownloadState::default(),
             async_tx,
+            unsent: VecDeque::new(),
             output_dir: PathBuf::from("."),
+            default_format: AudioFormat::Flac,
+            name_format: None,
+            view_path: None,
+            saved_view: LibraryView::default(),
+        }
+    }
+
+    /// Makes `format` the one quick downloads use and the format popup starts on
+    pub fn set_default_format(&mut self, format: AudioFormat) {
+        self.default_format = format;
+        self.library_state.selected_format = AudioFormat::ALL
+            .iter()
+            .position(|f| *f == format)
+            .unwrap_or(0);
+    }
+
+    /// Marks the items the output directory's manifest lists as downloaded
+    pub fn load_manifest(&mut self) {
+        match Manifest::load(&self.output_dir) {
+            Ok(manifest) => {
+                self.library_state.downloaded = manifest.ids().map(str::to_string).collect();
+            }
+            Err(e) => debug!("Ignoring unreadable manifest: {e}"),
+        }
+    }
+
+    /// Lists the collection last saved in the output directory, skipping the login. Nothing
+    /// that needs the owner's account is offered.
+    pub fn open_read_only(&mut self) {
+        self.library_state.read_only = true;
+        self.screen = Screen::Library;
+        match CollectionSnapshot::load(&self.output_dir) {
+            Ok(snapshot) if !snapshot.items.is_empty() => {
+                self.library_state.append_items(snapshot.items);
+                self.library_state.restore_highlight(true);
+            }
+            Ok(_) => {
+                let dir = self.output_dir.display().to_string();
+                self.library_state.error = Some(tr_fmt(Msg::NoSnapshot, &[&dir]));
+            }
+            Err(e) => self.library_state.error = Some(e.to_string()),
+        }
+    }
+
+    /// Picks up the library view an earlier run left in `path` and keeps it updated there
+    pub fn restore_library_view(&mut self, path: PathBuf) {
+        let view = LibraryView::load(&path);
+        view.clone().apply(&mut self.library_state);
+        self.saved_view = view;
+        self.view_path = Some(path);
+    }
+
+    /// Writes the library view if it changed since the last write. Nothing is written before
+    /// login, the collection isn't there yet and the view would just be the initial one.
+    pub fn save_library_view(&mut self) {
+        let Some(path) = &self.view_path else {
+            return;
+        };
+        if self.screen == Screen::Login {
+            return;
+        }
+
+        let mut view = LibraryView::capture(self.collection());
+        view.show_preview = self.library_state.show_preview;
+        if view != self.saved_view {
+            view.save(path);
+            self.saved_view = view;
+        }
+    }
+
+    /// The collection's list, whether its tab is the active one or not
+    pub fn collection(&self) -> &LibraryState {
+        match self.tabs.get(&LibraryTab::Collection) {
+            Some(collection) => collection,
+            None => &self.library_state,
+        }
+    }
+
+    fn tab_mut(&mut self, tab: LibraryTab) -> &mut LibraryState {
+        if self.library_state.tab == tab {
+            &mut self.library_state
+        } else {
+            self.tabs
+                .entry(tab)
+                .or_insert_with(|| LibraryState::for_tab(tab))
         }
     }
 
@@ -377,17 +926,47 @@ impl App {
         self.should_quit = true;
     }
 
+    /// Sends a request for something the user did. Never dropped: when the channel is
+    /// full it waits for the next tick behind the others still waiting.
+    fn request(&mut self, request: AsyncRequest) {
+        self.unsent.push_back(request);
+        self.send_unsent();
+    }
+
+    fn send_unsent(&mut self) {
+        while let Some(request) = self.unsent.pop_front() {
+            match self.async_tx.try_send(request) {
+                Ok(()) => {}
+                Err(TrySendError::Full(request)) => {
+                    self.unsent.push_front(request);
+                    return;
+                }
+                Err(TrySendError::Closed(_)) => {
+                    debug!("Async bridge is gone, dropping requests");
+                    self.unsent.clear();
+                    return;
+                }
+            }
+        }
+    }
+
     // Spinner progressing
     pub fn tick(&mut self) {
+        self.send_unsent();
         if self.login_state.loading {
             self.login_state.spinner.tick();
         }
-        if self.library_state.loading {
+        if self.library_state.loading || !self.library_state.details_pending.is_empty() {
             self.library_state.spinner.tick();
         }
         if self.download_state.is_active {
             self.download_state.spinner.tick();
+            self.download_state.disk.sample(&self.output_dir);
+        }
+        if self.screen == Screen::Library {
+            self.library_prefetch_details();
         }
+        self.save_library_view();
     }
 
     /// Handle async response from the bridge
@@ -401,7 +980,7 @@ impl App {
                         self.login_state.error = None;
                         self.screen = Screen::Library;
                         self.library_state.loading = true;
-                        let _ = self.async_tx.try_send(AsyncRequest::FetchCollection);
+                        self.request(AsyncRequest::FetchCollection);
                     }
                     Err(e) => {
                         self.login_state.error = Some(e);
@@ -409,15 +988,34 @@ impl App {
                 }
             }
             AsyncResponse::LibraryPageFetched { items, done } => {
-                self.library_state.error = None;
-                self.library_state.append_items(items);
+                let collection = self.tab_mut(LibraryTab::Collection);
+                collection.error = None;
+                collection.append_items(items);
+                collection.restore_highlight(done);
                 if done {
-                    self.library_state.loading = false;
+                    collection.loading = false;
+                    // Only into output directories bannedcamp already keeps state in, browsing
+                    // shouldn't leave a folder behind wherever the TUI was started
+                    if state_dir(&self.output_dir).is_dir() {
+                        let snapshot = CollectionSnapshot::new(&self.collection().items);
+                        if let Err(e) = snapshot.save(&self.output_dir) {
+                            debug!("Failed to save the collection snapshot: {e}");
+                        }
+                    }
                 }
             }
             AsyncResponse::CollectionFetchError(e) => {
-                self.library_state.loading = false;
-                self.library_state.error = Some(e);
+                let collection = self.tab_mut(LibraryTab::Collection);
+                collection.loading = false;
+                collection.error = Some(e);
+            }
+            AsyncResponse::WishlistFetched(result) => {
+                let wishlist = self.tab_mut(LibraryTab::Wishlist);
+                wishlist.loading = false;
+                match result {
+                    Ok(items) => wishlist.set_items(items),
+                    Err(e) => wishlist.error = Some(e),
+                }
             }
             AsyncResponse::BatchDownloadStarted { .. } => {
                 self.download_state.is_active = true;
@@ -474,10 +1072,17 @@ impl App {
                 result,
             } => {
                 self.download_state.clear_slot(&item_id);
+                self.library_state.downloaded.insert(item_id.clone());
                 if let Some(di) = self.download_state.find_item_mut(&item_id) {
                     di.status = DownloadItemStatus::Done(result);
                 }
             }
+            AsyncResponse::ItemDownloadSkipped { item_id, reason } => {
+                self.download_state.clear_slot(&item_id);
+                if let Some(di) = self.download_state.find_item_mut(&item_id) {
+                    di.status = DownloadItemStatus::Skipped(reason);
+                }
+            }
             AsyncResponse::BatchDownloadComplete => {
                 self.download_state.is_active = false;
                 self.download_state.clear_all_slots();
@@ -485,12 +1090,125 @@ impl App {
             AsyncResponse::DownloadsCancelled => {
                 self.download_state.is_active = false;
                 for di in &mut self.download_state.queue {
-                    if !matches!(di.status, DownloadItemStatus::Done(_)) {
+                    if !matches!(
+                        di.status,
+                        DownloadItemStatus::Done(_) | DownloadItemStatus::Skipped(_)
+                    ) {
                         di.status = DownloadItemStatus::Cancelled;
                     }
                 }
                 self.download_state.clear_all_slots();
             }
+            AsyncResponse::ItemDetailsFetched { item_id, result } => {
+                self.library_state.details_pending.remove(&item_id);
+                match result {
+                    Ok(details) => {
+                        let release_date =
+                            details.release_date.as_deref().and_then(parse_release_date);
+                        let lists =
+                            std::iter::once(&mut self.library_state).chain(self.tabs.values_mut());
+                        for item in lists.flat_map(|list| list.items.iter_mut()) {
+                            if item.id == item_id {
+                                item.release_date = release_date;
+                            }
+                        }
+                        self.library_state.details_errors.remove(&item_id);
+                        self.library_state.details.insert(item_id, details);
+                    }
+                    Err(e) => {
+                        self.library_state.details_errors.insert(item_id, e);
+                    }
+                }
+            }
+            AsyncResponse::ItemHiddenChanged { item_id, result } => {
+                let collection = self.tab_mut(LibraryTab::Collection);
+                match result {
+                    Ok(hidden) => {
+                        if let Some(item) = collection.items.iter_mut().find(|i| i.id == item_id) {
+                            item.is_hidden = hidden;
+                        }
+                        // Items that just disappeared from the list shouldn't be downloaded either
+                        if hidden && !collection.show_hidden {
+                            collection.selected_items.remove(&item_id);
+                        }
+                        collection.update_filter();
+                    }
+                    Err(e) => collection.error = Some(e),
+                }
+            }
+            AsyncResponse::BackgroundDownloadFinished { item_id, result } => {
+                let status = match result {
+                    Ok(_) => {
+                        self.library_state.downloaded.insert(item_id.clone());
+                        BackgroundDownload::Done
+                    }
+                    Err(e) => {
+                        let title = self
+                            .library_state
+                            .items
+                            .iter()
+                            .chain(&self.collection().items)
+                            .find(|i| i.id == item_id)
+                            .map(|i| i.title.as_str())
+                            .unwrap_or(&item_id);
+                        self.library_state.error = Some(format!("{title}: {e}"));
+                        BackgroundDownload::Failed
+                    }
+                };
+                self.library_state
+                    .background_downloads
+                    .insert(item_id, status);
+            }
+        }
+    }
+
+    /// Opens the full error of the current screen, if it has one
+    pub fn show_error_detail(&mut self) {
+        let text = match self.screen {
+            Screen::Login => self.login_state.error.clone(),
+            Screen::Library => self.library_state.error.clone(),
+            Screen::Download => {
+                let failures: Vec<String> = self
+                    .download_state
+                    .group_items(DownloadGroup::Failed)
+                    .filter_map(|di| match &di.status {
+                        DownloadItemStatus::Done(Err(e)) => {
+                            Some(format!("{} - {}: {e}", di.item.artist, di.item.title))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                (!failures.is_empty()).then(|| failures.join("\n\n"))
+            }
+        };
+
+        self.error_detail = text.map(|text| ErrorDetail {
+            text,
+            ..Default::default()
+        });
+    }
+
+    pub fn close_error_detail(&mut self) {
+        self.error_detail = None;
+    }
+
+    pub fn error_detail_scroll_down(&mut self) {
+        if let Some(detail) = &mut self.error_detail {
+            detail.scroll = detail.scroll.saturating_add(1);
+        }
+    }
+
+    pub fn error_detail_scroll_up(&mut self) {
+        if let Some(detail) = &mut self.error_detail {
+            detail.scroll = detail.scroll.saturating_sub(1);
+        }
+    }
+
+    /// Copies the error through the terminal (OSC 52), which also works over SSH
+    pub fn copy_error_detail(&mut self) {
+        if let Some(detail) = &mut self.error_detail {
+            let copy = CopyToClipboard::to_clipboard_from(detail.text.as_bytes());
+            detail.copied = execute!(std::io::stdout(), copy).is_ok();
         }
     }
 
@@ -529,17 +1247,18 @@ impl App {
 
     pub fn login_submit(&mut self) {
         if self.login_state.cookie_input.is_empty() {
-            self.login_state.error = Some("Please enter a cookie".to_string());
+            self.login_state.error = Some(tr(Msg::EnterCookie).to_string());
             return;
         }
         self.login_state.loading = true;
         self.login_state.error = None;
         let cookie = self.login_state.cookie_input.clone();
-        let _ = self.async_tx.try_send(AsyncRequest::ValidateCookie(cookie));
+        self.request(AsyncRequest::ValidateCookie(cookie));
     }
 
     // Library screen actions - Browse mode
     pub fn library_move_up(&mut self) {
+        self.library_state.pending_highlight = None;
         if self.library_state.selected > 0 {
             self.library_state.selected -= 1;
             if self.library_state.selected < self.library_state.scroll_offset {
@@ -549,6 +1268,7 @@ impl App {
     }
 
     pub fn library_move_down(&mut self) {
+        self.library_state.pending_highlight = None;
         let max = self.library_state.visible_count().saturating_sub(1);
         if self.library_state.selected < max {
             self.library_state.selected += 1;
@@ -569,7 +1289,7 @@ impl App {
 
     /// Select all visible items (respects current filter)
     pub fn library_select_all(&mut self) {
-        if self.library_state.search_query.is_empty() {
+        if !self.library_state.is_filtered() {
             self.library_state
                 .selected_items
                 .extend(self.library_state.items.iter().map(|item| item.id.clone()));
@@ -618,6 +1338,186 @@ impl App {
         self.library_state.focus = LibraryFocus::List;
     }
 
+    /// Hide the highlighted item on Bandcamp, or unhide it if it is hidden already
+    pub fn library_toggle_hidden(&mut self) {
+        if self.library_state.read_only || self.library_state.tab != LibraryTab::Collection {
+            return;
+        }
+        if let Some(item) = self.library_state.selected_item() {
+            let request = AsyncRequest::SetItemHidden {
+                item: Box::new(item.clone()),
+                hidden: !item.is_hidden,
+            };
+            self.request(request);
+        }
+    }
+
+    /// Download the highlighted item in the default format in the background, leaving the
+    /// selection and the library screen as they are
+    pub fn library_download_highlighted(&mut self) {
+        if !self.library_state.can_download() {
+            return;
+        }
+        let Some(item) = self.library_state.selected_item().cloned() else {
+            return;
+        };
+        let downloads = &mut self.library_state.background_downloads;
+        if downloads.get(&item.id) == Some(&BackgroundDownload::Running) {
+            return;
+        }
+        downloads.insert(item.id.clone(), BackgroundDownload::Running);
+
+        self.request(AsyncRequest::DownloadNow {
+            item: Box::new(item),
+            format: self.default_format,
+            output_dir: self.output_dir.clone(),
+            name_format: self.name_format.clone(),
+        });
+    }
+
+    /// Makes `tab` the listed one. A tab opened for the first time starts loading its items,
+    /// the history is read again every time as downloads keep adding to it.
+    pub fn library_switch_tab(&mut self, tab: LibraryTab) {
+        if tab == self.library_state.tab || self.library_state.mode != LibraryMode::Browse {
+            return;
+        }
+        let opened_before = self.tabs.contains_key(&tab);
+        let mut next = self
+            .tabs
+            .remove(&tab)
+            .unwrap_or_else(|| LibraryState::for_tab(tab));
+        next.share(&mut self.library_state);
+        let previous = std::mem::replace(&mut self.library_state, next);
+        self.tabs.insert(previous.tab, previous);
+
+        match tab {
+            LibraryTab::Wishlist if !opened_before => {
+                if self.credentials.is_none() {
+                    self.library_state.error = Some(tr(Msg::NotLoggedIn).to_string());
+                } else {
+                    self.request(AsyncRequest::FetchWishlist);
+                    self.library_state.loading = true;
+                }
+            }
+            LibraryTab::History => self.library_load_history(),
+            _ => {}
+        }
+    }
+
+    pub fn library_next_tab(&mut self) {
+        self.library_switch_tab(self.library_state.tab.next());
+    }
+
+    pub fn library_previous_tab(&mut self) {
+        self.library_switch_tab(self.library_state.tab.previous());
+    }
+
+    fn library_load_history(&mut self) {
+        let manifest = Manifest::load(&self.output_dir)
+            .inspect_err(|e| debug!("Ignoring unreadable manifest: {e}"))
+            .unwrap_or_default();
+        let mut downloads: Vec<_> = self
+            .collection()
+            .items
+            .iter()
+            .filter_map(|item| Some((manifest.get(item)?.downloaded_at, item.clone())))
+            .collect();
+        downloads.sort_by_key(|(downloaded_at, _)| std::cmp::Reverse(*downloaded_at));
+        self.library_state
+            .set_items(downloads.into_iter().map(|(_, item)| item).collect());
+    }
+
+    /// Cycle through the list orders
+    pub fn library_cycle_sort(&mut self) {
+        self.library_state.sort = self.library_state.sort.next();
+        self.library_state.update_filter();
+    }
+
+    /// Toggle whether hidden items are listed
+    pub fn library_toggle_show_hidden(&mut self) {
+        self.library_state.show_hidden = !self.library_state.show_hidden;
+        if !self.library_state.show_hidden {
+            let items = &self.library_state.items;
+            self.library_state
+                .selected_items
+                .retain(|id| items.iter().any(|item| &item.id == id && !item.is_hidden));
+        }
+        self.library_state.update_filter();
+    }
+
+    pub fn library_toggle_preview(&mut self) {
+        self.library_state.show_preview = !self.library_state.show_preview;
+        self.library_state.prefetched_for = None;
+        self.library_prefetch_details();
+    }
+
+    /// Requests details for the rows around the highlighted one while the preview is open,
+    /// so scrolling doesn't wait on the network. Does nothing until the highlight or the
+    /// listed rows change.
+    ///
+    /// Artwork is left out on purpose: the preview is text only, a thumbnail couldn't be
+    /// drawn without a terminal graphics protocol.
+    pub fn library_prefetch_details(&mut self) {
+        let state = &mut self.library_state;
+        if !state.show_preview || !self.unsent.is_empty() {
+            return;
+        }
+        let key = (
+            state
+                .selected_item()
+                .map(|item| item.id.clone())
+                .unwrap_or_default(),
+            state.visible_count(),
+        );
+        if state.prefetched_for.as_ref() == Some(&key) {
+            return;
+        }
+        state.prefetched_for = Some(key);
+
+        let skip = state.selected.saturating_sub(PREFETCH_BEHIND);
+        let window: Vec<LibraryItem> = state
+            .visible_items_range(skip, PREFETCH_BEHIND + PREFETCH_AHEAD)
+            .map(|(_, _, item)| item.clone())
+            .collect();
+        state
+            .details_errors
+            .retain(|id, _| window.iter().any(|item| &item.id == id));
+
+        let mut missing = Vec::new();
+        for item in window {
+            // Looking rows up keeps them from being evicted while on screen
+            if state.details.get(&item.id).is_some()
+                || state.details_pending.contains(&item.id)
+                || state.details_errors.contains_key(&item.id)
+            {
+                continue;
+            }
+            if item.item_url.is_some() {
+                missing.push(item);
+            }
+        }
+        if missing.is_empty() {
+            return;
+        }
+
+        state
+            .details_pending
+            .extend(missing.iter().map(|item| item.id.clone()));
+        if let Err(e) = self
+            .async_tx
+            .try_send(AsyncRequest::PrefetchDetails(missing))
+        {
+            // Channel full, try again on the next tick
+            let AsyncRequest::PrefetchDetails(missing) = e.into_inner() else {
+                return;
+            };
+            for item in missing {
+                state.details_pending.remove(&item.id);
+            }
+            state.prefetched_for = None;
+        }
+    }
+
     /// Clear all selections
     pub fn library_clear_selection(&mut self) {
         self.library_state.selected_items.clear();
@@ -625,7 +1525,7 @@ impl App {
 
     /// Show format selection dialog (called when user presses 'd' to download)
     pub fn library_show_format_selection(&mut self) {
-        if self.library_state.selected_items.is_empty() {
+        if !self.library_state.can_download() || self.library_state.selected_items.is_empty() {
             return;
         }
         self.library_state.mode = LibraryMode::FormatSelection;
@@ -652,7 +1552,18 @@ impl App {
     /// Confirm format selection and start download
     pub fn format_confirm(&mut self) {
         let format = AudioFormat::ALL[self.library_state.selected_format];
+        self.start_download(format);
+    }
+
+    /// Download the selection right away in the default format, without the format popup
+    pub fn library_quick_download(&mut self) {
+        if !self.library_state.can_download() || self.library_state.selected_items.is_empty() {
+            return;
+        }
+        self.start_download(self.default_format);
+    }
 
+    fn start_download(&mut self, format: AudioFormat) {
         let items: Vec<LibraryItem> = self
             .library_state
             .items
@@ -667,10 +1578,11 @@ impl App {
         }
 
         // Start the download
-        let _ = self.async_tx.try_send(AsyncRequest::StartBatchDownload {
+        self.request(AsyncRequest::StartBatchDownload {
             items: items.clone(),
             format,
             output_dir: self.output_dir.clone(),
+            name_format: self.name_format.clone(),
         });
 
         let queue = items
@@ -691,7 +1603,43 @@ impl App {
 
     // Download screen actions
     pub fn cancel_downloads(&mut self) {
-        let _ = self.async_tx.try_send(AsyncRequest::CancelDownloads);
+        self.request(AsyncRequest::CancelDownloads);
+    }
+
+    pub fn download_toggle_group(&mut self, group: DownloadGroup) {
+        let collapsed = &mut self.download_state.collapsed;
+        if !collapsed.remove(&group) {
+            collapsed.insert(group);
+        }
+    }
+
+    pub fn download_toggle_failures(&mut self) {
+        let state = &mut self.download_state;
+        state.failures_only = !state.failures_only;
+        state.failure_scroll = 0;
+    }
+
+    pub fn download_scroll_down(&mut self) {
+        if self.download_state.failures_only {
+            self.download_state.failure_scroll =
+                self.download_state.failure_scroll.saturating_add(1);
+        }
+    }
+
+    pub fn download_scroll_up(&mut self) {
+        if self.download_state.failures_only {
+            self.download_state.failure_scroll =
+                self.download_state.failure_scroll.saturating_sub(1);
+        }
+    }
+
+    /// Offered once a batch finished without failures
+    pub fn download_open_output(&mut self) {
+        let state = &self.download_state;
+        if !state.is_active && state.all_succeeded() {
+            // Best effort, there is no sensible place for an error on this screen
+            let _ = open_in_file_manager(&self.output_dir);
+        }
     }
 
     pub fn download_back_to_library(&mut self) {
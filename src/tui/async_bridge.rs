@@ -1,16 +1,19 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use tokio::sync::{Mutex, Semaphore, mpsc};
+use tokio::sync::{Semaphore, mpsc};
 use tokio::task::JoinHandle;
 use tracing::debug;
 
 use crate::core::auth::Credentials;
-use crate::core::client::BandcampClient;
-use crate::core::download::{DownloadProgressReporter, download_item};
-use crate::core::library::{AudioFormat, LibraryItem};
+use crate::core::client::{BandcampClient, collection_paging};
+use crate::core::download::{DownloadOptions, DownloadProgressReporter, SkipReason};
+use crate::core::library::{AudioFormat, ItemDetails, LibraryItem};
+use crate::core::queue::{BatchProgressReporter, DownloadQueue, ReporterRegistry};
+use crate::i18n::{Msg, tr};
 use crate::tui::app::MAX_CONCURRENT_DOWNLOADS;
 
 /// Messages sent from the TUI to the async runtime
@@ -18,12 +21,28 @@ use crate::tui::app::MAX_CONCURRENT_DOWNLOADS;
 pub enum AsyncRequest {
     ValidateCookie(String),
     FetchCollection,
+    FetchWishlist,
     StartBatchDownload {
         items: Vec<LibraryItem>,
         format: AudioFormat,
         output_dir: PathBuf,
+        name_format: Option<String>,
     },
     CancelDownloads,
+    /// Download a single item next to any running batch, cancelling the batch leaves it alone
+    DownloadNow {
+        item: Box<LibraryItem>,
+        format: AudioFormat,
+        output_dir: PathBuf,
+        name_format: Option<String>,
+    },
+    /// Fetch preview details for items the user is about to look at
+    PrefetchDetails(Vec<LibraryItem>),
+    /// Hide or unhide an item in the collection
+    SetItemHidden {
+        item: Box<LibraryItem>,
+        hidden: bool,
+    },
 }
 
 /// Messages sent from the async runtime to the TUI
@@ -37,6 +56,8 @@ pub enum AsyncResponse {
     },
     /// Collection fetch failed
     CollectionFetchError(String),
+    /// The whole wishlist, listed like collection items that can't be downloaded
+    WishlistFetched(Result<Vec<LibraryItem>, String>),
     /// Batch download started with total item count
     BatchDownloadStarted {
         total_items: usize,
@@ -63,32 +84,98 @@ pub enum AsyncResponse {
         item_index: usize,
         result: Result<PathBuf, String>,
     },
+    /// Individual item was left out, e.g. because the format isn't offered
+    ItemDownloadSkipped {
+        item_id: String,
+        reason: SkipReason,
+    },
     /// Entire batch completed
     BatchDownloadComplete,
     /// Downloads were cancelled
     DownloadsCancelled,
+    /// An item started with [`AsyncRequest::DownloadNow`] finished, skipped items are errors
+    BackgroundDownloadFinished {
+        item_id: String,
+        result: Result<PathBuf, String>,
+    },
+    /// Preview details for an item arrived
+    ItemDetailsFetched {
+        item_id: String,
+        result: Result<ItemDetails, String>,
+    },
+    /// An item was hidden or unhidden, carries the new hidden state
+    ItemHiddenChanged {
+        item_id: String,
+        result: Result<bool, String>,
+    },
+}
+
+/// Room for responses between two frames. Senders wait when it is full, nothing is dropped.
+pub const RESPONSE_CHANNEL_SIZE: usize = 256;
+
+/// Latest byte counts of the running downloads by item ID, replacing the ones the TUI hasn't
+/// picked up yet. Progress goes through here instead of the response channel, so fast slots
+/// can't queue up behind each other or hold up status and completion messages.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressBoard(Arc<Mutex<HashMap<String, ByteCounts>>>);
+
+/// Bytes downloaded and, if known, the total
+type ByteCounts = (u64, Option<u64>);
+
+impl ProgressBoard {
+    fn update(&self, item_id: &str, downloaded: u64, total: Option<u64>) {
+        let mut board = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        board.insert(item_id.to_string(), (downloaded, total));
+    }
+
+    /// The newest progress of every item that moved since the last call
+    pub fn take(&self) -> Vec<AsyncResponse> {
+        let mut board = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        board
+            .drain()
+            .map(
+                |(item_id, (downloaded, total))| AsyncResponse::DownloadProgress {
+                    item_id,
+                    downloaded,
+                    total,
+                },
+            )
+            .collect()
+    }
 }
 
+/// Item pages fetched at once while prefetching preview details
+const DETAILS_CONCURRENCY: usize = 4;
+
 /// Bridge between sync TUI and async operations
 pub struct AsyncBridge {
     request_rx: mpsc::Receiver<AsyncRequest>,
     response_tx: mpsc::Sender<AsyncResponse>,
+    progress: ProgressBoard,
     client: Option<Arc<BandcampClient>>,
-    active_handles: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    download_options: DownloadOptions,
     coordinator_handle: Option<JoinHandle<()>>,
+    collection_handle: Option<JoinHandle<()>>,
+    /// Shared by every prefetch so overlapping requests still respect the limit
+    details_permits: Arc<Semaphore>,
 }
 
 impl AsyncBridge {
     pub fn new(
         request_rx: mpsc::Receiver<AsyncRequest>,
         response_tx: mpsc::Sender<AsyncResponse>,
+        progress: ProgressBoard,
+        download_options: DownloadOptions,
     ) -> Self {
         Self {
             request_rx,
             response_tx,
+            progress,
             client: None,
-            active_handles: Arc::new(Mutex::new(Vec::new())),
+            download_options,
             coordinator_handle: None,
+            collection_handle: None,
+            details_permits: Arc::new(Semaphore::new(DETAILS_CONCURRENCY)),
         }
     }
 
@@ -105,28 +192,61 @@ impl AsyncBridge {
                         .await;
                 }
                 AsyncRequest::FetchCollection => {
-                    self.fetch_collection_streaming().await;
+                    self.fetch_collection_streaming();
+                }
+                AsyncRequest::FetchWishlist => {
+                    let result = self.fetch_wishlist().await;
+                    let _ = self
+                        .response_tx
+                        .send(AsyncResponse::WishlistFetched(result))
+                        .await;
                 }
                 AsyncRequest::StartBatchDownload {
                     items,
                     format,
                     output_dir,
+                    name_format,
                 } => {
-                    self.start_batch_download(items, format, output_dir).await;
+                    self.start_batch_download(items, format, output_dir, name_format)
+                        .await;
                 }
                 AsyncRequest::CancelDownloads => {
                     if let Some(handle) = self.coordinator_handle.take() {
                         handle.abort();
                     }
-                    let mut handles = self.active_handles.lock().await;
-                    for handle in handles.drain(..) {
-                        handle.abort();
-                    }
                     let _ = self
                         .response_tx
                         .send(AsyncResponse::DownloadsCancelled)
                         .await;
                 }
+                AsyncRequest::DownloadNow {
+                    item,
+                    format,
+                    output_dir,
+                    name_format,
+                } => {
+                    self.download_now(*item, format, output_dir, name_format);
+                }
+                AsyncRequest::PrefetchDetails(items) => {
+                    self.prefetch_details(items);
+                }
+                AsyncRequest::SetItemHidden { item, hidden } => {
+                    let result = match self.client.as_ref() {
+                        Some(client) => client
+                            .set_item_hidden(&item, hidden)
+                            .await
+                            .map(|()| hidden)
+                            .map_err(|e| e.to_string()),
+                        None => Err(tr(Msg::NotLoggedIn).to_string()),
+                    };
+                    let _ = self
+                        .response_tx
+                        .send(AsyncResponse::ItemHiddenChanged {
+                            item_id: item.id,
+                            result,
+                        })
+                        .await;
+                }
             }
         }
     }
@@ -142,62 +262,71 @@ impl AsyncBridge {
         }
     }
 
-    async fn fetch_collection_streaming(&self) {
-        let client = match self.client.as_ref() {
-            Some(c) => c,
-            None => {
-                let _ = self
-                    .response_tx
-                    .send(AsyncResponse::CollectionFetchError("Not logged in".into()))
-                    .await;
-                return;
-            }
-        };
-
-        let mut token = BandcampClient::initial_collection_token();
-        let mut seen_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-        loop {
-            match client.get_collection_page(&token).await {
-                Ok(page) => {
-                    let items: Vec<LibraryItem> = page
-                        .items
-                        .into_iter()
-                        .filter(|item| seen_ids.insert(item.id.clone()))
-                        .collect();
+    async fn fetch_wishlist(&self) -> Result<Vec<LibraryItem>, String> {
+        let client = self
+            .client
+            .as_ref()
+            .ok_or_else(|| tr(Msg::NotLoggedIn).to_string())?;
+        let wishlist = client.get_wishlist().await.map_err(|e| e.to_string())?;
+        Ok(wishlist
+            .into_iter()
+            .map(|item| LibraryItem {
+                id: item.id,
+                item_type: item.item_type,
+                title: item.title,
+                artist: item.artist,
+                artist_id: String::new(),
+                artist_subdomain: None,
+                slug: None,
+                item_url: item.item_url,
+                download_url: String::new(),
+                is_preorder: false,
+                is_hidden: false,
+                purchase: Default::default(),
+                release_date: None,
+            })
+            .collect())
+    }
 
-                    let done = !page.more_available;
-                    let _ = self
-                        .response_tx
-                        .send(AsyncResponse::LibraryPageFetched { items, done })
-                        .await;
+    /// Streams the collection page by page from a task of its own, so requests sent while
+    /// it loads, e.g. starting a download, aren't held up behind it
+    fn fetch_collection_streaming(&mut self) {
+        if let Some(handle) = self.collection_handle.take() {
+            handle.abort();
+        }
+        let client = self.client.clone();
+        let response_tx = self.response_tx.clone();
+        self.collection_handle = Some(tokio::spawn(async move {
+            stream_collection(client, response_tx).await;
+        }));
+    }
 
-                    if done {
-                        break;
-                    }
+    /// Fetches details in the background, a few at a time, so the list stays responsive
+    fn prefetch_details(&self, items: Vec<LibraryItem>) {
+        let Some(client) = self.client.clone() else {
+            return;
+        };
+        let response_tx = self.response_tx.clone();
 
-                    match page.next_token {
-                        Some(t) => token = t,
-                        None => {
-                            let _ = self
-                                .response_tx
-                                .send(AsyncResponse::LibraryPageFetched {
-                                    items: vec![],
-                                    done: true,
-                                })
-                                .await;
-                            break;
-                        }
-                    }
-                }
-                Err(e) => {
-                    let _ = self
-                        .response_tx
-                        .send(AsyncResponse::CollectionFetchError(e.to_string()))
-                        .await;
-                    break;
-                }
-            }
+        for item in items {
+            let client = client.clone();
+            let response_tx = response_tx.clone();
+            let permits = self.details_permits.clone();
+            tokio::spawn(async move {
+                let Ok(_permit) = permits.acquire().await else {
+                    return;
+                };
+                let result = client
+                    .get_item_details(&item)
+                    .await
+                    .map_err(|e| e.to_string());
+                let _ = response_tx
+                    .send(AsyncResponse::ItemDetailsFetched {
+                        item_id: item.id,
+                        result,
+                    })
+                    .await;
+            });
         }
     }
 
@@ -206,6 +335,7 @@ impl AsyncBridge {
         items: Vec<LibraryItem>,
         format: AudioFormat,
         output_dir: PathBuf,
+        name_format: Option<String>,
     ) {
         let total_items = items.len();
 
@@ -225,7 +355,7 @@ impl AsyncBridge {
                         .send(AsyncResponse::ItemDownloadComplete {
                             item_id: item.id.clone(),
                             item_index: 0,
-                            result: Err("Not logged in".to_string()),
+                            result: Err(tr(Msg::NotLoggedIn).to_string()),
                         })
                         .await;
                 }
@@ -237,59 +367,227 @@ impl AsyncBridge {
             }
         };
 
-        let active_handles = self.active_handles.clone();
         let response_tx = self.response_tx.clone();
-
+        let progress = self.progress.clone();
+        let queue = DownloadQueue::new(
+            client,
+            output_dir,
+            format,
+            name_format,
+            self.download_options.clone(),
+            MAX_CONCURRENT_DOWNLOADS,
+        );
+
+        // Aborting this task drops the queue, which aborts every in-flight download
         self.coordinator_handle = Some(tokio::spawn(async move {
-            let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_DOWNLOADS));
+            let reporter = Arc::new(TuiBatchReporter {
+                response_tx: response_tx.clone(),
+                progress,
+            });
+            queue.run(items, reporter).await;
 
-            for (item_index, item) in items.into_iter().enumerate() {
-                let permit = semaphore.clone().acquire_owned().await.unwrap();
-                let client = client.clone();
-                let response_tx = response_tx.clone();
-                let output_dir = output_dir.clone();
+            let _ = response_tx.send(AsyncResponse::BatchDownloadComplete).await;
+        }));
+    }
+}
 
-                let handle = tokio::spawn(async move {
-                    tui_download(client, response_tx, item, item_index, format, output_dir).await;
-                    drop(permit);
-                });
+/// Hands the collection to the TUI a page at a time as it arrives
+async fn stream_collection(
+    client: Option<Arc<BandcampClient>>,
+    response_tx: mpsc::Sender<AsyncResponse>,
+) {
+    let client = match client.as_ref() {
+        Some(c) => c,
+        None => {
+            let _ = response_tx
+                .send(AsyncResponse::CollectionFetchError(
+                    tr(Msg::NotLoggedIn).into(),
+                ))
+                .await;
+            return;
+        }
+    };
+
+    let mut token = BandcampClient::initial_collection_token();
+    let mut seen_ids: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let max_items = collection_paging().max_items.unwrap_or(usize::MAX);
+
+    loop {
+        match client.get_collection_page(&token).await {
+            Ok(page) => {
+                let room = max_items.saturating_sub(seen_ids.len());
+                let items: Vec<LibraryItem> = page
+                    .items
+                    .into_iter()
+                    // Preorders have nothing to download until they are released
+                    .filter(|item| !item.is_preorder)
+                    .filter(|item| seen_ids.insert(item.id.clone()))
+                    .take(room)
+                    .collect();
+
+                let done = !page.more_available || seen_ids.len() >= max_items;
+                let _ = response_tx
+                    .send(AsyncResponse::LibraryPageFetched { items, done })
+                    .await;
 
-                active_handles.lock().await.push(handle);
-            }
+                if done {
+                    break;
+                }
 
-            // Wait for all downloads by polling handles
-            loop {
-                let handle = {
-                    let mut handles = active_handles.lock().await;
-                    handles.retain(|h| !h.is_finished());
-                    if handles.is_empty() {
+                match page.next_token {
+                    Some(t) => token = t,
+                    None => {
+                        let _ = response_tx
+                            .send(AsyncResponse::LibraryPageFetched {
+                                items: vec![],
+                                done: true,
+                            })
+                            .await;
                         break;
                     }
-                    // We can't await while holding the lock, so just check periodically
-                    drop(handles);
-                    None::<()>
-                };
-                let _ = handle;
-                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                }
+            }
+            Err(e) => {
+                let _ = response_tx
+                    .send(AsyncResponse::CollectionFetchError(e.to_string()))
+                    .await;
+                break;
             }
+        }
+    }
+}
 
-            let _ = response_tx.send(AsyncResponse::BatchDownloadComplete).await;
-        }));
+impl Drop for AsyncBridge {
+    fn drop(&mut self) {
+        if let Some(handle) = self.coordinator_handle.take() {
+            handle.abort();
+        }
+        if let Some(handle) = self.collection_handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl AsyncBridge {
+    fn download_now(
+        &self,
+        item: LibraryItem,
+        format: AudioFormat,
+        output_dir: PathBuf,
+        name_format: Option<String>,
+    ) {
+        let response_tx = self.response_tx.clone();
+        let item_id = item.id.clone();
+        let Some(client) = self.client.clone() else {
+            // Waits for room like every other finish, the item would stay "downloading" otherwise
+            tokio::spawn(async move {
+                let _ = response_tx
+                    .send(AsyncResponse::BackgroundDownloadFinished {
+                        item_id,
+                        result: Err(tr(Msg::NotLoggedIn).to_string()),
+                    })
+                    .await;
+            });
+            return;
+        };
+
+        let queue = DownloadQueue::new(
+            client,
+            output_dir,
+            format,
+            name_format,
+            self.download_options.clone(),
+            1,
+        );
+        tokio::spawn(async move {
+            let summary = queue
+                .run(vec![item], Arc::new(ReporterRegistry::new()))
+                .await;
+            // Only a panicked download leaves the summary empty
+            let result = (summary.succeeded.into_iter().map(|(_, path)| Ok(path)))
+                .chain(summary.failed.into_iter().map(|(_, error)| Err(error)))
+                .chain((summary.skipped.into_iter()).map(|(_, reason)| Err(reason.to_string())))
+                .next()
+                .unwrap_or_else(|| Err(tr(Msg::StatusFailed).to_string()));
+            let _ = response_tx
+                .send(AsyncResponse::BackgroundDownloadFinished { item_id, result })
+                .await;
+        });
+    }
+}
+
+/// Forwards queue events to the TUI as [`AsyncResponse`] messages
+struct TuiBatchReporter {
+    response_tx: mpsc::Sender<AsyncResponse>,
+    progress: ProgressBoard,
+}
+
+impl BatchProgressReporter for TuiBatchReporter {
+    fn on_item_started(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        let item_id = item.id.clone();
+        Box::pin(async move {
+            let _ = self
+                .response_tx
+                .send(AsyncResponse::ItemDownloadStarted {
+                    item_id: item_id.clone(),
+                    item_index: index,
+                })
+                .await;
+
+            Box::new(TuiProgressReporter::new(
+                item_id,
+                self.response_tx.clone(),
+                self.progress.clone(),
+            )) as Box<dyn DownloadProgressReporter>
+        })
+    }
+
+    fn on_item_finished(
+        &self,
+        index: usize,
+        item: &LibraryItem,
+        result: &crate::error::Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        let item_id = item.id.clone();
+        let response = match result {
+            Err(e) if let Some(reason) = SkipReason::from_error(e) => {
+                AsyncResponse::ItemDownloadSkipped { item_id, reason }
+            }
+            result => AsyncResponse::ItemDownloadComplete {
+                item_id,
+                item_index: index,
+                result: match result {
+                    Ok(path) => Ok(path.clone()),
+                    Err(e) => Err(e.to_string()),
+                },
+            },
+        };
+        Box::pin(async move {
+            let _ = self.response_tx.send(response).await;
+        })
     }
 }
 
 pub struct TuiProgressReporter {
     item_id: String,
     response_tx: mpsc::Sender<AsyncResponse>,
-    last_progress: std::sync::Mutex<Instant>,
+    progress: ProgressBoard,
 }
 
 impl TuiProgressReporter {
-    pub fn new(item_id: String, response_tx: mpsc::Sender<AsyncResponse>) -> Self {
+    pub fn new(
+        item_id: String,
+        response_tx: mpsc::Sender<AsyncResponse>,
+        progress: ProgressBoard,
+    ) -> Self {
         Self {
             item_id,
             response_tx,
-            last_progress: std::sync::Mutex::new(Instant::now()),
+            progress,
         }
     }
 
@@ -318,37 +616,45 @@ impl DownloadProgressReporter for TuiProgressReporter {
         self.send_status(crate::tui::app::DownloadItemStatus::FetchingUrl)
     }
 
+    fn on_encoding_wait(
+        &self,
+        attempt: u32,
+        elapsed: Duration,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.send_status(crate::tui::app::DownloadItemStatus::WaitingForEncoding {
+            attempt,
+            elapsed_secs: elapsed.as_secs(),
+        })
+    }
+
     fn on_progress(
         &self,
         downloaded: u64,
         total: Option<u64>,
     ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async move {
-            let should_send = {
-                let mut last = self.last_progress.lock().unwrap();
-                if last.elapsed().as_millis() >= 100 {
-                    *last = Instant::now();
-                    true
-                } else {
-                    false
-                }
-            };
+        self.progress.update(&self.item_id, downloaded, total);
+        Box::pin(async move {})
+    }
 
-            if should_send {
-                let _ = self
-                    .response_tx
-                    .send(AsyncResponse::DownloadProgress {
-                        item_id: self.item_id.clone(),
-                        downloaded,
-                        total,
-                    })
-                    .await;
-            }
+    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.send_status(crate::tui::app::DownloadItemStatus::Extracting {
+            entry: String::new(),
+            done: 0,
+            total: 0,
         })
     }
 
-    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        self.send_status(crate::tui::app::DownloadItemStatus::Extracting)
+    fn on_extract_progress(
+        &self,
+        entry: &str,
+        done: usize,
+        total: usize,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.send_status(crate::tui::app::DownloadItemStatus::Extracting {
+            entry: entry.to_string(),
+            done,
+            total,
+        })
     }
 
     fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
@@ -359,36 +665,3 @@ impl DownloadProgressReporter for TuiProgressReporter {
         Box::pin(async move {})
     }
 }
-
-// Usage in TUI:
-async fn tui_download(
-    client: Arc<BandcampClient>,
-    response_tx: mpsc::Sender<AsyncResponse>,
-    item: LibraryItem,
-    item_index: usize,
-    format: AudioFormat,
-    output_dir: PathBuf,
-) {
-    let item_id = item.id.clone();
-
-    let _ = response_tx
-        .send(AsyncResponse::ItemDownloadStarted {
-            item_id: item_id.clone(),
-            item_index,
-        })
-        .await;
-
-    let reporter = TuiProgressReporter::new(item_id.to_string(), response_tx.clone());
-
-    let result = download_item(&client, &item, &output_dir, format, None, reporter)
-        .await
-        .map_err(|e| e.to_string());
-
-    let _ = response_tx
-        .send(AsyncResponse::ItemDownloadComplete {
-            item_id,
-            item_index,
-            result,
-        })
-        .await;
-}
@@ -0,0 +1,118 @@
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::{DateTime, Local};
+use crossterm::{
+    cursor::Show,
+    event::DisableMouseCapture,
+    execute,
+    terminal::{LeaveAlternateScreen, disable_raw_mode},
+};
+
+use crate::core::utils::data_dir;
+use crate::term::logs::recent_log_lines;
+
+type PanicHook = dyn Fn(&PanicHookInfo<'_>) + Sync + Send + 'static;
+
+/// Restores the terminal and writes a crash report when the TUI panics, instead of leaving
+/// the terminal in raw mode on the alternate screen with the message drawn over. Dropping
+/// it puts the previous panic hook back.
+pub struct CrashGuard {
+    previous: Arc<PanicHook>,
+}
+
+impl CrashGuard {
+    /// Only panics of the calling thread, the one drawing the TUI, count as a crash. Those of
+    /// the runtime's worker threads end their task and the TUI carries on.
+    pub fn install() -> Self {
+        let previous: Arc<PanicHook> = Arc::from(std::panic::take_hook());
+        let tui_thread = std::thread::current().id();
+
+        let hook = previous.clone();
+        std::panic::set_hook(Box::new(move |info| {
+            if std::thread::current().id() != tui_thread {
+                return hook(info);
+            }
+            restore_terminal();
+            hook(info);
+            report(info);
+        }));
+        Self { previous }
+    }
+}
+
+impl Drop for CrashGuard {
+    fn drop(&mut self) {
+        // Not while unwinding, the hook has to stay for the panic in flight
+        if !std::thread::panicking() {
+            let previous = self.previous.clone();
+            std::panic::set_hook(Box::new(move |info| previous(info)));
+        }
+    }
+}
+
+fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let _ = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    );
+}
+
+fn report(info: &PanicHookInfo<'_>) {
+    let report = render_crash_report(
+        &info.to_string(),
+        &Backtrace::force_capture().to_string(),
+        &recent_log_lines(),
+        Local::now(),
+    );
+    let dir = data_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("crashes");
+    match write_crash_report(&dir, &report, Local::now()) {
+        Ok(path) => eprintln!(
+            "\nbannedcamp crashed. A report with the backtrace and the last log lines was saved \
+             to {}, please attach it to a bug report.",
+            path.display()
+        ),
+        Err(e) => eprintln!("\nbannedcamp crashed and the crash report couldn't be saved: {e}"),
+    }
+}
+
+/// Saves `report` as `crash-<time>.txt` in `dir`, returning its path
+pub fn write_crash_report(dir: &Path, report: &str, at: DateTime<Local>) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("crash-{}.txt", at.format("%Y%m%d-%H%M%S")));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+pub fn render_crash_report(
+    panic: &str,
+    backtrace: &str,
+    logs: &[String],
+    at: DateTime<Local>,
+) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "bannedcamp {} on {} {}, crashed at {}\n",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        at.format("%Y-%m-%d %H:%M:%S %:z")
+    );
+    let _ = writeln!(out, "{panic}\n");
+    let _ = writeln!(out, "Backtrace:\n{backtrace}");
+    let _ = writeln!(out, "Last {} log lines:", logs.len());
+    for line in logs {
+        let _ = writeln!(out, "{line}");
+    }
+    out
+}
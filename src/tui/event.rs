@@ -1,5 +1,7 @@
-use crossterm::event::{self, Event, KeyEvent};
+use crossterm::event::{Event, EventStream, KeyEvent};
 use std::time::Duration;
+use tokio::time::{Interval, MissedTickBehavior};
+use tokio_stream::StreamExt;
 
 pub enum AppEvent {
     Key(KeyEvent),
@@ -7,24 +9,42 @@ pub enum AppEvent {
     Resize(u16, u16),
 }
 
+/// Terminal input as it arrives, with a tick in between for spinners and sampling
 pub struct EventHandler {
-    tick_rate: Duration,
+    events: EventStream,
+    ticks: Interval,
 }
 
 impl EventHandler {
     pub fn new(tick_rate: Duration) -> Self {
-        Self { tick_rate }
+        let mut ticks = tokio::time::interval(tick_rate);
+        // A busy terminal shouldn't be followed by a burst of catch-up ticks
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        Self {
+            events: EventStream::new(),
+            ticks,
+        }
     }
 
-    pub fn next(&self) -> std::io::Result<AppEvent> {
-        if event::poll(self.tick_rate)? {
-            match event::read()? {
-                Event::Key(key) => Ok(AppEvent::Key(key)),
-                Event::Resize(w, h) => Ok(AppEvent::Resize(w, h)),
-                _ => Ok(AppEvent::Tick),
+    /// Waits for the next key press, resize or tick, whichever comes first
+    pub async fn next(&mut self) -> std::io::Result<AppEvent> {
+        loop {
+            tokio::select! {
+                event = self.events.next() => match event {
+                    Some(Ok(Event::Key(key))) => return Ok(AppEvent::Key(key)),
+                    Some(Ok(Event::Resize(w, h))) => return Ok(AppEvent::Resize(w, h)),
+                    // Mouse and focus events aren't used
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => return Err(e),
+                    None => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::UnexpectedEof,
+                            "terminal input closed",
+                        ));
+                    }
+                },
+                _ = self.ticks.tick() => return Ok(AppEvent::Tick),
             }
-        } else {
-            Ok(AppEvent::Tick)
         }
     }
 }
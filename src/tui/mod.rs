@@ -1,9 +1,13 @@
 pub mod app;
 pub mod async_bridge;
+pub mod cache;
+pub mod crash;
 pub mod event;
 pub mod run;
 pub mod screens;
+pub mod theme;
 pub mod ui;
+pub mod view_state;
 pub mod widgets;
 
-pub use run::run;
+pub use run::{TuiOptions, run};
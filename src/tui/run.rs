@@ -1,8 +1,15 @@
 use std::io;
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::Duration;
 
+use crate::core::audit::AuditLog;
+use crate::core::download::DownloadOptions;
+use crate::core::library::AudioFormat;
+use crate::core::state::BandwidthMeter;
+use crate::core::utils::SanitizeOptions;
 use crate::error::Result;
+use crate::term::title::TitleGuard;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture, KeyModifiers},
     execute,
@@ -11,12 +18,64 @@ use crossterm::{
 use ratatui::{Terminal, backend::CrosstermBackend};
 use tokio::sync::mpsc;
 
-use crate::tui::app::{App, LibraryFocus, LibraryMode, Screen};
-use crate::tui::async_bridge::{AsyncBridge, AsyncRequest, AsyncResponse};
+use crate::tui::app::{App, DownloadGroup, LibraryFocus, LibraryMode, LibraryTab, Screen};
+use crate::tui::async_bridge::{
+    AsyncBridge, AsyncRequest, AsyncResponse, ProgressBoard, RESPONSE_CHANNEL_SIZE,
+};
+use crate::tui::crash::CrashGuard;
 use crate::tui::event::{AppEvent, EventHandler};
-use crate::tui::ui;
+use crate::tui::{ui, view_state};
+
+/// What `bannedcamp library` was started with
+#[derive(Debug, Clone)]
+pub struct TuiOptions {
+    pub output_dir: PathBuf,
+    pub format: AudioFormat,
+    /// Naming template for downloads, the built-in one when unset
+    pub custom_format: Option<String>,
+    /// File every download attempt is appended to
+    pub audit_log: Option<PathBuf>,
+    pub checksums: bool,
+    pub purchase_info: bool,
+    pub verify_audio: bool,
+    /// Browse the library without downloading or changing anything
+    pub read_only: bool,
+    pub sanitize: SanitizeOptions,
+}
+
+/// Runs the TUI on the runtime of the caller, next to the bridge doing the network work
+pub async fn run(options: TuiOptions, tick_rate: Duration) -> Result<()> {
+    let TuiOptions {
+        output_dir,
+        format,
+        custom_format,
+        audit_log,
+        checksums,
+        purchase_info,
+        verify_audio,
+        read_only,
+        sanitize,
+    } = options;
 
-pub fn run(output_dir: PathBuf) -> Result<()> {
+    // Open the audit log before touching the terminal so errors are readable
+    let audit_log = audit_log
+        .as_deref()
+        .map(AuditLog::open)
+        .transpose()?
+        .map(Arc::new);
+    let bandwidth = Arc::new(BandwidthMeter::open(&output_dir, None)?);
+    let download_options = DownloadOptions {
+        audit_log,
+        checksums,
+        purchase_info,
+        verify_audio,
+        sanitize,
+        bandwidth: Some(bandwidth),
+        output_root: Some(output_dir.clone()),
+        ..Default::default()
+    };
+
+    let _crash_guard = CrashGuard::install();
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
@@ -25,23 +84,37 @@ pub fn run(output_dir: PathBuf) -> Result<()> {
     terminal.clear()?;
 
     let (request_tx, request_rx) = mpsc::channel::<AsyncRequest>(32);
-    let (response_tx, response_rx) = mpsc::channel::<AsyncResponse>(32);
+    let (response_tx, mut response_rx) = mpsc::channel::<AsyncResponse>(RESPONSE_CHANNEL_SIZE);
+    let progress = ProgressBoard::default();
 
     let mut app = App::new(request_tx.clone());
     app.output_dir = output_dir;
+    app.set_default_format(format);
+    app.name_format = custom_format;
+    if let Some(path) = view_state::default_path() {
+        app.restore_library_view(path);
+    }
+    app.load_manifest();
+    if read_only {
+        app.open_read_only();
+    }
 
-    let bridge = AsyncBridge::new(request_rx, response_tx);
-    std::thread::spawn(move || {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(bridge.run());
-    });
-
-    let event_handler = EventHandler::new(Duration::from_millis(100));
+    let bridge = AsyncBridge::new(request_rx, response_tx, progress.clone(), download_options);
+    let bridge = tokio::spawn(bridge.run());
 
-    // Response receiver needs to be checked without blocking
-    let mut response_rx = response_rx;
+    let mut event_handler = EventHandler::new(tick_rate);
 
-    let result = run_loop(&mut terminal, &mut app, &event_handler, &mut response_rx);
+    let result = run_loop(
+        &mut terminal,
+        &mut app,
+        &mut event_handler,
+        &mut response_rx,
+        &progress,
+    )
+    .await;
+    app.save_library_view();
+    // Also cancels a batch that is still running
+    bridge.abort();
 
     execute!(
         terminal.backend_mut(),
@@ -56,30 +129,52 @@ pub fn run(output_dir: PathBuf) -> Result<()> {
     result
 }
 
-fn run_loop(
+async fn run_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    event_handler: &EventHandler,
+    event_handler: &mut EventHandler,
     response_rx: &mut mpsc::Receiver<AsyncResponse>,
+    progress: &ProgressBoard,
 ) -> Result<()> {
+    // Only present while a batch runs, dropping it restores the terminal title
+    let mut title: Option<TitleGuard> = None;
+
     while !app.should_quit {
         // Draw
         terminal.draw(|f| ui::draw(f, app))?;
 
-        // Check for async responses (non-blocking)
+        let state = &app.download_state;
+        if state.is_active {
+            title.get_or_insert_with(TitleGuard::new).set_progress(
+                state.done_count(),
+                state.total_items(),
+                state.speed_bytes_per_sec(),
+            );
+        } else {
+            title = None;
+        }
+
+        // Sleeps until there is input, a response or a tick
+        tokio::select! {
+            event = event_handler.next() => match event? {
+                AppEvent::Key(key) => {
+                    handle_key_event(app, key);
+                }
+                AppEvent::Tick => {
+                    app.tick();
+                }
+                AppEvent::Resize(_, _) => {}
+            },
+            Some(response) = response_rx.recv() => app.handle_async_response(response),
+        }
+
+        // Everything else that queued up meanwhile goes into the same frame
         while let Ok(response) = response_rx.try_recv() {
             app.handle_async_response(response);
         }
-
-        // Handle events
-        match event_handler.next()? {
-            AppEvent::Key(key) => {
-                handle_key_event(app, key);
-            }
-            AppEvent::Tick => {
-                app.tick();
-            }
-            AppEvent::Resize(_, _) => {}
+        // After the queued messages, so downloads that just started have a slot to show it in
+        for response in progress.take() {
+            app.handle_async_response(response);
         }
     }
 
@@ -95,6 +190,11 @@ fn handle_key_event(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    if app.error_detail.is_some() {
+        handle_error_detail_keys(app, key);
+        return;
+    }
+
     match app.screen {
         Screen::Login => handle_login_keys(app, key),
         Screen::Library => handle_library_keys(app, key),
@@ -114,6 +214,12 @@ fn handle_login_keys(app: &mut App, key: crossterm::event::KeyEvent) {
         return;
     }
 
+    // Plain letters go into the cookie field
+    if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == Char('e') {
+        app.show_error_detail();
+        return;
+    }
+
     match key.code {
         Char(c) => app.login_input_char(c),
         Backspace => app.login_delete_char(),
@@ -155,7 +261,20 @@ fn handle_library_keys(app: &mut App, key: crossterm::event::KeyEvent) {
                     Char('a') => app.library_select_all(),
                     Char('n') => app.library_clear_selection(),
                     Char('d') => app.library_show_format_selection(),
+                    Char('D') => app.library_quick_download(),
+                    Char('g') => app.library_download_highlighted(),
+                    Char('h') => app.library_toggle_hidden(),
+                    Char('H') => app.library_toggle_show_hidden(),
+                    Char('s') => app.library_cycle_sort(),
+                    Char('p') => app.library_toggle_preview(),
+                    Char('e') => app.show_error_detail(),
                     Char('/') => app.library_focus_search(),
+                    Char(c @ '1'..='4') => {
+                        let tab = LibraryTab::ALL[c as usize - '1' as usize];
+                        app.library_switch_tab(tab);
+                    }
+                    Char('[') => app.library_previous_tab(),
+                    Char(']') => app.library_next_tab(),
                     Esc => {
                         if !app.library_state.search_query.is_empty() {
                             app.library_search_clear();
@@ -179,6 +298,18 @@ fn handle_library_keys(app: &mut App, key: crossterm::event::KeyEvent) {
     }
 }
 
+fn handle_error_detail_keys(app: &mut App, key: crossterm::event::KeyEvent) {
+    use crossterm::event::KeyCode::*;
+
+    match key.code {
+        Esc | Enter | Char('e') | Char('q') => app.close_error_detail(),
+        Down | Char('j') => app.error_detail_scroll_down(),
+        Up | Char('k') => app.error_detail_scroll_up(),
+        Char('c') => app.copy_error_detail(),
+        _ => {}
+    }
+}
+
 fn handle_download_keys(app: &mut App, key: crossterm::event::KeyEvent) {
     use crossterm::event::KeyCode::*;
 
@@ -191,6 +322,15 @@ fn handle_download_keys(app: &mut App, key: crossterm::event::KeyEvent) {
             }
         }
         Enter => app.download_back_to_library(),
+        Char('o') => app.download_open_output(),
+        Char('f') => app.download_toggle_failures(),
+        Char('e') => app.show_error_detail(),
+        Down | Char('j') => app.download_scroll_down(),
+        Up | Char('k') => app.download_scroll_up(),
+        Char(c @ '1'..='5') => {
+            let group = DownloadGroup::ALL[c as usize - '1' as usize];
+            app.download_toggle_group(group);
+        }
         _ => {}
     }
 }
@@ -1,21 +1,28 @@
 use ratatui::{
     Frame,
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Wrap},
 };
 
 use crate::{
-    core::utils::{format_bytes, truncate_str},
-    tui::app::{DownloadItemStatus, DownloadState},
+    core::utils::{LOW_SPACE_BYTES, format_bytes, truncate_str},
+    i18n::{Msg, tr, tr_fmt},
+    tui::{
+        app::{DownloadGroup, DownloadItem, DownloadItemStatus, DownloadState},
+        theme::theme,
+    },
 };
 
 pub fn draw(frame: &mut Frame, area: Rect, state: &DownloadState) {
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray))
-        .title(" Downloads ");
+        .title(format!(" {} ", tr(Msg::Downloads)));
+    if state.is_active {
+        block = block.title(throughput_line(state).right_aligned());
+    }
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -24,10 +31,51 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &DownloadState) {
         .constraints([Constraint::Min(1), Constraint::Length(1)])
         .split(inner);
 
-    draw_download_list(frame, chunks[0], state);
+    if state.failures_only {
+        draw_failures(frame, chunks[0], state);
+    } else {
+        draw_download_list(frame, chunks[0], state);
+    }
     draw_help_bar(frame, chunks[1], state);
 }
 
+/// Network and disk rates next to free space, which turns red once the disk is nearly full
+fn throughput_line(state: &DownloadState) -> Line<'static> {
+    let muted = Style::default().fg(Color::DarkGray);
+    let mut parts = vec![
+        Span::styled(" ", muted),
+        Span::styled(
+            tr_fmt(
+                Msg::NetworkSpeed,
+                &[&format_bytes(state.speed_bytes_per_sec())],
+            ),
+            muted,
+        ),
+        Span::styled("  ", muted),
+        Span::styled(
+            tr_fmt(
+                Msg::WriteSpeed,
+                &[&format_bytes(state.disk.write_bytes_per_sec)],
+            ),
+            muted,
+        ),
+    ];
+    if let Some(free) = state.disk.free_bytes {
+        let style = if free < LOW_SPACE_BYTES {
+            theme().failure.add_modifier(Modifier::BOLD)
+        } else {
+            muted
+        };
+        parts.push(Span::styled("  ", muted));
+        parts.push(Span::styled(
+            tr_fmt(Msg::FreeSpace, &[&format_bytes(free as f64)]),
+            style,
+        ));
+    }
+    parts.push(Span::styled(" ", muted));
+    Line::from(parts)
+}
+
 fn name_width(area_width: u16) -> usize {
     let bar_width = 20usize;
     let right_width = 1 + (bar_width + 2) + 5 + 13;
@@ -36,65 +84,46 @@ fn name_width(area_width: u16) -> usize {
 
 fn draw_download_list(frame: &mut Frame, area: Rect, state: &DownloadState) {
     let mut items: Vec<ListItem> = Vec::new();
-    let name_width = name_width(area.width);
-
-    for di in &state.queue {
-        let display_name = format!("{} - {}", di.item.artist, di.item.title);
-
-        // Check if this item has an active download slot (for progress display)
-        if let Some(slot) = state
-            .slots
-            .iter()
-            .find(|s| s.item_id.as_deref() == Some(di.item.id.as_str()))
-        {
-            let spinner = state.spinner.current();
-            items.push(create_progress_item(
-                &display_name,
-                slot,
-                spinner,
-                area.width,
-            ));
+
+    for (key, group) in DownloadGroup::ALL.into_iter().enumerate() {
+        let count = state.group_items(group).count();
+        if count == 0 {
             continue;
         }
 
-        match &di.status {
-            DownloadItemStatus::Done(Ok(_)) => {
-                let max_len = area.width.saturating_sub(4) as usize;
-                let truncated = truncate_str(&display_name, max_len);
-                items.push(ListItem::new(Line::from(vec![
-                    Span::styled("✓ ", Style::default().fg(Color::Green)),
-                    Span::styled(truncated, Style::default().fg(Color::Green)),
-                ])));
-            }
-            DownloadItemStatus::Done(Err(e)) => {
-                let max_len = area.width.saturating_sub(4) as usize;
-                let full_text = format!("{display_name} - {e}");
-                let truncated = truncate_str(&full_text, max_len);
-                items.push(ListItem::new(Line::from(vec![
-                    Span::styled("✗ ", Style::default().fg(Color::Red)),
-                    Span::styled(truncated, Style::default().fg(Color::Red)),
-                ])));
-            }
-            DownloadItemStatus::Cancelled => {
-                let max_len = area.width.saturating_sub(4) as usize;
-                let truncated = truncate_str(&display_name, max_len);
-                items.push(ListItem::new(Line::from(vec![
-                    Span::styled("⊘ ", Style::default().fg(Color::DarkGray)),
-                    Span::styled(truncated, Style::default().fg(Color::DarkGray)),
-                ])));
-            }
-            _ => {
-                // Pending or active without a slot yet
-                let name_display = if display_name.chars().count() > name_width {
-                    truncate_str(&display_name, name_width)
-                } else {
-                    format!("{display_name:<name_width$}")
-                };
-                items.push(ListItem::new(Line::from(vec![
-                    Span::styled(name_display, Style::default().fg(Color::DarkGray)),
-                    Span::styled(" in queue", Style::default().fg(Color::DarkGray)),
-                ])));
-            }
+        let collapsed = state.collapsed.contains(&group);
+        let style = match group {
+            DownloadGroup::Active => theme().active,
+            DownloadGroup::Queued => theme().muted,
+            DownloadGroup::Completed => theme().success,
+            DownloadGroup::Failed => theme().failure,
+            DownloadGroup::Skipped => theme().muted,
+        };
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(
+                format!(
+                    "{} {} ({count})",
+                    if collapsed { "▶" } else { "▼" },
+                    group.label()
+                ),
+                style.add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" [{}]", key + 1),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])));
+
+        if !collapsed {
+            // Cancelled items share the group but have no place in the queue
+            let mut position = 0;
+            items.extend(state.group_items(group).map(|di| {
+                let position = (di.status == DownloadItemStatus::Pending).then(|| {
+                    position += 1;
+                    position
+                });
+                create_item(di, position, state, area.width)
+            }));
         }
     }
 
@@ -102,6 +131,142 @@ fn draw_download_list(frame: &mut Frame, area: Rect, state: &DownloadState) {
     frame.render_widget(list, area);
 }
 
+/// Every failed item with its whole error message wrapped below it
+fn draw_failures(frame: &mut Frame, area: Rect, state: &DownloadState) {
+    let mut lines = Vec::new();
+    for di in state.group_items(DownloadGroup::Failed) {
+        let DownloadItemStatus::Done(Err(error)) = &di.status else {
+            continue;
+        };
+        lines.push(Line::styled(
+            format!("✗ {} - {}", di.item.artist, di.item.title),
+            theme().failure.add_modifier(Modifier::BOLD),
+        ));
+        lines.push(Line::from(format!("  {error}")));
+        lines.push(Line::default());
+    }
+
+    if lines.is_empty() {
+        lines.push(Line::styled(tr(Msg::NoFailures), theme().muted));
+    }
+
+    let failures = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((state.failure_scroll, 0));
+    frame.render_widget(failures, area);
+}
+
+/// `position` is the 1-based place among the items still waiting, for pending items
+fn create_item(
+    di: &DownloadItem,
+    position: Option<usize>,
+    state: &DownloadState,
+    width: u16,
+) -> ListItem<'static> {
+    let display_name = format!("{} - {}", di.item.artist, di.item.title);
+    let name_width = name_width(width);
+
+    // Check if this item has an active download slot (for progress display)
+    if let Some(slot) = state
+        .slots
+        .iter()
+        .find(|s| s.item_id.as_deref() == Some(di.item.id.as_str()))
+    {
+        let spinner = state.spinner.current();
+        return create_progress_item(&display_name, slot, spinner, width);
+    }
+
+    match &di.status {
+        DownloadItemStatus::Done(Ok(_)) => finished_item(
+            "✓",
+            tr(Msg::StatusOk),
+            &display_name,
+            theme().success,
+            width,
+        ),
+        DownloadItemStatus::Done(Err(e)) => finished_item(
+            "✗",
+            tr(Msg::StatusFailed),
+            &format!("{display_name} - {e}"),
+            theme().failure,
+            width,
+        ),
+        DownloadItemStatus::Skipped(reason) => finished_item(
+            "»",
+            tr(Msg::StatusSkipped),
+            &format!("{display_name} - {reason}"),
+            theme().muted,
+            width,
+        ),
+        DownloadItemStatus::Cancelled => finished_item(
+            "⊘",
+            tr(Msg::StatusCancelled),
+            &display_name,
+            theme().muted,
+            width,
+        ),
+        status => {
+            // Pending or active without a slot yet
+            let name_display = if display_name.chars().count() > name_width {
+                truncate_str(&display_name, name_width)
+            } else {
+                format!("{display_name:<name_width$}")
+            };
+            let status_text = match (status, position) {
+                (DownloadItemStatus::Pending, Some(position)) => {
+                    tr_fmt(Msg::QueuedPosition, &[&position])
+                }
+                (status, _) => stage_text(status).unwrap_or_default(),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(name_display, Style::default().fg(Color::DarkGray)),
+                Span::styled(
+                    format!(" {status_text}"),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        }
+    }
+}
+
+/// Pipeline stage of an item that has left the queue: resolving the download URL, waiting
+/// for Bandcamp to encode it, downloading, then extracting
+fn stage_text(status: &DownloadItemStatus) -> Option<String> {
+    Some(match status {
+        DownloadItemStatus::FetchingUrl => tr(Msg::FetchingUrl).to_string(),
+        DownloadItemStatus::WaitingForEncoding {
+            attempt,
+            elapsed_secs,
+        } => tr_fmt(Msg::Preparing, &[attempt, elapsed_secs]),
+        DownloadItemStatus::Downloading => tr(Msg::DownloadingStage).to_string(),
+        DownloadItemStatus::Extracting { entry, .. } if entry.is_empty() => {
+            tr(Msg::Extracting).to_string()
+        }
+        DownloadItemStatus::Extracting { entry, done, total } => tr_fmt(
+            Msg::ExtractingEntry,
+            &[&(done * 100 / (*total).max(1)), &truncate_str(entry, 30)],
+        ),
+        _ => return None,
+    })
+}
+
+/// Symbol and label both carry the status, so it reads the same without color
+fn finished_item(
+    symbol: &str,
+    label: &str,
+    text: &str,
+    style: Style,
+    width: u16,
+) -> ListItem<'static> {
+    let prefix = format!("{symbol} {label}");
+    let max_len = (width as usize).saturating_sub(prefix.chars().count() + 4);
+    ListItem::new(Line::from(vec![
+        Span::styled(prefix, style),
+        Span::raw(" "),
+        Span::styled(truncate_str(text, max_len), style),
+    ]))
+}
+
 fn create_progress_item(
     name: &str,
     slot: &crate::tui::app::DownloadSlot,
@@ -117,13 +282,11 @@ fn create_progress_item(
         format!("{name:<name_width$}")
     };
 
-    match slot.status {
-        DownloadItemStatus::FetchingUrl | DownloadItemStatus::Extracting => {
-            let status_text = match slot.status {
-                DownloadItemStatus::FetchingUrl => "fetching URL...",
-                DownloadItemStatus::Extracting => "extracting...",
-                _ => unreachable!(),
-            };
+    match &slot.status {
+        DownloadItemStatus::FetchingUrl
+        | DownloadItemStatus::WaitingForEncoding { .. }
+        | DownloadItemStatus::Extracting { .. } => {
+            let status_text = stage_text(&slot.status).unwrap_or_default();
 
             let line = Line::from(vec![
                 Span::styled(name_display, Style::default().fg(Color::Cyan)),
@@ -168,45 +331,86 @@ fn draw_help_bar(frame: &mut Frame, area: Rect, state: &DownloadState) {
         let total = state.total_items();
         let ok = state.success_count();
         let fail = state.failure_count();
+        let skipped = state.skipped_count();
         let mut parts = vec![Span::styled(
-            format!("{done}/{total} complete"),
+            tr_fmt(Msg::CompleteCount, &[&done, &total]),
             Style::default().fg(Color::DarkGray),
         )];
-        if ok > 0 || fail > 0 {
+        let counts: Vec<_> = [
+            (ok, tr_fmt(Msg::OkCount, &[&ok]), theme().success),
+            (fail, tr_fmt(Msg::FailedCount, &[&fail]), theme().failure),
+            (
+                skipped,
+                tr_fmt(Msg::SkippedCount, &[&skipped]),
+                theme().muted,
+            ),
+        ]
+        .into_iter()
+        .filter(|(count, _, _)| *count > 0)
+        .collect();
+        if !counts.is_empty() {
             parts.push(Span::styled(" (", Style::default().fg(Color::DarkGray)));
-            if ok > 0 {
-                parts.push(Span::styled(
-                    format!("{ok} ok"),
-                    Style::default().fg(Color::Green),
-                ));
-                if fail > 0 {
+            for (i, (_, text, style)) in counts.into_iter().enumerate() {
+                if i > 0 {
                     parts.push(Span::styled(", ", Style::default().fg(Color::DarkGray)));
                 }
-            }
-            if fail > 0 {
-                parts.push(Span::styled(
-                    format!("{fail} failed"),
-                    Style::default().fg(Color::Red),
-                ));
+                parts.push(Span::styled(text, style));
             }
             parts.push(Span::styled(")", Style::default().fg(Color::DarkGray)));
         }
         parts.push(Span::raw("  "));
+        parts.push(Span::styled("1-5", Style::default().fg(Color::Yellow)));
+        parts.push(Span::styled(
+            format!(" {}  ", tr(Msg::Collapse)),
+            Style::default().fg(Color::DarkGray),
+        ));
+        parts.push(Span::styled("f", Style::default().fg(Color::Yellow)));
+        parts.push(Span::styled(
+            format!(" {}  ", failures_toggle_label(state)),
+            Style::default().fg(Color::DarkGray),
+        ));
         parts.push(Span::styled("Esc", Style::default().fg(Color::Yellow)));
         parts.push(Span::styled(
-            " Cancel",
+            format!(" {}", tr(Msg::Cancel)),
             Style::default().fg(Color::DarkGray),
         ));
         Line::from(parts)
-    } else {
+    } else if state.all_succeeded() {
         Line::from(vec![
+            Span::styled(format!("{} ", tr(Msg::AllFinished)), theme().success),
+            Span::styled("o", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {} ", tr(Msg::OpenFolder))),
             Span::styled("Enter", Style::default().fg(Color::Yellow)),
-            Span::raw(" Back to library "),
+            Span::raw(format!(" {} ", tr(Msg::BackToLibrary))),
             Span::styled("Esc", Style::default().fg(Color::Yellow)),
-            Span::raw(" Quit"),
+            Span::raw(format!(" {}", tr(Msg::Quit))),
         ])
+    } else {
+        let mut parts = vec![
+            Span::styled("Enter", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {} ", tr(Msg::BackToLibrary))),
+            Span::styled("1-5", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {} ", tr(Msg::Collapse))),
+            Span::styled("f", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {} ", failures_toggle_label(state))),
+        ];
+        if state.failure_count() > 0 {
+            parts.push(Span::styled("e", Style::default().fg(Color::Yellow)));
+            parts.push(Span::raw(format!(" {} ", tr(Msg::Details))));
+        }
+        parts.push(Span::styled("Esc", Style::default().fg(Color::Yellow)));
+        parts.push(Span::raw(format!(" {}", tr(Msg::Quit))));
+        Line::from(parts)
     };
 
     let help = Paragraph::new(help_text).style(Style::default().fg(Color::DarkGray));
     frame.render_widget(help, area);
 }
+
+fn failures_toggle_label(state: &DownloadState) -> &'static str {
+    if state.failures_only {
+        tr(Msg::ShowAll)
+    } else {
+        tr(Msg::FailuresOnly)
+    }
+}
@@ -3,18 +3,20 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap},
 };
 
 use crate::core::library::AudioFormat;
 use crate::core::utils::truncate_str;
-use crate::tui::app::{LibraryFocus, LibraryMode, LibraryState};
+use crate::i18n::{Msg, tr, tr_fmt};
+use crate::tui::app::{BackgroundDownload, LibraryFocus, LibraryMode, LibraryState, LibraryTab};
 
 pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
     // Main layout
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
+            Constraint::Length(1), // Tabs
             Constraint::Length(3), // Search bar
             Constraint::Length(1), // Header
             Constraint::Min(5),    // List
@@ -22,6 +24,20 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
         ])
         .split(area);
 
+    let titles = LibraryTab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| format!("{} {}", i + 1, tab.label()));
+    let tabs = Tabs::new(titles)
+        .select(LibraryTab::ALL.iter().position(|tab| *tab == state.tab))
+        .style(Style::default().fg(Color::DarkGray))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_widget(tabs, chunks[0]);
+
     // Search bar
     let search_focused = state.focus == LibraryFocus::SearchBar;
     let search_border_color = if search_focused {
@@ -40,9 +56,9 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(search_border_color))
-            .title(" Search (/) "),
+            .title(format!(" {} (/) ", tr(Msg::Search))),
     );
-    frame.render_widget(search_bar, chunks[0]);
+    frame.render_widget(search_bar, chunks[1]);
 
     // Header with counts
     let selected_count = state.selected_items.len();
@@ -51,16 +67,22 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
 
     let (header_text, header_style) = if let Some(ref error) = state.error {
         (
-            format!("Error: {}", error),
+            format!("{} (e: {})", tr_fmt(Msg::Error, &[error]), tr(Msg::Details)),
             Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
         )
+    } else if state.tab == LibraryTab::Feed {
+        (
+            tr(Msg::FeedUnavailable).to_string(),
+            Style::default().fg(Color::DarkGray),
+        )
     } else if state.loading {
         let text = if total_count == 0 {
-            format!("{} Loading library...", state.spinner.current())
+            format!("{} {}", state.spinner.current(), tr(Msg::LoadingLibrary))
         } else {
             format!(
-                "{} Loading library... ({total_count} items)",
+                "{} {}",
                 state.spinner.current(),
+                tr_fmt(Msg::LoadingLibraryCount, &[&total_count])
             )
         };
         (
@@ -69,14 +91,14 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )
-    } else if !state.search_query.is_empty() {
+    } else if visible_count != total_count || !state.search_query.is_empty() {
         let text = if selected_count > 0 {
-            format!(
-                "Showing {}/{} items ({} selected)",
-                visible_count, total_count, selected_count
+            tr_fmt(
+                Msg::ShowingItemsSelected,
+                &[&visible_count, &total_count, &selected_count],
             )
         } else {
-            format!("Showing {}/{} items", visible_count, total_count)
+            tr_fmt(Msg::ShowingItems, &[&visible_count, &total_count])
         };
         (
             text,
@@ -86,25 +108,57 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
         )
     } else if selected_count > 0 {
         (
-            format!("{} items ({} selected)", total_count, selected_count),
+            tr_fmt(Msg::ItemCountSelected, &[&total_count, &selected_count]),
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )
     } else {
         (
-            format!("{} items", total_count),
+            tr_fmt(Msg::ItemCount, &[&total_count]),
             Style::default()
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         )
     };
 
+    let hidden_count = state.hidden_count();
+    let header_text = if hidden_count == 0 || state.error.is_some() || state.loading {
+        header_text
+    } else if state.show_hidden {
+        format!(
+            "{header_text} · {}",
+            tr_fmt(Msg::HiddenShown, &[&hidden_count])
+        )
+    } else {
+        format!(
+            "{header_text} · {}",
+            tr_fmt(Msg::HiddenNotShown, &[&hidden_count])
+        )
+    };
+    let header_text = match state.sort.label() {
+        Some(label) if state.error.is_none() => format!("{header_text} · {label}"),
+        _ => header_text,
+    };
+    let header_text = if state.read_only {
+        format!("{header_text} · {}", tr(Msg::ReadOnly))
+    } else {
+        header_text
+    };
+
     let header = Paragraph::new(header_text).style(header_style);
-    frame.render_widget(header, chunks[1]);
+    frame.render_widget(header, chunks[2]);
 
-    // Library list
-    let list_area = chunks[2];
+    // Library list, sharing its row with the preview when that is open
+    let (list_area, preview_area) = if state.show_preview {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[3]);
+        (columns[0], Some(columns[1]))
+    } else {
+        (chunks[3], None)
+    };
     let visible_height = list_area.height.saturating_sub(2) as usize; // Account for borders
 
     // Adjust scroll offset if selected item is out of view
@@ -127,7 +181,30 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
             let artist = truncate_str(&item.artist, 25);
             let title = truncate_str(&item.title, 40);
 
-            let line = format!("{:<25} - {}", artist, title);
+            let date = item
+                .purchase
+                .purchased_at
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+
+            let mut line = format!("{:<10}  {:<25} - {}", date, artist, title);
+            if item.is_hidden {
+                line.push(' ');
+                line.push_str(tr(Msg::HiddenTag));
+            }
+            let tag = match state.background_downloads.get(&item.id) {
+                Some(BackgroundDownload::Running) => Some(Msg::DownloadingTag),
+                Some(BackgroundDownload::Done) => Some(Msg::DownloadedTag),
+                Some(BackgroundDownload::Failed) => Some(Msg::DownloadFailedTag),
+                None => state
+                    .downloaded
+                    .contains(&item.id)
+                    .then_some(Msg::DownloadedTag),
+            };
+            if let Some(tag) = tag {
+                line.push(' ');
+                line.push_str(tr(tag));
+            }
 
             let style = if is_highlighted {
                 Style::default()
@@ -138,6 +215,8 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
                 Style::default()
                     .fg(Color::Green)
                     .add_modifier(Modifier::BOLD)
+            } else if item.is_hidden {
+                Style::default().fg(Color::DarkGray)
             } else {
                 Style::default()
             };
@@ -158,7 +237,7 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
         if state.search_query.is_empty() {
             " 0/0 ".to_string()
         } else {
-            " No matches ".to_string()
+            format!(" {} ", tr(Msg::NoMatches))
         }
     } else {
         format!(" {}/{} ", state.selected + 1, visible_count)
@@ -179,34 +258,69 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
     );
     frame.render_widget(list, list_area);
 
+    if let Some(preview_area) = preview_area {
+        draw_preview(frame, preview_area, state);
+    }
+
     // Help bar - show different hints based on focus
     let help = if state.focus == LibraryFocus::SearchBar {
         Paragraph::new(Line::from(vec![
             Span::styled("Tab", Style::default().fg(Color::Yellow)),
-            Span::raw(" List  "),
+            Span::raw(format!(" {}  ", tr(Msg::List))),
             Span::styled("Enter", Style::default().fg(Color::Yellow)),
-            Span::raw(" Done  "),
+            Span::raw(format!(" {}  ", tr(Msg::Done))),
+            Span::styled("Esc", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}", tr(Msg::Clear))),
+        ]))
+    } else if !state.can_download() {
+        Paragraph::new(Line::from(vec![
+            Span::styled("j/k", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Nav))),
+            Span::styled("1-4/[]", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Tabs))),
+            Span::styled("/", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Search))),
+            Span::styled("H", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::ShowHidden))),
+            Span::styled("s", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Sort))),
+            Span::styled("p", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Preview))),
             Span::styled("Esc", Style::default().fg(Color::Yellow)),
-            Span::raw(" Clear"),
+            Span::raw(format!(" {}", tr(Msg::Quit))),
         ]))
     } else {
         Paragraph::new(Line::from(vec![
             Span::styled("j/k", Style::default().fg(Color::Yellow)),
-            Span::raw(" Nav  "),
+            Span::raw(format!(" {}  ", tr(Msg::Nav))),
+            Span::styled("1-4/[]", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Tabs))),
             Span::styled("Space/Enter", Style::default().fg(Color::Yellow)),
-            Span::raw(" Select  "),
+            Span::raw(format!(" {}  ", tr(Msg::Select))),
             Span::styled("a", Style::default().fg(Color::Yellow)),
-            Span::raw(" All  "),
+            Span::raw(format!(" {}  ", tr(Msg::All))),
             Span::styled("/", Style::default().fg(Color::Yellow)),
-            Span::raw(" Search  "),
+            Span::raw(format!(" {}  ", tr(Msg::Search))),
             Span::styled("d", Style::default().fg(Color::Yellow)),
-            Span::raw(" Download  "),
+            Span::raw(format!(" {}  ", tr(Msg::Download))),
+            Span::styled("D", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::QuickDownload))),
+            Span::styled("g", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::DownloadNow))),
+            Span::styled("h", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Hide))),
+            Span::styled("H", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::ShowHidden))),
+            Span::styled("s", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Sort))),
+            Span::styled("p", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(" {}  ", tr(Msg::Preview))),
             Span::styled("Esc", Style::default().fg(Color::Yellow)),
-            Span::raw(" Quit"),
+            Span::raw(format!(" {}", tr(Msg::Quit))),
         ]))
     }
     .style(Style::default().fg(Color::DarkGray));
-    frame.render_widget(help, chunks[3]);
+    frame.render_widget(help, chunks[4]);
 
     // Draw format selection popup if active
     if state.mode == LibraryMode::FormatSelection {
@@ -214,6 +328,73 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LibraryState) {
     }
 }
 
+fn draw_preview(frame: &mut Frame, area: Rect, state: &LibraryState) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(format!(" {} ", tr(Msg::Preview)));
+
+    let Some(item) = state.selected_item() else {
+        frame.render_widget(block, area);
+        return;
+    };
+
+    let muted = Style::default().fg(Color::DarkGray);
+    let mut lines = vec![
+        Line::styled(
+            item.title.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Line::from(item.artist.clone()),
+        Line::styled(format!("{:?}", item.item_type), muted),
+    ];
+    if let Some(date) = item.purchase.purchased_at {
+        lines.push(Line::styled(
+            tr_fmt(Msg::PurchasedOn, &[&date.format("%Y-%m-%d")]),
+            muted,
+        ));
+    }
+    if let Some(url) = &item.item_url {
+        lines.push(Line::styled(url.clone(), muted));
+    }
+    lines.push(Line::default());
+
+    if let Some(details) = state.details.peek(&item.id) {
+        if let Some(date) = &details.release_date {
+            lines.push(Line::from(tr_fmt(Msg::ReleasedOn, &[date])));
+        }
+        if !details.tracks.is_empty() {
+            lines.push(Line::from(tr_fmt(
+                Msg::TrackCount,
+                &[&details.tracks.len()],
+            )));
+        }
+        for (number, track) in details.tracks.iter().enumerate() {
+            let secs = track.duration_secs as u64;
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:>2}. ", number + 1), muted),
+                Span::raw(track.title.clone()),
+                Span::styled(format!(" {}:{:02}", secs / 60, secs % 60), muted),
+            ]));
+        }
+    } else if let Some(e) = state.details_errors.get(&item.id) {
+        lines.push(Line::styled(
+            tr_fmt(Msg::Error, &[e]),
+            Style::default().fg(Color::Red),
+        ));
+    } else if state.details_pending.contains(&item.id) {
+        lines.push(Line::styled(
+            format!("{} {}", state.spinner.current(), tr(Msg::LoadingDetails)),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+
+    let preview = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, area);
+}
+
 fn draw_format_selection(frame: &mut Frame, area: Rect, state: &LibraryState) {
     // Calculate popup size and position
     let popup_width = 40;
@@ -226,7 +407,7 @@ fn draw_format_selection(frame: &mut Frame, area: Rect, state: &LibraryState) {
 
     // Draw popup block
     let block = Block::default()
-        .title(" Select Format ")
+        .title(format!(" {} ", tr(Msg::SelectFormat)))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan))
         .style(Style::default().bg(Color::Black));
@@ -271,9 +452,9 @@ fn draw_format_selection(frame: &mut Frame, area: Rect, state: &LibraryState) {
     // Help
     let help = Paragraph::new(Line::from(vec![
         Span::styled("Enter", Style::default().fg(Color::Yellow)),
-        Span::raw(" Confirm  "),
+        Span::raw(format!(" {}  ", tr(Msg::Confirm))),
         Span::styled("Esc", Style::default().fg(Color::Yellow)),
-        Span::raw(" Cancel"),
+        Span::raw(format!(" {}", tr(Msg::Cancel))),
     ]))
     .style(Style::default().fg(Color::DarkGray))
     .alignment(Alignment::Center);
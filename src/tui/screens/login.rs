@@ -6,6 +6,7 @@ use ratatui::{
     widgets::{Block, Borders, Paragraph},
 };
 
+use crate::i18n::{Msg, tr};
 use crate::tui::app::LoginState;
 
 pub fn draw(frame: &mut Frame, area: Rect, state: &LoginState) {
@@ -38,23 +39,23 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LoginState) {
     let instructions = if state.loading {
         vec![Line::from(vec![
             Span::raw(state.spinner.current()),
-            Span::raw(" Validating cookie..."),
+            Span::raw(format!(" {}", tr(Msg::ValidatingCookie))),
         ])]
     } else if env_prefilled && !state.cookie_input.is_empty() {
         vec![
             Line::from(Span::styled(
-                "Cookie loaded from BANDCAMP_COOKIE",
+                tr(Msg::CookieFromEnv),
                 Style::default().fg(Color::Green),
             )),
             Line::from(""),
-            Line::from("Press Enter to continue"),
+            Line::from(tr(Msg::PressEnterToContinue)),
         ]
     } else {
         vec![
-            Line::from("Paste your Bandcamp identity cookie below:"),
+            Line::from(tr(Msg::PasteCookie)),
             Line::from(""),
             Line::from(Span::styled(
-                "(Browser DevTools -> Application -> Cookies -> identity)",
+                tr(Msg::CookieHint),
                 Style::default().fg(Color::DarkGray),
             )),
         ]
@@ -100,27 +101,41 @@ pub fn draw(frame: &mut Frame, area: Rect, state: &LoginState) {
 
     // Error or hint
     let status_text = if let Some(ref error) = state.error {
-        vec![Line::from(Span::styled(
-            error.as_str(),
-            Style::default().fg(Color::Red),
-        ))]
+        vec![Line::from(vec![
+            Span::styled(error.as_str(), Style::default().fg(Color::Red)),
+            Span::styled("  Ctrl+E", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!(" {}", tr(Msg::Details)),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])]
     } else if state.loading {
         vec![]
     } else {
         {
-            let visibility_label = if state.cookie_visible { "Hide" } else { "Show" };
+            let visibility_label = if state.cookie_visible {
+                tr(Msg::Hide)
+            } else {
+                tr(Msg::Show)
+            };
             vec![
                 Line::from(""),
                 Line::from(vec![
                     Span::styled("Enter", Style::default().fg(Color::Yellow)),
-                    Span::styled(" Submit  ", Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!(" {}  ", tr(Msg::Submit)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
                     Span::styled("Ctrl+R", Style::default().fg(Color::Yellow)),
                     Span::styled(
                         format!(" {visibility_label}  "),
                         Style::default().fg(Color::DarkGray),
                     ),
                     Span::styled("Esc", Style::default().fg(Color::Yellow)),
-                    Span::styled(" Quit", Style::default().fg(Color::DarkGray)),
+                    Span::styled(
+                        format!(" {}", tr(Msg::Quit)),
+                        Style::default().fg(Color::DarkGray),
+                    ),
                 ]),
             ]
         }
@@ -0,0 +1,51 @@
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::term::theme::ThemeName;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Styles for download statuses, every status also has its own symbol and label
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub active: Style,
+    pub success: Style,
+    pub failure: Style,
+    pub muted: Style,
+}
+
+impl Theme {
+    pub fn new(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Default => Self {
+                active: Style::default().fg(Color::Cyan),
+                success: Style::default().fg(Color::Green),
+                failure: Style::default().fg(Color::Red),
+                muted: Style::default().fg(Color::DarkGray),
+            },
+            ThemeName::HighContrast => Self {
+                active: Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD),
+                success: Style::default()
+                    .fg(Color::LightCyan)
+                    .add_modifier(Modifier::BOLD),
+                failure: Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::LightYellow)
+                    .add_modifier(Modifier::BOLD),
+                muted: Style::default().fg(Color::Gray),
+            },
+        }
+    }
+}
+
+/// Picks the theme for the rest of the process, only the first call has an effect
+pub fn set_theme(name: ThemeName) {
+    let _ = THEME.set(Theme::new(name));
+}
+
+pub fn theme() -> &'static Theme {
+    THEME.get_or_init(|| Theme::new(ThemeName::Default))
+}
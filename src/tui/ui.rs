@@ -1,12 +1,14 @@
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
 
-use super::app::{App, Screen};
+use super::app::{App, ErrorDetail, Screen};
 use super::screens;
+use crate::i18n::{Msg, tr};
 
 pub fn draw(frame: &mut Frame, app: &App) {
     let area = frame.area();
@@ -35,4 +37,53 @@ pub fn draw(frame: &mut Frame, app: &App) {
         Screen::Library => screens::library::draw(frame, inner_area, &app.library_state),
         Screen::Download => screens::download::draw(frame, inner_area, &app.download_state),
     }
+
+    if let Some(detail) = &app.error_detail {
+        draw_error_detail(frame, inner_area, detail);
+    }
+}
+
+fn draw_error_detail(frame: &mut Frame, area: Rect, detail: &ErrorDetail) {
+    let width = (area.width * 4 / 5).max(20).min(area.width);
+    let height = (area.height * 3 / 5).max(6).min(area.height);
+    let popup = Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    );
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .title(format!(" {} ", tr(Msg::ErrorDetails)))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner);
+
+    let text = Paragraph::new(detail.text.as_str())
+        .wrap(Wrap { trim: false })
+        .scroll((detail.scroll, 0));
+    frame.render_widget(text, chunks[0]);
+
+    let mut help = vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(format!(" {}  ", tr(Msg::Scroll))),
+        Span::styled("c", Style::default().fg(Color::Yellow)),
+        Span::raw(format!(" {}  ", tr(Msg::Copy))),
+        Span::styled("Esc", Style::default().fg(Color::Yellow)),
+        Span::raw(format!(" {}", tr(Msg::Close))),
+    ];
+    if detail.copied {
+        help.push(Span::styled(
+            format!("  {}", tr(Msg::Copied)),
+            Style::default().fg(Color::Green),
+        ));
+    }
+    let help = Paragraph::new(Line::from(help)).style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(help, chunks[1]);
 }
@@ -0,0 +1,79 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::core::state::{load_json, save_json};
+use crate::core::utils::data_dir;
+use crate::tui::app::{LibrarySort, LibraryState};
+
+/// Where the library screen was left, so reopening the TUI after quitting, a crash or a reboot
+/// lands on the same search, order and item
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibraryView {
+    pub search_query: String,
+    pub sort: LibrarySort,
+    pub show_hidden: bool,
+    pub show_preview: bool,
+    /// ID of the highlighted item
+    pub highlighted: Option<String>,
+    /// Rows listed above the highlighted one
+    pub rows_above: usize,
+    /// Items marked for download
+    pub selected_items: BTreeSet<String>,
+}
+
+/// `$XDG_DATA_HOME/bannedcamp/library_view.json`, falling back to `~/.local/share`
+pub fn default_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("library_view.json"))
+}
+
+impl LibraryView {
+    /// A missing or unreadable file just means starting from scratch
+    pub fn load(path: &Path) -> Self {
+        load_json(path)
+            .inspect_err(|e| debug!("Ignoring unreadable library view: {e}"))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) {
+        if let Err(e) = save_json(path, self) {
+            debug!("Failed to write library view {}: {e}", path.display());
+        }
+    }
+
+    pub fn capture(state: &LibraryState) -> Self {
+        // Until the remembered item has been loaded, keep remembering it
+        let (highlighted, rows_above) = match &state.pending_highlight {
+            Some((id, rows_above)) => (Some(id.clone()), *rows_above),
+            None => (
+                state.selected_item().map(|item| item.id.clone()),
+                state.selected.saturating_sub(state.scroll_offset),
+            ),
+        };
+
+        Self {
+            search_query: state.search_query.clone(),
+            sort: state.sort,
+            show_hidden: state.show_hidden,
+            show_preview: state.show_preview,
+            highlighted,
+            rows_above,
+            selected_items: state.selected_items.iter().cloned().collect(),
+        }
+    }
+
+    /// Sets up `state` before the collection is fetched, the highlight moves to its item once
+    /// that arrives
+    pub fn apply(self, state: &mut LibraryState) {
+        state.search_query = self.search_query;
+        state.sort = self.sort;
+        state.show_hidden = self.show_hidden;
+        state.show_preview = self.show_preview;
+        state.selected_items = self.selected_items.into_iter().collect();
+        state.pending_highlight = self.highlighted.map(|id| (id, self.rows_above));
+        state.update_filter();
+    }
+}
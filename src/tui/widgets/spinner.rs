@@ -1,4 +1,4 @@
-const SPINNER_FRAMES: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+use crate::term::spinner::frames;
 
 pub struct Spinner {
     frame: usize,
@@ -10,11 +10,11 @@ impl Spinner {
     }
 
     pub fn tick(&mut self) {
-        self.frame = (self.frame + 1) % SPINNER_FRAMES.len();
+        self.frame = (self.frame + 1) % frames().len();
     }
 
     pub fn current(&self) -> &'static str {
-        SPINNER_FRAMES[self.frame]
+        frames()[self.frame]
     }
 }
 
@@ -0,0 +1,71 @@
+#![cfg(feature = "blocking")]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bannedcamp::BandcampError;
+use bannedcamp::blocking::Client;
+use bannedcamp::core::download::DownloadOptions;
+use bannedcamp::core::library::AudioFormat;
+
+mod common;
+
+#[test]
+fn test_blocking_client_reports_errors() {
+    let client = Client::new().unwrap();
+    assert!(matches!(
+        client.get_collection(),
+        Err(BandcampError::NotLoggedIn)
+    ));
+
+    // Never reaches the network, Bandcamp lists no download for it
+    let dir = tempfile::tempdir().unwrap();
+    let received = AtomicU64::new(0);
+    let result = client.download_item_with_progress(
+        &common::library_item(),
+        dir.path(),
+        AudioFormat::Flac,
+        None,
+        &DownloadOptions::default(),
+        |downloaded, _| received.store(downloaded, Ordering::Relaxed),
+    );
+    assert!(matches!(result, Err(BandcampError::StreamOnly)));
+    assert_eq!(received.load(Ordering::Relaxed), 0);
+}
+
+#[cfg(feature = "mock")]
+#[test]
+fn test_blocking_client_downloads_from_mock() {
+    use bannedcamp::core::library::ItemType;
+
+    // The mock server needs a runtime of its own, the blocking client brings one too
+    let server = tokio::runtime::Runtime::new().unwrap();
+    let mock = server.block_on(bannedcamp::mock::start()).unwrap();
+
+    let mut client = Client::with_client(mock.client()).unwrap();
+    assert_eq!(client.validate_cookie("anything").unwrap().fan_id, 1);
+
+    let items = client.get_collection().unwrap();
+    let track = items
+        .iter()
+        .find(|i| i.item_type == ItemType::Track && !i.is_preorder && !i.is_stream_only())
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let received = AtomicU64::new(0);
+    let path = client
+        .download_item_with_progress(
+            track,
+            dir.path(),
+            AudioFormat::Mp3320,
+            None,
+            &DownloadOptions::default(),
+            |downloaded, _| received.store(downloaded, Ordering::Relaxed),
+        )
+        .unwrap();
+
+    assert!(path.is_file());
+    assert_eq!(
+        received.load(Ordering::Relaxed),
+        std::fs::metadata(&path).unwrap().len()
+    );
+}
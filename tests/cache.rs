@@ -0,0 +1,17 @@
+use bannedcamp::tui::cache::LruCache;
+
+#[test]
+fn test_lru_cache_evicts_least_recently_used() {
+    let mut cache = LruCache::new(2);
+    cache.insert("a", 1);
+    cache.insert("b", 2);
+
+    // Reading "a" makes "b" the oldest entry
+    assert_eq!(cache.get(&"a"), Some(&1));
+    cache.insert("c", 3);
+
+    assert!(cache.contains(&"a"));
+    assert!(!cache.contains(&"b"));
+    assert_eq!(cache.peek(&"c"), Some(&3));
+    assert_eq!(cache.len(), 2);
+}
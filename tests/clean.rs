@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use bannedcamp::core::clean::{CleanTarget, Disposal, find_clean_targets, remove_target};
+use bannedcamp::core::state::{STATE_DIR, Scratch};
+
+#[test]
+fn test_quarantine_keeps_removed_entries_until_they_expire() {
+    let output = tempfile::tempdir().unwrap();
+    let quarantine = tempfile::tempdir().unwrap();
+    let album = output.path().join("Bad Math");
+    std::fs::create_dir_all(album.join("Missing Narrative.partial")).unwrap();
+    std::fs::write(album.join("Missing Narrative.partial/01.flac"), b"audio").unwrap();
+    std::fs::write(album.join(".123.tmp"), b"zip").unwrap();
+    std::fs::write(album.join("cover.jpg"), b"jpg").unwrap();
+    Scratch::track(
+        output.path(),
+        &[
+            album.join("Missing Narrative.partial"),
+            album.join(".123.tmp"),
+        ],
+    )
+    .unwrap();
+
+    let disposal = Disposal::Quarantine {
+        dir: quarantine.path().to_path_buf(),
+        ttl: Duration::from_secs(30 * 24 * 60 * 60),
+    };
+    for target in find_clean_targets(output.path()).unwrap() {
+        remove_target(&target, output.path(), &disposal).unwrap();
+    }
+    assert!(!album.join(".123.tmp").exists());
+    assert!(album.join("cover.jpg").exists());
+
+    let today = quarantine
+        .path()
+        .join(chrono::Local::now().format("%Y-%m-%d").to_string());
+    assert_eq!(
+        std::fs::read(today.join("Bad Math/Missing Narrative.partial/01.flac")).unwrap(),
+        b"audio"
+    );
+    assert!(today.join("Bad Math/.123.tmp").exists());
+
+    // A second leftover of the same name lands next to the first
+    std::fs::write(album.join(".123.tmp"), b"zip").unwrap();
+    disposal
+        .remove(&album.join(".123.tmp"), output.path())
+        .unwrap();
+    assert!(today.join("Bad Math/.123.tmp.1").exists());
+
+    // Only day folders past the TTL go, whatever else is in there stays
+    std::fs::create_dir(quarantine.path().join("2001-02-03")).unwrap();
+    std::fs::create_dir(quarantine.path().join("keep me")).unwrap();
+    assert_eq!(disposal.purge_expired().unwrap(), 1);
+    assert!(!quarantine.path().join("2001-02-03").exists());
+    assert!(quarantine.path().join("keep me").exists());
+    assert!(today.exists());
+}
+
+#[test]
+fn test_clean_only_removes_what_downloads_left_behind() {
+    let output = tempfile::tempdir().unwrap();
+    let root = output.path();
+    let artist = root.join("Bad Math");
+    std::fs::create_dir_all(artist.join("Missing Narrative")).unwrap();
+    std::fs::write(artist.join("Missing Narrative/01.flac"), b"audio").unwrap();
+    std::fs::write(artist.join(".1.tmp"), b"zip").unwrap();
+    std::fs::create_dir_all(root.join("Hypothermia/Winter Sessions")).unwrap();
+    std::fs::write(
+        root.join("Hypothermia/Winter Sessions/.tagging.01.flac"),
+        b"audio",
+    )
+    .unwrap();
+    Scratch::track(
+        root,
+        &[
+            artist.join("Missing Narrative"),
+            artist.join(".1.tmp"),
+            root.join("Hypothermia/Winter Sessions/.tagging.01.flac"),
+            root.join("gone.tmp"),
+        ],
+    )
+    .unwrap();
+
+    // Lookalikes nobody listed, and bannedcamp's own state, stay
+    std::fs::write(root.join(".notes.tmp"), b"mine").unwrap();
+    std::fs::create_dir_all(root.join("Sketches.partial")).unwrap();
+    std::fs::create_dir_all(root.join("Empty On Purpose")).unwrap();
+    let state = root.join(STATE_DIR);
+    std::fs::write(state.join(".manifest.json.lock"), b"").unwrap();
+    std::fs::write(state.join(".manifest.json.42-0.tmp"), b"{}").unwrap();
+
+    let mut targets = find_clean_targets(root).unwrap();
+    targets.sort_by(|a, b| a.path().cmp(b.path()));
+    assert_eq!(
+        targets,
+        vec![
+            CleanTarget::EmptyDir(artist.clone()),
+            CleanTarget::EmptyDir(root.join("Hypothermia")),
+        ]
+    );
+
+    for target in &targets {
+        remove_target(target, root, &Disposal::Delete).unwrap();
+    }
+    assert!(root.join(".notes.tmp").exists());
+    assert!(root.join("Sketches.partial").exists());
+    assert!(root.join("Empty On Purpose").exists());
+    assert!(state.join(".manifest.json.lock").exists());
+    assert!(state.join(".manifest.json.42-0.tmp").exists());
+
+    // Leftovers that are gone are dropped from the list, and a folder with a track of its
+    // own only loses the leftover
+    Scratch::forget_missing(root).unwrap();
+    assert_eq!(Scratch::load(root).unwrap().paths(root).count(), 0);
+    std::fs::create_dir_all(&artist).unwrap();
+    std::fs::write(artist.join("Demo.flac"), b"audio").unwrap();
+    std::fs::write(artist.join(".2.tmp"), b"zip").unwrap();
+    Scratch::track(root, &[artist.join(".2.tmp")]).unwrap();
+    assert_eq!(
+        find_clean_targets(root).unwrap(),
+        vec![CleanTarget::TempFile(artist.join(".2.tmp"))]
+    );
+}
@@ -0,0 +1,99 @@
+use bannedcamp::core::client::BandcampClient;
+use bannedcamp::core::library::ItemType;
+use serde_json::json;
+
+#[test]
+fn test_collection_page_survives_schema_changes() {
+    // A renamed type field and a band ID sent as a string fail the strict types, an item
+    // without a title can't be used at all
+    let page = json!({
+        "items": [
+            {
+                "band_id": 500,
+                "sale_item_id": 1001,
+                "sale_item_type": "a",
+                "item_type": "album",
+                "item_title": "Missing Narrative",
+                "band_name": "Bad Math",
+                "purchased": "14 Mar 2024 18:02:11 GMT",
+            },
+            {
+                "band_id": "501",
+                "sale_item_id": 1002,
+                "sale_item_type": "t",
+                "tralbum_type": "t",
+                "item_title": "Demo",
+                "band_name": "Bad Math",
+            },
+            {
+                "band_id": 502,
+                "sale_item_id": 1003,
+                "sale_item_type": "a",
+                "tralbum_type": "a",
+                "band_name": "Bad Math",
+            },
+        ],
+        "more_available": false,
+        "last_token": null,
+        "redownload_urls": {
+            "a1001": "https://bandcamp.com/download?payment_id=1001&sig=abc&sitem_id=1001",
+        },
+    });
+
+    let client = BandcampClient::new();
+    let parsed = client.parse_collection_page(page).unwrap();
+    assert_eq!(parsed.items.len(), 2);
+    assert_eq!(parsed.items[0].id, "1001");
+    assert_eq!(parsed.items[0].item_type, ItemType::Album);
+    assert_eq!(
+        parsed.items[0].download_url,
+        "https://bandcamp.com/download?payment_id=1001&sig=abc&sitem_id=1001"
+    );
+    assert_eq!(parsed.items[1].item_type, ItemType::Track);
+    assert_eq!(parsed.items[1].artist_id, "501");
+    assert!(parsed.items[1].is_stream_only());
+    assert!(!parsed.more_available);
+
+    assert!(
+        client
+            .parse_collection_page(json!({ "error": true }))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_item_details_from_release_page() {
+    let client = BandcampClient::new();
+    let html = r#"<script data-tralbum="{&quot;album_release_date&quot;:&quot;14 Mar 2024 00:00:00 GMT&quot;,&quot;trackinfo&quot;:[{&quot;title&quot;:&quot;Intro&quot;,&quot;duration&quot;:61.5},{&quot;title&quot;:&quot;Outro&quot;}],&quot;freeDownloadPage&quot;:&quot;https://bandcamp.com/download?id=1&quot;}"></script>"#;
+    let details = client.parse_item_details(html).unwrap();
+    assert_eq!(
+        details.release_date.as_deref(),
+        Some("14 Mar 2024 00:00:00 GMT")
+    );
+    assert_eq!(details.tracks.len(), 2);
+    assert_eq!(details.tracks[0].title, "Intro");
+    assert_eq!(details.tracks[0].duration_secs, 61.5);
+    // Tracks without a duration still list
+    assert_eq!(details.tracks[1].duration_secs, 0.0);
+    assert_eq!(
+        details.free_download_url.as_deref(),
+        Some("https://bandcamp.com/download?id=1")
+    );
+
+    // Single tracks keep their date under `current`, and paid releases have no free page
+    let html = r#"<div data-tralbum='{"current":{"release_date":"01 Jan 2020 00:00:00 GMT"}}'>"#;
+    let details = client.parse_item_details(html).unwrap();
+    assert_eq!(
+        details.release_date.as_deref(),
+        Some("01 Jan 2020 00:00:00 GMT")
+    );
+    assert!(details.tracks.is_empty());
+    assert!(details.free_download_url.is_none());
+
+    assert!(client.parse_item_details("<html></html>").is_err());
+    assert!(
+        client
+            .parse_item_details(r#"<div data-tralbum="{not json">"#)
+            .is_err()
+    );
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests, each test file pulls them in with `mod common;`
+
+use std::os::unix::fs::PermissionsExt;
+
+use bannedcamp::core::external::set_program;
+use bannedcamp::core::library::{ItemType, LibraryItem};
+
+/// An album by Bad Math without any links. Tests change what they care about with struct
+/// update syntax, `LibraryItem { title: ..., ..library_item() }`.
+#[allow(dead_code)]
+pub fn library_item() -> LibraryItem {
+    LibraryItem {
+        id: "1".to_string(),
+        item_type: ItemType::Album,
+        title: "Missing Narrative".to_string(),
+        artist: "Bad Math".to_string(),
+        artist_id: "2".to_string(),
+        artist_subdomain: None,
+        slug: None,
+        item_url: None,
+        download_url: String::new(),
+        is_preorder: false,
+        is_hidden: false,
+        purchase: Default::default(),
+        release_date: None,
+    }
+}
+
+/// Has bannedcamp run `script` wherever it would run the program `name` from PATH. The
+/// script lives in the returned directory, keep it around for as long as the test runs.
+#[allow(dead_code)]
+pub fn fake_program(name: &'static str, script: &str) -> tempfile::TempDir {
+    let bin = tempfile::tempdir().unwrap();
+    let program = bin.path().join(name);
+    std::fs::write(&program, script).unwrap();
+    std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+    set_program(name, program);
+    bin
+}
@@ -0,0 +1,87 @@
+use std::os::unix::fs::MetadataExt;
+
+use bannedcamp::core::dedupe::{LinkMode, find_duplicates, link_duplicates};
+use bannedcamp::core::state::Scratch;
+
+#[test]
+fn test_find_and_link_duplicates() {
+    let dir = tempfile::tempdir().unwrap();
+    let album = dir.path().join("Artist - Album");
+    std::fs::create_dir(&album).unwrap();
+
+    std::fs::write(album.join("01 Single.flac"), b"same audio").unwrap();
+    std::fs::write(dir.path().join("Artist - Single.flac"), b"same audio").unwrap();
+    std::fs::write(album.join("02 Other.flac"), b"different!").unwrap();
+    std::fs::write(album.join("cover.jpg"), b"same audio").unwrap();
+    // Still being written by a download
+    let unfinished = dir.path().join("Artist - Other Album");
+    std::fs::create_dir(&unfinished).unwrap();
+    std::fs::write(unfinished.join("01 Single.flac"), b"same audio").unwrap();
+    Scratch::track(dir.path(), &[&unfinished]).unwrap();
+
+    let groups = find_duplicates(dir.path(), false).unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].original(), album.join("01 Single.flac"));
+    assert_eq!(
+        groups[0].duplicates(),
+        &[dir.path().join("Artist - Single.flac")]
+    );
+    assert_eq!(groups[0].wasted_bytes(), 10);
+
+    assert_eq!(link_duplicates(&groups[0], LinkMode::Hardlink).unwrap(), 10);
+    let original = std::fs::metadata(album.join("01 Single.flac")).unwrap();
+    let linked = std::fs::metadata(dir.path().join("Artist - Single.flac")).unwrap();
+    assert_eq!(original.ino(), linked.ino());
+    assert_eq!(
+        std::fs::read(dir.path().join("Artist - Single.flac")).unwrap(),
+        b"same audio"
+    );
+
+    // Hardlinked files are the same file, not duplicates
+    assert!(find_duplicates(dir.path(), false).unwrap().is_empty());
+}
+
+#[test]
+fn test_find_duplicate_extras_across_formats() {
+    let dir = tempfile::tempdir().unwrap();
+    for format in ["flac", "mp3"] {
+        let album = dir.path().join(format).join("Artist - Album");
+        std::fs::create_dir_all(&album).unwrap();
+        std::fs::write(album.join(format!("01 Track.{format}")), format).unwrap();
+        std::fs::write(album.join("cover.jpg"), b"artwork").unwrap();
+        std::fs::write(album.join("booklet.pdf"), b"liner notes").unwrap();
+        std::fs::write(album.join(".bannedcamp"), b"state").unwrap();
+    }
+
+    assert!(find_duplicates(dir.path(), false).unwrap().is_empty());
+
+    let groups = find_duplicates(dir.path(), true).unwrap();
+    let originals: Vec<_> = groups.iter().map(|g| g.original().to_path_buf()).collect();
+    assert_eq!(
+        originals,
+        [
+            dir.path().join("flac/Artist - Album/booklet.pdf"),
+            dir.path().join("flac/Artist - Album/cover.jpg"),
+        ]
+    );
+    assert_eq!(
+        groups[1].duplicates(),
+        &[dir.path().join("mp3/Artist - Album/cover.jpg")]
+    );
+}
+
+#[test]
+fn test_failed_link_keeps_duplicate() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.flac"), b"audio").unwrap();
+    std::fs::write(dir.path().join("b.flac"), b"audio").unwrap();
+    std::fs::create_dir(dir.path().join("c.flac")).unwrap();
+
+    let mut groups = find_duplicates(dir.path(), false).unwrap();
+    // A directory can't be replaced by a file, so the rename fails
+    groups[0].files.push(dir.path().join("c.flac"));
+    assert!(link_duplicates(&groups[0], LinkMode::Hardlink).is_err());
+
+    assert_eq!(std::fs::read(dir.path().join("b.flac")).unwrap(), b"audio");
+    assert!(!dir.path().join(".c.flac.dedupe.tmp").exists());
+}
@@ -0,0 +1,38 @@
+use bannedcamp::core::discogs::{SearchResult, pick_release};
+use bannedcamp::core::library::LibraryItem;
+
+mod common;
+
+fn item(artist: &str, title: &str) -> LibraryItem {
+    LibraryItem {
+        title: title.to_string(),
+        artist: artist.to_string(),
+        ..common::library_item()
+    }
+}
+
+fn result(id: u64, title: &str, format: &[&str]) -> SearchResult {
+    SearchResult {
+        id,
+        title: title.to_string(),
+        format: format.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
+#[test]
+fn test_pick_release_prefers_digital() {
+    let results = [
+        result(1, "Bad Math - Other Album", &["File"]),
+        result(2, "Bad Math (2) - Missing Narrative", &["Vinyl", "LP"]),
+        result(3, "Bad Math (2) - Missing Narrative", &["File", "FLAC"]),
+    ];
+    assert_eq!(
+        pick_release(&results, &item("Bad Math", "Missing Narrative")),
+        Some(3)
+    );
+    assert_eq!(
+        pick_release(&results[..2], &item("bad math", "missing narrative")),
+        Some(2)
+    );
+    assert_eq!(pick_release(&results, &item("Bad Math", "Unknown")), None);
+}
@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use bannedcamp::BandcampError;
+use bannedcamp::core::client::parse_retry_after;
+
+fn http(status: u16, retry_after: Option<Duration>) -> BandcampError {
+    BandcampError::HttpStatus {
+        status,
+        url: "https://bandcamp.com/download".to_string(),
+        retry_after,
+    }
+}
+
+#[test]
+fn test_retryable_errors() {
+    assert!(http(503, None).is_retryable());
+    assert!(http(429, None).is_retryable());
+    assert!(!http(404, None).is_retryable());
+    assert!(BandcampError::BadDownload("empty response".to_string()).is_retryable());
+    assert!(!BandcampError::SessionExpired.is_retryable());
+    assert!(!BandcampError::FormatUnavailable("flac".to_string()).is_retryable());
+}
+
+#[test]
+fn test_error_info() {
+    let info = http(429, Some(Duration::from_secs(30))).info();
+    assert_eq!(info.kind, "http_status");
+    assert_eq!(info.status, Some(429));
+    assert_eq!(info.retry_after_secs, Some(30));
+    assert_eq!(info.url.as_deref(), Some("https://bandcamp.com/download"));
+    assert!(info.retryable);
+
+    let json = serde_json::to_value(BandcampError::StreamOnly.info()).unwrap();
+    assert_eq!(json["kind"], "stream_only");
+    assert_eq!(json["retryable"], false);
+    assert!(json.get("status").is_none());
+}
+
+#[test]
+fn test_parse_retry_after() {
+    assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon"), None);
+}
+
+#[tokio::test]
+async fn test_network_errors_hide_the_signature() {
+    // Nothing listens on port 1, so the request fails with the URL attached
+    let url = "http://127.0.0.1:1/download/album?enc=flac&id=1&sig=s3cr3t&payment_id=42";
+    let error = BandcampError::from(reqwest::get(url).await.unwrap_err());
+
+    let info = error.info();
+    assert_eq!(info.kind, "network");
+    for text in [info.message, info.url.unwrap()] {
+        assert!(text.contains("enc=flac"), "{text}");
+        assert!(!text.contains("s3cr3t"), "{text}");
+        assert!(!text.contains("payment_id=42"), "{text}");
+    }
+}
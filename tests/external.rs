@@ -0,0 +1,45 @@
+use bannedcamp::core::external::aria2c;
+use bannedcamp::error::BandcampError;
+
+mod common;
+
+/// Stands in for aria2c: saves its arguments and the input file it was given as the download,
+/// and fails for URLs ending in `/gone`
+const FAKE_ARIA2C: &str = r#"#!/bin/sh
+input=$(cat)
+dir=$(printf '%s\n' "$input" | sed -n 's/^  dir=//p')
+out=$(printf '%s\n' "$input" | sed -n 's/^  out=//p')
+case "$input" in
+    */gone*) echo "errorCode=3 Resource not found" >&2; exit 3 ;;
+esac
+printf 'args: %s\n%s\n' "$*" "$input" > "$dir/$out"
+"#;
+
+#[tokio::test]
+async fn test_aria2c_gets_the_request_through_stdin() {
+    let _bin = common::fake_program("aria2c", FAKE_ARIA2C);
+
+    let output = tempfile::tempdir().unwrap();
+    let file = output.path().join("album.zip");
+    let headers = vec!["cookie: identity=secret".to_string()];
+    aria2c("https://bandcamp.com/files/1", &headers, &file)
+        .await
+        .unwrap();
+
+    let saved = std::fs::read_to_string(&file).unwrap();
+    assert!(saved.contains("https://bandcamp.com/files/1\n"));
+    assert!(saved.contains("  out=album.zip\n"));
+    assert!(saved.contains("  header=cookie: identity=secret\n"));
+    // Never on the command line, where other users could read the cookie
+    let args = saved.lines().next().unwrap();
+    assert!(args.starts_with("args: --input-file=-"));
+    assert!(!args.contains("identity"));
+
+    let err = aria2c("https://bandcamp.com/files/gone", &headers, &file)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, BandcampError::BadDownload(message) if message.contains("Resource not found")),
+        "{err}"
+    );
+}
@@ -0,0 +1,416 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bannedcamp::BandcampError;
+use bannedcamp::core::artwork::{ArtworkPolicy, ArtworkTarget, MediaServer};
+use bannedcamp::core::client::{
+    content_disposition_filename, download_limit_cooldown, download_page_release_date,
+    is_maintenance_page, scrub_page,
+};
+use bannedcamp::core::download::{
+    check_download_start, extract_zip, extract_zip_into, extract_zip_with_checksums,
+    extract_zip_with_progress, sniff_extension,
+};
+use bannedcamp::core::format_map::ArtworkMap;
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::permissions::{OutputPermissions, lookup_group, lookup_user, parse_mode};
+use bannedcamp::core::target::{DirectoryTarget, ExtractTarget, ZipTarget};
+use zip::write::SimpleFileOptions;
+
+mod common;
+
+fn write_zip(path: &Path, entries: &[&str]) {
+    let file = std::fs::File::create(path).expect("failed to create zip");
+    let mut zip = zip::ZipWriter::new(file);
+
+    for entry in entries {
+        if entry.ends_with('/') {
+            zip.add_directory(*entry, SimpleFileOptions::default())
+                .expect("failed to add directory");
+        } else {
+            zip.start_file(*entry, SimpleFileOptions::default())
+                .expect("failed to start file");
+            zip.write_all(entry.as_bytes())
+                .expect("failed to write file");
+        }
+    }
+
+    zip.finish().expect("failed to finish zip");
+}
+
+#[test]
+fn test_extract_flattens_single_root_folder() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("album.zip");
+    write_zip(
+        &zip_path,
+        &[
+            "Artist - Album/",
+            "Artist - Album/01 Track.flac",
+            "Artist - Album/02 Track.flac",
+            "__MACOSX/._01 Track.flac",
+        ],
+    );
+
+    let out = tmp.path().join("Artist - Album");
+    extract_zip(&zip_path, &out).expect("extract_zip should succeed");
+
+    assert!(out.join("01 Track.flac").is_file());
+    assert!(out.join("02 Track.flac").is_file());
+    assert!(!out.join("Artist - Album").exists());
+}
+
+#[test]
+fn test_extract_into_zip_archive() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("download.zip");
+    write_zip(
+        &zip_path,
+        &[
+            "Artist - Album/",
+            "Artist - Album/01 Track.flac",
+            "Artist - Album/Extras/booklet.pdf",
+        ],
+    );
+
+    let out = tmp.path().join("Artist - Album.zip");
+    let mut target = ZipTarget::create(&out).expect("failed to create zip target");
+    extract_zip_into(&zip_path, &mut target, false, |_, _, _| {})
+        .expect("extract_zip_into should succeed");
+    assert!(!out.exists(), "archive should only appear once finished");
+    target.finish().expect("finish should succeed");
+    drop(target);
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&out).unwrap()).unwrap();
+    let mut names: Vec<_> = archive.file_names().map(str::to_string).collect();
+    names.sort();
+    assert_eq!(names, ["01 Track.flac", "Extras/booklet.pdf"]);
+
+    let mut contents = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name("01 Track.flac").unwrap(),
+        &mut contents,
+    )
+    .unwrap();
+    assert_eq!(contents, "Artist - Album/01 Track.flac");
+
+    // Only the finished archive is left
+    let leftovers: Vec<_> = std::fs::read_dir(tmp.path())
+        .unwrap()
+        .map(|e| e.unwrap().file_name())
+        .collect();
+    assert_eq!(leftovers.len(), 2);
+}
+
+#[test]
+fn test_artwork_policy() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("download.zip");
+    write_zip(
+        &zip_path,
+        &["01 Track.flac", "cover.jpg", "Extras/cover.jpg"],
+    );
+
+    let out = tmp.path().join("Kodi");
+    let mut inner = DirectoryTarget::new(&out).unwrap();
+    let mut target = ArtworkTarget::new(&mut inner, MediaServer::Kodi.artwork());
+    extract_zip_into(&zip_path, &mut target, false, |_, _, _| {}).unwrap();
+    assert!(out.join("folder.jpg").is_file());
+    assert!(!out.join("cover.jpg").exists());
+    // Only the album cover at the top is touched
+    assert!(out.join("Extras/cover.jpg").is_file());
+
+    // Kept for ffmpeg to embed into the tracks after extraction
+    let out = tmp.path().join("Plex");
+    let mut inner = DirectoryTarget::new(&out).unwrap();
+    let mut target = ArtworkTarget::new(&mut inner, MediaServer::Plex.artwork());
+    extract_zip_into(&zip_path, &mut target, false, |_, _, _| {}).unwrap();
+    assert!(out.join("01 Track.flac").is_file());
+    assert!(out.join("cover.jpg").is_file());
+
+    assert_eq!(
+        ArtworkPolicy::Folder.rename(Path::new("Cover.PNG")),
+        PathBuf::from("folder.png")
+    );
+    assert_eq!(
+        MediaServer::Navidrome
+            .artwork()
+            .rename(Path::new("cover.jpg")),
+        PathBuf::from("cover.jpg")
+    );
+
+    let map = ArtworkMap::parse("1 = embedded\n*Orchestra* = folder\n").unwrap();
+    assert!(map.uses(ArtworkPolicy::Embedded));
+    let item = common::library_item();
+    assert_eq!(
+        map.resolve(&item, ArtworkPolicy::Keep),
+        ArtworkPolicy::Embedded
+    );
+    let other = LibraryItem {
+        id: "2".to_string(),
+        ..item
+    };
+    assert_eq!(
+        map.resolve(&other, ArtworkPolicy::Keep),
+        ArtworkPolicy::Keep
+    );
+    assert!(ArtworkMap::parse("1 = cover").is_err());
+}
+
+#[test]
+fn test_extract_keeps_flat_archives() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("album.zip");
+    write_zip(
+        &zip_path,
+        &["01 Track.flac", "cover.jpg", "Extras/booklet.pdf"],
+    );
+
+    let out = tmp.path().join("out");
+    extract_zip(&zip_path, &out).expect("extract_zip should succeed");
+
+    assert!(out.join("01 Track.flac").is_file());
+    assert!(out.join("cover.jpg").is_file());
+    assert!(out.join("Extras/booklet.pdf").is_file());
+}
+
+#[test]
+fn test_extract_with_checksums() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("album.zip");
+    write_zip(
+        &zip_path,
+        &["Album/", "Album/01 Track.flac", "Album/Extras/booklet.pdf"],
+    );
+
+    let out = tmp.path().join("out");
+    let sums = extract_zip_with_checksums(&zip_path, &out)
+        .expect("extract_zip_with_checksums should succeed");
+
+    assert!(out.join("01 Track.flac").is_file());
+    assert!(out.join("Extras/booklet.pdf").is_file());
+
+    let paths: Vec<_> = sums.iter().map(|(p, _)| p.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("01 Track.flac"),
+            PathBuf::from("Extras/booklet.pdf")
+        ]
+    );
+
+    // sha256("Album/01 Track.flac"), the test archive stores each entry's name as its content
+    assert_eq!(
+        sums[0].1,
+        "765337ec3be697a907fc40bf9222f7e26a0235317d3f4bb78e893d2c2a381f38"
+    );
+}
+
+#[test]
+fn test_extract_reports_each_file() {
+    let tmp = tempfile::tempdir().expect("failed to create tempdir");
+    let zip_path = tmp.path().join("album.zip");
+    write_zip(
+        &zip_path,
+        &[
+            "Album/",
+            "Album/01 Track.flac",
+            "Album/Extras/",
+            "Album/02 Track.flac",
+        ],
+    );
+
+    let mut seen = Vec::new();
+    let sums = extract_zip_with_progress(
+        &zip_path,
+        &tmp.path().join("out"),
+        false,
+        |entry, done, total| {
+            seen.push((entry.to_path_buf(), done, total));
+        },
+    )
+    .expect("extract_zip_with_progress should succeed");
+
+    assert!(sums.is_empty());
+    assert_eq!(
+        seen,
+        vec![
+            (PathBuf::from("01 Track.flac"), 0, 2),
+            (PathBuf::from("02 Track.flac"), 1, 2)
+        ]
+    );
+}
+
+#[test]
+fn test_check_download_start_rejects_error_pages() {
+    let page = b"\n<!DOCTYPE html><html><head><title>Access Denied</title></head></html>";
+    let err = check_download_start(page, Some("application/zip"), true).unwrap_err();
+    assert!(matches!(err, BandcampError::BadDownload(_)));
+    assert!(err.is_retryable());
+    assert!(err.to_string().contains("Access Denied"));
+
+    assert!(check_download_start(b"", None, false).is_err());
+    assert!(check_download_start(b"ID3\x04", Some("text/html; charset=utf-8"), false).is_err());
+    assert!(check_download_start(b"fLaC\0\0", None, true).is_err());
+
+    assert!(check_download_start(b"PK\x03\x04", Some("application/zip"), true).is_ok());
+    assert!(check_download_start(b"fLaC\0\0", Some("audio/flac"), false).is_ok());
+}
+
+#[test]
+fn test_maintenance_page_means_site_down() {
+    let page = b"<html><head><title>Bandcamp</title></head>\
+        <body><h1>Bandcamp is down for maintenance</h1><p>We'll be back shortly.</p></body></html>";
+    assert!(is_maintenance_page(page));
+    let err = check_download_start(page, Some("text/html"), true).unwrap_err();
+    assert!(matches!(err, BandcampError::SiteDown));
+    assert!(!err.is_retryable());
+
+    // Large pages are real pages, whatever their descriptions say
+    let mut release = b"<html><body><p>Recorded during scheduled maintenance</p>".to_vec();
+    release.resize(64 * 1024, b' ');
+    assert!(!is_maintenance_page(&release));
+}
+
+#[test]
+fn test_download_limit_page() {
+    let page = b"<html><body><h2>Too many download attempts</h2>\
+        <p>Please try again in 30 minutes.</p></body></html>";
+    assert_eq!(
+        download_limit_cooldown(page),
+        Some(Duration::from_secs(30 * 60))
+    );
+    let err = check_download_start(page, Some("text/html"), true).unwrap_err();
+    assert!(matches!(err, BandcampError::DownloadLimited { .. }));
+    assert!(!err.is_retryable());
+    assert_eq!(err.retry_after(), Some(Duration::from_secs(30 * 60)));
+    assert!(err.to_string().contains("30 minutes"));
+
+    // Without a stated cool-down, wait an hour
+    assert_eq!(
+        download_limit_cooldown(b"<p>You have made too many downloads.</p>"),
+        Some(Duration::from_secs(60 * 60))
+    );
+    assert_eq!(
+        download_limit_cooldown(b"<p>Download again in 2 hours</p>"),
+        None
+    );
+}
+
+#[test]
+fn test_sniff_extension() {
+    assert_eq!(sniff_extension(b"PK\x03\x04\x14\0"), Some("zip"));
+    assert_eq!(sniff_extension(b"fLaC\0\0\0\x22"), Some("flac"));
+    assert_eq!(sniff_extension(b"ID3\x04\0"), Some("mp3"));
+    assert_eq!(sniff_extension(b"\xff\xfb\x90\x64"), Some("mp3"));
+    assert_eq!(sniff_extension(b"OggS\0\x02"), Some("ogg"));
+    assert_eq!(sniff_extension(b"RIFF\x24\0\0\0WAVEfmt "), Some("wav"));
+    assert_eq!(sniff_extension(b"FORM\0\0\0\0AIFFCOMM"), Some("aiff"));
+    assert_eq!(sniff_extension(b"\0\0\0\x20ftypM4A "), Some("m4a"));
+    // Too short or unknown, the item type decides
+    assert_eq!(sniff_extension(b"RIFF"), None);
+    assert_eq!(sniff_extension(b"<html>"), None);
+    assert_eq!(sniff_extension(b""), None);
+}
+
+#[test]
+fn test_content_disposition_filename() {
+    assert_eq!(
+        content_disposition_filename(r#"attachment; filename="Bad Math - Missing Narrative.zip""#)
+            .as_deref(),
+        Some("Bad Math - Missing Narrative.zip")
+    );
+    // The UTF-8 form wins over the ASCII fallback
+    assert_eq!(
+        content_disposition_filename(
+            "attachment; filename=\"Sigur R_s - ( ).flac\"; \
+             filename*=UTF-8''Sigur%20R%C3%B3s%20-%20%28%20%29.flac"
+        )
+        .as_deref(),
+        Some("Sigur Rós - ( ).flac")
+    );
+    assert_eq!(
+        content_disposition_filename("attachment; filename=track.mp3").as_deref(),
+        Some("track.mp3")
+    );
+    // Never a path, only the last component
+    assert_eq!(
+        content_disposition_filename(r#"attachment; filename="../../etc/passwd""#).as_deref(),
+        Some("passwd")
+    );
+    assert_eq!(
+        content_disposition_filename(r#"attachment; filename="..""#),
+        None
+    );
+    assert_eq!(content_disposition_filename("inline"), None);
+}
+
+#[test]
+fn test_scrub_page_for_debug_dump() {
+    let cookie = "7%09secret%3D%3D";
+    let html = format!(
+        r#"<div data-blob="{{&quot;url&quot;:&quot;https://p4.bcbits.com/download/album?id=1&amp;sig=abc123&amp;ts=9&amp;token=1_2_ffee&quot;}}"></div><script>var c = "{cookie}";</script>"#
+    );
+
+    let scrubbed = scrub_page(&html, cookie);
+    assert!(!scrubbed.contains("secret"));
+    assert!(!scrubbed.contains("abc123"));
+    assert!(scrubbed.contains("sig=REDACTED&amp;ts=9&amp;token=REDACTED&quot;"));
+    assert!(scrubbed.contains("p4.bcbits.com/download/album?id=1"));
+}
+
+#[test]
+fn test_download_page_release_date() {
+    let date = chrono::NaiveDate::from_ymd_opt(2021, 6, 20);
+    let escaped =
+        r#"<div data-blob="{&quot;release_date&quot;:&quot;20 Jun 2021 00:00:00 GMT&quot;}">"#;
+    assert_eq!(download_page_release_date(escaped), date);
+    let plain = r#"{"package_release_date":"20 Jun 2021 00:00:00 GMT"}"#;
+    assert_eq!(download_page_release_date(plain), date);
+    assert_eq!(download_page_release_date(r#"{"release_date":null}"#), None);
+}
+
+#[test]
+fn test_output_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    assert_eq!(parse_mode("664"), Some(0o664));
+    assert_eq!(parse_mode("0o2775"), Some(0o2775));
+    assert_eq!(parse_mode("888"), None);
+    assert_eq!(parse_mode("17777"), None);
+    assert_eq!(lookup_user("1000"), Some(1000));
+    assert_eq!(lookup_user("root"), Some(0));
+    assert_eq!(lookup_group("root"), Some(0));
+    assert_eq!(lookup_user("no-such-user-here"), None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let zip_path = dir.path().join("album.zip");
+    write_zip(
+        &zip_path,
+        &["Album/", "Album/cd1/01.flac", "Album/cover.jpg"],
+    );
+    let album = dir.path().join("album");
+    extract_zip(&zip_path, &album).unwrap();
+
+    let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+    assert_eq!(mode(&album.join("cd1")), 0o755);
+    assert_eq!(mode(&album.join("cover.jpg")), 0o644);
+
+    // A symlink out of the album leaves its target alone
+    let outside = dir.path().join("outside.txt");
+    std::fs::write(&outside, "private").unwrap();
+    std::fs::set_permissions(&outside, std::fs::Permissions::from_mode(0o600)).unwrap();
+    std::os::unix::fs::symlink(&outside, album.join("link.txt")).unwrap();
+
+    let permissions = OutputPermissions {
+        file_mode: 0o664,
+        dir_mode: 0o2775,
+        ..Default::default()
+    };
+    permissions.apply(&album).unwrap();
+    assert_eq!(mode(&album), 0o2775);
+    assert_eq!(mode(&album.join("cd1")), 0o2775);
+    assert_eq!(mode(&album.join("cd1/01.flac")), 0o664);
+    assert_eq!(mode(&outside), 0o600);
+}
@@ -0,0 +1,75 @@
+use bannedcamp::core::format_map::FormatMap;
+use bannedcamp::core::library::{AudioFormat, LibraryItem};
+
+mod common;
+
+fn item(id: &str, artist: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        artist: artist.to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_format_map_rules() {
+    let map = FormatMap::parse(
+        "# keep the big ones lossless\n\
+         \n\
+         1234567890 = WAV\n\
+         \t*orchestra* =flac  \n\
+         Bad Math = mp3-v0\n\
+         * = ogg\n",
+    )
+    .unwrap();
+
+    // An item ID rule wins over the globs after it, then the first matching glob does
+    assert_eq!(
+        map.resolve(&item("1234567890", "Berlin Orchestra"), AudioFormat::Mp3320),
+        AudioFormat::Wav
+    );
+    assert_eq!(
+        map.resolve(&item("2", "BERLIN ORCHESTRA"), AudioFormat::Mp3320),
+        AudioFormat::Flac
+    );
+    assert_eq!(
+        map.resolve(&item("3", "bad math"), AudioFormat::Mp3320),
+        AudioFormat::Mp3V0
+    );
+    assert_eq!(
+        map.resolve(&item("4", "Hypothermia"), AudioFormat::Mp3320),
+        AudioFormat::OggVorbis
+    );
+    assert!(map.uses(AudioFormat::Flac));
+    assert!(!map.uses(AudioFormat::Aiff));
+}
+
+#[test]
+fn test_format_map_falls_back_to_the_default() {
+    let map = FormatMap::parse("1 = wav\nHypothermia = flac\n").unwrap();
+    // Digits only match the item ID, never an artist named like one
+    assert_eq!(
+        map.resolve(&item("2", "1"), AudioFormat::Aac),
+        AudioFormat::Aac
+    );
+    assert_eq!(
+        FormatMap::default().resolve(&item("1", "Hypothermia"), AudioFormat::Alac),
+        AudioFormat::Alac
+    );
+}
+
+#[test]
+fn test_format_map_rejects_invalid_lines() {
+    let err = FormatMap::parse("1 = wav\n\nBad Math flac\n").unwrap_err();
+    assert_eq!(err, "line 3: expected `pattern = value`");
+
+    let err = FormatMap::parse("Bad Math = mp4\n").unwrap_err();
+    assert_eq!(err, "line 1: unknown value \"mp4\"");
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("formats.txt");
+    std::fs::write(&path, "Bad Math = mp4\n").unwrap();
+    let err = FormatMap::load(&path).unwrap_err().to_string();
+    assert!(err.contains("formats.txt: line 1"));
+    assert!(FormatMap::load(&dir.path().join("missing.txt")).is_err());
+}
@@ -0,0 +1,74 @@
+use bannedcamp::cli::export::{ExportTargets, run_export};
+use bannedcamp::core::download::DownloadSummary;
+use bannedcamp::core::gallery::write_gallery;
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::state::Manifest;
+
+mod common;
+
+fn item(id: &str, title: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        title: title.to_string(),
+        artist_subdomain: Some("badmathhk".to_string()),
+        slug: Some("missing-narrative".to_string()),
+        item_url: Some("https://badmathhk.bandcamp.com/album/missing-narrative".to_string()),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_gallery_links_downloads_and_covers() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("music");
+    let album = output.join("Bad Math - Missing Narrative");
+    std::fs::create_dir_all(&album).unwrap();
+    std::fs::write(album.join("folder.JPG"), b"jpeg").unwrap();
+    std::fs::write(album.join("01 Intro.flac"), b"flac").unwrap();
+
+    let mut manifest = Manifest::load(&output).unwrap();
+    manifest.record(
+        &output,
+        &DownloadSummary {
+            succeeded: vec![(item("1", "Missing Narrative"), album.clone())],
+            ..Default::default()
+        },
+    );
+
+    let gallery = dir.path().join("gallery");
+    let items = [
+        item("1", "Missing Narrative"),
+        item("2", "<Not> Downloaded"),
+    ];
+    let entries = write_gallery(&gallery, &output, &items, &manifest).unwrap();
+
+    assert_eq!(
+        entries[0].local.as_deref(),
+        Some("../music/Bad%20Math%20-%20Missing%20Narrative/")
+    );
+    assert_eq!(entries[0].cover.as_deref(), Some("covers/1.jpg"));
+    assert_eq!(
+        std::fs::read(gallery.join("covers/1.jpg")).unwrap(),
+        b"jpeg"
+    );
+    assert_eq!(entries[1].local, None);
+    assert_eq!(entries[1].cover, None);
+
+    let html = std::fs::read_to_string(gallery.join("index.html")).unwrap();
+    assert!(html.contains("2 items, 1 downloaded"));
+    assert!(html.contains("&lt;Not&gt; Downloaded"));
+}
+
+#[tokio::test]
+async fn test_export_without_snapshot() {
+    let dir = tempfile::tempdir().unwrap();
+    let targets = ExportTargets {
+        html: Some(&dir.path().join("gallery")),
+        discogs_token: None,
+        listenbrainz_token: None,
+        dry_run: false,
+    };
+    let err = run_export(dir.path(), targets).await.unwrap_err();
+    assert_eq!(err.kind(), "no_snapshot");
+    assert!(!dir.path().join("gallery").exists());
+}
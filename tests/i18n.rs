@@ -0,0 +1,23 @@
+use bannedcamp::i18n::{Lang, Msg, fill};
+
+#[test]
+fn test_translations_keep_placeholders() {
+    for msg in Msg::ALL {
+        let expected = msg.text(Lang::En).matches("{}").count();
+        for lang in Lang::ALL {
+            assert_eq!(
+                msg.text(lang).matches("{}").count(),
+                expected,
+                "{msg:?} in {lang:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_lang_from_locale() {
+    assert_eq!(Lang::from_locale("de_AT.UTF-8"), Lang::De);
+    assert_eq!(Lang::from_locale("es"), Lang::Es);
+    assert_eq!(Lang::from_locale("C"), Lang::En);
+    assert_eq!(fill("{}/{} complete", &[&3, &7]), "3/7 complete");
+}
@@ -1,10 +1,8 @@
-use std::future::Future;
-use std::pin::Pin;
-
 use bannedcamp::core::auth::Credentials;
 use bannedcamp::core::client::BandcampClient;
-use bannedcamp::core::download::{DownloadProgressReporter, download_item};
+use bannedcamp::core::download::{DownloadOptions, download_item};
 use bannedcamp::core::library::AudioFormat;
+use bannedcamp::core::queue::SilentReporter;
 
 fn get_cookie() -> Option<String> {
     std::env::var("BANDCAMP_COOKIE").ok()
@@ -19,38 +17,6 @@ async fn authenticated_client(cookie: &str) -> (BandcampClient, Credentials) {
     (client, creds)
 }
 
-struct NoopReporter;
-
-impl DownloadProgressReporter for NoopReporter {
-    fn on_start(&self, _total_size: Option<u64>) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-
-    fn on_fetching_url(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-
-    fn on_progress(
-        &self,
-        _downloaded: u64,
-        _total: Option<u64>,
-    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-
-    fn on_extracting(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-
-    fn on_complete(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-
-    fn on_error(&self, _error: &str) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
-        Box::pin(async {})
-    }
-}
-
 #[tokio::test]
 async fn test_validate_cookie() {
     let Some(cookie) = get_cookie() else {
@@ -208,7 +174,8 @@ async fn test_download_item() {
         tmp.path(),
         AudioFormat::Mp3320,
         None,
-        NoopReporter,
+        &DownloadOptions::default(),
+        SilentReporter,
     )
     .await
     .expect("download_item should succeed");
@@ -0,0 +1,421 @@
+#![cfg(feature = "mock")]
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::Datelike;
+
+use bannedcamp::BandcampError;
+use bannedcamp::cli::resolve::resolve_links;
+use bannedcamp::core::clean::{CleanTarget, find_clean_targets};
+use bannedcamp::core::client::{BandcampClient, CollectionPaging, set_collection_paging};
+use bannedcamp::core::download::{
+    DownloadOptions, DownloadProgressReporter, EXTRAS_DIR, download_item,
+};
+use bannedcamp::core::library::{AudioFormat, ItemType, LibraryItem, parse_release_date};
+use bannedcamp::core::queue::SilentReporter;
+use bannedcamp::core::routing::RouteMap;
+use bannedcamp::core::state::{STATE_DIR, Scratch};
+use bannedcamp::mock::MockServer;
+use bannedcamp::mock::fixtures::{collection_response, library_item};
+
+mod common;
+
+/// A client of `server` with its fan logged in, and the collection it serves
+async fn logged_in(server: &MockServer) -> (BandcampClient, Vec<LibraryItem>) {
+    let mut client = server.client();
+    client.validate_cookie("anything").await.unwrap();
+    let items = client.get_collection().await.unwrap();
+    (client, items)
+}
+
+fn downloadable(items: &[LibraryItem], item_type: ItemType) -> &LibraryItem {
+    items
+        .iter()
+        .find(|i| i.item_type == item_type && !i.is_preorder && !i.is_stream_only())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_mock_collection() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    // Small pages so the collection takes several requests, like a real account
+    set_collection_paging(CollectionPaging {
+        page_size: 3,
+        max_items: None,
+    });
+
+    let mut client = server.client();
+    let creds = client.validate_cookie("anything").await.unwrap();
+    assert_eq!(creds.fan_id, 1);
+    // What --downloader passes on to the external program
+    let headers = client.download_headers().unwrap();
+    assert!(headers.contains(&"cookie: identity=anything".to_string()));
+
+    let items = client.get_collection().await.unwrap();
+    assert_eq!(items.len(), 7);
+    assert_eq!(client.requests().counts().collection_pages, 3);
+    let expected = bannedcamp::mock::library(server.url()).unwrap();
+    for (item, expected) in items.iter().zip(&expected) {
+        assert_eq!(item.id, expected.id);
+        assert_eq!(item.item_url, expected.item_url);
+        assert_eq!(item.download_url, expected.download_url);
+        assert_eq!(item.purchase, expected.purchase);
+    }
+    assert!(items.iter().any(|i| i.is_preorder));
+    assert!(items.iter().any(|i| i.is_hidden));
+    assert!(items.iter().any(|i| i.is_stream_only()));
+}
+
+#[tokio::test]
+async fn test_mock_fan_pages() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+
+    // Another fan's collection never comes with redownload links
+    assert_eq!(client.get_fan_id("mock").await.unwrap(), 1);
+    let friend = client.get_fan_id("friend").await.unwrap();
+    assert_eq!(friend, 2);
+    assert!(client.get_fan_id("nobody").await.is_err());
+    let shared = client.get_fan_collection(friend).await.unwrap();
+    assert!(!shared.is_empty());
+    assert!(shared.iter().all(|i| i.is_stream_only() && !i.is_hidden));
+
+    // A free release the friend has and the logged in fan doesn't, its page links the download
+    let (free, owned): (Vec<_>, Vec<_>) = shared
+        .iter()
+        .partition(|i| !items.iter().any(|owned| owned.item_url == i.item_url));
+    let [free] = free.as_slice() else {
+        panic!("expected one free release, got {free:?}");
+    };
+    let details = client.get_item_details(owned[0]).await.unwrap();
+    assert_eq!(details.free_download_url, None);
+    let details = client.get_item_details(free).await.unwrap();
+    let url = details.free_download_url.unwrap();
+    assert_eq!(client.requests().counts().item_pages, 2);
+
+    let free = LibraryItem {
+        download_url: url,
+        ..(*free).clone()
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = download_item(
+        &client,
+        &free,
+        dir.path(),
+        AudioFormat::Flac,
+        None,
+        &DownloadOptions::default(),
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+    assert!(path.is_file());
+}
+
+#[tokio::test]
+async fn test_mock_item_details() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let album = downloadable(&items, ItemType::Album);
+
+    let details = client.get_item_details(album).await.unwrap();
+    assert!(!details.tracks.is_empty());
+
+    let mut dated = vec![album.clone()];
+    client.fill_release_dates(&mut dated).await;
+    assert_eq!(
+        dated[0].release_date,
+        details.release_date.as_deref().and_then(parse_release_date)
+    );
+    assert!(dated[0].release_date.is_some());
+}
+
+#[tokio::test]
+async fn test_mock_album_download() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let album = downloadable(&items, ItemType::Album);
+    let details = client.get_item_details(album).await.unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = download_item(
+        &client,
+        album,
+        dir.path(),
+        AudioFormat::Mp3320,
+        None,
+        &DownloadOptions::default(),
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+
+    let tracks = std::fs::read_dir(&path)
+        .unwrap()
+        .filter(|e| e.as_ref().unwrap().path().extension().unwrap() == "mp3")
+        .count();
+    assert_eq!(tracks, details.tracks.len());
+    assert_eq!(client.requests().counts().download_pages, 1);
+    // Nothing is left for `clean` once the album is complete
+    assert_eq!(
+        Scratch::load(dir.path()).unwrap().paths(dir.path()).count(),
+        0
+    );
+}
+
+/// Lets the test know once the first bytes of a download are on disk
+#[derive(Default)]
+struct FirstChunk(tokio::sync::Notify);
+
+impl DownloadProgressReporter for FirstChunk {
+    fn on_progress(
+        &self,
+        _downloaded: u64,
+        _total: Option<u64>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        self.0.notify_one();
+        Box::pin(async {})
+    }
+}
+
+#[tokio::test]
+async fn test_mock_clean_finds_routed_leftovers() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let album = downloadable(&items, ItemType::Album);
+
+    // What the queue does with --route-map: the item goes below the root, its state doesn't
+    let root = tempfile::tempdir().unwrap();
+    let routes = RouteMap::parse("type:album = Albums\n").unwrap();
+    let albums = routes.resolve(album, AudioFormat::Flac, root.path());
+    let options = DownloadOptions {
+        output_root: Some(root.path().to_path_buf()),
+        ..Default::default()
+    };
+
+    // Interrupted after the first chunk, like a killed process
+    let reporter = FirstChunk::default();
+    tokio::select! {
+        result = download_item(
+            &client,
+            album,
+            &albums,
+            AudioFormat::Flac,
+            None,
+            &options,
+            &reporter,
+        ) => panic!("download finished: {result:?}"),
+        _ = reporter.0.notified() => {}
+    }
+
+    // Nor did the claim on the item, so the routed folder only holds the leftover
+    assert!(albums.join(format!(".{}.tmp", album.id)).is_file());
+    assert!(!albums.join(STATE_DIR).exists());
+    let targets = find_clean_targets(root.path()).unwrap();
+    assert_eq!(targets, [CleanTarget::EmptyDir(albums)]);
+}
+
+#[tokio::test]
+async fn test_mock_release_year_from_download_page() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let album = downloadable(&items, ItemType::Album);
+    assert_eq!(album.release_date, None);
+    let other_requests = client.requests().counts().other;
+
+    let dir = tempfile::tempdir().unwrap();
+    let options = DownloadOptions {
+        nfo: true,
+        ..Default::default()
+    };
+    let path = download_item(
+        &client,
+        album,
+        dir.path(),
+        AudioFormat::Flac,
+        Some("{artist}/{year} - {title}"),
+        &options,
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+
+    // Read off the download page, without asking the album page
+    let year = client.release_date(album).unwrap().year();
+    assert_eq!(
+        path,
+        dir.path()
+            .join(&album.artist)
+            .join(format!("{year} - {}", album.title))
+    );
+    let nfo = std::fs::read_to_string(path.join("album.nfo")).unwrap();
+    assert!(nfo.contains(&format!("<year>{year}</year>")));
+    // Only the one starting the encoding, the album page was never asked for
+    assert_eq!(client.requests().counts().other, other_requests + 1);
+}
+
+#[tokio::test]
+async fn test_mock_resolve_links() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let downloadable = items.iter().filter(|i| !i.is_stream_only()).count();
+
+    let mut links = Vec::new();
+    let failed = resolve_links(
+        Arc::new(client),
+        items.clone(),
+        AudioFormat::Flac,
+        |item, url| links.push((item.id.clone(), url.to_string())),
+    )
+    .await;
+
+    assert_eq!(links.len(), downloadable);
+    assert!(
+        links
+            .iter()
+            .all(|(id, url)| url.contains(&format!("/files/{id}?enc=flac")))
+    );
+    assert_eq!(failed.len(), items.len() - downloadable);
+    assert!(
+        failed
+            .iter()
+            .all(|(_, e)| matches!(e, BandcampError::StreamOnly))
+    );
+}
+
+/// Stands in for ffprobe, rejecting every file like a corrupt one
+const BROKEN_FFPROBE: &str =
+    "#!/bin/sh\necho 'Invalid data found when processing input' >&2\nexit 1\n";
+
+#[tokio::test]
+async fn test_mock_verify_audio_discards_broken_downloads() {
+    let _bin = common::fake_program("ffprobe", BROKEN_FFPROBE);
+
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let dir = tempfile::tempdir().unwrap();
+    let options = DownloadOptions {
+        verify_audio: true,
+        checksums: true,
+        ..Default::default()
+    };
+
+    for item in [
+        downloadable(&items, ItemType::Album),
+        downloadable(&items, ItemType::Track),
+    ] {
+        let err = download_item(
+            &client,
+            item,
+            dir.path(),
+            AudioFormat::Flac,
+            None,
+            &options,
+            SilentReporter,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Undecodable audio"), "{err}");
+    }
+    // Nothing left for --skip-existing to take for a finished download
+    let left: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name != STATE_DIR)
+        .collect();
+    assert!(left.is_empty(), "{left:?}");
+}
+
+#[tokio::test]
+async fn test_mock_bonus_items() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let album = downloadable(&items, ItemType::Album);
+
+    let dir = tempfile::tempdir().unwrap();
+    let options = DownloadOptions {
+        include_bonus_items: true,
+        ..Default::default()
+    };
+    let path = download_item(
+        &client,
+        album,
+        dir.path(),
+        AudioFormat::Flac,
+        None,
+        &options,
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+    let booklet = path.join(EXTRAS_DIR).join("Liner Notes.pdf");
+    assert!(std::fs::read(booklet).unwrap().starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_mock_keep_original_names() {
+    let server = bannedcamp::mock::start().await.unwrap();
+    let (client, items) = logged_in(&server).await;
+    let track = downloadable(&items, ItemType::Track);
+
+    // The name Bandcamp serves wins over the template
+    let dir = tempfile::tempdir().unwrap();
+    let options = DownloadOptions {
+        keep_original_names: true,
+        ..Default::default()
+    };
+    let path = download_item(
+        &client,
+        track,
+        dir.path(),
+        AudioFormat::Mp3320,
+        Some("{id}{ext}"),
+        &options,
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+    assert_eq!(path, dir.path().join("Loopback - 127.0.0.1.mp3"));
+}
+
+#[test]
+fn test_fixture_collection_response() {
+    let album = library_item(1001, ItemType::Album, "Bad Math", "Missing Narrative");
+    assert_eq!(album.artist_subdomain.as_deref(), Some("badmath"));
+    assert_eq!(
+        album.item_url.as_deref(),
+        Some("https://badmath.bandcamp.com/album/missing-narrative")
+    );
+
+    let mut stream_only = library_item(1002, ItemType::Track, "Bad Math", "Demo");
+    stream_only.download_url.clear();
+
+    let page = collection_response(&[album.clone(), stream_only], true, Some("token"));
+    assert_eq!(page["items"].as_array().unwrap().len(), 2);
+    assert_eq!(page["items"][0]["purchased"], "14 Mar 2024 18:02:11 GMT");
+    assert_eq!(
+        page["redownload_urls"]["a1001"],
+        album.download_url.as_str()
+    );
+    assert!(page["redownload_urls"].get("t1002").is_none());
+    assert_eq!(page["last_token"], "token");
+}
+
+#[tokio::test]
+async fn test_mock_servers_run_side_by_side() {
+    let first = bannedcamp::mock::start().await.unwrap();
+    let second = bannedcamp::mock::start().await.unwrap();
+    assert_ne!(first.url(), second.url());
+
+    for server in [&first, &second] {
+        let mut client = server.client();
+        assert_eq!(client.base_url(), server.url());
+        assert_eq!(client.validate_cookie("anything").await.unwrap().fan_id, 1);
+    }
+
+    // Stopping one leaves the other serving
+    drop(first);
+    let mut client = second.client();
+    assert!(client.validate_cookie("anything").await.is_ok());
+}
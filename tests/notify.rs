@@ -0,0 +1,160 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use bannedcamp::BandcampError;
+use bannedcamp::core::download::DownloadSummary;
+use bannedcamp::core::notify::{Notifier, NotifyTarget};
+use bannedcamp::core::queue::BatchProgressReporter;
+
+mod common;
+
+/// A request the test server got, header names lowercased
+struct Received {
+    request_line: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Hands every request to the test and answers it with `status` after `delay`
+fn serve(status: u16, delay: Duration) -> (String, mpsc::Receiver<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let server = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                return;
+            };
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut headers = Vec::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let Some((key, value)) = line.trim_end().split_once(": ") else {
+                    break;
+                };
+                headers.push((key.to_ascii_lowercase(), value.to_string()));
+            }
+            let length = headers
+                .iter()
+                .find(|(key, _)| key == "content-length")
+                .map_or(0, |(_, value)| value.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            let _ = tx.send(Received {
+                request_line: request_line.trim_end().to_string(),
+                headers,
+                body: String::from_utf8(body).unwrap(),
+            });
+            // Handed over before answering, so it is there once the notifier has its reply
+            std::thread::sleep(delay);
+            let _ = write!(
+                stream,
+                "HTTP/1.1 {status} Whatever\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+        }
+    });
+
+    (server, rx)
+}
+
+#[tokio::test]
+async fn test_failures_are_sent_in_the_background() {
+    let (server, rx) = serve(200, Duration::from_millis(500));
+    let notifier = Notifier::new(vec![NotifyTarget::Ntfy {
+        server: format!("{server}/"),
+        topic: "downloads".to_string(),
+        token: Some("secret".to_string()),
+    }])
+    .unwrap();
+
+    let item = common::library_item();
+    let started = Instant::now();
+    notifier
+        .on_item_finished(0, &item, &Err(BandcampError::StreamOnly))
+        .await;
+    notifier
+        .on_item_finished(1, &item, &Ok(PathBuf::from("/music/album")))
+        .await;
+    // The slow server doesn't hold up the queue
+    assert!(started.elapsed() < Duration::from_millis(400));
+
+    notifier.flush().await;
+    let received = rx.try_recv().unwrap();
+    assert_eq!(received.request_line, "POST /downloads HTTP/1.1");
+    assert_eq!(
+        received.header("title"),
+        Some("Failed: Bad Math - Missing Narrative")
+    );
+    assert_eq!(received.header("priority"), Some("high"));
+    assert_eq!(received.header("authorization"), Some("Bearer secret"));
+    assert_eq!(received.body, BandcampError::StreamOnly.to_string());
+    // Nothing for the item that downloaded
+    assert!(rx.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_summary_goes_to_gotify() {
+    let (server, rx) = serve(200, Duration::ZERO);
+    let notifier = Notifier::new(vec![NotifyTarget::Gotify {
+        server,
+        token: "app-token".to_string(),
+    }])
+    .unwrap();
+
+    let item = common::library_item();
+    notifier
+        .send_summary(&DownloadSummary {
+            succeeded: vec![(item.clone(), PathBuf::from("/music/album"))],
+            failed: vec![(item, "Download failed: 404".to_string())],
+            ..Default::default()
+        })
+        .await;
+
+    let received = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+    assert_eq!(received.request_line, "POST /message HTTP/1.1");
+    assert_eq!(received.header("x-gotify-key"), Some("app-token"));
+    let message: serde_json::Value = serde_json::from_str(&received.body).unwrap();
+    assert_eq!(message["title"], "Downloaded 1 of 2 items");
+    assert_eq!(message["priority"], 8);
+    assert!(
+        message["message"]
+            .as_str()
+            .unwrap()
+            .contains("Bad Math - Missing Narrative: Download failed: 404")
+    );
+}
+
+#[tokio::test]
+async fn test_delivery_problems_are_only_logged() {
+    let (server, rx) = serve(500, Duration::ZERO);
+    let notifier = Notifier::new(vec![NotifyTarget::Ntfy {
+        server,
+        topic: "downloads".to_string(),
+        token: None,
+    }])
+    .unwrap();
+
+    notifier
+        .on_item_finished(0, &common::library_item(), &Err(BandcampError::StreamOnly))
+        .await;
+    notifier.flush().await;
+    assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+}
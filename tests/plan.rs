@@ -0,0 +1,181 @@
+use bannedcamp::core::download::DownloadSummary;
+use bannedcamp::core::library::{AudioFormat, ItemType, LibraryItem};
+use bannedcamp::core::plan::{NameChange, PlanStatus, audit_names, plan_status, recorded_status};
+use bannedcamp::core::report::{ReportFormat, render_naming_report};
+use bannedcamp::core::state::Manifest;
+use bannedcamp::core::utils::SanitizeOptions;
+use chrono::Utc;
+
+mod common;
+
+fn item(item_type: ItemType) -> LibraryItem {
+    LibraryItem {
+        item_type,
+        download_url: "https://bandcamp.com/download".to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_plan_status_compares_against_output_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let album = item(ItemType::Album);
+    let album_dir = dir.path().join("Bad Math - Missing Narrative");
+
+    assert_eq!(
+        plan_status(&album, &album_dir, AudioFormat::Flac),
+        PlanStatus::New
+    );
+
+    std::fs::create_dir(&album_dir).unwrap();
+    std::fs::write(album_dir.join("01 Intro.mp3"), b"").unwrap();
+    std::fs::write(album_dir.join("cover.jpg"), b"").unwrap();
+    assert_eq!(
+        plan_status(&album, &album_dir, AudioFormat::Flac),
+        PlanStatus::OtherFormat("mp3".to_string())
+    );
+    assert_eq!(
+        plan_status(&album, &album_dir, AudioFormat::Mp3320),
+        PlanStatus::Exists
+    );
+
+    let track = item(ItemType::Track);
+    let track_path = dir.path().join("Bad Math - Missing Narrative.flac");
+    std::fs::write(dir.path().join("Bad Math - Missing Narrative.ogg"), b"").unwrap();
+    assert_eq!(
+        plan_status(&track, &track_path, AudioFormat::Flac),
+        PlanStatus::OtherFormat("ogg".to_string())
+    );
+    std::fs::write(&track_path, b"").unwrap();
+    assert_eq!(
+        plan_status(&track, &track_path, AudioFormat::Flac),
+        PlanStatus::Exists
+    );
+
+    // A one-track album that came as a bare file next to where its folder would be
+    let single = dir.path().join("Clark Rainbow - Chainsaw");
+    assert_eq!(
+        plan_status(&album, &single, AudioFormat::Flac),
+        PlanStatus::New
+    );
+    std::fs::write(dir.path().join("Clark Rainbow - Chainsaw.flac"), b"").unwrap();
+    assert_eq!(
+        plan_status(&album, &single, AudioFormat::Flac),
+        PlanStatus::Exists
+    );
+}
+
+#[test]
+fn test_recorded_status_finds_downloads_under_other_names() {
+    let dir = tempfile::tempdir().unwrap();
+    let track = item(ItemType::Track);
+    let track_path = dir.path().join("Bad Math - Missing Narrative.flac");
+
+    // A track served as a zip with extras ends up in a folder
+    let folder = dir.path().join("Bad Math - Missing Narrative");
+    std::fs::create_dir(&folder).unwrap();
+    std::fs::write(folder.join("Missing Narrative.flac"), b"").unwrap();
+    assert_eq!(
+        plan_status(&track, &track_path, AudioFormat::Flac),
+        PlanStatus::Exists
+    );
+    std::fs::remove_dir_all(&folder).unwrap();
+
+    // Downloaded under the name Bandcamp served, only the manifest knows where
+    let served = dir.path().join("Loopback - 127.0.0.1.mp3");
+    std::fs::write(&served, b"").unwrap();
+    let mut manifest = Manifest::default();
+    manifest.record(
+        dir.path(),
+        &DownloadSummary {
+            succeeded: vec![(track.clone(), served)],
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        plan_status(&track, &track_path, AudioFormat::Flac),
+        PlanStatus::New
+    );
+    assert_eq!(
+        recorded_status(
+            &track,
+            &track_path,
+            AudioFormat::Mp3320,
+            &manifest,
+            dir.path()
+        ),
+        PlanStatus::Exists
+    );
+    assert_eq!(
+        recorded_status(
+            &track,
+            &track_path,
+            AudioFormat::Flac,
+            &manifest,
+            dir.path()
+        ),
+        PlanStatus::OtherFormat("mp3".to_string())
+    );
+}
+
+#[test]
+fn test_format_fallbacks_prefer_same_class() {
+    let fallbacks = AudioFormat::Flac.fallbacks();
+    assert_eq!(
+        &fallbacks[..3],
+        &[AudioFormat::Alac, AudioFormat::Aiff, AudioFormat::Wav]
+    );
+    assert!(!fallbacks.contains(&AudioFormat::Flac));
+
+    let fallbacks = AudioFormat::Mp3V0.fallbacks();
+    assert_eq!(fallbacks[0], AudioFormat::Mp3320);
+    assert_eq!(fallbacks.last(), Some(&AudioFormat::Wav));
+}
+
+#[test]
+fn test_audit_names_finds_sanitized_and_colliding_items() {
+    let sanitize = SanitizeOptions::default();
+    let named = |id: &str, artist: &str, title: &str| LibraryItem {
+        id: id.to_string(),
+        artist: artist.to_string(),
+        title: title.to_string(),
+        ..item(ItemType::Album)
+    };
+    let plan = |items: Vec<LibraryItem>| -> Vec<_> {
+        items
+            .into_iter()
+            .map(|item| {
+                let path = item.construct_filename(AudioFormat::Flac, None, &sanitize);
+                (item, std::path::PathBuf::from(path))
+            })
+            .collect()
+    };
+
+    let planned = plan(vec![
+        named("1", "AC/DC", "Live"),
+        named("2", "Bad Math", "Missing Narrative"),
+        named("3", "Bad Math", "missing narrative"),
+        named("4", "Clark Rainbow", "Chainsaw"),
+    ]);
+    let audit = audit_names(&planned, None, &sanitize);
+    assert_eq!(audit.sanitized.len(), 1);
+    assert_eq!(
+        audit.sanitized[0].changes,
+        vec![NameChange {
+            before: "AC/DC".to_string(),
+            after: "AC_DC".to_string(),
+        }]
+    );
+    // Told apart only by case, which not every filesystem does
+    assert_eq!(audit.collisions.len(), 1);
+    let ids: Vec<_> = audit.collisions[0].items.iter().map(|i| &i.id).collect();
+    assert_eq!(ids, ["2", "3"]);
+
+    let markdown = render_naming_report(&audit, ReportFormat::Markdown, Utc::now());
+    assert!(markdown.contains("| Live | `AC/DC` | `AC_DC` | `AC_DC - Live` |"));
+    assert!(markdown.contains("Bad Math - Missing Narrative; Bad Math - missing narrative"));
+
+    // Names the template leaves out can't end up in a path
+    let audit = audit_names(&planned, Some("{title}"), &sanitize);
+    assert!(audit.sanitized.is_empty());
+}
@@ -0,0 +1,61 @@
+use std::path::PathBuf;
+
+use bannedcamp::BandcampError;
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::progress_file::{BatchState, ItemStage, ProgressFile};
+use bannedcamp::core::queue::BatchProgressReporter;
+
+mod common;
+
+fn item(id: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        ..common::library_item()
+    }
+}
+
+#[tokio::test]
+async fn test_progress_file_follows_the_batch() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("progress.json");
+    let read = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap()
+    };
+
+    let progress = ProgressFile::new(&path, 2);
+    assert_eq!(read()["state"], "running");
+    assert_eq!(read()["total"], 2);
+
+    let (first, second) = (item("1"), item("2"));
+    let reporter = progress.on_item_started(0, &first).await;
+    reporter.on_start(Some(1000)).await;
+    reporter.on_progress(400, Some(1000)).await;
+    let _second = progress.on_item_started(1, &second).await;
+
+    let snapshot = progress.snapshot();
+    assert_eq!(snapshot.bytes, 400);
+    assert_eq!(snapshot.eta_secs, None);
+    assert_eq!(snapshot.active.len(), 2);
+    assert_eq!(snapshot.active[0].stage, ItemStage::Downloading);
+    assert_eq!(snapshot.active[0].total_bytes, Some(1000));
+    assert_eq!(snapshot.active[1].stage, ItemStage::FetchingUrl);
+
+    reporter.on_progress(1000, Some(1000)).await;
+    progress
+        .on_item_finished(0, &first, &Ok(PathBuf::from("/music/a")))
+        .await;
+    let written = read();
+    assert_eq!(written["finished"], 1);
+    assert_eq!(written["bytes"], 1000);
+    assert_eq!(written["active"].as_array().unwrap().len(), 1);
+    assert!(written["eta_secs"].is_u64());
+
+    progress
+        .on_item_finished(1, &second, &Err(BandcampError::StreamOnly))
+        .await;
+    let written = read();
+    assert_eq!(written["state"], "finished");
+    assert_eq!(written["succeeded"], 1);
+    assert_eq!(written["failed"], 1);
+    assert_eq!(progress.snapshot().state, BatchState::Finished);
+}
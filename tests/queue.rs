@@ -0,0 +1,353 @@
+use bannedcamp::core::library::{ItemType, LibraryItem};
+use bannedcamp::core::queue::{Lanes, Slot, SlotLimits};
+
+mod common;
+
+fn album(id: &str, artist_id: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        artist_id: artist_id.to_string(),
+        ..common::library_item()
+    }
+}
+
+fn track(id: &str, artist_id: &str) -> LibraryItem {
+    LibraryItem {
+        item_type: ItemType::Track,
+        ..album(id, artist_id)
+    }
+}
+
+fn slots(items: &[LibraryItem]) -> Vec<Slot> {
+    items.iter().map(Slot::of).collect()
+}
+
+fn ids(taken: Vec<(usize, LibraryItem)>) -> Vec<String> {
+    taken.into_iter().map(|(_, item)| item.id).collect()
+}
+
+/// ID of the next item to download
+fn next_download(
+    lanes: &mut Lanes,
+    probes_in_flight: bool,
+    fits: impl Fn(&LibraryItem) -> bool,
+) -> Option<String> {
+    lanes
+        .next_download(probes_in_flight, fits)
+        .map(|(_, item)| item.id)
+}
+
+#[test]
+fn test_tracks_take_a_slot_each_by_default() {
+    let limits = SlotLimits::new(2);
+    let in_flight = slots(&[track("1", "a")]);
+    assert!(limits.has_slot(&track("2", "b"), &in_flight, &in_flight));
+    assert!(limits.has_slot(&album("2", "b"), &in_flight, &in_flight));
+
+    let in_flight = slots(&[track("1", "a"), album("2", "b")]);
+    assert!(!limits.has_slot(&track("3", "c"), &in_flight, &in_flight));
+    assert!(!limits.has_slot(&album("3", "c"), &in_flight, &in_flight));
+
+    assert!(limits.fit_at_once(&[track("1", "a"), album("2", "b")]));
+    assert!(!limits.fit_at_once(&[track("1", "a"), track("2", "b"), track("3", "c")]));
+}
+
+#[test]
+fn test_tracks_share_a_slot_when_asked_to() {
+    let limits = SlotLimits::new(2).with_tracks_per_slot(4);
+
+    // One album and four tracks fill both slots, never more connections than that
+    let mut in_flight = vec![Slot::of(&album("1", "a"))];
+    for n in 0..4 {
+        let next = track(&format!("t{n}"), "b");
+        assert!(limits.has_slot(&next, &in_flight, &in_flight));
+        in_flight.push(Slot::of(&next));
+    }
+    assert!(!limits.has_slot(&track("t4", "b"), &in_flight, &in_flight));
+    assert!(!limits.has_slot(&album("2", "c"), &in_flight, &in_flight));
+
+    // An album waits for a whole slot to free up
+    let in_flight = slots(&[album("1", "a"), track("t0", "b")]);
+    assert!(!limits.has_slot(&album("2", "c"), &in_flight, &in_flight));
+    assert!(limits.has_slot(&track("t1", "b"), &in_flight, &in_flight));
+
+    assert!(
+        SlotLimits::new(1)
+            .with_tracks_per_slot(0)
+            .has_slot(&album("1", "a"), [], [])
+    );
+}
+
+#[test]
+fn test_per_artist_limit_holds_back_busy_artists() {
+    let limits = SlotLimits::new(4).with_per_artist(1);
+    let in_flight = slots(&[album("1", "a")]);
+    assert!(!limits.has_slot(&album("2", "a"), &in_flight, &in_flight));
+    assert!(!limits.has_slot(&track("3", "a"), &in_flight, &in_flight));
+    assert!(limits.has_slot(&album("4", "b"), &in_flight, &in_flight));
+
+    // The subdomain tells apart artists sharing an id
+    let subdomain = |item: LibraryItem, subdomain: &str| LibraryItem {
+        artist_subdomain: Some(subdomain.to_string()),
+        ..item
+    };
+    let in_flight = vec![Slot::of(&subdomain(album("1", "a"), "badmath"))];
+    assert!(limits.has_slot(&subdomain(album("2", "a"), "other"), &in_flight, &in_flight));
+    assert!(!limits.has_slot(
+        &subdomain(album("3", "b"), "BadMath"),
+        &in_flight,
+        &in_flight
+    ));
+}
+
+#[test]
+fn test_per_artist_limit_counts_probes_and_downloads() {
+    let limits = SlotLimits::new(2).with_per_artist(1);
+    let mut lanes = Lanes::new(
+        vec![album("1", "a"), album("2", "a"), album("3", "b")],
+        true,
+    );
+    let mut probing = Vec::new();
+    let mut running = Vec::new();
+
+    // The scheduler's turn: probes count their own slots, the artist limit counts both stages
+    let next_probe = |lanes: &mut Lanes, probing: &[Slot], running: &[Slot]| {
+        lanes
+            .next_probe(|item| limits.has_slot(item, probing, probing.iter().chain(running)))
+            .map(|(index, item)| (index, item.id.clone(), item))
+    };
+
+    let (index, id, first) = next_probe(&mut lanes, &probing, &running).unwrap();
+    assert_eq!(id, "1");
+    probing.push(Slot::of(&first));
+    // The other item of artist a waits, b takes the second probe slot
+    let (_, id, third) = next_probe(&mut lanes, &probing, &running).unwrap();
+    assert_eq!(id, "3");
+    probing.push(Slot::of(&third));
+
+    // Once downloading, item 1 still holds the only place of artist a
+    probing.remove(0);
+    lanes.probed(index, first, true);
+    let (_, started) = lanes
+        .next_download(true, |item| {
+            limits.has_slot(item, &running, probing.iter().chain(&running))
+        })
+        .unwrap();
+    running.push(Slot::of(&started));
+    probing.clear();
+    assert!(next_probe(&mut lanes, &probing, &running).is_none());
+
+    running.clear();
+    let (_, id, _) = next_probe(&mut lanes, &probing, &running).unwrap();
+    assert_eq!(id, "2");
+}
+
+/// Starts every ready item that gets a slot next to `running`, like the queue does on each turn
+fn start_downloads(
+    lanes: &mut Lanes,
+    limits: SlotLimits,
+    running: &mut Vec<(String, Slot)>,
+) -> Vec<String> {
+    let mut started = Vec::new();
+    loop {
+        let slots: Vec<_> = running.iter().map(|(_, slot)| slot.clone()).collect();
+        let Some((_, item)) =
+            lanes.next_download(false, |item| limits.has_slot(item, &slots, &slots))
+        else {
+            return started;
+        };
+        running.push((item.id.clone(), Slot::of(&item)));
+        started.push(item.id);
+    }
+}
+
+#[test]
+fn test_slots_free_up_as_items_finish() {
+    let limits = SlotLimits::new(2).with_tracks_per_slot(2);
+    let mut lanes = Lanes::new(
+        vec![
+            album("1", "a"),
+            track("2", "b"),
+            track("3", "c"),
+            track("4", "d"),
+            album("5", "e"),
+        ],
+        false,
+    );
+    let mut running = Vec::new();
+
+    // The album holds a whole slot, two tracks share the other
+    assert_eq!(
+        start_downloads(&mut lanes, limits, &mut running),
+        ["1", "2", "3"]
+    );
+
+    // A finished track makes room for the next track, not for the album behind it
+    running.retain(|(id, _)| id != "2");
+    assert_eq!(start_downloads(&mut lanes, limits, &mut running), ["4"]);
+
+    running.retain(|(id, _)| id != "1");
+    assert_eq!(start_downloads(&mut lanes, limits, &mut running), ["5"]);
+    assert!(lanes.is_empty());
+}
+
+#[test]
+fn test_lanes_take_items_in_queue_order() {
+    let mut lanes = Lanes::new(
+        vec![album("1", "a"), album("2", "a"), album("3", "b")],
+        false,
+    );
+    assert!(lanes.next_probe(|_| true).is_none());
+
+    // Items that don't fit are skipped, not dropped
+    let not_a = |item: &LibraryItem| item.artist_id != "a";
+    assert_eq!(
+        next_download(&mut lanes, false, not_a).as_deref(),
+        Some("3")
+    );
+    assert_eq!(next_download(&mut lanes, false, not_a), None);
+    let (index, _) = lanes.next_download(false, |_| true).unwrap();
+    assert_eq!(index, 0);
+    assert_eq!(ids(lanes.drain()), ["2"]);
+    assert!(lanes.is_empty());
+}
+
+#[test]
+fn test_pending_lane_waits_for_every_probe() {
+    let mut lanes = Lanes::new(
+        vec![album("1", "a"), album("2", "b"), album("3", "c")],
+        true,
+    );
+
+    let (index, first) = lanes.next_probe(|_| true).unwrap();
+    assert_eq!(index, 0);
+    lanes.probed(index, first, false);
+    let (index, second) = lanes.next_probe(|_| true).unwrap();
+
+    // Still encoding, so it doesn't take a slot while other items may turn out ready
+    assert_eq!(next_download(&mut lanes, false, |_| true), None);
+    lanes.probed(index, second, true);
+    assert_eq!(
+        next_download(&mut lanes, false, |_| true).as_deref(),
+        Some("2")
+    );
+
+    let (index, third) = lanes.next_probe(|_| true).unwrap();
+    assert_eq!(next_download(&mut lanes, true, |_| true), None);
+    lanes.probed(index, third, false);
+
+    // Nothing ready is left, so the pending lane goes in queue order
+    assert_eq!(
+        next_download(&mut lanes, false, |_| true).as_deref(),
+        Some("1")
+    );
+    assert_eq!(
+        next_download(&mut lanes, false, |_| true).as_deref(),
+        Some("3")
+    );
+    assert!(lanes.is_empty());
+}
+
+/// The whole queue against the mock server
+#[cfg(feature = "mock")]
+mod download_queue {
+    use std::path::PathBuf;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use bannedcamp::core::client::RequestCounter;
+    use bannedcamp::core::download::{DownloadOptions, DownloadProgressReporter};
+    use bannedcamp::core::library::{AudioFormat, LibraryItem};
+    use bannedcamp::core::queue::{BatchProgressReporter, DownloadQueue, SilentReporter};
+
+    /// Keeps track of the order items start in, how many run at once and how many encodings
+    /// were requested before the first one started
+    struct Recorder {
+        requests: RequestCounter,
+        started: Mutex<Vec<usize>>,
+        encodings_before_first: Mutex<Option<u64>>,
+        active: AtomicUsize,
+        most_active: AtomicUsize,
+    }
+
+    impl BatchProgressReporter for Recorder {
+        fn on_item_started(
+            &self,
+            index: usize,
+            _item: &LibraryItem,
+        ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+            self.encodings_before_first
+                .lock()
+                .unwrap()
+                .get_or_insert(self.requests.counts().other);
+            self.started.lock().unwrap().push(index);
+            let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most_active.fetch_max(active, Ordering::SeqCst);
+            Box::pin(async { Box::new(SilentReporter) as Box<dyn DownloadProgressReporter> })
+        }
+
+        fn on_item_finished(
+            &self,
+            _index: usize,
+            _item: &LibraryItem,
+            _result: &bannedcamp::Result<PathBuf>,
+        ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+            self.active.fetch_sub(1, Ordering::SeqCst);
+            Box::pin(async {})
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_queue_order_and_slots() {
+        let server = bannedcamp::mock::start().await.unwrap();
+        let mut client = server.client();
+        client.validate_cookie("anything").await.unwrap();
+        let items: Vec<_> = client
+            .get_collection()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|item| !item.is_preorder && !item.is_stream_only())
+            .collect();
+        assert!(items.len() > 2);
+
+        let client = Arc::new(client);
+        let logging_in = client.requests().counts().other;
+        let recorder = Arc::new(Recorder {
+            requests: client.requests(),
+            started: Mutex::default(),
+            encodings_before_first: Mutex::default(),
+            active: AtomicUsize::new(0),
+            most_active: AtomicUsize::new(0),
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let queue = DownloadQueue::new(
+            client,
+            dir.path().to_path_buf(),
+            AudioFormat::Mp3320,
+            None,
+            DownloadOptions::default(),
+            2,
+        );
+        let summary = queue.run(items.clone(), recorder.clone()).await;
+
+        // More items than slots, so each was probed first. The mock never has an encoding ready
+        // right away, every item waited in the pending lane until the last probe was done.
+        assert_eq!(
+            *recorder.encodings_before_first.lock().unwrap(),
+            Some(logging_in + items.len() as u64)
+        );
+        assert_eq!(
+            *recorder.started.lock().unwrap(),
+            (0..items.len()).collect::<Vec<_>>()
+        );
+        assert!(recorder.most_active.load(Ordering::SeqCst) <= 2);
+
+        // Reported in queue order, however they finished
+        assert!(summary.failed.is_empty());
+        let succeeded: Vec<_> = summary.succeeded.iter().map(|(item, _)| &item.id).collect();
+        let queued: Vec<_> = items.iter().map(|item| &item.id).collect();
+        assert_eq!(succeeded, queued);
+    }
+}
@@ -0,0 +1,65 @@
+use bannedcamp::core::client::BandcampClient;
+use bannedcamp::core::recording;
+
+#[tokio::test]
+async fn test_replay_answers_from_recorded_exchanges() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("0001.json"),
+        r#"{
+            "method": "GET",
+            "url": "https://bandcamp.com/api/fan/2/collection_summary",
+            "status": 200,
+            "headers": [["content-type", "application/json"]],
+            "body_file": "0001.body"
+        }"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.path().join("0001.body"),
+        r#"{"fan_id": 42, "collection_summary": {"fan_id": 42, "username": "replayed",
+            "url": "https://bandcamp.com/replayed", "tralbum_lookup": {}}}"#,
+    )
+    .unwrap();
+
+    recording::replay_from(dir.path()).unwrap();
+
+    let client = BandcampClient::new();
+    let summary = client.fetch_collection_summary("any").await.unwrap();
+    assert_eq!(summary.fan_id, 42);
+    assert_eq!(summary.collection_summary.username, "replayed");
+
+    // Served again once the recording is used up, as repeated polls would be
+    let summary = client.fetch_collection_summary("any").await.unwrap();
+    assert_eq!(summary.fan_id, 42);
+
+    // Nothing was recorded for the reachability check
+    assert!(client.server_time().await.is_err());
+}
+
+#[test]
+fn test_redact_url_blanks_signatures() {
+    let url = url::Url::parse(
+        "https://bandcamp.com/download?id=12&sig=abc123&token=1700000000:xyz&payment_id=7",
+    )
+    .unwrap();
+    assert_eq!(
+        recording::redact_url(&url),
+        "https://bandcamp.com/download?id=12&sig=REDACTED&token=REDACTED&payment_id=REDACTED"
+    );
+}
+
+#[test]
+fn test_redact_text_blanks_signed_links_in_bodies() {
+    let page = r#"<div id="pagedata" data-blob="{&quot;url&quot;:&quot;https://p4.bcbits.com/download/album/1?id=12&amp;sig=abc&amp;token=99:xyz&quot;,&quot;design&quot;:1}">"#;
+    assert_eq!(
+        recording::redact_text(page),
+        r#"<div id="pagedata" data-blob="{&quot;url&quot;:&quot;https://p4.bcbits.com/download/album/1?id=12&amp;sig=REDACTED&amp;token=REDACTED&quot;,&quot;design&quot;:1}">"#
+    );
+
+    let poll = r#"{"result":"ok","download_url":"https://p4.bcbits.com/download/album/1?fsig=keep&sig=abc&.rand=123"}"#;
+    assert_eq!(
+        recording::redact_text(poll),
+        r#"{"result":"ok","download_url":"https://p4.bcbits.com/download/album/1?fsig=keep&sig=REDACTED&.rand=REDACTED"}"#
+    );
+}
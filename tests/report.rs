@@ -0,0 +1,46 @@
+use bannedcamp::core::download::{DownloadSummary, SkipReason};
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::report::{ReportFormat, render_report};
+use chrono::Utc;
+
+mod common;
+
+fn item(title: &str) -> LibraryItem {
+    LibraryItem {
+        title: title.to_string(),
+        artist_subdomain: Some("badmathhk".to_string()),
+        slug: Some("missing-narrative".to_string()),
+        item_url: Some("https://badmathhk.bandcamp.com/album/missing-narrative".to_string()),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_report_lists_successes_and_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let track = dir.path().join("track.flac");
+    std::fs::write(&track, vec![0u8; 2048]).unwrap();
+
+    let summary = DownloadSummary {
+        succeeded: vec![(item("Missing Narrative"), track)],
+        failed: vec![(item("A | B"), "Download failed: 404".to_string())],
+        skipped: vec![(item("Preorder"), SkipReason::Preorder)],
+        fallbacks: vec![],
+        site_down: false,
+        errors: Default::default(),
+    };
+
+    let markdown = render_report(&summary, ReportFormat::Markdown, Utc::now());
+    assert!(markdown.contains("- Succeeded: 1"));
+    assert!(
+        markdown.contains(
+            "[Missing Narrative](https://badmathhk.bandcamp.com/album/missing-narrative)"
+        )
+    );
+    assert!(markdown.contains("2.00 KB"));
+    assert!(markdown.contains("A \\| B"));
+    assert!(markdown.contains("- Skipped: 1"));
+
+    let html = render_report(&summary, ReportFormat::Html, Utc::now());
+    assert!(html.contains("<td>Download failed: 404</td>"));
+}
@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use bannedcamp::core::library::{AudioFormat, ItemType, LibraryItem};
+use bannedcamp::core::routing::{Layout, RouteMap};
+
+mod common;
+
+fn item(item_type: ItemType, artist: &str) -> LibraryItem {
+    LibraryItem {
+        item_type,
+        artist: artist.to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_route_map_first_match_wins() {
+    let routes = RouteMap::parse(
+        "# masters first\n\
+         format:wav = /mnt/archive\n\
+         type:track = Singles\n\
+         artist:*orchestra* = Classical\n",
+    )
+    .unwrap();
+    let output = Path::new("/music");
+
+    let track = item(ItemType::Track, "Bad Math");
+    assert_eq!(
+        routes.resolve(&track, AudioFormat::Wav, output),
+        Path::new("/mnt/archive")
+    );
+    assert_eq!(
+        routes.resolve(&track, AudioFormat::Flac, output),
+        Path::new("/music/Singles")
+    );
+
+    let album = item(ItemType::Album, "London Symphony Orchestra");
+    assert_eq!(
+        routes.resolve(&album, AudioFormat::Flac, output),
+        Path::new("/music/Classical")
+    );
+    assert_eq!(
+        routes.resolve(
+            &item(ItemType::Album, "Bad Math"),
+            AudioFormat::Flac,
+            output
+        ),
+        output
+    );
+
+    assert!(RouteMap::parse("label:foo = Elsewhere").is_err());
+}
+
+#[test]
+fn test_layout_purchase_year() {
+    let output = Path::new("/music");
+    let mut bought = item(ItemType::Album, "Bad Math");
+    bought.purchase.purchased_at = Some("2023-12-01T12:00:00Z".parse().unwrap());
+
+    assert_eq!(Layout::Flat.resolve(&bought, output), output);
+    assert_eq!(
+        Layout::PurchaseYear.resolve(&bought, output),
+        Path::new("/music/2023")
+    );
+    assert_eq!(
+        Layout::PurchaseYear.resolve(&item(ItemType::Album, "Bad Math"), output),
+        Path::new("/music/Unknown")
+    );
+}
@@ -0,0 +1,109 @@
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use bannedcamp::core::download::DownloadProgressReporter;
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::queue::{BatchProgressReporter, SilentReporter};
+use bannedcamp::core::schedule::{DownloadWindow, FreeSpace};
+use bannedcamp::error::Result;
+use chrono::NaiveTime;
+
+/// Records the directories the queue ran low on
+#[derive(Default)]
+struct SpaceRecorder(Mutex<Vec<PathBuf>>);
+
+impl BatchProgressReporter for SpaceRecorder {
+    fn on_item_started(
+        &self,
+        _index: usize,
+        _item: &LibraryItem,
+    ) -> Pin<Box<dyn Future<Output = Box<dyn DownloadProgressReporter>> + Send + '_>> {
+        Box::pin(async { Box::new(SilentReporter) as Box<dyn DownloadProgressReporter> })
+    }
+
+    fn on_item_finished(
+        &self,
+        _index: usize,
+        _item: &LibraryItem,
+        _result: &Result<PathBuf>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(async {})
+    }
+
+    fn on_space_low(
+        &self,
+        dir: &Path,
+        _free: u64,
+        min_free: u64,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        assert_eq!(min_free, u64::MAX);
+        self.0.lock().unwrap().push(dir.to_path_buf());
+        Box::pin(async {})
+    }
+}
+
+fn at(hour: u32, minute: u32) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+}
+
+#[test]
+fn test_download_window() {
+    let night = DownloadWindow::parse("01:00-07:00").unwrap();
+    assert_eq!(night.to_string(), "01:00-07:00");
+    assert!(night.contains(at(1, 0)));
+    assert!(night.contains(at(6, 59)));
+    assert!(!night.contains(at(7, 0)));
+    assert_eq!(night.until_open(at(3, 0)), Duration::ZERO);
+    assert_eq!(night.until_open(at(0, 30)), Duration::from_secs(30 * 60));
+    assert_eq!(
+        night.until_open(at(7, 0)),
+        Duration::from_secs(18 * 60 * 60)
+    );
+
+    // Ending before it starts runs past midnight
+    let evening = DownloadWindow::parse("22:00 - 02:30").unwrap();
+    assert!(evening.contains(at(23, 0)));
+    assert!(evening.contains(at(2, 0)));
+    assert!(!evening.contains(at(12, 0)));
+    assert_eq!(evening.until_open(at(21, 0)), Duration::from_secs(60 * 60));
+
+    assert_eq!(DownloadWindow::parse("01:00"), None);
+    assert_eq!(DownloadWindow::parse("01:00-25:00"), None);
+    assert_eq!(DownloadWindow::parse("05:00-05:00"), None);
+}
+
+#[tokio::test]
+async fn test_free_space() {
+    let dir = tempfile::tempdir().unwrap();
+    // Not created yet, measured at the temp dir
+    let album = dir.path().join("Artist").join("Album");
+
+    assert_eq!(FreeSpace::new(0).shortage(&album), None);
+    assert!(FreeSpace::new(u64::MAX).shortage(&album).is_some());
+
+    let reporter = SpaceRecorder::default();
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        FreeSpace::new(0).wait(&album, &reporter),
+    )
+    .await
+    .expect("enough space shouldn't wait");
+    assert!(reporter.0.lock().unwrap().is_empty());
+
+    // Clones share the pause, running low is reported once
+    let low = FreeSpace::new(u64::MAX);
+    for free_space in [low.clone(), low] {
+        assert!(
+            tokio::time::timeout(
+                Duration::from_millis(100),
+                free_space.wait(&album, &reporter)
+            )
+            .await
+            .is_err()
+        );
+    }
+    assert_eq!(*reporter.0.lock().unwrap(), [album]);
+}
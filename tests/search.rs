@@ -0,0 +1,32 @@
+use bannedcamp::core::library::{ItemType, LibraryItem};
+use bannedcamp::tui::app::SearchQuery;
+
+mod common;
+
+fn item(artist: &str, title: &str, item_type: ItemType) -> LibraryItem {
+    LibraryItem {
+        id: "1234567".to_string(),
+        item_type,
+        title: title.to_string(),
+        artist: artist.to_string(),
+        artist_subdomain: Some("hypothermiaband".to_string()),
+        slug: Some("winter-sessions".to_string()),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_search_query_fields() {
+    let track = item("Hypothermia", "Winter Sessions", ItemType::Track);
+    let album = item("Hypothermia", "Winter Sessions", ItemType::Album);
+
+    assert!(SearchQuery::parse("winter").matches(&track));
+    assert!(SearchQuery::parse("artist:hypothermia type:track").matches(&track));
+    assert!(!SearchQuery::parse("artist:hypothermia type:track").matches(&album));
+    assert!(SearchQuery::parse("subdomain:hypothermiaband").matches(&album));
+    assert!(SearchQuery::parse("slug:winter-sessions id:1234567").matches(&album));
+    assert!(!SearchQuery::parse("title:hypothermia").matches(&album));
+
+    // An unfinished prefix doesn't filter anything out yet
+    assert!(SearchQuery::parse("artist:").matches(&album));
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::session;
+
+mod common;
+
+const SERVER: &str = "https://bandcamp.com";
+
+fn item(id: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        download_url: "https://bandcamp.com/download".to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_session_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bannedcamp").join("session.json");
+
+    // Nothing is cached before the cache is enabled
+    session::store_fan_id(SERVER, "cookie", 42);
+    assert!(!path.exists());
+
+    session::enable(path.clone(), Duration::from_secs(60));
+    assert_eq!(session::cached_fan_id(SERVER, "cookie"), None);
+
+    session::store_fan_id(SERVER, "cookie", 42);
+    assert_eq!(session::cached_fan_id(SERVER, "cookie"), Some(42));
+    assert_eq!(session::cached_fan_id(SERVER, "other cookie"), None);
+    assert_eq!(
+        session::cached_fan_id("http://127.0.0.1:8080", "cookie"),
+        None
+    );
+    assert!(!std::fs::read_to_string(&path).unwrap().contains("cookie"));
+
+    let signed = LibraryItem {
+        download_url: "https://bandcamp.com/download?payment_id=7&sig=abc&sitem_id=2".to_string(),
+        ..item("2")
+    };
+    session::store_collection(SERVER, "cookie", &[item("1"), signed]);
+    let items = session::cached_collection(SERVER, "cookie").unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[1].id, "2");
+    // Kept signed, so downloading a cached item doesn't page through the collection again
+    assert_eq!(
+        items[1].download_url,
+        "https://bandcamp.com/download?payment_id=7&sig=abc&sitem_id=2"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    assert!(session::cached_collection(SERVER, "other cookie").is_none());
+
+    session::forget_collection(SERVER, "cookie");
+    assert!(session::cached_collection(SERVER, "cookie").is_none());
+    assert_eq!(session::cached_fan_id(SERVER, "cookie"), Some(42));
+
+    // Another account replaces the cache instead of mixing with it
+    session::store_fan_id(SERVER, "other cookie", 7);
+    assert_eq!(session::cached_fan_id(SERVER, "cookie"), None);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert_eq!(session::cached_fan_id(SERVER, "other cookie"), None);
+}
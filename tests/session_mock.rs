@@ -0,0 +1,46 @@
+#![cfg(feature = "mock")]
+
+use std::time::Duration;
+
+use bannedcamp::core::download::{DownloadOptions, download_item};
+use bannedcamp::core::library::{AudioFormat, ItemType};
+use bannedcamp::core::queue::SilentReporter;
+use bannedcamp::core::session;
+
+// Its own test binary, the session cache can only be enabled once per process
+#[tokio::test]
+async fn test_cached_collection_downloads_without_refetching() {
+    let dir = tempfile::tempdir().unwrap();
+    session::enable(dir.path().join("session.json"), Duration::from_secs(60));
+    let server = bannedcamp::mock::start().await.unwrap();
+
+    let mut first = server.client();
+    first.validate_cookie("anything").await.unwrap();
+    first.get_collection().await.unwrap();
+    assert!(first.requests().counts().collection_pages > 0);
+
+    // The next command gets everything from the cache, download links included
+    let mut second = server.client();
+    second.validate_cookie("anything").await.unwrap();
+    let items = second.get_collection().await.unwrap();
+    let track = items
+        .iter()
+        .find(|i| i.item_type == ItemType::Track && !i.is_preorder && !i.is_stream_only())
+        .unwrap();
+    let path = download_item(
+        &second,
+        track,
+        dir.path(),
+        AudioFormat::Flac,
+        None,
+        &DownloadOptions::default(),
+        SilentReporter,
+    )
+    .await
+    .unwrap();
+
+    assert!(path.is_file());
+    let counts = second.requests().counts();
+    assert_eq!(counts.collection_pages, 0);
+    assert_eq!(counts.download_pages, 1);
+}
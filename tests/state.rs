@@ -0,0 +1,337 @@
+use bannedcamp::core::download::{DownloadSummary, SkipReason};
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::state::{
+    BandwidthLog, BandwidthMeter, BatchRecord, CollectionSnapshot, ItemClaim, LastRun, Manifest,
+    PendingEncodings, PreorderTracks,
+};
+use bannedcamp::error::BandcampError;
+use chrono::{TimeZone, Utc};
+
+mod common;
+
+fn item(id: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_batch_record_remembers_completed_items() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut batch = BatchRecord::load(dir.path(), "nightly-sync").unwrap();
+    batch.record(&DownloadSummary {
+        succeeded: vec![(item("1"), dir.path().join("a"))],
+        failed: vec![(item("2"), "Download failed: 404".to_string())],
+        skipped: vec![],
+        fallbacks: vec![],
+        site_down: false,
+        errors: Default::default(),
+    });
+    batch.save(dir.path()).unwrap();
+
+    let batch = BatchRecord::load(dir.path(), "nightly-sync").unwrap();
+    assert!(batch.is_completed(&item("1")));
+    assert!(!batch.is_completed(&item("2")));
+
+    let other = BatchRecord::load(dir.path(), &BatchRecord::new_id()).unwrap();
+    assert!(!other.is_completed(&item("1")));
+
+    assert!(BatchRecord::load(dir.path(), "../escape").is_err());
+}
+
+#[test]
+fn test_batch_records_are_capped() {
+    let dir = tempfile::tempdir().unwrap();
+    let summary = DownloadSummary {
+        succeeded: vec![(item("1"), dir.path().join("a"))],
+        ..Default::default()
+    };
+
+    for n in 0..60 {
+        let mut batch = BatchRecord::load(dir.path(), &format!("run-{n}")).unwrap();
+        assert!(batch.is_empty());
+        batch.record(&summary);
+        assert!(!batch.is_empty());
+        batch.save(dir.path()).unwrap();
+    }
+
+    let batches = dir.path().join(".bannedcamp/batches");
+    assert_eq!(std::fs::read_dir(&batches).unwrap().count(), 50);
+    // The batch just saved always stays
+    assert!(batches.join("run-59.json").exists());
+}
+
+#[test]
+fn test_bandwidth_log_counts_per_month_and_item() {
+    let march = Utc.with_ymd_and_hms(2026, 3, 31, 23, 0, 0).unwrap();
+    let april = Utc.with_ymd_and_hms(2026, 4, 1, 1, 0, 0).unwrap();
+
+    let mut log = BandwidthLog::default();
+    log.add("1", 100, march);
+    log.add("2", 300, march);
+    log.add("1", 250, april);
+
+    assert_eq!(log.month_total(march), 400);
+    assert_eq!(log.month_total(april), 250);
+    assert_eq!(
+        log.months().collect::<Vec<_>>(),
+        vec![("2026-03", 400), ("2026-04", 250)]
+    );
+    assert_eq!(log.largest_items(), vec![("1", 350), ("2", 300)]);
+}
+
+#[test]
+fn test_bandwidth_meter_persists_and_enforces_cap() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let meter = BandwidthMeter::open(dir.path(), Some(1000)).unwrap();
+    meter.record("1", 600);
+    assert!(!meter.cap_reached());
+
+    // Another batch picks up where the first one left off
+    let meter = BandwidthMeter::open(dir.path(), Some(1000)).unwrap();
+    meter.record("2", 400);
+    assert!(meter.cap_reached());
+
+    let log = BandwidthLog::load(dir.path()).unwrap();
+    assert_eq!(log.month_total(Utc::now()), 1000);
+
+    let uncapped = BandwidthMeter::open(dir.path(), None).unwrap();
+    assert!(!uncapped.cap_reached());
+}
+
+#[test]
+fn test_bandwidth_meters_of_concurrent_runs_add_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = BandwidthMeter::open(dir.path(), Some(1000)).unwrap();
+    let second = BandwidthMeter::open(dir.path(), Some(1000)).unwrap();
+
+    std::thread::scope(|scope| {
+        for (meter, id) in [(&first, "1"), (&second, "2")] {
+            scope.spawn(move || {
+                for _ in 0..50 {
+                    meter.record(id, 10);
+                }
+            });
+        }
+    });
+
+    // Neither run overwrote the other's transfers, and both see the cap
+    let log = BandwidthLog::load(dir.path()).unwrap();
+    assert_eq!(log.month_total(Utc::now()), 1000);
+    assert_eq!(log.largest_items(), vec![("1", 500), ("2", 500)]);
+    assert!(first.cap_reached() || second.cap_reached());
+}
+
+#[test]
+fn test_item_claim_blocks_second_download() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let claim = ItemClaim::acquire(dir.path(), "1").unwrap();
+    assert!(matches!(
+        ItemClaim::acquire(dir.path(), "1"),
+        Err(BandcampError::InProgressElsewhere)
+    ));
+    // Other items and other output directories are unaffected
+    let other = ItemClaim::acquire(dir.path(), "2").unwrap();
+    let elsewhere = tempfile::tempdir().unwrap();
+    drop(ItemClaim::acquire(elsewhere.path(), "1").unwrap());
+
+    drop(claim);
+    drop(other);
+    drop(ItemClaim::acquire(dir.path(), "1").unwrap());
+    assert!(
+        std::fs::read_dir(dir.path().join(".bannedcamp/active"))
+            .unwrap()
+            .next()
+            .is_none()
+    );
+}
+
+#[test]
+fn test_last_run_picks_new_and_pending_items() {
+    let dir = tempfile::tempdir().unwrap();
+    let bought = |id: &str, day: u32| {
+        let mut item = item(id);
+        item.purchase.purchased_at = Some(Utc.with_ymd_and_hms(2024, 3, day, 12, 0, 0).unwrap());
+        item
+    };
+    let collection = || vec![bought("1", 1), bought("2", 2), bought("3", 20), item("4")];
+
+    // Without an earlier run everything is new
+    let last_run = LastRun::load(dir.path()).unwrap();
+    assert_eq!(last_run.new_items(collection()).len(), 4);
+
+    let mut last_run = last_run;
+    last_run.record(
+        Utc.with_ymd_and_hms(2024, 3, 10, 0, 0, 0).unwrap(),
+        &DownloadSummary {
+            succeeded: vec![(bought("1", 1), dir.path().join("a"))],
+            failed: vec![],
+            skipped: vec![(bought("2", 2), SkipReason::Preorder)],
+            fallbacks: vec![],
+            site_down: false,
+            errors: Default::default(),
+        },
+    );
+    last_run.save(dir.path()).unwrap();
+
+    let last_run = LastRun::load(dir.path()).unwrap();
+    let ids: Vec<_> = last_run
+        .new_items(collection())
+        .into_iter()
+        .map(|item| item.id)
+        .collect();
+    assert_eq!(ids, ["2", "3"]);
+}
+
+#[test]
+fn test_preorder_tracks_wait_for_the_release() {
+    let dir = tempfile::tempdir().unwrap();
+    let preorder = LibraryItem {
+        is_preorder: true,
+        ..item("1")
+    };
+    let early = DownloadSummary {
+        succeeded: vec![(preorder.clone(), dir.path().join("a"))],
+        skipped: vec![(preorder.clone(), SkipReason::AlreadyExists)],
+        ..Default::default()
+    };
+
+    let mut preorders = PreorderTracks::load(dir.path()).unwrap();
+    preorders.record(&early);
+    preorders.save(dir.path()).unwrap();
+    let mut last_run = LastRun::default();
+    last_run.record(Utc::now(), &early);
+
+    // Still a preorder, so nothing to replace yet and `new` keeps asking
+    let preorders = PreorderTracks::load(dir.path()).unwrap();
+    assert!(!preorders.is_released(&preorder));
+    // Its early tracks aren't downloaded again with every run
+    assert!(preorders.has_early_tracks(&preorder));
+    assert!(!preorders.has_early_tracks(&item("1")));
+    assert_eq!(last_run.new_items(vec![item("1"), item("2")]).len(), 1);
+
+    let mut preorders = preorders;
+    assert!(preorders.is_released(&item("1")));
+    let full = DownloadSummary {
+        succeeded: vec![(item("1"), dir.path().join("a"))],
+        ..Default::default()
+    };
+    preorders.record(&full);
+    last_run.record(Utc::now(), &full);
+    assert!(!preorders.is_released(&item("1")));
+    assert!(last_run.new_items(vec![item("1")]).is_empty());
+}
+
+#[test]
+fn test_pending_encodings_are_kept_until_done() {
+    let dir = tempfile::tempdir().unwrap();
+    let error = BandcampError::EncodingPending { attempts: 30 };
+    let reason = SkipReason::from_error(&error).unwrap();
+
+    let mut pending = PendingEncodings::load(dir.path()).unwrap();
+    pending.record(&DownloadSummary {
+        skipped: vec![(item("1"), reason), (item("2"), reason)],
+        ..Default::default()
+    });
+    pending.save(dir.path()).unwrap();
+
+    let mut pending = PendingEncodings::load(dir.path()).unwrap();
+    assert_eq!(pending.len(), 2);
+    assert!(pending.contains(&item("1")));
+
+    pending.record(&DownloadSummary {
+        succeeded: vec![(item("1"), dir.path().join("a"))],
+        skipped: vec![(item("2"), reason)],
+        ..Default::default()
+    });
+    assert!(!pending.contains(&item("1")));
+    assert!(pending.contains(&item("2")));
+
+    // Gone from the collection, so no pass will ever get it
+    pending.forget_missing(&[item("1"), item("3")]);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn test_manifest_survives_moving_the_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let old_root = dir.path().join("music");
+
+    let mut manifest = Manifest::load(&old_root).unwrap();
+    assert!(manifest.is_empty());
+    manifest.record(
+        &old_root,
+        &DownloadSummary {
+            succeeded: vec![
+                (item("1"), old_root.join("Bad Math - Missing Narrative")),
+                (
+                    item("2"),
+                    dir.path().join("archive/Bad Math - Missing Narrative"),
+                ),
+            ],
+            ..Default::default()
+        },
+    );
+    manifest.save(&old_root).unwrap();
+
+    let new_root = dir.path().join("nas");
+    std::fs::rename(&old_root, &new_root).unwrap();
+    let manifest = Manifest::load(&new_root).unwrap();
+    assert_eq!(manifest.len(), 2);
+    assert_eq!(
+        manifest.get(&item("1")).unwrap().path,
+        std::path::Path::new("Bad Math - Missing Narrative")
+    );
+    assert_eq!(
+        manifest.locate(&new_root, &item("1")),
+        Some(new_root.join("Bad Math - Missing Narrative"))
+    );
+    // Routed outside the output root, so kept as it was
+    assert_eq!(
+        manifest.locate(&new_root, &item("2")),
+        Some(dir.path().join("archive/Bad Math - Missing Narrative"))
+    );
+    assert_eq!(manifest.locate(&new_root, &item("3")), None);
+}
+
+#[test]
+fn test_manifest_adds_of_concurrent_downloads_add_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+
+    std::thread::scope(|scope| {
+        for worker in 0..2 {
+            scope.spawn(move || {
+                for n in 0..10 {
+                    let id = format!("{worker}-{n}");
+                    Manifest::add(root, &item(&id), &root.join(&id)).unwrap();
+                }
+            });
+        }
+    });
+
+    // No download overwrote another one's entry
+    let manifest = Manifest::load(root).unwrap();
+    assert_eq!(manifest.len(), 20);
+    assert_eq!(manifest.locate(root, &item("1-9")), Some(root.join("1-9")));
+}
+
+#[test]
+fn test_collection_snapshot_drops_signatures() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut signed = item("1");
+    signed.download_url = "https://bandcamp.com/download?payment_id=1&sig=secret".to_string();
+
+    CollectionSnapshot::new(&[signed]).save(dir.path()).unwrap();
+    let snapshot = CollectionSnapshot::load(dir.path()).unwrap();
+    assert_eq!(snapshot.items.len(), 1);
+    assert_eq!(
+        snapshot.items[0].download_url,
+        "https://bandcamp.com/download"
+    );
+    assert!(snapshot.fetched_at.is_some());
+}
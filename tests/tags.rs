@@ -0,0 +1,74 @@
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::core::tags::{embed_cover, render_album_nfo};
+
+mod common;
+
+/// Stands in for ffmpeg: copies its first input to the output, the last argument, and notes
+/// its arguments in `ffmpeg.log` next to it
+const FAKE_FFMPEG: &str = r#"#!/bin/sh
+input=""
+prev=""
+for arg; do
+    if [ "$prev" = "-i" ] && [ -z "$input" ]; then input=$arg; fi
+    prev=$arg
+    out=$arg
+done
+printf '%s\n' "$*" >> "$(dirname "$out")/ffmpeg.log"
+cp "$input" "$out"
+"#;
+
+#[test]
+fn test_embed_cover_replaces_the_image_file() {
+    let _bin = common::fake_program("ffmpeg", FAKE_FFMPEG);
+
+    let album = tempfile::tempdir().unwrap();
+    std::fs::write(album.path().join("01 Intro.flac"), b"fLaC one").unwrap();
+    std::fs::write(album.path().join("02 Outro.mp3"), b"ID3 two").unwrap();
+    std::fs::write(album.path().join("cover.jpg"), b"jpg").unwrap();
+
+    assert!(embed_cover(album.path()).unwrap());
+    assert!(!album.path().join("cover.jpg").exists());
+    assert_eq!(
+        std::fs::read(album.path().join("01 Intro.flac")).unwrap(),
+        b"fLaC one"
+    );
+    let log = std::fs::read_to_string(album.path().join("ffmpeg.log")).unwrap();
+    assert_eq!(log.lines().count(), 2);
+    assert!(
+        log.lines().all(
+            |line| line.contains("cover.jpg") && line.contains("-disposition:v:0 attached_pic")
+        )
+    );
+    // Written next to the track and renamed over it, nothing left behind
+    assert!(std::fs::read_dir(album.path()).unwrap().all(|entry| {
+        !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with('.')
+    }));
+
+    // Ogg Vorbis can't carry a cover picture, so the album keeps the file
+    let album = tempfile::tempdir().unwrap();
+    std::fs::write(album.path().join("01 Intro.ogg"), b"OggS").unwrap();
+    std::fs::write(album.path().join("folder.png"), b"png").unwrap();
+    assert!(!embed_cover(album.path()).unwrap());
+    assert!(album.path().join("folder.png").is_file());
+
+    // Nothing to embed
+    assert!(!embed_cover(tempfile::tempdir().unwrap().path()).unwrap());
+}
+
+#[test]
+fn test_album_nfo_escapes_names() {
+    let item = LibraryItem {
+        title: "Rock & Roll <Live>".to_string(),
+        release_date: chrono::NaiveDate::from_ymd_opt(2021, 6, 20),
+        ..common::library_item()
+    };
+    let nfo = render_album_nfo(&item);
+    assert!(nfo.contains("<title>Rock &amp; Roll &lt;Live&gt;</title>"));
+    assert!(nfo.contains("<artist>Bad Math</artist>"));
+    assert!(nfo.contains("<year>2021</year>"));
+    assert!(nfo.contains("<releasedate>2021-06-20</releasedate>"));
+}
@@ -0,0 +1,348 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use bannedcamp::cli::progress::LogWriter;
+use bannedcamp::core::auth::Credentials;
+use bannedcamp::core::download::DownloadProgressReporter;
+use bannedcamp::core::library::{AudioFormat, ItemDetails, LibraryItem};
+use bannedcamp::core::state::CollectionSnapshot;
+use bannedcamp::term::logs::recent_log_lines;
+use bannedcamp::tui::app::{App, BackgroundDownload, LibraryMode, LibraryTab, Screen};
+use bannedcamp::tui::async_bridge::{
+    AsyncRequest, AsyncResponse, ProgressBoard, TuiProgressReporter,
+};
+use bannedcamp::tui::crash::{render_crash_report, write_crash_report};
+use tokio::sync::mpsc;
+
+mod common;
+
+fn item(id: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        title: "Winter Sessions".to_string(),
+        artist: "Hypothermia".to_string(),
+        ..common::library_item()
+    }
+}
+
+fn app() -> (App, mpsc::Receiver<AsyncRequest>) {
+    let (tx, rx) = mpsc::channel(8);
+    let mut app = App::new(tx);
+    app.screen = Screen::Library;
+    app.output_dir = PathBuf::from("/music");
+    app.library_state
+        .set_items((1..=3).map(|i| item(&i.to_string())).collect());
+    (app, rx)
+}
+
+#[test]
+fn test_quick_download_skips_format_popup() {
+    let (mut app, mut rx) = app();
+    app.set_default_format(AudioFormat::Mp3V0);
+    app.name_format = Some("{artist}/{title}".to_string());
+
+    // Nothing selected, nothing to download
+    app.library_quick_download();
+    assert_eq!(app.screen, Screen::Library);
+    assert!(rx.try_recv().is_err());
+
+    app.library_state.selected_items.insert("2".to_string());
+    app.library_quick_download();
+    assert_eq!(app.screen, Screen::Download);
+    assert_eq!(app.library_state.mode, LibraryMode::Browse);
+
+    let Ok(AsyncRequest::StartBatchDownload {
+        items,
+        format,
+        output_dir,
+        name_format,
+    }) = rx.try_recv()
+    else {
+        panic!("no download started");
+    };
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].id, "2");
+    assert_eq!(format, AudioFormat::Mp3V0);
+    assert_eq!(output_dir, PathBuf::from("/music"));
+    assert_eq!(name_format.as_deref(), Some("{artist}/{title}"));
+}
+
+#[test]
+fn test_format_popup_starts_on_default_format() {
+    let (mut app, _rx) = app();
+    app.set_default_format(AudioFormat::Wav);
+    assert_eq!(
+        AudioFormat::ALL[app.library_state.selected_format],
+        AudioFormat::Wav
+    );
+}
+
+#[test]
+fn test_download_now_keeps_selection() {
+    let (mut app, mut rx) = app();
+    app.library_state.selected_items.insert("1".to_string());
+    app.library_state.selected = 2;
+
+    app.library_download_highlighted();
+    assert_eq!(app.screen, Screen::Library);
+    assert_eq!(app.library_state.selected_items.len(), 1);
+    let Ok(AsyncRequest::DownloadNow { item, format, .. }) = rx.try_recv() else {
+        panic!("no download started");
+    };
+    assert_eq!(item.id, "3");
+    assert_eq!(format, AudioFormat::Flac);
+    assert_eq!(
+        app.library_state.background_downloads.get("3"),
+        Some(&BackgroundDownload::Running)
+    );
+
+    // Pressing it again while it runs doesn't start it twice
+    app.library_download_highlighted();
+    assert!(rx.try_recv().is_err());
+
+    app.handle_async_response(AsyncResponse::BackgroundDownloadFinished {
+        item_id: "3".to_string(),
+        result: Err("connection reset".to_string()),
+    });
+    assert_eq!(
+        app.library_state.background_downloads.get("3"),
+        Some(&BackgroundDownload::Failed)
+    );
+    assert_eq!(
+        app.library_state.error.as_deref(),
+        Some("Winter Sessions: connection reset")
+    );
+}
+
+#[test]
+fn test_read_only_library() {
+    let dir = tempfile::tempdir().unwrap();
+    let (tx, _rx) = mpsc::channel(8);
+    let mut app = App::new(tx);
+    app.output_dir = dir.path().to_path_buf();
+
+    // Nothing saved yet
+    app.open_read_only();
+    assert_eq!(app.screen, Screen::Library);
+    assert!(app.library_state.error.is_some());
+
+    CollectionSnapshot::new(&[item("1"), item("2")])
+        .save(dir.path())
+        .unwrap();
+    let (tx, mut rx) = mpsc::channel(8);
+    let mut app = App::new(tx);
+    app.output_dir = dir.path().to_path_buf();
+    app.open_read_only();
+    assert!(app.library_state.error.is_none());
+    assert_eq!(app.library_state.items.len(), 2);
+
+    app.library_state.selected_items.insert("1".to_string());
+    app.library_quick_download();
+    app.library_download_highlighted();
+    app.library_toggle_hidden();
+    assert_eq!(app.screen, Screen::Library);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_library_tabs() {
+    let (mut app, mut rx) = app();
+    app.credentials = Some(Credentials {
+        identity_cookie: "x".to_string(),
+        fan_id: 1,
+    });
+    app.library_state.selected_items.insert("1".to_string());
+    app.library_search_input('w');
+
+    app.library_next_tab();
+    assert_eq!(app.library_state.tab, LibraryTab::Wishlist);
+    assert!(app.library_state.loading);
+    assert!(app.library_state.search_query.is_empty());
+    assert!(matches!(rx.try_recv(), Ok(AsyncRequest::FetchWishlist)));
+
+    // Collection pages still arriving land in the collection
+    app.handle_async_response(AsyncResponse::LibraryPageFetched {
+        items: vec![item("4")],
+        done: true,
+    });
+    assert!(app.library_state.items.is_empty());
+    assert_eq!(app.collection().items.len(), 4);
+
+    app.handle_async_response(AsyncResponse::WishlistFetched(Ok(vec![item("9")])));
+    assert!(!app.library_state.loading);
+    assert_eq!(app.library_state.items.len(), 1);
+
+    // Wishlist items aren't owned
+    app.library_toggle_selection();
+    app.library_quick_download();
+    app.library_download_highlighted();
+    assert_eq!(app.screen, Screen::Library);
+    assert!(rx.try_recv().is_err());
+
+    // Going back leaves the wishlist loaded and the collection as it was
+    app.library_switch_tab(LibraryTab::Collection);
+    assert_eq!(app.library_state.search_query, "w");
+    assert_eq!(app.library_state.items.len(), 4);
+    assert_eq!(app.library_state.selected_items.len(), 1);
+    app.library_previous_tab();
+    app.library_next_tab();
+    app.library_next_tab();
+    assert_eq!(app.library_state.tab, LibraryTab::Wishlist);
+    assert_eq!(app.library_state.selected_items.len(), 1);
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_user_actions_wait_for_room_in_the_channel() {
+    let (mut app, mut rx) = app();
+    // Whatever the bridge is busy with fills the channel
+    while app.async_tx.try_send(AsyncRequest::FetchWishlist).is_ok() {}
+
+    app.library_state.selected_items.insert("2".to_string());
+    app.library_quick_download();
+    app.cancel_downloads();
+    assert_eq!(app.screen, Screen::Download);
+
+    // Still waiting while the channel stays full
+    app.tick();
+    while let Ok(request) = rx.try_recv() {
+        assert!(matches!(request, AsyncRequest::FetchWishlist));
+    }
+
+    app.tick();
+    assert!(matches!(
+        rx.try_recv(),
+        Ok(AsyncRequest::StartBatchDownload { .. })
+    ));
+    assert!(matches!(rx.try_recv(), Ok(AsyncRequest::CancelDownloads)));
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn test_preview_prefetch() {
+    let (mut app, mut rx) = app();
+    app.library_state.set_items(
+        (1..=3)
+            .map(|i| LibraryItem {
+                item_url: Some(format!("https://badmath.bandcamp.com/album/{i}")),
+                ..item(&i.to_string())
+            })
+            .collect(),
+    );
+    app.library_toggle_preview();
+    let Ok(AsyncRequest::PrefetchDetails(items)) = rx.try_recv() else {
+        panic!("nothing prefetched");
+    };
+    assert_eq!(items.len(), 3);
+
+    // Ticks don't ask again while the highlight stays put
+    app.tick();
+    assert!(rx.try_recv().is_err());
+
+    app.handle_async_response(AsyncResponse::ItemDetailsFetched {
+        item_id: "1".to_string(),
+        result: Ok(ItemDetails::default()),
+    });
+    app.handle_async_response(AsyncResponse::ItemDetailsFetched {
+        item_id: "2".to_string(),
+        result: Err("timed out".to_string()),
+    });
+    assert!(app.library_state.details.contains(&"1".to_string()));
+    assert!(!app.library_state.details.contains(&"2".to_string()));
+    assert_eq!(app.library_state.details_errors["2"], "timed out");
+
+    // A failed row isn't asked for again while it stays near the highlight
+    app.library_move_down();
+    app.tick();
+    assert!(rx.try_recv().is_err());
+
+    // Once it drops out of view it's forgotten and fetched again when it comes back
+    app.library_state.set_items(vec![LibraryItem {
+        item_url: Some("https://badmath.bandcamp.com/album/1".to_string()),
+        ..item("1")
+    }]);
+    app.tick();
+    assert!(app.library_state.details_errors.is_empty());
+}
+
+#[tokio::test]
+async fn test_progress_keeps_the_latest_update_per_item() {
+    let (tx, mut rx) = mpsc::channel(1);
+    let board = ProgressBoard::default();
+    let first = TuiProgressReporter::new("1".to_string(), tx.clone(), board.clone());
+    let second = TuiProgressReporter::new("2".to_string(), tx, board.clone());
+
+    for downloaded in [10, 20, 30] {
+        first.on_progress(downloaded, Some(100)).await;
+    }
+    second.on_progress(5, None).await;
+    // Progress never takes room in the channel
+    assert!(rx.try_recv().is_err());
+
+    let mut updates: Vec<_> = board
+        .take()
+        .into_iter()
+        .map(|response| match response {
+            AsyncResponse::DownloadProgress {
+                item_id,
+                downloaded,
+                total,
+            } => (item_id, downloaded, total),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    updates.sort();
+    assert_eq!(
+        updates,
+        [("1".to_string(), 30, Some(100)), ("2".to_string(), 5, None)]
+    );
+    assert!(board.take().is_empty());
+}
+
+#[test]
+fn test_crash_report() {
+    let at = chrono::Local::now();
+    let logs = vec![
+        "WARN Bandcamp refuses more downloads for 60s".to_string(),
+        "ERROR Failed to download Winter Sessions".to_string(),
+    ];
+    let report = render_crash_report(
+        "panicked at src/tui/app.rs:1:1:\nindex out of bounds",
+        "0: bannedcamp::tui::app::App::handle_key",
+        &logs,
+        at,
+    );
+    assert!(report.starts_with(&format!("bannedcamp {}", env!("CARGO_PKG_VERSION"))));
+    assert!(report.contains("index out of bounds"));
+    assert!(report.contains("Backtrace:\n0: bannedcamp::tui::app::App::handle_key"));
+    assert!(report.ends_with("Last 2 log lines:\nWARN Bandcamp refuses more downloads for 60s\nERROR Failed to download Winter Sessions\n"));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_crash_report(&dir.path().join("crashes"), &report, at).unwrap();
+    assert!(
+        path.file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("crash-")
+    );
+    assert_eq!(std::fs::read_to_string(path).unwrap(), report);
+}
+
+#[test]
+fn test_crash_report_logs_leave_out_signatures() {
+    writeln!(
+        LogWriter { stderr: true },
+        "DEBUG GET https://bandcamp.com/download?id=1&sig=0123abcd&token=99:ff"
+    )
+    .unwrap();
+
+    let logs = recent_log_lines();
+    let line = logs
+        .iter()
+        .find(|line| line.contains("download?id=1"))
+        .unwrap();
+    assert_eq!(
+        line,
+        "DEBUG GET https://bandcamp.com/download?id=1&sig=REDACTED&token=REDACTED"
+    );
+}
@@ -0,0 +1,15 @@
+use bannedcamp::cli::commands::{BandcampUrl, FanUrl};
+
+#[test]
+fn test_fan_url() {
+    let fan = FanUrl::parse("https://bandcamp.com/somefan").unwrap();
+    assert_eq!(fan.username, "somefan");
+    assert!(FanUrl::parse("https://www.bandcamp.com/somefan/").is_some());
+
+    // Artist pages and bandcamp.com's own pages aren't fans
+    assert!(FanUrl::parse("https://badmathhk.bandcamp.com").is_none());
+    assert!(FanUrl::parse("https://bandcamp.com/").is_none());
+    assert!(FanUrl::parse("https://bandcamp.com/discover").is_none());
+    assert!(FanUrl::parse("https://bandcamp.com/somefan/wishlist").is_none());
+    assert!(BandcampUrl::parse("https://bandcamp.com/somefan").is_none());
+}
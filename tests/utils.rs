@@ -0,0 +1,231 @@
+use std::time::Duration;
+
+use bannedcamp::core::library::{
+    AudioFormat, LibraryItem, parse_release_date, unknown_template_variables, validate_name_format,
+};
+use bannedcamp::core::utils::{
+    SanitizeOptions, Transliterate, check_output_dir, glob_match, is_valid_replacement,
+    parse_duration, parse_size, sanitize_filename,
+};
+use chrono::NaiveDate;
+
+mod common;
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match(
+        "*field recordings*",
+        "Ambient Field Recordings Vol. 3"
+    ));
+    assert!(glob_match("The ?ure", "the cure"));
+    assert!(glob_match("*", ""));
+    assert!(glob_match("a*b*c", "aXbYbZc"));
+    assert!(!glob_match("a*b*c", "aXbYbZ"));
+    assert!(!glob_match("Bad Math", "Bad Math Remixes"));
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("512"), Some(512));
+    assert_eq!(parse_size("4k"), Some(4096));
+    assert_eq!(parse_size("100G"), Some(100 << 30));
+    assert_eq!(parse_size("1.5TB"), Some(3 << 39));
+    assert_eq!(parse_size(" 20 MB "), Some(20 << 20));
+    assert_eq!(parse_size("G"), None);
+    assert_eq!(parse_size("10X"), None);
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+    assert_eq!(parse_duration("30m"), Some(Duration::from_secs(30 * 60)));
+    assert_eq!(
+        parse_duration(" 6H "),
+        Some(Duration::from_secs(6 * 60 * 60))
+    );
+    assert_eq!(
+        parse_duration("1d"),
+        Some(Duration::from_secs(24 * 60 * 60))
+    );
+    assert_eq!(parse_duration("h"), None);
+    assert_eq!(parse_duration("1.5h"), None);
+}
+
+#[test]
+fn test_check_output_dir() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("music/new");
+    check_output_dir(&output).unwrap();
+    assert!(output.is_dir());
+    assert_eq!(std::fs::read_dir(&output).unwrap().count(), 0);
+
+    // A file in the way
+    let file = dir.path().join("file");
+    std::fs::write(&file, "").unwrap();
+    let err = check_output_dir(&file.join("music")).unwrap_err();
+    assert_eq!(err.kind(), "output_unusable");
+    assert!(err.to_string().contains("-o"));
+
+    // Root writes anywhere, the permission check only means something for other users
+    let locked = dir.path().join("locked");
+    std::fs::create_dir(&locked).unwrap();
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+    if std::fs::write(locked.join("probe"), "").is_err() {
+        assert!(check_output_dir(&locked).is_err());
+    }
+}
+
+#[test]
+fn test_sanitize_filename() {
+    let default = SanitizeOptions::default();
+    assert_eq!(sanitize_filename("AC/DC: Live?", &default), "AC_DC_ Live_");
+    assert_eq!(sanitize_filename("Sigur Rós", &default), "Sigur Rós");
+    assert_eq!(sanitize_filename("..", &default), "__");
+
+    let options = SanitizeOptions {
+        replacement: String::new(),
+        transliterate: Some(Transliterate::Latin),
+        collapse_whitespace: true,
+    };
+    assert_eq!(
+        sanitize_filename("  Sigur Rós –  Ágætis byrjun ", &options),
+        "Sigur Ros - Agaetis byrjun"
+    );
+    assert_eq!(
+        sanitize_filename("“Quoted” / Straße", &options),
+        "Quoted Strasse"
+    );
+    assert_eq!(sanitize_filename("坂本龍一", &options), "坂本龍一");
+
+    let ascii = SanitizeOptions {
+        transliterate: Some(Transliterate::All),
+        ..SanitizeOptions::default()
+    };
+    assert_eq!(sanitize_filename("坂本龍一", &ascii), "Ban Ben Long Yi");
+    assert_eq!(
+        sanitize_filename("Кино – Группа крови", &ascii),
+        "Kino - Gruppa krovi"
+    );
+    assert_eq!(
+        sanitize_filename("Sigur Rós／Ágætis", &ascii),
+        "Sigur Ros_Agaetis"
+    );
+}
+
+#[test]
+fn test_replacement_cant_add_separators() {
+    assert!(is_valid_replacement("_"));
+    assert!(is_valid_replacement(""));
+    assert!(is_valid_replacement(" - "));
+    assert!(is_valid_replacement("._"));
+    for invalid in ["/", "\\", "a/b", "\0", ":", ".", "..."] {
+        assert!(!is_valid_replacement(invalid), "{invalid:?}");
+    }
+
+    // Library callers setting one anyway get the default
+    let slash = SanitizeOptions {
+        replacement: "/".to_string(),
+        ..SanitizeOptions::default()
+    };
+    assert_eq!(sanitize_filename("AC/DC", &slash), "AC_DC");
+}
+
+#[test]
+fn test_validate_name_format() {
+    assert!(validate_name_format("{artist}/{title}").is_ok());
+    assert!(validate_name_format("{artist}\\{title}{ext}").is_ok());
+    assert!(validate_name_format("..{title}").is_ok());
+    assert!(validate_name_format("../{artist} - {title}").is_err());
+    assert!(validate_name_format("{artist}/../../{title}").is_err());
+    assert!(validate_name_format("/srv/music/{title}").is_err());
+    assert!(validate_name_format("C:{title}").is_err());
+}
+
+#[test]
+fn test_template_separators() {
+    let item = LibraryItem {
+        title: "Back in Black".to_string(),
+        artist: "AC/DC".to_string(),
+        download_url: "https://bandcamp.com/download".to_string(),
+        ..common::library_item()
+    };
+    let options = SanitizeOptions::default();
+    let sep = std::path::MAIN_SEPARATOR;
+
+    assert_eq!(
+        item.construct_filename(AudioFormat::Flac, Some("{artist}/{title}"), &options),
+        format!("AC_DC{sep}Back in Black")
+    );
+    assert_eq!(
+        item.construct_filename(AudioFormat::Flac, Some("{artist}\\{title}"), &options),
+        format!("AC_DC{sep}Back in Black")
+    );
+}
+
+#[test]
+fn test_filename_for_served_file() {
+    let item = LibraryItem {
+        title: "Chainsaw".to_string(),
+        artist: "Clark Rainbow".to_string(),
+        ..common::library_item()
+    };
+    let options = SanitizeOptions::default();
+
+    // A one-track album served as a bare file gets the track name and the served extension
+    assert_eq!(
+        item.construct_filename_with(Some("flac"), None, &options),
+        "Clark Rainbow - Chainsaw.flac"
+    );
+    assert_eq!(
+        item.construct_filename_with(None, None, &options),
+        item.construct_filename(AudioFormat::Flac, None, &options)
+    );
+}
+
+#[test]
+fn test_year_template_variable() {
+    assert_eq!(
+        parse_release_date("14 Mar 2024 00:00:00 GMT"),
+        NaiveDate::from_ymd_opt(2024, 3, 14)
+    );
+    assert_eq!(parse_release_date("soon"), None);
+
+    let mut item = LibraryItem {
+        release_date: NaiveDate::from_ymd_opt(2021, 6, 20),
+        ..common::library_item()
+    };
+    let options = SanitizeOptions::default();
+    assert_eq!(
+        item.construct_filename(AudioFormat::Flac, Some("{year} - {title}"), &options),
+        "2021 - Missing Narrative"
+    );
+    item.release_date = None;
+    assert_eq!(
+        item.construct_filename(AudioFormat::Flac, Some("{year} - {title}"), &options),
+        "Missing Narrative"
+    );
+
+    // An unknown year takes its brackets and separators with it, not the rest of the name
+    let render = |template| item.construct_filename(AudioFormat::Flac, Some(template), &options);
+    assert_eq!(render("{title} ({year})"), "Missing Narrative");
+    assert_eq!(
+        render("{artist} - {year} - {title}"),
+        "Bad Math - Missing Narrative"
+    );
+    assert_eq!(
+        render("{artist}/{year}/{title}"),
+        format!("Bad Math{}Missing Narrative", std::path::MAIN_SEPARATOR)
+    );
+    assert_eq!(render("{year}"), "1");
+}
+
+#[test]
+fn test_unknown_template_variables() {
+    assert!(unknown_template_variables("{artist}/{year} - {title}{ext} [{id}]").is_empty());
+    assert_eq!(
+        unknown_template_variables("{artst}/{genre} - {title} {genre} {"),
+        ["artst", "genre"]
+    );
+}
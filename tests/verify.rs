@@ -0,0 +1,35 @@
+use bannedcamp::core::verify::find_undecodable;
+
+mod common;
+
+/// Stands in for ffprobe: fails like it does on garbled input for files containing `corrupt`
+const FAKE_FFPROBE: &str = r#"#!/bin/sh
+for file; do :; done
+if grep -q corrupt "$file"; then
+    echo "$file: Invalid data found when processing input" >&2
+    exit 1
+fi
+echo 42
+"#;
+
+#[test]
+fn test_find_undecodable_flags_corrupt_tracks() {
+    let _bin = common::fake_program("ffprobe", FAKE_FFPROBE);
+
+    let album = tempfile::tempdir().unwrap();
+    std::fs::create_dir(album.path().join("Disc 2")).unwrap();
+    std::fs::write(album.path().join("01 Intro.flac"), b"fLaC fine").unwrap();
+    std::fs::write(album.path().join("Disc 2/01 Outro.FLAC"), b"fLaC corrupt").unwrap();
+    // Not audio, never handed to ffprobe
+    std::fs::write(album.path().join("cover.jpg"), b"corrupt").unwrap();
+
+    assert_eq!(
+        find_undecodable(album.path()).unwrap(),
+        [album.path().join("Disc 2/01 Outro.FLAC")]
+    );
+    assert!(
+        find_undecodable(&album.path().join("01 Intro.flac"))
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,85 @@
+use bannedcamp::core::library::LibraryItem;
+use bannedcamp::tui::app::{LibrarySort, LibraryState};
+use bannedcamp::tui::view_state::LibraryView;
+
+mod common;
+
+fn item(id: &str, artist: &str) -> LibraryItem {
+    LibraryItem {
+        id: id.to_string(),
+        title: "Winter Sessions".to_string(),
+        artist: artist.to_string(),
+        ..common::library_item()
+    }
+}
+
+#[test]
+fn test_library_view_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library_view.json");
+
+    let mut state = LibraryState {
+        search_query: "hypo".to_string(),
+        sort: LibrarySort::NewestFirst,
+        ..Default::default()
+    };
+    state.set_items(
+        (1..=5)
+            .map(|i| item(&i.to_string(), "Hypothermia"))
+            .collect(),
+    );
+    state.selected = 3;
+    state.scroll_offset = 2;
+    state.selected_items.insert("2".to_string());
+    LibraryView::capture(&state).save(&path);
+
+    let mut restored = LibraryState::default();
+    LibraryView::load(&path).apply(&mut restored);
+    assert_eq!(restored.search_query, "hypo");
+    assert_eq!(restored.sort, LibrarySort::NewestFirst);
+    assert!(restored.selected_items.contains("2"));
+
+    // The highlighted item is on the second page of the collection
+    restored.append_items(
+        (1..=2)
+            .map(|i| item(&i.to_string(), "Hypothermia"))
+            .collect(),
+    );
+    restored.restore_highlight(false);
+    assert_eq!(restored.selected, 0);
+    assert!(restored.pending_highlight.is_some());
+
+    restored.append_items(
+        (3..=5)
+            .map(|i| item(&i.to_string(), "Hypothermia"))
+            .collect(),
+    );
+    restored.restore_highlight(true);
+    assert_eq!(restored.selected_item().unwrap().id, "4");
+    assert_eq!(restored.selected - restored.scroll_offset, 1);
+    assert!(restored.pending_highlight.is_none());
+}
+
+#[test]
+fn test_library_view_forgets_missing_item() {
+    let mut state = LibraryState::default();
+    LibraryView {
+        highlighted: Some("gone".to_string()),
+        ..Default::default()
+    }
+    .apply(&mut state);
+
+    state.append_items(vec![item("1", "Bad Math")]);
+    state.restore_highlight(true);
+    assert!(state.pending_highlight.is_none());
+    assert_eq!(state.selected, 0);
+}
+
+#[test]
+fn test_unreadable_library_view_is_ignored() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("library_view.json");
+    std::fs::write(&path, "{ not json").unwrap();
+
+    assert_eq!(LibraryView::load(&path), LibraryView::default());
+}